@@ -0,0 +1,195 @@
+//! Verifying a corpus of replay files in parallel.
+//!
+//! Ref: INV-0006 (Replay Verifiability), DM-0017 (ReplayArtifact)
+//! - For regression runs over many recorded matches: `verify_corpus` reads
+//!   and verifies each file on a pool of worker threads, each taking the
+//!   next unverified file until none are left
+//! - Every artifact is verified independently, so the outcome of each is the
+//!   same as `verify_replay_report` on its own; results come back in the
+//!   order the paths were given
+
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{VerifyOptions, VerifyReport, read_replay_auto, verify_replay_report};
+
+/// One file's verification.
+#[derive(Debug)]
+pub struct CorpusEntry {
+    pub path: PathBuf,
+    /// The verification report, or why the file could not be read.
+    pub result: io::Result<VerifyReport>,
+}
+
+impl CorpusEntry {
+    /// True when the file was read and verified.
+    pub fn is_ok(&self) -> bool {
+        self.result.as_ref().is_ok_and(VerifyReport::is_ok)
+    }
+}
+
+/// Results of `verify_corpus`, one entry per path in the order given.
+#[derive(Debug)]
+pub struct CorpusReport {
+    pub entries: Vec<CorpusEntry>,
+    /// Wall-clock time of the whole run.
+    pub elapsed: Duration,
+}
+
+impl CorpusReport {
+    /// True when every file verified.
+    pub fn is_ok(&self) -> bool {
+        self.entries.iter().all(CorpusEntry::is_ok)
+    }
+
+    /// Files that verified.
+    pub fn passed(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_ok()).count()
+    }
+
+    /// Files that could not be read or did not verify.
+    pub fn failures(&self) -> impl Iterator<Item = &CorpusEntry> {
+        self.entries.iter().filter(|e| !e.is_ok())
+    }
+
+    /// Ticks resimulated across the corpus.
+    pub fn ticks_simulated(&self) -> u64 {
+        self.entries
+            .iter()
+            .filter_map(|e| e.result.as_ref().ok())
+            .map(|r| r.ticks_simulated)
+            .sum()
+    }
+}
+
+/// Verify every replay file in `paths` on one worker thread per available
+/// CPU.
+pub fn verify_corpus<P: AsRef<Path> + Sync>(paths: &[P], options: &VerifyOptions) -> CorpusReport {
+    let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    verify_corpus_on(paths, options, threads)
+}
+
+/// `verify_corpus` on at most `threads` worker threads (at least one).
+pub fn verify_corpus_on<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    options: &VerifyOptions,
+    threads: usize,
+) -> CorpusReport {
+    let started = Instant::now();
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        let mut done = Vec::new();
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let Some(path) = paths.get(index) else {
+                return done;
+            };
+            let path = path.as_ref();
+            let result = read_replay_auto(path).map(|a| verify_replay_report(&a, options));
+            done.push((
+                index,
+                CorpusEntry {
+                    path: path.to_path_buf(),
+                    result,
+                },
+            ));
+        }
+    };
+    let mut entries: Vec<(usize, CorpusEntry)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.clamp(1, paths.len().max(1)))
+            .map(|_| scope.spawn(worker))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("corpus worker panicked"))
+            .collect()
+    });
+    entries.sort_by_key(|&(index, _)| index);
+    CorpusReport {
+        entries: entries.into_iter().map(|(_, entry)| entry).collect(),
+        elapsed: started.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::recorded_match;
+    use crate::write_replay;
+
+    #[test]
+    fn test_verify_corpus_keeps_input_order() {
+        let dir = std::env::temp_dir().join(format!("flowstate-corpus-{}", std::process::id()));
+        let mut paths = Vec::new();
+        for ticks in 1..=6 {
+            let mut artifact = recorded_match(ticks * 5, 1, 0).artifact;
+            if ticks == 4 {
+                artifact.final_digest ^= 1;
+            }
+            let path = dir.join(format!("m{ticks}.replay"));
+            write_replay(&artifact, &path).unwrap();
+            paths.push(path);
+        }
+        paths.push(dir.join("missing.replay"));
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+
+        for threads in [1, 3, 16] {
+            let report = verify_corpus_on(&paths, &options, threads);
+            let listed: Vec<&PathBuf> = report.entries.iter().map(|e| &e.path).collect();
+            assert_eq!(listed, paths.iter().collect::<Vec<_>>());
+            assert_eq!(report.passed(), 5);
+            let failures: Vec<&Path> = report.failures().map(|e| e.path.as_path()).collect();
+            assert_eq!(failures, [paths[3].as_path(), paths[6].as_path()]);
+            assert!(report.entries[6].result.is_err());
+            assert_eq!(report.ticks_simulated(), 5 * (1..=6).sum::<u64>());
+        }
+        assert!(verify_corpus::<PathBuf>(&[], &options).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn test_verify_corpus_reports_unreadable_and_failing_files() {
+        let dir =
+            std::env::temp_dir().join(format!("flowstate-corpus-failures-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.replay");
+        write_replay(&recorded_match(10, 2, 5).artifact, &good).unwrap();
+        let tampered = dir.join("tampered.replay");
+        let mut artifact = recorded_match(10, 2, 5).artifact;
+        artifact.final_digest ^= 1;
+        write_replay(&artifact, &tampered).unwrap();
+        let garbage = dir.join("garbage.replay");
+        std::fs::write(&garbage, b"not a replay").unwrap();
+        let paths = [garbage.clone(), good, tampered.clone()];
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+
+        for threads in [1, 2, 8] {
+            let report = verify_corpus_on(&paths, &options, threads);
+            assert!(!report.is_ok());
+            assert_eq!(report.passed(), 1);
+            let failures: Vec<&Path> = report.failures().map(|e| e.path.as_path()).collect();
+            assert_eq!(failures, [garbage.as_path(), tampered.as_path()]);
+            let unreadable = &report.entries[0];
+            assert_eq!(
+                unreadable.result.as_ref().unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+            let failing = &report.entries[2];
+            assert!(!failing.result.as_ref().unwrap().is_ok());
+            assert!(!failing.is_ok());
+            // Only artifacts that loaded count towards simulated ticks
+            assert_eq!(report.ticks_simulated(), 20);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,248 @@
+//! Comparing two replay artifacts.
+//!
+//! Ref: DM-0017 (ReplayArtifact), DM-0024 (AppliedInput), ADR-0007
+//! - For "same match, different outcome" reports: `diff_replays` lists what
+//!   differs in the recorded setup (spawn order, tuning), the first differing
+//!   input in canonical `(tick, player_id)` order, and the first tick at which
+//!   the two resimulations' StateDigests differ
+//! - Both artifacts are resimulated by the current build, so a digest
+//!   difference without any input or setup difference points at the seed or
+//!   the initial baseline
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use flowstate_sim::Tick;
+use flowstate_wire::{AppliedInputProto, ReplayArtifact};
+
+use crate::VerifyError;
+use crate::playback::ReplayPlayer;
+
+/// Everything `diff_replays` found; empty when the replays agree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayDiff {
+    /// `entity_spawn_order` of each replay, when they differ.
+    pub spawn_order: Option<(Vec<u32>, Vec<u32>)>,
+    /// Tuning parameters whose values differ or that only one replay has,
+    /// ordered by key.
+    pub tuning: Vec<TuningDifference>,
+    /// First input in `(tick, player_id)` order that differs or that only
+    /// one replay has.
+    pub first_input: Option<InputDifference>,
+    /// First tick both replays reach with different StateDigests.
+    pub first_digest: Option<DigestDifference>,
+}
+
+impl ReplayDiff {
+    /// True when no difference was found.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// One tuning parameter's value in each replay (`None` = not recorded).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningDifference {
+    pub key: String,
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+}
+
+/// One `(tick, player_id)` input in each replay (`None` = not recorded).
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputDifference {
+    pub tick: Tick,
+    pub player_id: u32,
+    pub a: Option<AppliedInputProto>,
+    pub b: Option<AppliedInputProto>,
+}
+
+/// The StateDigest of each resimulation at `tick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestDifference {
+    pub tick: Tick,
+    pub a: u64,
+    pub b: u64,
+}
+
+impl std::fmt::Display for ReplayDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+        if let Some((a, b)) = &self.spawn_order {
+            writeln!(f, "spawn order: {a:?} vs {b:?}")?;
+        }
+        for t in &self.tuning {
+            writeln!(f, "tuning {}: {:?} vs {:?}", t.key, t.a, t.b)?;
+        }
+        if let Some(input) = &self.first_input {
+            let describe = |input: &Option<AppliedInputProto>| match input {
+                Some(i) => format!("{:?} (fallback: {})", i.move_dir, i.is_fallback),
+                None => "missing".to_string(),
+            };
+            writeln!(
+                f,
+                "first input difference at tick {} player {}: {} vs {}",
+                input.tick,
+                input.player_id,
+                describe(&input.a),
+                describe(&input.b)
+            )?;
+        }
+        if let Some(d) = &self.first_digest {
+            writeln!(
+                f,
+                "first digest difference at tick {}: {:#018x} vs {:#018x}",
+                d.tick, d.a, d.b
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare replays `a` and `b`. Fails if either cannot be resimulated
+/// (e.g., an invalid input stream).
+pub fn diff_replays(a: &ReplayArtifact, b: &ReplayArtifact) -> Result<ReplayDiff, VerifyError> {
+    Ok(ReplayDiff {
+        spawn_order: (a.entity_spawn_order != b.entity_spawn_order)
+            .then(|| (a.entity_spawn_order.clone(), b.entity_spawn_order.clone())),
+        tuning: tuning_differences(a, b),
+        first_input: first_input_difference(a, b),
+        first_digest: first_digest_difference(a, b)?,
+    })
+}
+
+fn tuning_differences(a: &ReplayArtifact, b: &ReplayArtifact) -> Vec<TuningDifference> {
+    let values = |artifact: &ReplayArtifact| -> BTreeMap<String, f64> {
+        artifact
+            .tuning_parameters
+            .iter()
+            .map(|t| (t.key.clone(), t.value))
+            .collect()
+    };
+    let (a, b) = (values(a), values(b));
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (a, b) = (a.get(key).copied(), b.get(key).copied());
+            // Bitwise, so -0.0 vs 0.0 counts: the simulation can tell them apart
+            (a.map(f64::to_bits) != b.map(f64::to_bits)).then(|| TuningDifference {
+                key: key.clone(),
+                a,
+                b,
+            })
+        })
+        .collect()
+}
+
+fn first_input_difference(a: &ReplayArtifact, b: &ReplayArtifact) -> Option<InputDifference> {
+    let by_key = |artifact: &ReplayArtifact| -> BTreeMap<(Tick, u32), AppliedInputProto> {
+        artifact
+            .inputs
+            .iter()
+            .map(|i| ((i.tick, i.player_id), i.clone()))
+            .collect()
+    };
+    let (a, b) = (by_key(a), by_key(b));
+    let keys: BTreeSet<&(Tick, u32)> = a.keys().chain(b.keys()).collect();
+    keys.into_iter().find_map(|&(tick, player_id)| {
+        let (a, b) = (a.get(&(tick, player_id)), b.get(&(tick, player_id)));
+        (a != b).then(|| InputDifference {
+            tick,
+            player_id,
+            a: a.cloned(),
+            b: b.cloned(),
+        })
+    })
+}
+
+fn first_digest_difference(
+    a: &ReplayArtifact,
+    b: &ReplayArtifact,
+) -> Result<Option<DigestDifference>, VerifyError> {
+    let digests = |artifact: &ReplayArtifact| -> Result<BTreeMap<Tick, u64>, VerifyError> {
+        let player = ReplayPlayer::new(artifact)?;
+        let initial = (player.world().tick(), player.world().state_digest());
+        Ok(std::iter::once(initial)
+            .chain(player.map(|(_, snapshot, _)| (snapshot.tick, snapshot.digest)))
+            .collect())
+    };
+    let (a, b) = (digests(a)?, digests(b)?);
+    Ok(a.iter().find_map(|(&tick, &a)| {
+        let &b = b.get(&tick)?;
+        (a != b).then_some(DigestDifference { tick, a, b })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::recorded_match;
+    use flowstate_sim::{PlayerId, World};
+
+    #[test]
+    fn test_diff_replays_reports_first_differences() {
+        let a = recorded_match(20, 2, 0).artifact;
+        let same = diff_replays(&a, &a.clone()).unwrap();
+        assert!(same.is_empty());
+        assert_eq!(same.to_string(), "no differences\n");
+
+        let mut b = a.clone();
+        for input in b
+            .inputs
+            .iter_mut()
+            .filter(|i| i.tick >= 7 && i.player_id == 1)
+        {
+            input.move_dir = vec![0.6, 0.8];
+        }
+        // A policy that only shaped the recorded inputs, so resimulation
+        // is unaffected
+        b.tuning_parameters.push(flowstate_wire::TuningParameter {
+            key: "lki_decay_after_ticks".to_string(),
+            value: 30.0,
+        });
+        let diff = diff_replays(&a, &b).unwrap();
+
+        assert_eq!(diff.spawn_order, None);
+        assert_eq!(diff.tuning.len(), 1);
+        assert_eq!(diff.tuning[0].key, "lki_decay_after_ticks");
+        assert_eq!((diff.tuning[0].a, diff.tuning[0].b), (None, Some(30.0)));
+        let input = diff.first_input.clone().unwrap();
+        assert_eq!((input.tick, input.player_id), (7, 1));
+        assert_eq!(input.b.unwrap().move_dir, vec![0.6, 0.8]);
+        // The input applied at tick 7 first shows in the post-step state
+        assert_eq!(diff.first_digest.unwrap().tick, 8);
+        assert!(
+            diff.to_string()
+                .contains("first input difference at tick 7 player 1")
+        );
+    }
+    #[test]
+    fn test_diff_replays_reports_spawn_order() {
+        let a = recorded_match(10, 2, 0).artifact;
+        // The same match with player 1 spawned first
+        let mut world = World::new(0, 60);
+        let first = world.spawn_character(PlayerId(1));
+        let second = world.spawn_character(PlayerId(0));
+        let mut b = a.clone();
+        b.entity_spawn_order = vec![1, 0];
+        b.sim_commands.reverse();
+        for mapping in &mut b.player_entity_mapping {
+            mapping.entity_id = if mapping.player_id == 1 {
+                first.0
+            } else {
+                second.0
+            };
+        }
+        b.initial_baseline = Some(world.baseline().into());
+
+        let diff = diff_replays(&a, &b).unwrap();
+        assert_eq!(diff.spawn_order, Some((vec![0, 1], vec![1, 0])));
+        assert!(diff.tuning.is_empty());
+        assert_eq!(diff.first_input, None);
+        assert!(
+            diff.to_string()
+                .starts_with("spawn order: [0, 1] vs [1, 0]\n")
+        );
+    }
+}
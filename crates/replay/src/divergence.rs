@@ -0,0 +1,187 @@
+//! Locating where a replay diverges.
+//!
+//! Ref: ADR-0007 (StateDigest), DM-0017 (ReplayArtifact)
+//! - `find_first_divergent_tick` resimulates a replay and compares every
+//!   tick that has a recorded reference: the initial baseline, the
+//!   `checkpoint_digests`, and the final digest
+//! - A digest only says that the state differs, so the simulation diverged
+//!   somewhere after the last matching reference
+//! - Where a full recorded state exists (the initial baseline, or segment
+//!   states via `SegmentedReplayReader::find_first_divergence`), it is
+//!   compared entity by entity to name the first entity that differs
+
+use std::collections::BTreeMap;
+
+use flowstate_sim::{Baseline, EntityId, EntitySnapshot, Tick};
+use flowstate_wire::ReplayArtifact;
+
+use crate::{
+    VerifyError, VerifyOptions, advance_recorded, check_build_fingerprint, check_format_version,
+    checkpoint_digests, inputs_by_tick, spawned_world, validate_input_stream,
+};
+
+/// The earliest recorded reference a resimulation disagrees with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Tick of the first recorded state or digest that differs.
+    pub tick: Tick,
+    /// Latest earlier tick whose recorded reference matched; the simulation
+    /// diverged after it. `None` when the initial baseline already differs.
+    pub last_matching_tick: Option<Tick>,
+    /// Lowest EntityId whose state differs at `tick`, when a full recorded
+    /// state exists at that tick.
+    pub entity_id: Option<EntityId>,
+}
+
+/// Resimulate `artifact` and report where it first disagrees with its
+/// recorded digests, or `None` when every digest matches.
+///
+/// Errors are the ones `verify_replay` reports for an artifact that cannot
+/// be resimulated at all (build mismatch, invalid input stream, ...).
+pub fn find_first_divergent_tick(
+    artifact: &ReplayArtifact,
+    options: &VerifyOptions,
+) -> Result<Option<Divergence>, VerifyError> {
+    find_divergence(artifact, options, &BTreeMap::new())
+}
+
+/// `find_first_divergent_tick`, also comparing the full recorded `states`
+/// (keyed by tick) entity by entity.
+pub(crate) fn find_divergence(
+    artifact: &ReplayArtifact,
+    options: &VerifyOptions,
+    states: &BTreeMap<Tick, Baseline>,
+) -> Result<Option<Divergence>, VerifyError> {
+    check_format_version(artifact)?;
+    check_build_fingerprint(artifact, options)?;
+    validate_input_stream(artifact)?;
+    let initial = artifact
+        .initial_baseline
+        .clone()
+        .ok_or(VerifyError::MissingBaseline)?;
+    let initial = Baseline::try_from(initial).map_err(|e| VerifyError::InvalidFormat {
+        reason: e.to_string(),
+    })?;
+    let mut digests = checkpoint_digests(artifact, initial.tick)?;
+    digests.insert(artifact.checkpoint_tick, artifact.final_digest);
+    let inputs_by_tick = inputs_by_tick(&artifact.inputs)?;
+
+    let mut world = spawned_world(artifact)?;
+    let baseline = world.baseline();
+    if baseline.digest != initial.digest {
+        return Ok(Some(Divergence {
+            tick: initial.tick,
+            last_matching_tick: None,
+            entity_id: first_differing_entity(&baseline, &initial),
+        }));
+    }
+
+    let mut last_matching_tick = initial.tick;
+    for tick in initial.tick..artifact.checkpoint_tick {
+        advance_recorded(&mut world, tick, &inputs_by_tick);
+        let tick = world.tick();
+        let state = states.get(&tick);
+        let digest = digests.get(&tick).copied().or(state.map(|s| s.digest));
+        let Some(expected) = digest else {
+            continue;
+        };
+        let actual = world.baseline();
+        if actual.digest != expected || state.is_some_and(|s| s.digest != actual.digest) {
+            return Ok(Some(Divergence {
+                tick,
+                last_matching_tick: Some(last_matching_tick),
+                entity_id: state.and_then(|s| first_differing_entity(&actual, s)),
+            }));
+        }
+        last_matching_tick = tick;
+    }
+    Ok(None)
+}
+
+/// Lowest EntityId present in only one of the states or different in them.
+fn first_differing_entity(actual: &Baseline, recorded: &Baseline) -> Option<EntityId> {
+    let by_id = |state: &Baseline| -> BTreeMap<EntityId, EntitySnapshot> {
+        state
+            .entities
+            .iter()
+            .map(|e| (e.entity_id, e.clone()))
+            .collect()
+    };
+    let (actual, recorded) = (by_id(actual), by_id(recorded));
+    actual
+        .keys()
+        .chain(recorded.keys())
+        .filter(|id| actual.get(id) != recorded.get(id))
+        .min()
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{RecordedMatch, recorded_match};
+    use crate::verify_replay;
+
+    fn options() -> VerifyOptions {
+        VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        }
+    }
+
+    #[test]
+    fn test_divergence_localized_by_recorded_references() {
+        let RecordedMatch {
+            artifact, states, ..
+        } = recorded_match(40, 2, 10);
+        assert_eq!(
+            find_first_divergent_tick(&artifact, &options()).unwrap(),
+            None
+        );
+
+        // Player 1 (entity 2) moves differently from tick 14 on
+        let mut tampered = artifact.clone();
+        let input = tampered
+            .inputs
+            .iter_mut()
+            .find(|i| i.tick == 14 && i.player_id == 1)
+            .unwrap();
+        input.move_dir = vec![0.0, 0.0];
+        assert!(verify_replay(&tampered, &options()).is_err());
+        assert_eq!(
+            find_first_divergent_tick(&tampered, &options()).unwrap(),
+            Some(Divergence {
+                tick: 20,
+                last_matching_tick: Some(10),
+                entity_id: None,
+            })
+        );
+
+        // A full recorded state pins the tick and names the entity
+        let recorded: BTreeMap<Tick, Baseline> = [(15, states[15].clone())].into();
+        let entity_id = states[15].entities[1].entity_id;
+        assert_eq!(
+            find_divergence(&tampered, &options(), &recorded).unwrap(),
+            Some(Divergence {
+                tick: 15,
+                last_matching_tick: Some(10),
+                entity_id: Some(entity_id),
+            })
+        );
+
+        // So does a wrong initial baseline
+        let mut moved = artifact;
+        let baseline = moved.initial_baseline.as_mut().unwrap();
+        baseline.entities[0].position = vec![9.0, 9.0];
+        baseline.digest ^= 1;
+        assert_eq!(
+            find_first_divergent_tick(&moved, &options()).unwrap(),
+            Some(Divergence {
+                tick: 0,
+                last_matching_tick: None,
+                entity_id: Some(states[0].entities[0].entity_id),
+            })
+        );
+    }
+}
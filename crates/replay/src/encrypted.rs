@@ -0,0 +1,207 @@
+//! Encrypted replay files, for storing and transferring replays confidentially.
+//!
+//! Ref: DM-0017 (ReplayArtifact)
+//! - Layout: `[ENCRYPTED_MAGIC][ENCRYPTED_VERSION][nonce: 12 bytes]`, then
+//!   the ChaCha20-Poly1305 ciphertext and tag of the zstd-compressed
+//!   container (`encode_container`); the magic and version are associated
+//!   data, so they cannot be swapped either
+//! - The key is the caller's (32 bytes); each file gets a fresh random
+//!   nonce, so one key can seal many replays
+//! - Decryption yields the artifact as recorded: it verifies exactly like
+//!   one read from a plain file
+//!
+//! Confidentiality and integrity of the file only: whoever holds the key
+//! can rewrite a replay and reseal it.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use flowstate_wire::ReplayArtifact;
+use flowstate_wire::compression::Codec;
+
+use crate::{decode_replay_auto, encode_container, write_new_replay_file};
+
+/// Magic bytes opening an encrypted replay file.
+pub const ENCRYPTED_MAGIC: [u8; 4] = *b"FSRE";
+
+/// Layout version, the byte after `ENCRYPTED_MAGIC`.
+pub const ENCRYPTED_VERSION: u8 = 1;
+
+/// Length of a replay encryption key.
+pub const REPLAY_KEY_LEN: usize = 32;
+
+/// Length of the per-file nonce.
+const NONCE_LEN: usize = 12;
+
+/// Magic and version.
+const HEADER_LEN: usize = ENCRYPTED_MAGIC.len() + 1;
+
+/// Write `artifact` to a new file at `path`, encrypted with `key`.
+pub fn write_replay_encrypted(
+    artifact: &ReplayArtifact,
+    path: &Path,
+    key: &[u8; REPLAY_KEY_LEN],
+) -> io::Result<()> {
+    write_new_replay_file(path, &encrypt_replay(artifact, key)?)
+}
+
+/// Read a replay written by `write_replay_encrypted`, migrated to
+/// `REPLAY_FORMAT_VERSION`. A wrong key or a modified file fails with
+/// `io::ErrorKind::InvalidData`.
+pub fn read_replay_encrypted(
+    path: &Path,
+    key: &[u8; REPLAY_KEY_LEN],
+) -> io::Result<ReplayArtifact> {
+    decrypt_replay(&fs::read(path)?, key)
+}
+
+/// Encode and encrypt `artifact` in the encrypted file format.
+pub fn encrypt_replay(
+    artifact: &ReplayArtifact,
+    key: &[u8; REPLAY_KEY_LEN],
+) -> io::Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce).map_err(|e| io::Error::other(format!("Randomness: {e}")))?;
+    let plaintext = encode_container(artifact, Codec::Zstd)?;
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + NONCE_LEN + plaintext.len() + 16);
+    bytes.extend_from_slice(&ENCRYPTED_MAGIC);
+    bytes.push(ENCRYPTED_VERSION);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &bytes,
+            },
+        )
+        .expect("ChaCha20-Poly1305 encryption cannot fail for in-memory buffers");
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&ciphertext);
+    Ok(bytes)
+}
+
+/// Decrypt and decode an encrypted replay.
+pub fn decrypt_replay(data: &[u8], key: &[u8; REPLAY_KEY_LEN]) -> io::Result<ReplayArtifact> {
+    let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, reason);
+    if !data.starts_with(&ENCRYPTED_MAGIC) {
+        return Err(invalid("Not an encrypted replay".to_string()));
+    }
+    let version = data[ENCRYPTED_MAGIC.len()..]
+        .first()
+        .copied()
+        .ok_or_else(|| invalid("Truncated encrypted replay".to_string()))?;
+    if version != ENCRYPTED_VERSION {
+        return Err(invalid(format!(
+            "Unsupported encrypted replay version {version}"
+        )));
+    }
+    let (header, rest) = data.split_at(HEADER_LEN);
+    if rest.len() < NONCE_LEN {
+        return Err(invalid("Truncated encrypted replay".to_string()));
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let plaintext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| {
+            invalid("Replay decryption failed (wrong key or modified file)".to_string())
+        })?;
+    decode_replay_auto(&plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::recorded_match;
+    use crate::{VerifyOptions, verify_replay};
+
+    #[test]
+    fn test_encrypted_replay_roundtrip_and_tamper() {
+        let artifact = recorded_match(30, 1, 0).artifact;
+        let key = [7u8; REPLAY_KEY_LEN];
+        let dir = std::env::temp_dir().join(format!("flowstate-encrypted-{}", std::process::id()));
+        let path = dir.join("m.replay");
+        write_replay_encrypted(&artifact, &path, &key).unwrap();
+        let decrypted = read_replay_encrypted(&path, &key).unwrap();
+        assert_eq!(decrypted, artifact);
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(verify_replay(&decrypted, &options).is_ok());
+
+        // Plain readers name the format instead of misparsing it
+        let bytes = fs::read(&path).unwrap();
+        let err = crate::read_replay_auto(&path).unwrap_err();
+        assert!(err.to_string().contains("encrypted"), "{err}");
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Fresh nonce per file
+        assert_ne!(encrypt_replay(&artifact, &key).unwrap(), bytes);
+
+        let wrong_key = decrypt_replay(&bytes, &[8u8; REPLAY_KEY_LEN]).unwrap_err();
+        assert_eq!(wrong_key.kind(), io::ErrorKind::InvalidData);
+        for index in [HEADER_LEN + 1, bytes.len() - 1] {
+            let mut tampered = bytes.clone();
+            tampered[index] ^= 1;
+            assert!(decrypt_replay(&tampered, &key).is_err());
+        }
+        let mut other_version = bytes.clone();
+        other_version[ENCRYPTED_MAGIC.len()] = ENCRYPTED_VERSION + 1;
+        assert!(decrypt_replay(&other_version, &key).is_err());
+        assert!(decrypt_replay(&bytes[..HEADER_LEN + 4], &key).is_err());
+        assert!(decrypt_replay(&ENCRYPTED_MAGIC, &key).is_err());
+    }
+    #[test]
+    fn test_encrypted_replay_rejects_truncated_and_unknown_versions() {
+        let artifact = recorded_match(10, 1, 0).artifact;
+        let key = [7u8; REPLAY_KEY_LEN];
+        let bytes = encrypt_replay(&artifact, &key).unwrap();
+        let message = |data: &[u8]| {
+            let err = decrypt_replay(data, &key).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            err.to_string()
+        };
+
+        assert_eq!(message(b"FSRZ\x01"), "Not an encrypted replay");
+        assert_eq!(message(&ENCRYPTED_MAGIC), "Truncated encrypted replay");
+        assert_eq!(
+            message(&bytes[..HEADER_LEN + NONCE_LEN - 1]),
+            "Truncated encrypted replay"
+        );
+        // A cut inside the ciphertext or tag fails authentication
+        for len in [HEADER_LEN + NONCE_LEN, bytes.len() - 1] {
+            assert!(message(&bytes[..len]).contains("decryption failed"));
+        }
+        for version in [0, ENCRYPTED_VERSION + 1, u8::MAX] {
+            let mut other = bytes.clone();
+            other[ENCRYPTED_MAGIC.len()] = version;
+            assert_eq!(
+                message(&other),
+                format!("Unsupported encrypted replay version {version}")
+            );
+        }
+
+        // Files truncated on disk read back the same way
+        let dir = std::env::temp_dir().join(format!(
+            "flowstate-encrypted-truncated-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("m.replay");
+        fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        let err = read_replay_encrypted(&path, &key).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
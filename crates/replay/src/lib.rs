@@ -6,7 +6,16 @@
 //!
 //! The replay system consists of:
 //! - `ReplayRecorder`: Collects AppliedInputs during a match
-//! - `ReplayVerifier`: Verifies replay artifacts produce identical outcomes
+//! - `ReplayVerifier`: Verifies replay artifacts produce identical outcomes, reporting
+//!   warnings and tick/digest counts via `VerifyReport`
+//! - `corpus`: Verifies many replay files in parallel
+//! - `diff`: Compares two artifacts' setup, inputs, and resimulated digests
+//! - `divergence`: Locates where a replay that fails verification diverged
+//! - `encrypted`: Replay files encrypted with a caller-provided key
+//! - `playback`: Steps through a replay tick by tick, yielding each Snapshot
+//! - `live`: Streams the record to sinks during a match, with an incremental follower
+//! - `segmented`: Indexed replay files that seek to a tick without resimulating the whole match
+//! - `spill`: Moves recorded inputs to disk during long matches
 //! - Build fingerprint acquisition for same-build verification scope
 //!
 //! # References
@@ -18,20 +27,40 @@
 
 #![deny(unsafe_code)]
 
-use std::collections::HashMap;
+pub mod corpus;
+pub mod diff;
+pub mod divergence;
+pub mod encrypted;
+pub mod live;
+pub mod playback;
+pub mod segmented;
+pub mod spill;
+#[cfg(test)]
+mod test_support;
+
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::io::{self, Read, Write};
+use std::ops::ControlFlow;
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use flowstate_sim::{
-    self, Baseline, MOVE_SPEED, PlayerId, STATE_DIGEST_ALGO_ID, StepInput, Tick, World,
+    self, Baseline, EntityId, EntitySnapshot, MOVE_SPEED, PlayerId, STATE_DIGEST_ALGO_ID, Snapshot,
+    StepInput, TeamId, Tick, Tuning, World,
 };
+use flowstate_wire::compression::{self, Codec, FrameLimits};
 use flowstate_wire::{
-    AppliedInputProto, BuildFingerprint, EntitySnapshotProto, JoinBaseline, PlayerEntityMapping,
-    ReplayArtifact, TuningParameter,
+    AppliedInputProto, BuildFingerprint, EntityKind, InputAudit, JoinBaseline, MatchEndReason,
+    PlayerEntityMapping, RejectedInput, ReplayArtifact, ReplayMetadata, SetTeamCommand,
+    SimCommandProto, SpawnCharacterCommand, TickDigest, TuningParameter, sim_command_proto,
 };
+use live::{LiveRecord, ReplaySink};
 use prost::Message;
 use sha2::{Digest, Sha256};
+use spill::{InputSpill, SPILL_SEGMENT_INPUTS};
 
 // ============================================================================
 // Applied Input
@@ -79,7 +108,7 @@ impl TryFrom<AppliedInputProto> for AppliedInput {
         }
         Ok(Self {
             tick: proto.tick,
-            player_id: proto.player_id as PlayerId,
+            player_id: PlayerId::try_from(proto.player_id).map_err(|_| "player_id out of range")?,
             move_dir: [proto.move_dir[0], proto.move_dir[1]],
             is_fallback: proto.is_fallback,
         })
@@ -90,24 +119,69 @@ impl TryFrom<AppliedInputProto> for AppliedInput {
 // Replay Recorder
 // ============================================================================
 
+/// `replay_format_version` written by `ReplayRecorder`. Older artifacts are
+/// upgraded by `migrate_artifact`.
+pub const REPLAY_FORMAT_VERSION: u32 = 2;
+
+/// Tuning-parameter key of the Simulation Core's `MOVE_SPEED`.
+pub const TUNING_KEY_MOVE_SPEED: &str = "move_speed";
+
+/// Tuning-parameter keys the verifier understands: the Simulation Core's
+/// `move_speed` and the Server Edge's LastKnownIntent decay policy. Others
+/// fail with `VerifyError::UnknownTuningParameter`.
+pub const KNOWN_TUNING_KEYS: [&str; 3] = [
+    TUNING_KEY_MOVE_SPEED,
+    "lki_decay_after_ticks",
+    "lki_decay_ramp_ticks",
+];
+
+/// Oldest `replay_format_version` the verifier accepts.
+pub const MIN_REPLAY_FORMAT_VERSION: u32 = 1;
+
 /// Configuration for replay recording.
 #[derive(Debug, Clone)]
 pub struct ReplayConfig {
+    /// MatchId (DM-0021), recorded in the artifact.
+    pub match_id: String,
     pub seed: u64,
     pub tick_rate_hz: u32,
     pub rng_algorithm: String,
     pub test_mode: bool,
     pub test_player_ids: Vec<PlayerId>,
+    /// Server Edge policies that shape the AppliedInput stream, recorded as
+    /// tuning parameters next to `move_speed`.
+    pub tuning: BTreeMap<String, f64>,
+    /// Dropped InputCmds kept in the artifact's `input_audit` (0 = no audit
+    /// track); drops past the limit are only counted.
+    pub rejected_input_limit: usize,
+}
+
+impl ReplayConfig {
+    /// `tuning` plus `move_speed` (`MOVE_SPEED` unless `tuning` sets it),
+    /// sorted by key, as recorded in the artifact.
+    pub fn tuning_parameters(&self) -> Vec<TuningParameter> {
+        let mut tuning = self.tuning.clone();
+        tuning
+            .entry(TUNING_KEY_MOVE_SPEED.to_string())
+            .or_insert(MOVE_SPEED);
+        tuning
+            .into_iter()
+            .map(|(key, value)| TuningParameter { key, value })
+            .collect()
+    }
 }
 
 impl Default for ReplayConfig {
     fn default() -> Self {
         Self {
+            match_id: String::new(),
             seed: 0,
             tick_rate_hz: 60,
             rng_algorithm: "none".to_string(), // v0 doesn't use RNG in movement
             test_mode: false,
             test_player_ids: Vec::new(),
+            tuning: BTreeMap::new(),
+            rejected_input_limit: 0,
         }
     }
 }
@@ -118,9 +192,21 @@ pub struct ReplayRecorder {
     config: ReplayConfig,
     entity_spawn_order: Vec<PlayerId>,
     player_entity_mapping: Vec<(PlayerId, flowstate_sim::EntityId)>,
+    teams: BTreeMap<PlayerId, TeamId>,
     initial_baseline: Option<Baseline>,
+    /// Inputs not yet spilled (all of them when not spilling).
     inputs: Vec<AppliedInput>,
+    spill: Option<InputSpill>,
+    spill_error: Option<io::Error>,
+    /// Intermediate digests, in tick order.
+    digests: Vec<(Tick, u64)>,
+    /// Full World states to check entity by entity, in tick order.
+    snapshot_anchors: Vec<Baseline>,
+    metadata: Option<ReplayMetadata>,
+    /// Dropped InputCmds, up to `ReplayConfig::rejected_input_limit`.
+    input_audit: InputAudit,
     build_fingerprint: Option<BuildFingerprintData>,
+    sinks: Vec<Box<dyn ReplaySink>>,
 }
 
 /// Build fingerprint data.
@@ -139,9 +225,49 @@ impl ReplayRecorder {
             config,
             entity_spawn_order: Vec::new(),
             player_entity_mapping: Vec::new(),
+            teams: BTreeMap::new(),
             initial_baseline: None,
             inputs: Vec::new(),
+            spill: None,
+            spill_error: None,
+            digests: Vec::new(),
+            snapshot_anchors: Vec::new(),
+            metadata: None,
+            input_audit: InputAudit::default(),
             build_fingerprint: None,
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Stream recorded inputs to a spill file at `path` (see `spill`), so
+    /// memory stays bounded however long the match runs. Inputs recorded
+    /// so far move to the file too; later matches after `rotate` reuse it.
+    ///
+    /// Building an artifact fails with the I/O error if the spilled inputs
+    /// cannot be read back.
+    pub fn spill_to(&mut self, path: &Path) -> io::Result<()> {
+        let mut spill = InputSpill::create(path)?;
+        spill.append(&self.inputs)?;
+        self.inputs.clear();
+        self.spill = Some(spill);
+        self.spill_error = None;
+        Ok(())
+    }
+
+    /// The write error that stopped spilling, if any. Recording continued
+    /// in memory.
+    pub fn spill_error(&self) -> Option<&io::Error> {
+        self.spill_error.as_ref()
+    }
+
+    /// Attach a live sink. Records made before attaching are not replayed to it.
+    pub fn add_sink(&mut self, sink: Box<dyn ReplaySink>) {
+        self.sinks.push(sink);
+    }
+
+    fn emit(&mut self, record: LiveRecord) {
+        for sink in &mut self.sinks {
+            sink.on_record(&record);
         }
     }
 
@@ -151,14 +277,103 @@ impl ReplayRecorder {
         self.player_entity_mapping.push((player_id, entity_id));
     }
 
+    /// Forget recorded spawns, e.g. when the Server Edge rebuilds its World
+    /// after dropping a session before match start.
+    ///
+    /// # Panics
+    /// If the baseline has already been recorded.
+    pub fn clear_spawns(&mut self) {
+        assert!(
+            self.initial_baseline.is_none(),
+            "Spawns are fixed once the baseline is recorded"
+        );
+        self.entity_spawn_order.clear();
+        self.player_entity_mapping.clear();
+        self.teams.clear();
+    }
+
+    /// Record a player's team (stored alongside its entity mapping).
+    pub fn record_team(&mut self, player_id: PlayerId, team: TeamId) {
+        self.teams.insert(player_id, team);
+    }
+
     /// Record the initial baseline.
     pub fn record_baseline(&mut self, baseline: Baseline) {
+        if !self.sinks.is_empty() {
+            self.emit(LiveRecord::Start {
+                seed: self.config.seed,
+                tick_rate_hz: self.config.tick_rate_hz,
+                tuning: self.config.tuning_parameters(),
+                spawns: self.player_entity_mapping.clone(),
+                baseline: baseline.clone(),
+            });
+        }
         self.initial_baseline = Some(baseline);
     }
 
     /// Record an applied input.
     pub fn record_input(&mut self, input: AppliedInput) {
+        if !self.sinks.is_empty() {
+            self.emit(LiveRecord::Input(input.clone()));
+        }
         self.inputs.push(input);
+        if self.spill_error.is_none()
+            && self.inputs.len() >= SPILL_SEGMENT_INPUTS
+            && let Some(spill) = &mut self.spill
+        {
+            match spill.append(&self.inputs) {
+                Ok(()) => self.inputs.clear(),
+                Err(e) => self.spill_error = Some(e),
+            }
+        }
+    }
+
+    /// Record an InputCmd the Server Edge dropped in the audit track. Kept
+    /// only while `ReplayConfig::rejected_input_limit` allows; later drops
+    /// are counted as omitted. Never affects verification.
+    pub fn record_rejected_input(&mut self, rejected: RejectedInput) {
+        let audit = &mut self.input_audit;
+        if audit.rejected.len() < self.config.rejected_input_limit {
+            audit.rejected.push(rejected);
+        } else {
+            audit.omitted += 1;
+        }
+    }
+
+    /// Set the descriptive metadata carried by every artifact built from now
+    /// on. It is not a simulation input, so it never affects verification.
+    pub fn set_metadata(&mut self, metadata: ReplayMetadata) {
+        self.metadata = Some(metadata);
+    }
+
+    /// Metadata set by `set_metadata` (or restored by `resume`).
+    pub fn metadata(&self) -> Option<&ReplayMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Record a StateDigest checkpoint: stored in the artifact's
+    /// `checkpoint_digests` (unless it is the final digest) and streamed to
+    /// live sinks.
+    pub fn record_digest(&mut self, tick: Tick, digest: u64) {
+        if !self.sinks.is_empty() {
+            self.emit(LiveRecord::Digest { tick, digest });
+        }
+        if self.digests.last().is_none_or(|&(last, _)| last < tick) {
+            self.digests.push((tick, digest));
+        }
+    }
+
+    /// Record the full World state at `state.tick` as a snapshot anchor,
+    /// which the verifier compares entity by entity. Anchors are kept in tick
+    /// order; one at or before the previous anchor's tick is ignored.
+    pub fn record_snapshot_anchor(&mut self, state: Baseline) {
+        if self
+            .snapshot_anchors
+            .last()
+            .is_none_or(|last| last.tick < state.tick)
+        {
+            self.snapshot_anchors.push(state);
+        }
     }
 
     /// Set the build fingerprint.
@@ -167,52 +382,186 @@ impl ReplayRecorder {
     }
 
     /// Finalize the replay artifact.
+    ///
+    /// Fails only when spilled inputs cannot be read back (see `spill_to`).
     pub fn finalize(
-        self,
+        mut self,
         final_digest: u64,
         checkpoint_tick: Tick,
-        end_reason: &str,
-    ) -> ReplayArtifact {
-        let initial_baseline = self.initial_baseline.map(|b| JoinBaseline {
-            tick: b.tick,
-            entities: b
-                .entities
-                .into_iter()
-                .map(|e| EntitySnapshotProto {
-                    entity_id: e.entity_id,
-                    position: e.position.to_vec(),
-                    velocity: e.velocity.to_vec(),
+        end_reason: MatchEndReason,
+    ) -> io::Result<ReplayArtifact> {
+        self.record_digest(checkpoint_tick, final_digest);
+        self.build_artifact(final_digest, checkpoint_tick, end_reason)
+    }
+
+    /// Finalize the current artifact and start recording a new match under
+    /// `config` (e.g., a rematch). Live sinks and the build fingerprint carry
+    /// over; sinks see the next match begin with its own `Start` record.
+    ///
+    /// The recorder moves on to the next match even when the finished one
+    /// cannot be assembled (spilled inputs unreadable); the error is returned
+    /// in place of its artifact.
+    pub fn rotate(
+        &mut self,
+        config: ReplayConfig,
+        final_digest: u64,
+        checkpoint_tick: Tick,
+        end_reason: MatchEndReason,
+    ) -> io::Result<ReplayArtifact> {
+        self.record_digest(checkpoint_tick, final_digest);
+        let artifact = self.build_artifact(final_digest, checkpoint_tick, end_reason);
+        let sinks = std::mem::take(&mut self.sinks);
+        let build_fingerprint = self.build_fingerprint.take();
+        let spill = self.spill.take();
+        *self = Self::new(config);
+        self.sinks = sinks;
+        self.build_fingerprint = build_fingerprint;
+        if let Some(mut spill) = spill {
+            match spill.clear() {
+                Ok(()) => self.spill = Some(spill),
+                Err(e) => self.spill_error = Some(e),
+            }
+        }
+        artifact
+    }
+
+    /// Artifact covering the inputs recorded so far, without ending recording.
+    ///
+    /// Verifiable like a final artifact when `digest` is the StateDigest at
+    /// `checkpoint_tick` (used for crash-recovery checkpoints). Fails only
+    /// when spilled inputs cannot be read back.
+    pub fn partial_artifact(
+        &self,
+        digest: u64,
+        checkpoint_tick: Tick,
+        end_reason: MatchEndReason,
+    ) -> io::Result<ReplayArtifact> {
+        self.build_artifact(digest, checkpoint_tick, end_reason)
+    }
+
+    /// Rebuild a recorder from a (partial) artifact to continue recording.
+    ///
+    /// Restores spawns, the initial baseline, recorded inputs, digests, and
+    /// snapshot anchors, metadata, the input audit track, the build
+    /// fingerprint, and the MatchId (unless `config` sets one). Live sinks
+    /// are not restored.
+    pub fn resume(
+        mut config: ReplayConfig,
+        artifact: &ReplayArtifact,
+    ) -> Result<Self, VerifyError> {
+        if config.match_id.is_empty() {
+            config.match_id = artifact.match_id.clone();
+        }
+        let baseline = artifact
+            .initial_baseline
+            .clone()
+            .ok_or(VerifyError::MissingBaseline)?;
+        let baseline = Baseline::try_from(baseline).map_err(|e| VerifyError::InvalidFormat {
+            reason: e.to_string(),
+        })?;
+        let inputs = artifact
+            .inputs
+            .iter()
+            .map(|input| {
+                AppliedInput::try_from(input.clone()).map_err(|e| VerifyError::InvalidFormat {
+                    reason: e.to_string(),
                 })
-                .collect(),
-            digest: b.digest,
-        });
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut recorder = Self::new(config);
+        recorder.entity_spawn_order = artifact
+            .entity_spawn_order
+            .iter()
+            .map(|&p| artifact_player_id(p))
+            .collect::<Result<_, _>>()?;
+        recorder.player_entity_mapping = artifact
+            .player_entity_mapping
+            .iter()
+            .map(|m| Ok((artifact_player_id(m.player_id)?, EntityId(m.entity_id))))
+            .collect::<Result<_, VerifyError>>()?;
+        recorder.teams = artifact
+            .player_entity_mapping
+            .iter()
+            .filter_map(|m| Some((artifact_player_id(m.player_id).ok()?, m.team_id? as TeamId)))
+            .collect();
+        recorder.initial_baseline = Some(baseline);
+        recorder.inputs = inputs;
+        recorder.metadata = artifact.metadata.clone();
+        recorder.input_audit = artifact.input_audit.clone().unwrap_or_default();
+        recorder.digests = artifact
+            .checkpoint_digests
+            .iter()
+            .map(|d| (d.tick, d.digest))
+            .collect();
+        recorder.snapshot_anchors = artifact
+            .snapshot_anchors
+            .iter()
+            .map(|a| {
+                Baseline::try_from(a.clone()).map_err(|e| VerifyError::InvalidFormat {
+                    reason: e.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        recorder.build_fingerprint =
+            artifact
+                .build_fingerprint
+                .clone()
+                .map(|f| BuildFingerprintData {
+                    binary_sha256: f.binary_sha256,
+                    target_triple: f.target_triple,
+                    profile: f.profile,
+                    git_commit: f.git_commit,
+                });
+        Ok(recorder)
+    }
+
+    fn build_artifact(
+        &self,
+        final_digest: u64,
+        checkpoint_tick: Tick,
+        end_reason: MatchEndReason,
+    ) -> io::Result<ReplayArtifact> {
+        let initial_baseline = self.initial_baseline.clone().map(recorded_baseline);
+        let sim_commands = sim_commands(
+            self.initial_baseline.as_ref().map_or(0, |b| b.tick),
+            self.entity_spawn_order.iter().map(|&p| u32::from(p)),
+            self.teams
+                .iter()
+                .map(|(&p, &t)| (u32::from(p), u32::from(t))),
+        );
 
         let player_entity_mapping: Vec<_> = self
             .player_entity_mapping
             .iter()
             .map(|(pid, eid)| PlayerEntityMapping {
                 player_id: u32::from(*pid),
-                entity_id: *eid,
+                entity_id: (*eid).into(),
+                team_id: self.teams.get(pid).map(|&t| u32::from(t)),
             })
             .collect();
 
-        let tuning_parameters = vec![TuningParameter {
-            key: "move_speed".to_string(),
-            value: MOVE_SPEED,
-        }];
+        let tuning_parameters = self.config.tuning_parameters();
+
+        let mut inputs = match &self.spill {
+            Some(spill) => spill.read_back()?,
+            None => Vec::new(),
+        };
+        inputs.extend(self.inputs.iter().cloned().map(AppliedInputProto::from));
+        let input_chain_hash = input_chain_hash(&inputs);
 
-        let build_fingerprint = self.build_fingerprint.map(|f| BuildFingerprint {
+        let build_fingerprint = self.build_fingerprint.clone().map(|f| BuildFingerprint {
             binary_sha256: f.binary_sha256,
             target_triple: f.target_triple,
             profile: f.profile,
             git_commit: f.git_commit,
         });
 
-        ReplayArtifact {
-            replay_format_version: 1,
+        Ok(ReplayArtifact {
+            replay_format_version: REPLAY_FORMAT_VERSION,
             initial_baseline,
             seed: self.config.seed,
-            rng_algorithm: self.config.rng_algorithm,
+            rng_algorithm: self.config.rng_algorithm.clone(),
             tick_rate_hz: self.config.tick_rate_hz,
             state_digest_algo_id: STATE_DIGEST_ALGO_ID.to_string(),
             entity_spawn_order: self
@@ -222,11 +571,13 @@ impl ReplayRecorder {
                 .collect(),
             player_entity_mapping,
             tuning_parameters,
-            inputs: self.inputs.into_iter().map(Into::into).collect(),
+            inputs,
             build_fingerprint,
             final_digest,
             checkpoint_tick,
-            end_reason: end_reason.to_string(),
+            legacy_end_reason: String::new(),
+            end_reason: end_reason as i32,
+            end_reason_detail: String::new(),
             test_mode: self.config.test_mode,
             test_player_ids: self
                 .config
@@ -234,8 +585,58 @@ impl ReplayRecorder {
                 .iter()
                 .map(|&p| u32::from(p))
                 .collect(),
-        }
+            match_id: self.config.match_id.clone(),
+            checkpoint_digests: self
+                .digests
+                .iter()
+                .take_while(|&&(tick, _)| tick < checkpoint_tick)
+                .map(|&(tick, digest)| TickDigest { tick, digest })
+                .collect(),
+            metadata: self.metadata.clone(),
+            input_chain_hash: Some(input_chain_hash),
+            input_audit: (self.config.rejected_input_limit > 0).then(|| self.input_audit.clone()),
+            snapshot_anchors: self
+                .snapshot_anchors
+                .iter()
+                .take_while(|a| a.tick <= checkpoint_tick)
+                .cloned()
+                .map(recorded_baseline)
+                .collect(),
+            sim_commands,
+        })
+    }
+}
+
+/// A World state as recorded in an artifact. Every v0 entity is a
+/// Character.
+fn recorded_baseline(baseline: Baseline) -> JoinBaseline {
+    let mut baseline = JoinBaseline::from(baseline);
+    for entity in &mut baseline.entities {
+        entity.set_entity_kind(EntityKind::Character);
     }
+    baseline
+}
+
+/// SimCommands applied before `tick`: a Character for each player in spawn
+/// order, then each `(player_id, team_id)` assignment.
+fn sim_commands(
+    tick: Tick,
+    spawn_order: impl IntoIterator<Item = u32>,
+    teams: impl IntoIterator<Item = (u32, u32)>,
+) -> Vec<SimCommandProto> {
+    let spawns = spawn_order.into_iter().map(|player_id| {
+        sim_command_proto::Command::SpawnCharacter(SpawnCharacterCommand { player_id })
+    });
+    let teams = teams.into_iter().map(|(player_id, team_id)| {
+        sim_command_proto::Command::SetTeam(SetTeamCommand { player_id, team_id })
+    });
+    spawns
+        .chain(teams)
+        .map(|command| SimCommandProto {
+            tick,
+            command: Some(command),
+        })
+        .collect()
 }
 
 // ============================================================================
@@ -265,6 +666,43 @@ pub enum VerifyError {
     CheckpointTickMismatch { expected: Tick, actual: Tick },
     /// Invalid replay artifact format.
     InvalidFormat { reason: String },
+    /// Streamed digest mismatch during live following.
+    LiveDigestMismatch {
+        tick: Tick,
+        expected: u64,
+        actual: u64,
+    },
+    /// `replay_format_version` outside
+    /// `MIN_REPLAY_FORMAT_VERSION..=REPLAY_FORMAT_VERSION`.
+    UnsupportedFormatVersion { version: u32 },
+    /// Recorded intermediate digest mismatch; the simulation diverged after
+    /// `since_tick`, the last tick that matched.
+    IntermediateDigestMismatch {
+        tick: Tick,
+        since_tick: Tick,
+        expected: u64,
+        actual: u64,
+    },
+    /// Verification stopped through its `CancellationToken` before `tick`
+    /// was resimulated.
+    Cancelled { tick: Tick },
+    /// The inputs do not hash to the recorded `input_chain_hash`: one was
+    /// removed, added, moved, or altered after recording.
+    InputChainMismatch { expected: u64, actual: u64 },
+    /// A recorded tuning parameter outside `KNOWN_TUNING_KEYS`: the match
+    /// was played with a model this build cannot resimulate.
+    UnknownTuningParameter { key: String },
+    /// A recorded tuning parameter with a value its model does not support.
+    TuningOutOfRange { key: String, value: f64 },
+    /// The resimulated World differs from a recorded snapshot anchor at
+    /// `tick`: `entity_id` is the first entity (by id) that is missing from
+    /// either state or has a different position or velocity.
+    SnapshotAnchorMismatch {
+        tick: Tick,
+        entity_id: EntityId,
+        expected: Option<EntitySnapshot>,
+        actual: Option<EntitySnapshot>,
+    },
 }
 
 impl std::fmt::Display for VerifyError {
@@ -311,6 +749,63 @@ impl std::fmt::Display for VerifyError {
             Self::InvalidFormat { reason } => {
                 write!(f, "Invalid replay format: {reason}")
             }
+            Self::LiveDigestMismatch {
+                tick,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Live digest mismatch at tick {tick}: expected {expected:#x}, got {actual:#x}"
+                )
+            }
+            Self::UnsupportedFormatVersion { version } => {
+                write!(
+                    f,
+                    "Unsupported replay_format_version {version} (supported: {MIN_REPLAY_FORMAT_VERSION} through {REPLAY_FORMAT_VERSION})"
+                )
+            }
+            Self::IntermediateDigestMismatch {
+                tick,
+                since_tick,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Digest mismatch at tick {tick} (diverged after tick {since_tick}): expected {expected:#x}, got {actual:#x}"
+                )
+            }
+            Self::Cancelled { tick } => write!(f, "Verification cancelled at tick {tick}"),
+            Self::InputChainMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Input chain hash mismatch: expected {expected:#x}, got {actual:#x}"
+                )
+            }
+            Self::UnknownTuningParameter { key } => {
+                write!(f, "Unknown tuning parameter {key:?}")
+            }
+            Self::TuningOutOfRange { key, value } => {
+                write!(f, "Tuning parameter {key:?} out of range: {value}")
+            }
+            Self::SnapshotAnchorMismatch {
+                tick,
+                entity_id,
+                expected,
+                actual,
+            } => {
+                let describe = |e: &Option<EntitySnapshot>| match e {
+                    Some(e) => format!("position {:?} velocity {:?}", e.position, e.velocity),
+                    None => "absent".to_string(),
+                };
+                write!(
+                    f,
+                    "Snapshot anchor mismatch at tick {tick}, entity {entity_id}: expected {}, got {}",
+                    describe(expected),
+                    describe(actual)
+                )
+            }
         }
     }
 }
@@ -326,6 +821,11 @@ pub struct VerifyOptions {
     pub strict_build_check: bool,
     /// Current build fingerprint for comparison.
     pub current_build: Option<BuildFingerprintData>,
+    /// Keep checking after a failure where the remaining steps still mean
+    /// something, listing every problem in `VerifyReport::errors`.
+    /// - true: collect all problems (debugging)
+    /// - false: stop at the first failure
+    pub collect_all_errors: bool,
 }
 
 impl Default for VerifyOptions {
@@ -333,6 +833,101 @@ impl Default for VerifyOptions {
         Self {
             strict_build_check: true,
             current_build: None,
+            collect_all_errors: false,
+        }
+    }
+}
+
+/// Stops a running verification from another thread.
+///
+/// Clones share the same flag, so a UI or CI wrapper can keep one handle and
+/// pass another in `VerifyHooks`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every verification holding this token to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress reporting and cancellation for long verifications.
+#[derive(Default)]
+pub struct VerifyHooks<'a> {
+    /// Called with `(ticks_done, ticks_total)` before the first tick and
+    /// after every resimulated tick.
+    pub progress: Option<&'a mut dyn FnMut(u64, u64)>,
+    /// Checked before every tick; once cancelled, verification stops with
+    /// `VerifyError::Cancelled`, even when collecting all errors.
+    pub cancel: Option<CancellationToken>,
+}
+
+/// Non-fatal verification finding.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyWarning {
+    /// Build fingerprint mismatch, tolerated because
+    /// `VerifyOptions::strict_build_check` is off.
+    BuildMismatch { expected: String, actual: String },
+}
+
+impl std::fmt::Display for VerifyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BuildMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Build mismatch (not strict): recorded {expected}, current {actual}"
+                )
+            }
+        }
+    }
+}
+
+/// Everything a verification run found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyReport {
+    /// Pass, or the first failure.
+    pub outcome: Result<(), VerifyError>,
+    /// Every failure found, in step order: at most one unless
+    /// `VerifyOptions::collect_all_errors` is set.
+    pub errors: Vec<VerifyError>,
+    /// Non-fatal findings, in the order found.
+    pub warnings: Vec<VerifyWarning>,
+    /// Ticks resimulated (fewer than recorded when verification failed early).
+    pub ticks_simulated: u64,
+    /// Recorded digests checked: intermediate ones plus the final digest.
+    pub digests_checked: usize,
+    /// Snapshot anchors compared entity by entity.
+    pub anchors_checked: usize,
+    /// Wall-clock time the run took.
+    pub elapsed: Duration,
+}
+
+impl VerifyReport {
+    /// True when the replay verified.
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
+
+    /// Record `error`; breaks unless `options.collect_all_errors` asks to
+    /// keep going.
+    fn fail(&mut self, options: &VerifyOptions, error: VerifyError) -> ControlFlow<()> {
+        self.errors.push(error);
+        if options.collect_all_errors {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
         }
     }
 }
@@ -340,135 +935,613 @@ impl Default for VerifyOptions {
 /// Verify a replay artifact produces the recorded outcome.
 /// Ref: INV-0006, T0.9
 ///
+/// Pass/fail only; `verify_replay_report` also returns warnings and
+/// statistics.
+pub fn verify_replay(
+    artifact: &ReplayArtifact,
+    options: &VerifyOptions,
+) -> Result<(), VerifyError> {
+    verify_replay_report(artifact, options).outcome
+}
+
+/// Verify a replay artifact, reporting the outcome with non-fatal warnings,
+/// tick and digest counts, and timing.
+/// Ref: INV-0006, T0.9
+///
+/// Every supported `replay_format_version` verifies as recorded; no fields
+/// that affect the outcome changed between versions, so migrating first is
+/// not required.
+///
 /// # Verification Steps (per spec):
+/// 0. Reject an unsupported `replay_format_version`
 /// 1. Verify build fingerprint matches (strict mode: fail; dev mode: warn)
-/// 2. Validate AppliedInput stream integrity
-/// 3. Initialize World with recorded seed and tick_rate_hz
+///    and every tuning parameter is known and in range
+/// 2. Validate AppliedInput stream integrity and the recorded
+///    `input_chain_hash`
+/// 3. Initialize World with recorded seed, tick_rate_hz, and tuning
 /// 4. Reconstruct initialization (spawn order, verify entity IDs)
 /// 5. Verify baseline digest (initialization anchor)
-/// 6. Replay ticks [initial_baseline.tick, checkpoint_tick)
+/// 6. Replay ticks [initial_baseline.tick, checkpoint_tick), comparing each
+///    snapshot anchor entity by entity and checking each recorded digest
 /// 7. Assert world.tick() == checkpoint_tick
 /// 8. Assert world.state_digest() == final_digest
-pub fn verify_replay(
+pub fn verify_replay_report(artifact: &ReplayArtifact, options: &VerifyOptions) -> VerifyReport {
+    verify_replay_with_hooks(artifact, options, VerifyHooks::default())
+}
+
+/// `verify_replay_report`, reporting progress and honoring cancellation
+/// through `hooks`.
+pub fn verify_replay_with_hooks(
     artifact: &ReplayArtifact,
     options: &VerifyOptions,
-) -> Result<(), VerifyError> {
-    // Step 1: Verify build fingerprint
-    if let (Some(recorded), Some(current)) = (&artifact.build_fingerprint, &options.current_build) {
-        let mismatch = recorded.binary_sha256 != current.binary_sha256
-            || recorded.target_triple != current.target_triple
-            || recorded.profile != current.profile;
-        if mismatch && options.strict_build_check {
-            return Err(VerifyError::BuildMismatch {
-                expected: recorded.binary_sha256.clone(),
-                actual: current.binary_sha256.clone(),
-            });
-        }
-        // In non-strict mode, we'd log a warning here (not implemented for v0)
+    mut hooks: VerifyHooks<'_>,
+) -> VerifyReport {
+    let started = Instant::now();
+    let mut report = VerifyReport {
+        outcome: Ok(()),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+        ticks_simulated: 0,
+        digests_checked: 0,
+        anchors_checked: 0,
+        elapsed: Duration::ZERO,
+    };
+    let _ = run_verification(artifact, options, &mut hooks, &mut report);
+    if let Some(first) = report.errors.first() {
+        report.outcome = Err(first.clone());
     }
+    report.elapsed = started.elapsed();
+    report
+}
 
-    // Step 2: Validate input stream integrity
-    validate_input_stream(artifact)?;
+fn run_verification(
+    artifact: &ReplayArtifact,
+    options: &VerifyOptions,
+    hooks: &mut VerifyHooks<'_>,
+    report: &mut VerifyReport,
+) -> ControlFlow<()> {
+    // Step 0: Verify format version (nothing else can be checked without it)
+    if let Err(e) = check_format_version(artifact) {
+        report.errors.push(e);
+        return ControlFlow::Break(());
+    }
 
-    // Get initial baseline
-    let baseline_proto = artifact
-        .initial_baseline
-        .as_ref()
-        .ok_or(VerifyError::MissingBaseline)?;
+    // Step 1: Verify build fingerprint and tuning parameters
+    match check_build_fingerprint(artifact, options) {
+        Ok(warning) => report.warnings.extend(warning),
+        Err(e) => report.fail(options, e)?,
+    }
+    let (tuning, tuning_problems) = read_tuning(&artifact.tuning_parameters);
+    let tuning_ok = tuning_problems.is_empty();
+    for e in tuning_problems {
+        report.fail(options, e)?;
+    }
 
-    let initial_tick = baseline_proto.tick;
+    // Step 2: Validate input stream integrity
+    let Some(baseline) = artifact.initial_baseline.as_ref() else {
+        report.errors.push(VerifyError::MissingBaseline);
+        return ControlFlow::Break(());
+    };
+    for e in input_stream_problems(artifact, baseline.tick) {
+        report.fail(options, e)?;
+    }
+    if let Some(expected) = artifact.input_chain_hash {
+        let actual = input_chain_hash(&artifact.inputs);
+        if actual != expected {
+            report.fail(
+                options,
+                VerifyError::InputChainMismatch { expected, actual },
+            )?;
+        }
+    }
+    let initial_tick = baseline.tick;
     let checkpoint_tick = artifact.checkpoint_tick;
 
-    // Step 3: Initialize World
-    let mut world = World::new(artifact.seed, artifact.tick_rate_hz);
-
-    // Step 4: Reconstruct initialization (spawn order)
-    let player_entity_map: HashMap<u32, flowstate_sim::EntityId> = artifact
-        .player_entity_mapping
-        .iter()
-        .map(|m| (m.player_id, m.entity_id))
-        .collect();
-
-    for &player_id_u32 in &artifact.entity_spawn_order {
-        let player_id = player_id_u32 as PlayerId;
-        let actual_entity_id = world.spawn_character(player_id);
-
-        if let Some(&expected_entity_id) = player_entity_map.get(&player_id_u32)
-            && actual_entity_id != expected_entity_id
-        {
-            return Err(VerifyError::SpawnReconstructionMismatch {
-                player_id,
-                expected_entity_id,
-                actual_entity_id,
-            });
+    // Steps 3-5: Initialize World, reconstruct spawns, check the anchor.
+    // Once a digest differs, later digests are not compared: the states
+    // they cover differ as a consequence.
+    if !tuning_ok {
+        // Nothing can be resimulated without the recorded model
+        return ControlFlow::Break(());
+    }
+    let (mut world, spawn_mismatches) = match spawn_recorded(artifact, tuning) {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            report.errors.push(e);
+            return ControlFlow::Break(());
         }
+    };
+    for e in spawn_mismatches {
+        report.fail(options, e)?;
     }
-
-    // Step 5: Verify initialization anchor (baseline digest)
-    let baseline = world.baseline();
-    if baseline.digest != baseline_proto.digest {
-        return Err(VerifyError::InitializationAnchorMismatch {
-            expected: baseline_proto.digest,
-            actual: baseline.digest,
-        });
+    let anchor = world.baseline().digest;
+    let mut digests_match = anchor == baseline.digest;
+    if !digests_match {
+        report.fail(
+            options,
+            VerifyError::InitializationAnchorMismatch {
+                expected: baseline.digest,
+                actual: anchor,
+            },
+        )?;
     }
+    let (inputs_by_tick, checkpoint_digests, snapshot_anchors) = match (
+        inputs_by_tick(&artifact.inputs),
+        checkpoint_digests(artifact, initial_tick),
+        snapshot_anchors(artifact, initial_tick),
+    ) {
+        (Ok(inputs), Ok(digests), Ok(anchors)) => (inputs, digests, anchors),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            report.errors.push(e);
+            return ControlFlow::Break(());
+        }
+    };
 
-    // Convert inputs to lookup map: tick -> Vec<AppliedInput>
-    let mut inputs_by_tick: HashMap<Tick, Vec<AppliedInput>> = HashMap::new();
-    for input_proto in &artifact.inputs {
-        let input: AppliedInput =
-            input_proto
-                .clone()
-                .try_into()
-                .map_err(|e: &str| VerifyError::InvalidFormat {
-                    reason: e.to_string(),
-                })?;
-        inputs_by_tick.entry(input.tick).or_default().push(input);
+    // Step 6: Replay ticks [initial_tick, checkpoint_tick), checking each
+    // recorded snapshot anchor and intermediate digest on the way
+    let ticks_total = checkpoint_tick.saturating_sub(initial_tick);
+    if let Some(progress) = hooks.progress.as_mut() {
+        progress(0, ticks_total);
     }
-
-    // Step 6: Replay ticks [initial_tick, checkpoint_tick)
+    let mut since_tick = initial_tick;
     for tick in initial_tick..checkpoint_tick {
-        let mut step_inputs: Vec<StepInput> = inputs_by_tick
-            .get(&tick)
-            .map(|inputs| inputs.iter().map(AppliedInput::to_step_input).collect())
-            .unwrap_or_default();
-
-        // Sort by player_id (INV-0007) - defense in depth, verifier canonicalizes
-        step_inputs.sort_by_key(|i| i.player_id);
-
-        let _ = world.advance(tick, &step_inputs);
+        if hooks.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            report.errors.push(VerifyError::Cancelled { tick });
+            return ControlFlow::Break(());
+        }
+        advance_recorded(&mut world, tick, &inputs_by_tick);
+        report.ticks_simulated += 1;
+        if let Some(progress) = hooks.progress.as_mut() {
+            progress(report.ticks_simulated, ticks_total);
+        }
+        if let Some(anchor) = snapshot_anchors.get(&world.tick())
+            && digests_match
+        {
+            report.anchors_checked += 1;
+            if let Some(e) = anchor_mismatch(&world, anchor, since_tick) {
+                digests_match = false;
+                report.fail(options, e)?;
+            } else {
+                since_tick = world.tick();
+            }
+        }
+        if let Some(&expected) = checkpoint_digests.get(&world.tick())
+            && digests_match
+        {
+            report.digests_checked += 1;
+            let actual = world.state_digest();
+            if actual != expected {
+                digests_match = false;
+                report.fail(
+                    options,
+                    VerifyError::IntermediateDigestMismatch {
+                        tick: world.tick(),
+                        since_tick,
+                        expected,
+                        actual,
+                    },
+                )?;
+            }
+            since_tick = world.tick();
+        }
     }
 
     // Step 7: Verify checkpoint tick
     if world.tick() != checkpoint_tick {
-        return Err(VerifyError::CheckpointTickMismatch {
-            expected: checkpoint_tick,
-            actual: world.tick(),
-        });
+        report.fail(
+            options,
+            VerifyError::CheckpointTickMismatch {
+                expected: checkpoint_tick,
+                actual: world.tick(),
+            },
+        )?;
     }
 
     // Step 8: Verify final digest
-    let actual_digest = world.state_digest();
-    if actual_digest != artifact.final_digest {
-        return Err(VerifyError::FinalDigestMismatch {
-            expected: artifact.final_digest,
-            actual: actual_digest,
-        });
+    if digests_match {
+        report.digests_checked += 1;
+        let actual_digest = world.state_digest();
+        if actual_digest != artifact.final_digest {
+            report.fail(
+                options,
+                VerifyError::FinalDigestMismatch {
+                    expected: artifact.final_digest,
+                    actual: actual_digest,
+                },
+            )?;
+        }
     }
 
-    Ok(())
+    ControlFlow::Continue(())
 }
 
-/// Validate the input stream integrity.
-/// Ref: INV-0006 AppliedInput stream validation
+/// The World once the replay has advanced to `tick`, resimulated from the
+/// initial baseline; `None` when `tick` is outside
+/// `[initial_baseline.tick, checkpoint_tick]`.
+pub fn world_at(artifact: &ReplayArtifact, tick: Tick) -> Result<Option<World>, VerifyError> {
+    validate_input_stream(artifact)?;
+    let mut world = initial_world(artifact)?;
+    if tick < world.tick() || tick > artifact.checkpoint_tick {
+        return Ok(None);
+    }
+    let inputs_by_tick = inputs_by_tick(&artifact.inputs)?;
+    for t in world.tick()..tick {
+        advance_recorded(&mut world, t, &inputs_by_tick);
+    }
+    Ok(Some(world))
+}
+
+/// Reject artifacts written by a newer (or unknown) format.
+fn check_format_version(artifact: &ReplayArtifact) -> Result<(), VerifyError> {
+    let version = artifact.replay_format_version;
+    if !(MIN_REPLAY_FORMAT_VERSION..=REPLAY_FORMAT_VERSION).contains(&version) {
+        return Err(VerifyError::UnsupportedFormatVersion { version });
+    }
+    Ok(())
+}
+
+/// Compare the recorded build fingerprint with the current build; a
+/// mismatch fails in strict mode and is a warning otherwise.
+fn check_build_fingerprint(
+    artifact: &ReplayArtifact,
+    options: &VerifyOptions,
+) -> Result<Option<VerifyWarning>, VerifyError> {
+    if let (Some(recorded), Some(current)) = (&artifact.build_fingerprint, &options.current_build) {
+        let mismatch = recorded.binary_sha256 != current.binary_sha256
+            || recorded.target_triple != current.target_triple
+            || recorded.profile != current.profile;
+        if mismatch && options.strict_build_check {
+            return Err(VerifyError::BuildMismatch {
+                expected: recorded.binary_sha256.clone(),
+                actual: current.binary_sha256.clone(),
+            });
+        }
+        if mismatch {
+            return Ok(Some(VerifyWarning::BuildMismatch {
+                expected: recorded.binary_sha256.clone(),
+                actual: current.binary_sha256.clone(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Recorded intermediate digests by tick, each checked to lie in
+/// `(initial_tick, checkpoint_tick]`.
+fn checkpoint_digests(
+    artifact: &ReplayArtifact,
+    initial_tick: Tick,
+) -> Result<BTreeMap<Tick, u64>, VerifyError> {
+    let checkpoint_tick = artifact.checkpoint_tick;
+    let mut digests = BTreeMap::new();
+    for d in &artifact.checkpoint_digests {
+        if d.tick <= initial_tick || d.tick > checkpoint_tick {
+            return Err(VerifyError::InvalidFormat {
+                reason: format!(
+                    "Checkpoint digest at tick {} is outside ({initial_tick}, {checkpoint_tick}]",
+                    d.tick
+                ),
+            });
+        }
+        digests.insert(d.tick, d.digest);
+    }
+    Ok(digests)
+}
+
+/// Recorded snapshot anchors by tick, each within
+/// `(initial_tick, checkpoint_tick]`.
+fn snapshot_anchors(
+    artifact: &ReplayArtifact,
+    initial_tick: Tick,
+) -> Result<BTreeMap<Tick, Baseline>, VerifyError> {
+    let checkpoint_tick = artifact.checkpoint_tick;
+    let mut anchors = BTreeMap::new();
+    for anchor in &artifact.snapshot_anchors {
+        if anchor.tick <= initial_tick || anchor.tick > checkpoint_tick {
+            return Err(VerifyError::InvalidFormat {
+                reason: format!(
+                    "Snapshot anchor at tick {} is outside ({initial_tick}, {checkpoint_tick}]",
+                    anchor.tick
+                ),
+            });
+        }
+        let anchor =
+            Baseline::try_from(anchor.clone()).map_err(|e| VerifyError::InvalidFormat {
+                reason: e.to_string(),
+            })?;
+        anchors.insert(anchor.tick, anchor);
+    }
+    Ok(anchors)
+}
+
+/// How `world` differs from the snapshot anchor at its tick: the first
+/// differing entity, or else a differing digest; `None` when they agree.
+fn anchor_mismatch(world: &World, anchor: &Baseline, since_tick: Tick) -> Option<VerifyError> {
+    let state = world.baseline();
+    let entity = |entities: &[EntitySnapshot], entity_id| {
+        entities.iter().find(|e| e.entity_id == entity_id).cloned()
+    };
+    let entity_ids: BTreeSet<EntityId> = anchor
+        .entities
+        .iter()
+        .chain(&state.entities)
+        .map(|e| e.entity_id)
+        .collect();
+    for entity_id in entity_ids {
+        let expected = entity(&anchor.entities, entity_id);
+        let actual = entity(&state.entities, entity_id);
+        if expected != actual {
+            return Some(VerifyError::SnapshotAnchorMismatch {
+                tick: state.tick,
+                entity_id,
+                expected,
+                actual,
+            });
+        }
+    }
+    (state.digest != anchor.digest).then_some(VerifyError::IntermediateDigestMismatch {
+        tick: state.tick,
+        since_tick,
+        expected: anchor.digest,
+        actual: state.digest,
+    })
+}
+
+/// Simulation Core tuning recorded in the artifact's tuning parameters.
+/// Ref: INV-0006
+///
+/// Fails on the first parameter outside `KNOWN_TUNING_KEYS` or out of range.
+/// An artifact without `move_speed` predates recording it and was played with
+/// `MOVE_SPEED`.
+pub fn recorded_tuning(artifact: &ReplayArtifact) -> Result<Tuning, VerifyError> {
+    tuning_from_parameters(&artifact.tuning_parameters)
+}
+
+/// `recorded_tuning` for tuning parameters recorded outside an artifact
+/// (the live stream's `Start` record).
+pub(crate) fn tuning_from_parameters(
+    parameters: &[TuningParameter],
+) -> Result<Tuning, VerifyError> {
+    let (tuning, mut problems) = read_tuning(parameters);
+    if problems.is_empty() {
+        Ok(tuning)
+    } else {
+        Err(problems.swap_remove(0))
+    }
+}
+
+/// Recorded tuning, with every parameter that is unknown or out of range.
+fn read_tuning(parameters: &[TuningParameter]) -> (Tuning, Vec<VerifyError>) {
+    let mut tuning = Tuning::default();
+    let mut problems = Vec::new();
+    for TuningParameter { key, value } in parameters {
+        let value = *value;
+        let in_range = match key.as_str() {
+            TUNING_KEY_MOVE_SPEED => {
+                let recorded = Tuning { move_speed: value };
+                if recorded.is_valid() {
+                    tuning.move_speed = value;
+                }
+                recorded.is_valid()
+            }
+            // Tick counts; they shaped the recorded inputs, not the model
+            "lki_decay_after_ticks" | "lki_decay_ramp_ticks" => {
+                value >= 0.0 && value.fract() == 0.0
+            }
+            _ => {
+                problems.push(VerifyError::UnknownTuningParameter { key: key.clone() });
+                continue;
+            }
+        };
+        if !in_range {
+            problems.push(VerifyError::TuningOutOfRange {
+                key: key.clone(),
+                value,
+            });
+        }
+    }
+    (tuning, problems)
+}
+
+/// World at the artifact's initial baseline, rebuilt from its spawn order
+/// and checked against the baseline digest (the initialization anchor).
+fn initial_world(artifact: &ReplayArtifact) -> Result<World, VerifyError> {
+    let baseline_proto = artifact
+        .initial_baseline
+        .as_ref()
+        .ok_or(VerifyError::MissingBaseline)?;
+    let world = spawned_world(artifact)?;
+    let baseline = world.baseline();
+    if baseline.digest != baseline_proto.digest {
+        return Err(VerifyError::InitializationAnchorMismatch {
+            expected: baseline_proto.digest,
+            actual: baseline.digest,
+        });
+    }
+    Ok(world)
+}
+
+/// World rebuilt from the artifact's spawn order, each spawn checked
+/// against the recorded player-entity mapping.
+fn spawned_world(artifact: &ReplayArtifact) -> Result<World, VerifyError> {
+    let (world, mut mismatches) = spawn_recorded(artifact, recorded_tuning(artifact)?)?;
+    if mismatches.is_empty() {
+        Ok(world)
+    } else {
+        Err(mismatches.swap_remove(0))
+    }
+}
+
+/// World rebuilt from the artifact's SimCommands, with every spawn that
+/// disagrees with the recorded player-entity mapping.
+///
+/// A version 1 artifact has no SimCommands; its spawn order and teams stand
+/// in for them, as `migrate_v1_to_v2` would record them.
+fn spawn_recorded(
+    artifact: &ReplayArtifact,
+    tuning: Tuning,
+) -> Result<(World, Vec<VerifyError>), VerifyError> {
+    let mut world = World::with_tuning(artifact.seed, artifact.tick_rate_hz, tuning);
+    let mut mismatches = Vec::new();
+
+    let initial_tick = artifact.initial_baseline.as_ref().map_or(0, |b| b.tick);
+    let commands = if artifact.replay_format_version < 2 {
+        v1_sim_commands(artifact)
+    } else {
+        artifact.sim_commands.clone()
+    };
+    let player_entity_map: BTreeMap<u32, flowstate_sim::EntityId> = artifact
+        .player_entity_mapping
+        .iter()
+        .map(|m| (m.player_id, EntityId(m.entity_id)))
+        .collect();
+    let mut spawn_order = Vec::new();
+    for command in &commands {
+        if command.tick != initial_tick {
+            return Err(VerifyError::InvalidFormat {
+                reason: format!(
+                    "SimCommand at tick {} is not at the initial tick {initial_tick}",
+                    command.tick
+                ),
+            });
+        }
+        match &command.command {
+            Some(sim_command_proto::Command::SpawnCharacter(spawn)) => {
+                let player_id = artifact_player_id(spawn.player_id)?;
+                let actual_entity_id = world.spawn_character(player_id);
+                spawn_order.push(spawn.player_id);
+
+                if let Some(&expected_entity_id) = player_entity_map.get(&spawn.player_id)
+                    && actual_entity_id != expected_entity_id
+                {
+                    mismatches.push(VerifyError::SpawnReconstructionMismatch {
+                        player_id,
+                        expected_entity_id,
+                        actual_entity_id,
+                    });
+                }
+            }
+            Some(sim_command_proto::Command::SetTeam(set)) => {
+                let player_id = artifact_player_id(set.player_id)?;
+                let team =
+                    TeamId::try_from(set.team_id).map_err(|_| VerifyError::InvalidFormat {
+                        reason: format!("team_id {} out of range", set.team_id),
+                    })?;
+                if !world.set_team(player_id, team) {
+                    return Err(VerifyError::InvalidFormat {
+                        reason: format!("SetTeam for player {player_id} before its spawn"),
+                    });
+                }
+            }
+            None => {
+                return Err(VerifyError::InvalidFormat {
+                    reason: "SimCommand without a command".to_string(),
+                });
+            }
+        }
+    }
+    if spawn_order != artifact.entity_spawn_order {
+        return Err(VerifyError::InvalidFormat {
+            reason: "SimCommand spawns do not follow entity_spawn_order".to_string(),
+        });
+    }
+    Ok((world, mismatches))
+}
+
+/// Recorded inputs grouped by tick.
+fn inputs_by_tick(
+    inputs: &[AppliedInputProto],
+) -> Result<BTreeMap<Tick, Vec<AppliedInput>>, VerifyError> {
+    let mut by_tick: BTreeMap<Tick, Vec<AppliedInput>> = BTreeMap::new();
+    for input_proto in inputs {
+        let input: AppliedInput =
+            input_proto
+                .clone()
+                .try_into()
+                .map_err(|e: &str| VerifyError::InvalidFormat {
+                    reason: e.to_string(),
+                })?;
+        by_tick.entry(input.tick).or_default().push(input);
+    }
+    Ok(by_tick)
+}
+
+/// Advance `world` through `tick` with that tick's recorded inputs.
+fn advance_recorded(
+    world: &mut World,
+    tick: Tick,
+    inputs_by_tick: &BTreeMap<Tick, Vec<AppliedInput>>,
+) -> Snapshot {
+    let mut step_inputs: Vec<StepInput> = inputs_by_tick
+        .get(&tick)
+        .map(|inputs| inputs.iter().map(AppliedInput::to_step_input).collect())
+        .unwrap_or_default();
+
+    // Sort by player_id (INV-0007) - defense in depth, verifier canonicalizes
+    step_inputs.sort_by_key(|i| i.player_id);
+
+    world.advance(tick, &step_inputs)
+}
+
+/// A PlayerId recorded in an artifact (wire `uint32`), rejected if it does
+/// not fit the Simulation Core's PlayerId.
+fn artifact_player_id(player_id: u32) -> Result<PlayerId, VerifyError> {
+    PlayerId::try_from(player_id).map_err(|_| VerifyError::InvalidFormat {
+        reason: format!("player_id {player_id} out of range"),
+    })
+}
+
+/// Chained hash over `inputs` in canonical `(tick, player_id)` order, as
+/// recorded in `ReplayArtifact::input_chain_hash`.
+///
+/// Each link is FNV-1a 64 over the previous link's value (the FNV offset
+/// basis for the first input) followed by the input's `tick` (u64 LE),
+/// `player_id` (u32 LE), each `move_dir` component's raw f64 bits (LE), and
+/// `is_fallback` (one byte). Raw bits, not ADR-0007's canonical floats: the
+/// hash must notice any edit, `-0.0` for `0.0` included.
+pub fn input_chain_hash(inputs: &[AppliedInputProto]) -> u64 {
+    const FNV1A_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV1A_PRIME: u64 = 0x100000001b3;
+
+    let mut sorted: Vec<&AppliedInputProto> = inputs.iter().collect();
+    sorted.sort_by_key(|i| (i.tick, i.player_id));
+    sorted.into_iter().fold(FNV1A_OFFSET_BASIS, |link, input| {
+        let mut bytes = Vec::with_capacity(21 + 8 * input.move_dir.len());
+        bytes.extend_from_slice(&link.to_le_bytes());
+        bytes.extend_from_slice(&input.tick.to_le_bytes());
+        bytes.extend_from_slice(&input.player_id.to_le_bytes());
+        for component in &input.move_dir {
+            bytes.extend_from_slice(&component.to_bits().to_le_bytes());
+        }
+        bytes.push(u8::from(input.is_fallback));
+        bytes.iter().fold(FNV1A_OFFSET_BASIS, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV1A_PRIME)
+        })
+    })
+}
+
+/// Validate the input stream integrity.
+/// Ref: INV-0006 AppliedInput stream validation
 fn validate_input_stream(artifact: &ReplayArtifact) -> Result<(), VerifyError> {
     let baseline = artifact
         .initial_baseline
         .as_ref()
         .ok_or(VerifyError::MissingBaseline)?;
+    match input_stream_problems(artifact, baseline.tick)
+        .into_iter()
+        .next()
+    {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
 
-    let initial_tick = baseline.tick;
+/// Every input stream integrity problem, missing and duplicate inputs first
+/// (by player, then tick), then out-of-range and unknown-player inputs in
+/// recorded order.
+fn input_stream_problems(artifact: &ReplayArtifact, initial_tick: Tick) -> Vec<VerifyError> {
     let checkpoint_tick = artifact.checkpoint_tick;
+    let mut problems = Vec::new();
 
     // Get player IDs from mapping
     let player_ids: Vec<u32> = artifact
@@ -478,7 +1551,7 @@ fn validate_input_stream(artifact: &ReplayArtifact) -> Result<(), VerifyError> {
         .collect();
 
     // Build a set of (player_id, tick) pairs from inputs
-    let mut input_pairs: HashMap<(u32, Tick), usize> = HashMap::new();
+    let mut input_pairs: BTreeMap<(u32, Tick), usize> = BTreeMap::new();
     for input in &artifact.inputs {
         let key = (input.player_id, input.tick);
         *input_pairs.entry(key).or_insert(0) += 1;
@@ -488,40 +1561,34 @@ fn validate_input_stream(artifact: &ReplayArtifact) -> Result<(), VerifyError> {
     for &player_id in &player_ids {
         for tick in initial_tick..checkpoint_tick {
             let key = (player_id, tick);
-            match input_pairs.get(&key) {
-                None => {
-                    return Err(VerifyError::InputStreamInvalid {
-                        reason: format!("Missing input for player {player_id} at tick {tick}"),
-                    });
-                }
+            let reason = match input_pairs.get(&key) {
+                None => format!("Missing input for player {player_id} at tick {tick}"),
                 Some(&count) if count > 1 => {
-                    return Err(VerifyError::InputStreamInvalid {
-                        reason: format!("Duplicate input for player {player_id} at tick {tick}"),
-                    });
+                    format!("Duplicate input for player {player_id} at tick {tick}")
                 }
-                Some(_) => {}
-            }
+                Some(_) => continue,
+            };
+            problems.push(VerifyError::InputStreamInvalid { reason });
         }
     }
 
     // Verify: no inputs outside the range
     for input in &artifact.inputs {
         if input.tick < initial_tick || input.tick >= checkpoint_tick {
-            return Err(VerifyError::InputStreamInvalid {
+            problems.push(VerifyError::InputStreamInvalid {
                 reason: format!(
                     "Input for player {} at tick {} is outside valid range [{}, {})",
                     input.player_id, input.tick, initial_tick, checkpoint_tick
                 ),
             });
-        }
-        if !player_ids.contains(&input.player_id) {
-            return Err(VerifyError::InputStreamInvalid {
+        } else if !player_ids.contains(&input.player_id) {
+            problems.push(VerifyError::InputStreamInvalid {
                 reason: format!("Input references unknown player_id {}", input.player_id),
             });
         }
     }
 
-    Ok(())
+    problems
 }
 
 // ============================================================================
@@ -626,8 +1693,46 @@ fn get_git_commit() -> Option<String> {
 // Replay I/O
 // ============================================================================
 
+/// Magic bytes opening a compressed replay container.
+///
+/// An encoded ReplayArtifact starts with a field tag, and `F` (0x46) is not
+/// a valid one, so the two formats never collide.
+pub const CONTAINER_MAGIC: [u8; 4] = *b"FSRZ";
+
+/// Container layout version, the byte after `CONTAINER_MAGIC`.
+pub const CONTAINER_VERSION: u8 = 1;
+
+/// Largest decompressed artifact accepted from a container (256 MiB).
+pub const MAX_CONTAINER_RAW_LEN: usize = 256 * 1024 * 1024;
+
 /// Write a replay artifact to a file.
 pub fn write_replay(artifact: &ReplayArtifact, path: &Path) -> io::Result<()> {
+    write_new_replay_file(path, &artifact.encode_to_vec())
+}
+
+/// Write a replay artifact as a zstd-compressed container:
+/// `[CONTAINER_MAGIC][CONTAINER_VERSION]` followed by a
+/// `flowstate_wire::compression` frame (codec, lengths, body) of the
+/// encoded artifact.
+pub fn write_replay_compressed(artifact: &ReplayArtifact, path: &Path) -> io::Result<()> {
+    write_new_replay_file(path, &encode_container(artifact, Codec::Zstd)?)
+}
+
+/// Encode `artifact` in the compressed container format.
+pub fn encode_container(artifact: &ReplayArtifact, codec: Codec) -> io::Result<Vec<u8>> {
+    let limits = FrameLimits {
+        max_raw_len: MAX_CONTAINER_RAW_LEN,
+    };
+    let frame = compression::encode_frame(codec, &artifact.encode_to_vec(), &limits)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut bytes = Vec::with_capacity(CONTAINER_MAGIC.len() + 1 + frame.len());
+    bytes.extend_from_slice(&CONTAINER_MAGIC);
+    bytes.push(CONTAINER_VERSION);
+    bytes.extend_from_slice(&frame);
+    Ok(bytes)
+}
+
+fn write_new_replay_file(path: &Path, bytes: &[u8]) -> io::Result<()> {
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -641,23 +1746,133 @@ pub fn write_replay(artifact: &ReplayArtifact, path: &Path) -> io::Result<()> {
         ));
     }
 
-    // Encode and write
-    let encoded = artifact.encode_to_vec();
     let mut file = fs::File::create(path)?;
-    file.write_all(&encoded)?;
+    file.write_all(bytes)?;
 
     Ok(())
 }
 
-/// Read a replay artifact from a file.
+/// Read a replay artifact from a file, migrated to `REPLAY_FORMAT_VERSION`.
 pub fn read_replay(path: &Path) -> io::Result<ReplayArtifact> {
     let data = fs::read(path)?;
-    ReplayArtifact::decode(data.as_slice()).map_err(|e| {
+    decode_artifact(&data)
+}
+
+/// Read a replay artifact written by `write_replay`,
+/// `write_replay_compressed`, or `segmented::write_replay_segmented`,
+/// migrated to `REPLAY_FORMAT_VERSION`.
+pub fn read_replay_auto(path: &Path) -> io::Result<ReplayArtifact> {
+    decode_replay_auto(&fs::read(path)?)
+}
+
+/// Decode a plain, containerized, or segmented replay artifact.
+pub fn decode_replay_auto(data: &[u8]) -> io::Result<ReplayArtifact> {
+    let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, reason);
+    if data.starts_with(&encrypted::ENCRYPTED_MAGIC) {
+        return Err(invalid(
+            "Replay is encrypted; read it with encrypted::read_replay_encrypted".to_string(),
+        ));
+    }
+    if data.starts_with(&segmented::SEGMENTED_MAGIC) {
+        return segmented::SegmentedReplayReader::new(io::Cursor::new(data))
+            .and_then(|mut reader| reader.to_artifact())
+            .map_err(|e| invalid(e.to_string()));
+    }
+    let Some(rest) = data.strip_prefix(&CONTAINER_MAGIC) else {
+        return decode_artifact(data);
+    };
+    let (&version, frame) = rest
+        .split_first()
+        .ok_or_else(|| invalid("Truncated replay container".to_string()))?;
+    if version != CONTAINER_VERSION {
+        return Err(invalid(format!(
+            "Unsupported replay container version {version}"
+        )));
+    }
+    let limits = FrameLimits {
+        max_raw_len: MAX_CONTAINER_RAW_LEN,
+    };
+    let (encoded, len) = compression::decode_frame(frame, &limits)
+        .map_err(|e| invalid(format!("Failed to decompress replay: {e}")))?;
+    if len != frame.len() {
+        return Err(invalid("Trailing bytes after replay container".to_string()));
+    }
+    decode_artifact(&encoded)
+}
+
+fn decode_artifact(data: &[u8]) -> io::Result<ReplayArtifact> {
+    let mut artifact = ReplayArtifact::decode(data).map_err(|e| {
         io::Error::new(
             io::ErrorKind::InvalidData,
             format!("Failed to decode replay: {e}"),
         )
-    })
+    })?;
+    migrate_artifact(&mut artifact);
+    Ok(artifact)
+}
+
+/// Upgrade an artifact of an older `replay_format_version` in place,
+/// applying each version step in turn. Artifacts of a newer version are
+/// left as they are (the verifier rejects them).
+pub fn migrate_artifact(artifact: &mut ReplayArtifact) {
+    if artifact.replay_format_version <= 1 {
+        migrate_v1_to_v2(artifact);
+    }
+}
+
+/// Version 1 to 2:
+/// - The free-form end reason string becomes the matching `MatchEndReason`,
+///   or `Other` with the string kept in `end_reason_detail`
+/// - `sim_commands` is recorded from the spawn order and the teams in the
+///   player-entity mapping, which is what the version 1 verifier applied
+/// - Baseline and snapshot anchor entities recorded without a kind become
+///   Characters, the only v0 entity kind
+///
+/// Checkpoint digests and snapshot anchors are version 2 fields; version 1
+/// artifacts recorded by servers that already wrote them keep them as they
+/// are. Nothing that affects the outcome changes, so migration never
+/// affects verification.
+pub fn migrate_v1_to_v2(artifact: &mut ReplayArtifact) {
+    let legacy = std::mem::take(&mut artifact.legacy_end_reason);
+    let end_reason = match legacy.as_str() {
+        "complete" => MatchEndReason::Complete,
+        "disconnect" => MatchEndReason::Disconnect,
+        "rematch" => MatchEndReason::Rematch,
+        "checkpoint" => MatchEndReason::Checkpoint,
+        "in_progress" => MatchEndReason::InProgress,
+        "" => MatchEndReason::Unspecified,
+        _ => {
+            artifact.end_reason_detail = legacy;
+            MatchEndReason::Other
+        }
+    };
+    artifact.end_reason = end_reason as i32;
+    if artifact.sim_commands.is_empty() {
+        artifact.sim_commands = v1_sim_commands(artifact);
+    }
+    let baselines = artifact
+        .initial_baseline
+        .iter_mut()
+        .chain(&mut artifact.snapshot_anchors);
+    for entity in baselines.flat_map(|b| &mut b.entities) {
+        if entity.entity_kind() == EntityKind::Unspecified {
+            entity.set_entity_kind(EntityKind::Character);
+        }
+    }
+    artifact.replay_format_version = 2;
+}
+
+/// The SimCommands a version 1 artifact implies: its spawn order, then its
+/// recorded teams.
+fn v1_sim_commands(artifact: &ReplayArtifact) -> Vec<SimCommandProto> {
+    sim_commands(
+        artifact.initial_baseline.as_ref().map_or(0, |b| b.tick),
+        artifact.entity_spawn_order.iter().copied(),
+        artifact
+            .player_entity_mapping
+            .iter()
+            .filter_map(|m| Some((m.player_id, m.team_id?))),
+    )
 }
 
 // ============================================================================
@@ -670,19 +1885,22 @@ mod tests {
 
     fn create_test_artifact() -> ReplayArtifact {
         let mut recorder = ReplayRecorder::new(ReplayConfig {
+            match_id: "m-1".to_string(),
             seed: 42,
             tick_rate_hz: 60,
             rng_algorithm: "none".to_string(),
             test_mode: false,
             test_player_ids: Vec::new(),
+            tuning: BTreeMap::new(),
+            rejected_input_limit: 0,
         });
 
         // Create a world and record spawns
         let mut world = World::new(42, 60);
-        let entity1 = world.spawn_character(0);
-        let entity2 = world.spawn_character(1);
-        recorder.record_spawn(0, entity1);
-        recorder.record_spawn(1, entity2);
+        let entity1 = world.spawn_character(PlayerId(0));
+        let entity2 = world.spawn_character(PlayerId(1));
+        recorder.record_spawn(PlayerId(0), entity1);
+        recorder.record_spawn(PlayerId(1), entity2);
 
         // Record baseline
         recorder.record_baseline(world.baseline());
@@ -691,13 +1909,13 @@ mod tests {
         for tick in 0..10 {
             recorder.record_input(AppliedInput {
                 tick,
-                player_id: 0,
+                player_id: PlayerId(0),
                 move_dir: [1.0, 0.0],
                 is_fallback: false,
             });
             recorder.record_input(AppliedInput {
                 tick,
-                player_id: 1,
+                player_id: PlayerId(1),
                 move_dir: [0.0, 1.0],
                 is_fallback: false,
             });
@@ -705,11 +1923,11 @@ mod tests {
             // Advance world
             let inputs = [
                 StepInput {
-                    player_id: 0,
+                    player_id: PlayerId(0),
                     move_dir: [1.0, 0.0],
                 },
                 StepInput {
-                    player_id: 1,
+                    player_id: PlayerId(1),
                     move_dir: [0.0, 1.0],
                 },
             ];
@@ -717,7 +1935,9 @@ mod tests {
         }
 
         // Finalize
-        recorder.finalize(world.state_digest(), world.tick(), "complete")
+        recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap()
     }
 
     /// T0.8: Replay artifact generated with all required fields.
@@ -725,7 +1945,7 @@ mod tests {
     fn test_t0_08_replay_artifact_has_required_fields() {
         let artifact = create_test_artifact();
 
-        assert_eq!(artifact.replay_format_version, 1);
+        assert_eq!(artifact.replay_format_version, REPLAY_FORMAT_VERSION);
         assert!(artifact.initial_baseline.is_some());
         assert_eq!(artifact.seed, 42);
         assert!(!artifact.rng_algorithm.is_empty());
@@ -736,10 +1956,20 @@ mod tests {
         );
         assert_eq!(artifact.entity_spawn_order.len(), 2);
         assert_eq!(artifact.player_entity_mapping.len(), 2);
+        assert_eq!(artifact.sim_commands.len(), 2);
+        assert!(
+            artifact
+                .initial_baseline
+                .as_ref()
+                .unwrap()
+                .entities
+                .iter()
+                .all(|e| e.entity_kind() == EntityKind::Character)
+        );
         assert!(!artifact.tuning_parameters.is_empty());
         assert_eq!(artifact.inputs.len(), 20); // 10 ticks * 2 players
         assert_eq!(artifact.checkpoint_tick, 10);
-        assert_eq!(artifact.end_reason, "complete");
+        assert_eq!(artifact.end_reason(), MatchEndReason::Complete);
     }
 
     /// T0.9: Replay verification passes.
@@ -749,12 +1979,684 @@ mod tests {
         let options = VerifyOptions {
             strict_build_check: false, // Don't check build in unit tests
             current_build: None,
+            collect_all_errors: false,
         };
 
         let result = verify_replay(&artifact, &options);
         assert!(result.is_ok(), "Replay verification failed: {result:?}");
     }
 
+    #[test]
+    fn test_verify_report_warnings_and_counts() {
+        let mut artifact = create_test_artifact();
+        let fingerprint = |binary_sha256: &str| BuildFingerprintData {
+            binary_sha256: binary_sha256.to_string(),
+            target_triple: "x86_64-unknown-linux-gnu".to_string(),
+            profile: "dev".to_string(),
+            git_commit: "abc".to_string(),
+        };
+        let recorded = fingerprint("aa");
+        artifact.build_fingerprint = Some(BuildFingerprint {
+            binary_sha256: recorded.binary_sha256,
+            target_triple: recorded.target_triple,
+            profile: recorded.profile,
+            git_commit: recorded.git_commit,
+        });
+        let mut options = VerifyOptions {
+            strict_build_check: false,
+            current_build: Some(fingerprint("bb")),
+            collect_all_errors: false,
+        };
+
+        let report = verify_replay_report(&artifact, &options);
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.ticks_simulated, 10);
+        assert_eq!(report.digests_checked, 1);
+        assert_eq!(
+            report.warnings,
+            vec![VerifyWarning::BuildMismatch {
+                expected: "aa".to_string(),
+                actual: "bb".to_string(),
+            }]
+        );
+
+        options.strict_build_check = true;
+        let report = verify_replay_report(&artifact, &options);
+        assert!(matches!(
+            report.outcome,
+            Err(VerifyError::BuildMismatch { .. })
+        ));
+        assert_eq!(report.ticks_simulated, 0);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_verify_uses_recorded_tuning() {
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+
+        // A match played at a tuned move_speed verifies with it
+        let tuning = Tuning { move_speed: 8.0 };
+        let mut recorder = ReplayRecorder::new(ReplayConfig {
+            tuning: BTreeMap::from([(TUNING_KEY_MOVE_SPEED.to_string(), 8.0)]),
+            ..ReplayConfig::default()
+        });
+        let mut world = World::with_tuning(0, 60, tuning);
+        let entity_id = world.spawn_character(PlayerId(0));
+        recorder.record_spawn(PlayerId(0), entity_id);
+        recorder.record_baseline(world.baseline());
+        for tick in 0..10 {
+            let input = StepInput {
+                player_id: PlayerId(0),
+                move_dir: [1.0, 0.0],
+            };
+            recorder.record_input(AppliedInput {
+                tick,
+                player_id: input.player_id,
+                move_dir: input.move_dir,
+                is_fallback: false,
+            });
+            world.advance(tick, &[input]);
+        }
+        let tuned = recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap();
+        assert_eq!(recorded_tuning(&tuned), Ok(tuning));
+        assert!(verify_replay(&tuned, &options).is_ok());
+
+        // The same inputs at the compiled move_speed end elsewhere
+        let mut compiled = tuned.clone();
+        compiled.tuning_parameters.clear();
+        assert_eq!(recorded_tuning(&compiled), Ok(Tuning::default()));
+        assert!(matches!(
+            verify_replay(&compiled, &options),
+            Err(VerifyError::FinalDigestMismatch { .. })
+        ));
+
+        // Unknown and out-of-range parameters fail before resimulating
+        let mut artifact = create_test_artifact();
+        artifact.tuning_parameters.extend([
+            TuningParameter {
+                key: "gravity".to_string(),
+                value: 9.8,
+            },
+            TuningParameter {
+                key: "lki_decay_ramp_ticks".to_string(),
+                value: 1.5,
+            },
+        ]);
+        let report = verify_replay_report(&artifact, &options);
+        assert_eq!(
+            report.outcome,
+            Err(VerifyError::UnknownTuningParameter {
+                key: "gravity".to_string(),
+            })
+        );
+        assert_eq!(report.ticks_simulated, 0);
+        let report = verify_replay_report(
+            &artifact,
+            &VerifyOptions {
+                collect_all_errors: true,
+                ..options.clone()
+            },
+        );
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(
+            report.errors[1],
+            VerifyError::TuningOutOfRange {
+                key: "lki_decay_ramp_ticks".to_string(),
+                value: 1.5,
+            }
+        );
+        assert_eq!(report.ticks_simulated, 0);
+
+        let mut artifact = create_test_artifact();
+        for t in &mut artifact.tuning_parameters {
+            t.value = -1.0;
+        }
+        assert_eq!(
+            verify_replay(&artifact, &options),
+            Err(VerifyError::TuningOutOfRange {
+                key: TUNING_KEY_MOVE_SPEED.to_string(),
+                value: -1.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_collect_all_errors_reports_every_problem() {
+        let mut artifact = create_test_artifact();
+        artifact
+            .inputs
+            .retain(|i| !(i.tick == 3 && i.player_id == 1));
+        artifact.player_entity_mapping[1].entity_id = 99;
+        artifact.initial_baseline.as_mut().unwrap().digest ^= 1;
+        artifact.final_digest ^= 1;
+        let mut options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+
+        let first_only = verify_replay_report(&artifact, &options);
+        assert_eq!(first_only.errors.len(), 1);
+        assert_eq!(first_only.outcome, Err(first_only.errors[0].clone()));
+
+        options.collect_all_errors = true;
+        let report = verify_replay_report(&artifact, &options);
+        assert_eq!(report.outcome, first_only.outcome);
+        assert_eq!(report.ticks_simulated, 10);
+        // The final digest is not compared once the anchor already differs
+        assert_eq!(report.digests_checked, 0);
+        assert!(matches!(
+            report.errors.as_slice(),
+            [
+                VerifyError::InputStreamInvalid { reason },
+                VerifyError::InputChainMismatch { .. },
+                VerifyError::SpawnReconstructionMismatch { .. },
+                VerifyError::InitializationAnchorMismatch { .. },
+            ] if reason == "Missing input for player 1 at tick 3"
+        ));
+
+        let mut diverged = create_test_artifact();
+        diverged.final_digest ^= 1;
+        let report = verify_replay_report(&diverged, &options);
+        assert!(matches!(
+            report.errors.as_slice(),
+            [VerifyError::FinalDigestMismatch { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_verify_progress_and_cancellation() {
+        let artifact = create_test_artifact();
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: true,
+        };
+
+        let mut calls = Vec::new();
+        let mut record = |done, total| calls.push((done, total));
+        let report = verify_replay_with_hooks(
+            &artifact,
+            &options,
+            VerifyHooks {
+                progress: Some(&mut record),
+                cancel: None,
+            },
+        );
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(calls, (0..=10).map(|done| (done, 10)).collect::<Vec<_>>());
+
+        // Cancelled from another handle once 4 ticks are done
+        let cancel = CancellationToken::new();
+        let handle = cancel.clone();
+        let mut stop_at_four = |done, _| {
+            if done == 4 {
+                handle.cancel();
+            }
+        };
+        let report = verify_replay_with_hooks(
+            &artifact,
+            &options,
+            VerifyHooks {
+                progress: Some(&mut stop_at_four),
+                cancel: Some(cancel),
+            },
+        );
+        assert_eq!(report.outcome, Err(VerifyError::Cancelled { tick: 4 }));
+        assert_eq!(report.ticks_simulated, 4);
+        assert_eq!(report.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_input_chain_hash_catches_edits_before_resimulation() {
+        let artifact = create_test_artifact();
+        assert_eq!(
+            artifact.input_chain_hash,
+            Some(input_chain_hash(&artifact.inputs))
+        );
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+
+        // The list order is not significant; the (tick, player_id) order is
+        let mut shuffled = artifact.clone();
+        shuffled.inputs.reverse();
+        assert!(verify_replay(&shuffled, &options).is_ok());
+
+        let mut altered = artifact.clone();
+        altered.inputs[5].move_dir = vec![-0.0, 1.0];
+        let report = verify_replay_report(&altered, &options);
+        assert!(matches!(
+            report.outcome,
+            Err(VerifyError::InputChainMismatch { .. })
+        ));
+        assert_eq!(report.ticks_simulated, 0);
+
+        // Swapping two players' inputs keeps the stream complete
+        let mut swapped = artifact.clone();
+        swapped.inputs.swap(0, 1);
+        let (a, b) = (swapped.inputs[0].player_id, swapped.inputs[1].player_id);
+        swapped.inputs[0].player_id = b;
+        swapped.inputs[1].player_id = a;
+        assert!(matches!(
+            verify_replay(&swapped, &options),
+            Err(VerifyError::InputChainMismatch { .. })
+        ));
+
+        // Artifacts recorded before the hash existed are not checked
+        let unhashed = ReplayArtifact {
+            input_chain_hash: None,
+            ..artifact
+        };
+        assert!(verify_replay(&unhashed, &options).is_ok());
+    }
+
+    #[test]
+    fn test_resume_roundtrips_artifact() {
+        let artifact = create_test_artifact();
+        let recorder = ReplayRecorder::resume(
+            ReplayConfig {
+                seed: 42,
+                ..Default::default()
+            },
+            &artifact,
+        )
+        .unwrap();
+        let rebuilt = recorder
+            .partial_artifact(
+                artifact.final_digest,
+                artifact.checkpoint_tick,
+                MatchEndReason::Complete,
+            )
+            .unwrap();
+        assert_eq!(rebuilt, artifact);
+
+        let mut missing = artifact;
+        missing.initial_baseline = None;
+        assert!(matches!(
+            ReplayRecorder::resume(ReplayConfig::default(), &missing),
+            Err(VerifyError::MissingBaseline)
+        ));
+    }
+
+    #[test]
+    fn test_intermediate_digest_localizes_divergence() {
+        let mut recorder = ReplayRecorder::new(ReplayConfig::default());
+        let mut world = World::new(0, 60);
+        let entity_id = world.spawn_character(PlayerId(0));
+        recorder.record_spawn(PlayerId(0), entity_id);
+        recorder.record_baseline(world.baseline());
+        for tick in 0..30 {
+            recorder.record_input(AppliedInput {
+                tick,
+                player_id: PlayerId(0),
+                move_dir: [1.0, 0.0],
+                is_fallback: false,
+            });
+            let snapshot = world.advance(
+                tick,
+                &[StepInput {
+                    player_id: PlayerId(0),
+                    move_dir: [1.0, 0.0],
+                }],
+            );
+            if snapshot.tick.is_multiple_of(10) {
+                recorder.record_digest(snapshot.tick, snapshot.digest);
+            }
+        }
+        let artifact = recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap();
+        let digest_ticks: Vec<_> = artifact.checkpoint_digests.iter().map(|d| d.tick).collect();
+        assert_eq!(digest_ticks, vec![10, 20]);
+
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(verify_replay(&artifact, &options).is_ok());
+
+        let mut corrupted = artifact.clone();
+        corrupted.checkpoint_digests[1].digest ^= 1;
+        assert!(matches!(
+            verify_replay(&corrupted, &options),
+            Err(VerifyError::IntermediateDigestMismatch {
+                tick: 20,
+                since_tick: 10,
+                ..
+            })
+        ));
+
+        let mut out_of_range = artifact;
+        out_of_range.checkpoint_digests[0].tick = 31;
+        assert!(matches!(
+            verify_replay(&out_of_range, &options),
+            Err(VerifyError::InvalidFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_anchors_name_the_diverging_entity() {
+        let mut recorder = ReplayRecorder::new(ReplayConfig::default());
+        let mut world = World::new(0, 60);
+        for player_id in [PlayerId(0), PlayerId(1)] {
+            let entity_id = world.spawn_character(player_id);
+            recorder.record_spawn(player_id, entity_id);
+        }
+        recorder.record_baseline(world.baseline());
+        for tick in 0..30 {
+            let inputs = [PlayerId(0), PlayerId(1)].map(|player_id| StepInput {
+                player_id,
+                move_dir: [0.0, 1.0],
+            });
+            for input in &inputs {
+                recorder.record_input(AppliedInput {
+                    tick,
+                    player_id: input.player_id,
+                    move_dir: input.move_dir,
+                    is_fallback: false,
+                });
+            }
+            let snapshot = world.advance(tick, &inputs);
+            if snapshot.tick.is_multiple_of(10) {
+                recorder.record_snapshot_anchor(world.baseline());
+            }
+        }
+        let artifact = recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap();
+        let anchor_ticks: Vec<_> = artifact.snapshot_anchors.iter().map(|a| a.tick).collect();
+        assert_eq!(anchor_ticks, vec![10, 20, 30]);
+        let resumed = ReplayRecorder::resume(ReplayConfig::default(), &artifact).unwrap();
+        assert_eq!(
+            resumed
+                .partial_artifact(
+                    artifact.final_digest,
+                    artifact.checkpoint_tick,
+                    MatchEndReason::Complete
+                )
+                .unwrap(),
+            artifact
+        );
+
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        let report = verify_replay_report(&artifact, &options);
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.anchors_checked, 3);
+
+        // An input edited after tick 10 surfaces at the next anchor, naming
+        // the one entity it moved
+        let mut edited = artifact.clone();
+        edited.input_chain_hash = None;
+        for input in edited
+            .inputs
+            .iter_mut()
+            .filter(|i| i.tick == 15 && i.player_id == 1)
+        {
+            input.move_dir = vec![1.0, 0.0];
+        }
+        let report = verify_replay_report(&edited, &options);
+        let Err(VerifyError::SnapshotAnchorMismatch {
+            tick,
+            entity_id,
+            expected: Some(expected),
+            actual: Some(actual),
+        }) = &report.outcome
+        else {
+            panic!("{report:?}");
+        };
+        assert_eq!((*tick, *entity_id), (20, EntityId(2)));
+        assert_eq!(expected.velocity, [0.0, MOVE_SPEED]);
+        assert_eq!(actual.velocity, [0.0, MOVE_SPEED]);
+        assert!(actual.position[0] > expected.position[0]);
+        assert_eq!(report.anchors_checked, 2);
+        assert!(
+            report
+                .outcome
+                .unwrap_err()
+                .to_string()
+                .contains("tick 20, entity 2")
+        );
+
+        // A missing entity, and a digest that disagrees with the entities
+        let mut missing = artifact.clone();
+        missing.snapshot_anchors[0].entities.remove(0);
+        assert_eq!(
+            verify_replay(&missing, &options),
+            Err(VerifyError::SnapshotAnchorMismatch {
+                tick: 10,
+                entity_id: EntityId(1),
+                expected: None,
+                actual: Some(
+                    world_at(&artifact, 10)
+                        .unwrap()
+                        .unwrap()
+                        .baseline()
+                        .entities[0]
+                        .clone()
+                ),
+            })
+        );
+        let mut digest = artifact.clone();
+        digest.snapshot_anchors[1].digest ^= 1;
+        assert!(matches!(
+            verify_replay(&digest, &options),
+            Err(VerifyError::IntermediateDigestMismatch {
+                tick: 20,
+                since_tick: 10,
+                ..
+            })
+        ));
+
+        let mut out_of_range = artifact;
+        out_of_range.snapshot_anchors[0].tick = 31;
+        assert!(matches!(
+            verify_replay(&out_of_range, &options),
+            Err(VerifyError::InvalidFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_spilled_recording_matches_in_memory() {
+        let path = std::env::temp_dir().join(format!("flowstate-spill-{}", std::process::id()));
+        let record = |spill: bool| {
+            let mut recorder = ReplayRecorder::new(ReplayConfig::default());
+            if spill {
+                recorder.spill_to(&path).unwrap();
+            }
+            let mut world = World::new(0, 60);
+            for player_id in [PlayerId(0), PlayerId(1)] {
+                let entity_id = world.spawn_character(player_id);
+                recorder.record_spawn(player_id, entity_id);
+            }
+            recorder.record_baseline(world.baseline());
+            // Two players fill exactly two segments
+            let ticks = SPILL_SEGMENT_INPUTS as u64;
+            for tick in 0..ticks {
+                let inputs = [PlayerId(0), PlayerId(1)].map(|player_id| StepInput {
+                    player_id,
+                    move_dir: [1.0, 0.0],
+                });
+                for input in &inputs {
+                    recorder.record_input(AppliedInput {
+                        tick,
+                        player_id: input.player_id,
+                        move_dir: input.move_dir,
+                        is_fallback: false,
+                    });
+                }
+                world.advance(tick, &inputs);
+            }
+            let held = if spill { 0 } else { 2 * SPILL_SEGMENT_INPUTS };
+            assert_eq!(recorder.inputs.len(), held);
+            recorder
+                .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+                .unwrap()
+        };
+
+        let in_memory = record(false);
+        let spilled = record(true);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(spilled, in_memory);
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(verify_replay(&spilled, &options).is_ok());
+    }
+
+    #[test]
+    fn test_read_replay_auto_handles_both_formats() {
+        let artifact = create_test_artifact();
+        let dir = std::env::temp_dir().join(format!("flowstate-container-{}", std::process::id()));
+        let plain = dir.join("plain.replay");
+        let compressed = dir.join("compressed.replay");
+        write_replay(&artifact, &plain).unwrap();
+        write_replay_compressed(&artifact, &compressed).unwrap();
+        assert_eq!(read_replay_auto(&plain).unwrap(), artifact);
+        assert_eq!(read_replay_auto(&compressed).unwrap(), artifact);
+        assert!(fs::metadata(&compressed).unwrap().len() < fs::metadata(&plain).unwrap().len());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut bytes = encode_container(&artifact, Codec::Zstd).unwrap();
+        bytes[CONTAINER_MAGIC.len()] = CONTAINER_VERSION + 1;
+        let err = decode_replay_auto(&bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(decode_replay_auto(&CONTAINER_MAGIC).is_err());
+    }
+
+    #[test]
+    fn test_migrate_v1_end_reason() {
+        let current = create_test_artifact();
+        let mut v1 = ReplayArtifact {
+            replay_format_version: 1,
+            legacy_end_reason: "complete".to_string(),
+            end_reason: 0,
+            ..current.clone()
+        };
+        migrate_artifact(&mut v1);
+        assert_eq!(v1, current);
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(verify_replay(&v1, &options).is_ok());
+
+        // The verifier also accepts a version 1 artifact as recorded
+        let unmigrated = ReplayArtifact {
+            replay_format_version: 1,
+            legacy_end_reason: "complete".to_string(),
+            end_reason: 0,
+            ..current.clone()
+        };
+        assert!(verify_replay(&unmigrated, &options).is_ok());
+
+        let newer = ReplayArtifact {
+            replay_format_version: REPLAY_FORMAT_VERSION + 1,
+            ..current.clone()
+        };
+        let mut migrated = newer.clone();
+        migrate_artifact(&mut migrated);
+        assert_eq!(migrated, newer);
+        assert!(matches!(
+            verify_replay(&newer, &options),
+            Err(VerifyError::UnsupportedFormatVersion { version: 3 })
+        ));
+
+        let mut unknown = ReplayArtifact {
+            replay_format_version: 1,
+            legacy_end_reason: "server_shutdown".to_string(),
+            ..current
+        };
+        migrate_artifact(&mut unknown);
+        assert_eq!(unknown.end_reason(), MatchEndReason::Other);
+        assert_eq!(unknown.end_reason_detail, "server_shutdown");
+        assert!(unknown.legacy_end_reason.is_empty());
+    }
+
+    /// Migration records what a version 1 artifact left implicit: its
+    /// SimCommands and entity kinds.
+    #[test]
+    fn test_migrate_v1_records_v2_fields() {
+        let mut current = crate::test_support::recorded_match(30, 2, 10).artifact;
+        current.player_entity_mapping[1].team_id = Some(1);
+        current.sim_commands.push(SimCommandProto {
+            tick: 0,
+            command: Some(sim_command_proto::Command::SetTeam(SetTeamCommand {
+                player_id: current.player_entity_mapping[1].player_id,
+                team_id: 1,
+            })),
+        });
+        assert_eq!(current.sim_commands.len(), 3);
+        assert!(!current.checkpoint_digests.is_empty());
+
+        let mut v1 = ReplayArtifact {
+            replay_format_version: 1,
+            legacy_end_reason: "complete".to_string(),
+            end_reason: 0,
+            sim_commands: Vec::new(),
+            ..current.clone()
+        };
+        for entity in &mut v1.initial_baseline.as_mut().unwrap().entities {
+            entity.set_entity_kind(EntityKind::Unspecified);
+        }
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(verify_replay(&v1, &options).is_ok());
+
+        // Digests and anchors a version 1 server already recorded are kept
+        migrate_artifact(&mut v1);
+        assert_eq!(v1, current);
+        assert!(verify_replay(&v1, &options).is_ok());
+    }
+
+    #[test]
+    fn test_sim_commands_must_follow_spawn_order() {
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        let mut reordered = create_test_artifact();
+        reordered.sim_commands.swap(0, 1);
+        assert!(matches!(
+            verify_replay(&reordered, &options),
+            Err(VerifyError::InvalidFormat { .. })
+        ));
+
+        let mut late = create_test_artifact();
+        late.sim_commands[0].tick = 5;
+        assert!(matches!(
+            verify_replay(&late, &options),
+            Err(VerifyError::InvalidFormat { .. })
+        ));
+
+        let mut missing = create_test_artifact();
+        missing.sim_commands.clear();
+        assert!(matches!(
+            verify_replay(&missing, &options),
+            Err(VerifyError::InvalidFormat { .. })
+        ));
+    }
+
     /// T0.10: Initialization anchor failure.
     #[test]
     fn test_t0_10_initialization_anchor_failure() {
@@ -768,6 +2670,7 @@ mod tests {
         let options = VerifyOptions {
             strict_build_check: false,
             current_build: None,
+            collect_all_errors: false,
         };
 
         let result = verify_replay(&artifact, &options);
@@ -783,8 +2686,8 @@ mod tests {
         let mut recorder = ReplayRecorder::new(ReplayConfig::default());
 
         let mut world = World::new(0, 60);
-        let entity1 = world.spawn_character(0);
-        recorder.record_spawn(0, entity1);
+        let entity1 = world.spawn_character(PlayerId(0));
+        recorder.record_spawn(PlayerId(0), entity1);
         recorder.record_baseline(world.baseline());
 
         // Record inputs with some fallbacks
@@ -792,24 +2695,27 @@ mod tests {
             let is_fallback = tick % 3 == 0; // Every 3rd tick is LKI
             recorder.record_input(AppliedInput {
                 tick,
-                player_id: 0,
+                player_id: PlayerId(0),
                 move_dir: if is_fallback { [0.0, 0.0] } else { [1.0, 0.0] },
                 is_fallback,
             });
 
             let inputs = [StepInput {
-                player_id: 0,
+                player_id: PlayerId(0),
                 move_dir: if is_fallback { [0.0, 0.0] } else { [1.0, 0.0] },
             }];
             world.advance(tick, &inputs);
         }
 
-        let artifact = recorder.finalize(world.state_digest(), world.tick(), "complete");
+        let artifact = recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap();
 
         // Verify replay
         let options = VerifyOptions {
             strict_build_check: false,
             current_build: None,
+            collect_all_errors: false,
         };
         let result = verify_replay(&artifact, &options);
         assert!(result.is_ok(), "Replay with LKI inputs failed: {result:?}");
@@ -821,10 +2727,10 @@ mod tests {
         let mut recorder = ReplayRecorder::new(ReplayConfig::default());
 
         let mut world = World::new(0, 60);
-        let entity1 = world.spawn_character(0);
-        let entity2 = world.spawn_character(1);
-        recorder.record_spawn(0, entity1);
-        recorder.record_spawn(1, entity2);
+        let entity1 = world.spawn_character(PlayerId(0));
+        let entity2 = world.spawn_character(PlayerId(1));
+        recorder.record_spawn(PlayerId(0), entity1);
+        recorder.record_spawn(PlayerId(1), entity2);
         recorder.record_baseline(world.baseline());
 
         // Intentionally record inputs in non-canonical order (player 1 before player 0)
@@ -832,13 +2738,13 @@ mod tests {
             // Wrong order: player 1 first
             recorder.record_input(AppliedInput {
                 tick,
-                player_id: 1,
+                player_id: PlayerId(1),
                 move_dir: [0.0, 1.0],
                 is_fallback: false,
             });
             recorder.record_input(AppliedInput {
                 tick,
-                player_id: 0,
+                player_id: PlayerId(0),
                 move_dir: [1.0, 0.0],
                 is_fallback: false,
             });
@@ -846,23 +2752,26 @@ mod tests {
             // Advance world with correct order
             let inputs = [
                 StepInput {
-                    player_id: 0,
+                    player_id: PlayerId(0),
                     move_dir: [1.0, 0.0],
                 },
                 StepInput {
-                    player_id: 1,
+                    player_id: PlayerId(1),
                     move_dir: [0.0, 1.0],
                 },
             ];
             world.advance(tick, &inputs);
         }
 
-        let artifact = recorder.finalize(world.state_digest(), world.tick(), "complete");
+        let artifact = recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap();
 
         // Verifier should canonicalize and succeed
         let options = VerifyOptions {
             strict_build_check: false,
             current_build: None,
+            collect_all_errors: false,
         };
         let result = verify_replay(&artifact, &options);
         assert!(
@@ -875,7 +2784,7 @@ mod tests {
     fn test_applied_input_conversion() {
         let input = AppliedInput {
             tick: 100,
-            player_id: 5,
+            player_id: PlayerId(5),
             move_dir: [0.5, -0.5],
             is_fallback: true,
         };
@@ -0,0 +1,297 @@
+//! Live streaming of the replay record during a match.
+//!
+//! Ref: INV-0006 (Replay Verifiability), DM-0024 (AppliedInput), ADR-0007
+//! - `ReplayRecorder` forwards the match start, every AppliedInput, and periodic
+//!   StateDigests to attached sinks as they are recorded
+//! - `LiveFollower` re-simulates the stream incrementally (with the recorded
+//!   tuning) and checks each digest, so an external verifier need not wait
+//!   for `finalize()`
+//!
+//! Sinks observe the record; they never influence it.
+
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+
+use flowstate_sim::{Baseline, EntityId, PlayerId, StepInput, Tick, World};
+use flowstate_wire::TuningParameter;
+
+use crate::{AppliedInput, VerifyError, tuning_from_parameters};
+
+/// One record of the live replay stream.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveRecord {
+    /// Match start: everything needed to reconstruct the initial World.
+    Start {
+        seed: u64,
+        tick_rate_hz: u32,
+        /// Tuning parameters as recorded in the artifact, sorted by key.
+        tuning: Vec<TuningParameter>,
+        /// (player_id, entity_id) in spawn order.
+        spawns: Vec<(PlayerId, EntityId)>,
+        baseline: Baseline,
+    },
+    /// One AppliedInput, in recording order.
+    Input(AppliedInput),
+    /// StateDigest of the World once it has advanced to `tick`.
+    Digest { tick: Tick, digest: u64 },
+}
+
+/// Destination for live replay records.
+pub trait ReplaySink: Send {
+    fn on_record(&mut self, record: &LiveRecord);
+}
+
+impl ReplaySink for mpsc::Sender<LiveRecord> {
+    fn on_record(&mut self, record: &LiveRecord) {
+        // A disconnected follower must not disturb the match.
+        let _ = self.send(record.clone());
+    }
+}
+
+/// Incremental verifier for a live replay stream.
+#[derive(Default)]
+pub struct LiveFollower {
+    world: Option<World>,
+    player_count: usize,
+    pending: BTreeMap<Tick, Vec<StepInput>>,
+    verified_tick: Option<Tick>,
+}
+
+impl LiveFollower {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Last tick whose digest matched, if any.
+    pub fn verified_tick(&self) -> Option<Tick> {
+        self.verified_tick
+    }
+
+    /// Consume one record, advancing and checking as digests arrive.
+    pub fn apply(&mut self, record: LiveRecord) -> Result<(), VerifyError> {
+        match record {
+            LiveRecord::Start {
+                seed,
+                tick_rate_hz,
+                tuning,
+                spawns,
+                baseline,
+            } => {
+                let tuning = tuning_from_parameters(&tuning)?;
+                let mut world = World::with_tuning(seed, tick_rate_hz, tuning);
+                for &(player_id, expected_entity_id) in &spawns {
+                    let actual_entity_id = world.spawn_character(player_id);
+                    if actual_entity_id != expected_entity_id {
+                        return Err(VerifyError::SpawnReconstructionMismatch {
+                            player_id,
+                            expected_entity_id,
+                            actual_entity_id,
+                        });
+                    }
+                }
+                let actual = world.baseline().digest;
+                if actual != baseline.digest {
+                    return Err(VerifyError::InitializationAnchorMismatch {
+                        expected: baseline.digest,
+                        actual,
+                    });
+                }
+                self.world = Some(world);
+                self.player_count = spawns.len();
+                self.pending.clear();
+                self.verified_tick = None;
+                Ok(())
+            }
+            LiveRecord::Input(input) => {
+                let world = self.world.as_ref().ok_or(VerifyError::MissingBaseline)?;
+                if input.tick < world.tick() {
+                    return Err(VerifyError::InputStreamInvalid {
+                        reason: format!(
+                            "Input for player {} at tick {} arrived after tick was simulated",
+                            input.player_id, input.tick
+                        ),
+                    });
+                }
+                self.pending
+                    .entry(input.tick)
+                    .or_default()
+                    .push(input.to_step_input());
+                Ok(())
+            }
+            LiveRecord::Digest { tick, digest } => {
+                let world = self.world.as_mut().ok_or(VerifyError::MissingBaseline)?;
+                while world.tick() < tick {
+                    let current = world.tick();
+                    let mut step_inputs = self.pending.remove(&current).unwrap_or_default();
+                    if step_inputs.len() != self.player_count {
+                        return Err(VerifyError::InputStreamInvalid {
+                            reason: format!(
+                                "Expected {} inputs at tick {current}, got {}",
+                                self.player_count,
+                                step_inputs.len()
+                            ),
+                        });
+                    }
+                    step_inputs.sort_by_key(|i| i.player_id);
+                    world.advance(current, &step_inputs);
+                }
+                if world.tick() != tick {
+                    return Err(VerifyError::CheckpointTickMismatch {
+                        expected: tick,
+                        actual: world.tick(),
+                    });
+                }
+                let actual = world.state_digest();
+                if actual != digest {
+                    return Err(VerifyError::LiveDigestMismatch {
+                        tick,
+                        expected: digest,
+                        actual,
+                    });
+                }
+                self.verified_tick = Some(tick);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ReplayConfig, ReplayRecorder, TUNING_KEY_MOVE_SPEED};
+    use flowstate_sim::Tuning;
+    use flowstate_wire::MatchEndReason;
+
+    fn record_match(
+        recorder: &mut ReplayRecorder,
+        tuning: Tuning,
+        ticks: Tick,
+        digest_interval: Tick,
+    ) -> (u64, Tick) {
+        let mut world = World::with_tuning(7, 60, tuning);
+        for player_id in (0..2).map(PlayerId) {
+            let entity_id = world.spawn_character(player_id);
+            recorder.record_spawn(player_id, entity_id);
+        }
+        recorder.record_baseline(world.baseline());
+        for tick in 0..ticks {
+            let dirs = [[1.0, 0.0], [0.0, -1.0]];
+            let mut step_inputs = Vec::new();
+            for (player_id, move_dir) in (0..2).map(PlayerId).zip(dirs) {
+                recorder.record_input(AppliedInput {
+                    tick,
+                    player_id,
+                    move_dir,
+                    is_fallback: false,
+                });
+                step_inputs.push(StepInput {
+                    player_id,
+                    move_dir,
+                });
+            }
+            let snapshot = world.advance(tick, &step_inputs);
+            if snapshot.tick.is_multiple_of(digest_interval) {
+                recorder.record_digest(snapshot.tick, snapshot.digest);
+            }
+        }
+        (world.state_digest(), world.tick())
+    }
+
+    #[test]
+    fn test_follower_verifies_stream() {
+        let (tx, rx) = mpsc::channel();
+        let mut recorder = ReplayRecorder::new(ReplayConfig {
+            seed: 7,
+            ..Default::default()
+        });
+        recorder.add_sink(Box::new(tx));
+
+        let (final_digest, checkpoint_tick) =
+            record_match(&mut recorder, Tuning::default(), 25, 10);
+        recorder
+            .finalize(final_digest, checkpoint_tick, MatchEndReason::Complete)
+            .unwrap();
+
+        let mut follower = LiveFollower::new();
+        for record in rx.try_iter() {
+            follower.apply(record).unwrap();
+        }
+        assert_eq!(follower.verified_tick(), Some(25));
+    }
+
+    #[test]
+    fn test_follower_detects_digest_mismatch() {
+        let (tx, rx) = mpsc::channel();
+        let mut recorder = ReplayRecorder::new(ReplayConfig {
+            seed: 7,
+            ..Default::default()
+        });
+        recorder.add_sink(Box::new(tx));
+        record_match(&mut recorder, Tuning::default(), 10, 5);
+
+        let mut follower = LiveFollower::new();
+        let mut result = Ok(());
+        for record in rx.try_iter() {
+            let record = match record {
+                LiveRecord::Digest { tick: 10, digest } => LiveRecord::Digest {
+                    tick: 10,
+                    digest: digest ^ 1,
+                },
+                other => other,
+            };
+            result = follower.apply(record);
+            if result.is_err() {
+                break;
+            }
+        }
+        assert!(matches!(
+            result,
+            Err(VerifyError::LiveDigestMismatch { tick: 10, .. })
+        ));
+        assert_eq!(follower.verified_tick(), Some(5));
+    }
+
+    #[test]
+    fn test_follower_uses_recorded_tuning() {
+        let (tx, rx) = mpsc::channel();
+        let mut recorder = ReplayRecorder::new(ReplayConfig {
+            seed: 7,
+            tuning: BTreeMap::from([(TUNING_KEY_MOVE_SPEED.to_string(), 8.0)]),
+            ..Default::default()
+        });
+        recorder.add_sink(Box::new(tx));
+        record_match(&mut recorder, Tuning { move_speed: 8.0 }, 20, 10);
+        let records: Vec<_> = rx.try_iter().collect();
+
+        let mut follower = LiveFollower::new();
+        for record in records.clone() {
+            follower.apply(record).unwrap();
+        }
+        assert_eq!(follower.verified_tick(), Some(20));
+
+        // Validated like a replay artifact's tuning
+        let mut start = records[0].clone();
+        if let LiveRecord::Start { tuning, .. } = &mut start {
+            tuning.push(TuningParameter {
+                key: "gravity".to_string(),
+                value: 9.8,
+            });
+        }
+        assert_eq!(
+            LiveFollower::new().apply(start),
+            Err(VerifyError::UnknownTuningParameter {
+                key: "gravity".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_follower_requires_start() {
+        let mut follower = LiveFollower::new();
+        assert_eq!(
+            follower.apply(LiveRecord::Digest { tick: 1, digest: 0 }),
+            Err(VerifyError::MissingBaseline)
+        );
+    }
+}
@@ -0,0 +1,456 @@
+//! `replay`: work with replay artifacts from the command line.
+//!
+//! ```text
+//! replay verify <file> [--all]
+//! replay inspect <file>
+//! replay dump <file> [--json]
+//! replay digest <file> --at-tick N
+//! replay diff <a> <b>
+//! replay corpus <file-or-dir>...
+//! ```
+//!
+//! Every command reads plain, compressed, and segmented replay files.
+//! `verify` prints warnings, then exits non-zero when the replay does not
+//! reproduce its recorded digests and names where it diverged (`--all`
+//! lists every problem instead of stopping at the first); `diff` exits non-zero when the two
+//! replays differ; `corpus` verifies every file given and every `.replay`
+//! file in each directory given in parallel, and exits non-zero if any fails. Build fingerprints are not compared:
+//! the recorded one is the server's, not this tool's.
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use flowstate_replay::corpus::verify_corpus;
+use flowstate_replay::diff::diff_replays;
+use flowstate_replay::divergence::find_first_divergent_tick;
+use flowstate_replay::segmented::{SEGMENTED_MAGIC, SegmentedReplayReader};
+use flowstate_replay::{VerifyOptions, decode_replay_auto, verify_replay_report, world_at};
+use flowstate_sim::Tick;
+use flowstate_wire::ReplayArtifact;
+
+const USAGE: &str = "Usage: replay verify <file> [--all] | inspect <file> | dump <file> [--json] \
+| digest <file> --at-tick N | diff <a> <b> | corpus <file-or-dir>...";
+
+/// Parsed command line.
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Verify { path: PathBuf, all: bool },
+    Inspect { path: PathBuf },
+    Dump { path: PathBuf, json: bool },
+    Digest { path: PathBuf, tick: Tick },
+    Diff { a: PathBuf, b: PathBuf },
+    Corpus { paths: Vec<PathBuf> },
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let command = args.next().ok_or("Missing command")?;
+    let path = PathBuf::from(
+        args.next()
+            .ok_or_else(|| format!("{command} requires a file"))?,
+    );
+    let mut json = false;
+    let mut all = false;
+    let mut tick = None;
+    let mut other = None;
+    let mut more = Vec::new();
+    while let Some(flag) = args.next() {
+        match (command.as_str(), flag.as_str()) {
+            ("dump", "--json") => json = true,
+            ("verify", "--all") => all = true,
+            ("diff", _) if other.is_none() => other = Some(PathBuf::from(flag)),
+            ("corpus", _) => more.push(PathBuf::from(flag)),
+            ("digest", "--at-tick") => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{flag} requires a value"))?;
+                let parsed = value
+                    .parse()
+                    .map_err(|_| format!("{flag}: invalid number {value:?}"))?;
+                tick = Some(parsed);
+            }
+            _ => return Err(format!("Unknown argument {flag}")),
+        }
+    }
+    match command.as_str() {
+        "verify" => Ok(Command::Verify { path, all }),
+        "inspect" => Ok(Command::Inspect { path }),
+        "dump" => Ok(Command::Dump { path, json }),
+        "digest" => Ok(Command::Digest {
+            path,
+            tick: tick.ok_or("digest requires --at-tick N")?,
+        }),
+        "diff" => Ok(Command::Diff {
+            a: path,
+            b: other.ok_or("diff requires two files")?,
+        }),
+        "corpus" => Ok(Command::Corpus {
+            paths: std::iter::once(path).chain(more).collect(),
+        }),
+        _ => Err(format!("Unknown command {command}")),
+    }
+}
+
+/// `paths` with each directory replaced by its `.replay` files, sorted.
+fn corpus_files(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path);
+            continue;
+        }
+        let entries =
+            std::fs::read_dir(&path).map_err(|e| format!("Reading {}: {e}", path.display()))?;
+        let mut found: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "replay"))
+            .collect();
+        found.sort();
+        files.extend(found);
+    }
+    Ok(files)
+}
+
+/// The options `verify` and `digest` use.
+fn verify_options() -> VerifyOptions {
+    VerifyOptions {
+        strict_build_check: false,
+        current_build: None,
+        collect_all_errors: false,
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Reading {}: {e}", path.display()))
+}
+
+fn decode(path: &Path, data: &[u8]) -> Result<ReplayArtifact, String> {
+    decode_replay_auto(data).map_err(|e| format!("Decoding {}: {e}", path.display()))
+}
+
+fn format_digest(digest: u64) -> String {
+    format!("{digest:#018x}")
+}
+
+/// Metadata summary printed by `inspect`.
+fn inspect(artifact: &ReplayArtifact) -> String {
+    let mut out = String::new();
+    let initial_tick = artifact.initial_baseline.as_ref().map(|b| b.tick);
+    let fallback_inputs = artifact.inputs.iter().filter(|i| i.is_fallback).count();
+    let lines = [
+        ("match_id", artifact.match_id.clone()),
+        ("format_version", artifact.replay_format_version.to_string()),
+        ("seed", artifact.seed.to_string()),
+        ("tick_rate_hz", artifact.tick_rate_hz.to_string()),
+        (
+            "ticks",
+            match initial_tick {
+                Some(start) => format!("{start}..{}", artifact.checkpoint_tick),
+                None => format!("?..{} (no initial baseline)", artifact.checkpoint_tick),
+            },
+        ),
+        ("end_reason", end_reason(artifact)),
+        ("final_digest", format_digest(artifact.final_digest)),
+        (
+            "checkpoint_digests",
+            artifact.checkpoint_digests.len().to_string(),
+        ),
+        (
+            "snapshot_anchors",
+            artifact.snapshot_anchors.len().to_string(),
+        ),
+        (
+            "inputs",
+            format!("{} ({fallback_inputs} fallback)", artifact.inputs.len()),
+        ),
+        (
+            "build",
+            match &artifact.build_fingerprint {
+                Some(build) => format!(
+                    "{} {} {} {}",
+                    build.git_commit, build.profile, build.target_triple, build.binary_sha256
+                ),
+                None => "unknown".to_string(),
+            },
+        ),
+        ("test_mode", artifact.test_mode.to_string()),
+    ];
+    for (key, value) in lines {
+        let _ = writeln!(out, "{key}: {value}");
+    }
+    if let Some(audit) = &artifact.input_audit {
+        let _ = writeln!(
+            out,
+            "rejected_inputs: {} ({} omitted)",
+            audit.rejected.len(),
+            audit.omitted
+        );
+    }
+    let metadata = artifact.metadata.clone().unwrap_or_default();
+    if artifact.metadata.is_some() {
+        let _ = writeln!(out, "map_id: {}", metadata.map_id);
+        let _ = writeln!(out, "start_unix_ms: {}", metadata.start_unix_ms);
+        let _ = writeln!(out, "end_unix_ms: {}", metadata.end_unix_ms);
+    }
+    for mapping in &artifact.player_entity_mapping {
+        let _ = write!(
+            out,
+            "player {}: entity {}",
+            mapping.player_id, mapping.entity_id
+        );
+        if let Some(p) = metadata
+            .players
+            .iter()
+            .find(|p| p.player_id == mapping.player_id)
+        {
+            let _ = write!(out, " ({})", p.display_name);
+        }
+        let _ = writeln!(out);
+    }
+    out
+}
+
+fn end_reason(artifact: &ReplayArtifact) -> String {
+    let reason = artifact
+        .end_reason()
+        .as_str_name()
+        .trim_start_matches("MATCH_END_REASON_")
+        .to_string();
+    if artifact.end_reason_detail.is_empty() {
+        reason
+    } else {
+        format!("{reason} ({})", artifact.end_reason_detail)
+    }
+}
+
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Verify { path, all } => {
+            let artifact = decode(&path, &read(&path)?)?;
+            let options = VerifyOptions {
+                collect_all_errors: all,
+                ..verify_options()
+            };
+            let report = verify_replay_report(&artifact, &options);
+            for warning in &report.warnings {
+                eprintln!("warning: {warning}");
+            }
+            if let Err(e) = report.outcome {
+                let mut message = format!("{} does not verify: {e}", path.display());
+                for e in report.errors.iter().skip(1) {
+                    let _ = write!(message, "\nalso: {e}");
+                }
+                if let Ok(Some(divergence)) = find_first_divergent_tick(&artifact, &options) {
+                    let _ = write!(message, "\nfirst differing tick: {}", divergence.tick);
+                    if let Some(tick) = divergence.last_matching_tick {
+                        let _ = write!(message, " (last matching tick: {tick})");
+                    }
+                    if let Some(entity_id) = divergence.entity_id {
+                        let _ = write!(message, "\nfirst differing entity: {}", entity_id.0);
+                    }
+                }
+                return Err(message);
+            }
+            println!(
+                "{}: ok ({} ticks, {} digests, {} anchors, {:.1?})",
+                path.display(),
+                report.ticks_simulated,
+                report.digests_checked,
+                report.anchors_checked,
+                report.elapsed
+            );
+        }
+        Command::Inspect { path } => print!("{}", inspect(&decode(&path, &read(&path)?)?)),
+        Command::Dump { path, json } => {
+            let artifact = decode(&path, &read(&path)?)?;
+            if json {
+                let json = serde_json::to_string_pretty(&artifact)
+                    .map_err(|e| format!("Encoding JSON: {e}"))?;
+                println!("{json}");
+            } else {
+                println!("{artifact:#?}");
+            }
+        }
+        Command::Digest { path, tick } => {
+            let data = read(&path)?;
+            let world = if data.starts_with(&SEGMENTED_MAGIC) {
+                // Seek to the segment instead of resimulating from the start
+                SegmentedReplayReader::new(io::Cursor::new(data))
+                    .and_then(|mut reader| reader.world_at(tick))
+                    .map_err(|e| format!("{}: {e}", path.display()))?
+            } else {
+                world_at(&decode(&path, &data)?, tick)
+                    .map_err(|e| format!("{}: {e}", path.display()))?
+                    .ok_or_else(|| format!("Tick {tick} is outside the replay"))?
+            };
+            println!("{tick} {}", format_digest(world.state_digest()));
+        }
+        Command::Diff { a, b } => {
+            let (artifact_a, artifact_b) = (decode(&a, &read(&a)?)?, decode(&b, &read(&b)?)?);
+            let diff = diff_replays(&artifact_a, &artifact_b).map_err(|e| e.to_string())?;
+            print!("{diff}");
+            if !diff.is_empty() {
+                return Err(format!("{} and {} differ", a.display(), b.display()));
+            }
+        }
+        Command::Corpus { paths } => {
+            let files = corpus_files(paths)?;
+            let report = verify_corpus(&files, &verify_options());
+            for entry in report.failures() {
+                let error = match &entry.result {
+                    Ok(r) => r
+                        .errors
+                        .first()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    Err(e) => e.to_string(),
+                };
+                eprintln!("{}: {error}", entry.path.display());
+            }
+            println!(
+                "{}/{} verified ({} ticks, {:.1?})",
+                report.passed(),
+                report.entries.len(),
+                report.ticks_simulated(),
+                report.elapsed
+            );
+            if !report.is_ok() {
+                return Err(format!(
+                    "{} of {} replays failed",
+                    report.entries.len() - report.passed(),
+                    report.entries.len()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let result = parse_args(args)
+        .map_err(|e| format!("{e}\n{USAGE}"))
+        .and_then(run);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstate_wire::{
+        JoinBaseline, MatchEndReason, PlayerDisplayName, PlayerEntityMapping, ReplayMetadata,
+    };
+
+    fn args(list: &[&str]) -> Result<Command, String> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let path = PathBuf::from("m.replay");
+        assert_eq!(
+            args(&["verify", "m.replay"]).unwrap(),
+            Command::Verify {
+                path: path.clone(),
+                all: false
+            }
+        );
+        assert_eq!(
+            args(&["verify", "m.replay", "--all"]).unwrap(),
+            Command::Verify {
+                path: path.clone(),
+                all: true
+            }
+        );
+        assert_eq!(
+            args(&["dump", "m.replay", "--json"]).unwrap(),
+            Command::Dump {
+                path: path.clone(),
+                json: true
+            }
+        );
+        assert_eq!(
+            args(&["digest", "m.replay", "--at-tick", "30"]).unwrap(),
+            Command::Digest { path, tick: 30 }
+        );
+
+        assert_eq!(
+            args(&["diff", "a.replay", "b.replay"]).unwrap(),
+            Command::Diff {
+                a: PathBuf::from("a.replay"),
+                b: PathBuf::from("b.replay")
+            }
+        );
+
+        assert_eq!(
+            args(&["corpus", "replays", "x.replay"]).unwrap(),
+            Command::Corpus {
+                paths: vec![PathBuf::from("replays"), PathBuf::from("x.replay")]
+            }
+        );
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["corpus"]).is_err());
+        assert!(args(&["diff", "a.replay"]).is_err());
+        assert!(args(&["diff", "a.replay", "b.replay", "c.replay"]).is_err());
+        assert!(args(&["verify"]).is_err());
+        assert!(args(&["digest", "m.replay"]).is_err());
+        assert!(args(&["digest", "m.replay", "--at-tick", "x"]).is_err());
+        assert!(args(&["inspect", "m.replay", "--json"]).is_err());
+        assert!(args(&["bogus", "m.replay"]).is_err());
+    }
+
+    #[test]
+    fn test_inspect_summarizes_metadata() {
+        let artifact = ReplayArtifact {
+            replay_format_version: 2,
+            initial_baseline: Some(JoinBaseline::default()),
+            seed: 7,
+            tick_rate_hz: 60,
+            player_entity_mapping: vec![PlayerEntityMapping {
+                player_id: 0,
+                entity_id: 1,
+                ..Default::default()
+            }],
+            final_digest: 0xab,
+            checkpoint_tick: 120,
+            end_reason: MatchEndReason::Other as i32,
+            end_reason_detail: "server_shutdown".to_string(),
+            match_id: "m-1".to_string(),
+            metadata: Some(ReplayMetadata {
+                map_id: "dunes".to_string(),
+                players: vec![PlayerDisplayName {
+                    player_id: 0,
+                    display_name: "Ada".to_string(),
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let summary = inspect(&artifact);
+        for line in [
+            "match_id: m-1",
+            "ticks: 0..120",
+            "end_reason: OTHER (server_shutdown)",
+            "final_digest: 0x00000000000000ab",
+            "inputs: 0 (0 fallback)",
+            "build: unknown",
+            "map_id: dunes",
+            "player 0: entity 1 (Ada)",
+        ] {
+            assert!(summary.lines().any(|l| l == line), "{line} in {summary}");
+        }
+    }
+}
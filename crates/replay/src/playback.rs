@@ -0,0 +1,134 @@
+//! Tick-by-tick replay playback.
+//!
+//! Ref: DM-0017 (ReplayArtifact), DM-0024 (AppliedInput), DM-0007 (Snapshot)
+//! - `ReplayPlayer` rebuilds the World exactly as `verify_replay` does
+//!   (spawn reconstruction, initialization anchor) and then steps it one
+//!   recorded tick at a time
+//! - Each step yields the pre-step tick, the post-step Snapshot, and the
+//!   AppliedInputs of that tick in player_id order (INV-0007)
+//!
+//! Playback does not check the final digest; run `verify_replay` when the
+//! outcome must be trusted.
+
+use std::collections::BTreeMap;
+
+use flowstate_sim::{Snapshot, Tick, World};
+use flowstate_wire::ReplayArtifact;
+
+use crate::{
+    AppliedInput, VerifyError, advance_recorded, initial_world, inputs_by_tick,
+    validate_input_stream,
+};
+
+/// Iterator over a replay's ticks, from the initial baseline to
+/// `checkpoint_tick`.
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    world: World,
+    inputs_by_tick: BTreeMap<Tick, Vec<AppliedInput>>,
+    end_tick: Tick,
+}
+
+impl ReplayPlayer {
+    /// Validate `artifact` and rebuild its World at the initial baseline.
+    pub fn new(artifact: &ReplayArtifact) -> Result<Self, VerifyError> {
+        validate_input_stream(artifact)?;
+        let mut inputs_by_tick = inputs_by_tick(&artifact.inputs)?;
+        for inputs in inputs_by_tick.values_mut() {
+            inputs.sort_by_key(|i| i.player_id);
+        }
+        Ok(Self {
+            world: initial_world(artifact)?,
+            inputs_by_tick,
+            end_tick: artifact.checkpoint_tick,
+        })
+    }
+
+    /// The World after the last yielded step.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+}
+
+impl Iterator for ReplayPlayer {
+    /// `(tick, snapshot, inputs)`: `snapshot.tick == tick + 1`.
+    type Item = (Tick, Snapshot, Vec<AppliedInput>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tick = self.world.tick();
+        if tick >= self.end_tick {
+            return None;
+        }
+        let snapshot = advance_recorded(&mut self.world, tick, &self.inputs_by_tick);
+        let inputs = self.inputs_by_tick.remove(&tick).unwrap_or_default();
+        Some((tick, snapshot, inputs))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end_tick.saturating_sub(self.world.tick()) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ReplayPlayer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{RecordedMatch, recorded_match};
+    use flowstate_sim::PlayerId;
+
+    #[test]
+    fn test_player_yields_recorded_ticks() {
+        // Inputs are stored out of player_id order; playback yields them in order
+        let RecordedMatch {
+            artifact,
+            snapshots,
+            ..
+        } = recorded_match(20, 2, 0);
+
+        let player = ReplayPlayer::new(&artifact).unwrap();
+        assert_eq!(player.len(), 20);
+        let steps: Vec<_> = player.collect();
+        assert_eq!(steps.len(), 20);
+        for (i, (tick, snapshot, inputs)) in steps.into_iter().enumerate() {
+            assert_eq!(tick, i as Tick);
+            assert_eq!(snapshot, snapshots[i]);
+            let players: Vec<_> = inputs.iter().map(|i| i.player_id).collect();
+            assert_eq!(players, vec![PlayerId(0), PlayerId(1)]);
+        }
+
+        let mut missing = artifact;
+        missing.inputs.pop();
+        assert!(matches!(
+            ReplayPlayer::new(&missing),
+            Err(VerifyError::InputStreamInvalid { .. })
+        ));
+    }
+    #[test]
+    fn test_player_rejects_bad_artifacts() {
+        let artifact = recorded_match(10, 2, 0).artifact;
+
+        let mut no_baseline = artifact.clone();
+        no_baseline.initial_baseline = None;
+        assert!(matches!(
+            ReplayPlayer::new(&no_baseline),
+            Err(VerifyError::MissingBaseline)
+        ));
+
+        let mut wrong_anchor = artifact.clone();
+        wrong_anchor.initial_baseline.as_mut().unwrap().digest ^= 1;
+        assert!(matches!(
+            ReplayPlayer::new(&wrong_anchor),
+            Err(VerifyError::InitializationAnchorMismatch { .. })
+        ));
+
+        let mut beyond_checkpoint = artifact.clone();
+        beyond_checkpoint.checkpoint_tick = 5;
+        assert!(ReplayPlayer::new(&beyond_checkpoint).is_err());
+
+        let mut unknown_player = artifact;
+        unknown_player.inputs[0].player_id = 7;
+        assert!(ReplayPlayer::new(&unknown_player).is_err());
+    }
+}
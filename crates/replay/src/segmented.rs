@@ -0,0 +1,428 @@
+//! Segmented replay files, for seeking without resimulating from tick 0.
+//!
+//! Ref: DM-0017 (ReplayArtifact), DM-0024 (AppliedInput), ADR-0007
+//! - Layout: `[SEGMENTED_MAGIC][SEGMENTED_VERSION][header_len: u32 LE]`,
+//!   the `SegmentedReplayHeader`, then the encoded `ReplaySegment`s
+//! - The header holds the artifact without its inputs plus an index of
+//!   segments; each segment holds the World state at its first tick and the
+//!   inputs of its tick range
+//! - `SegmentedReplayReader::world_at` reads one segment, checks its state
+//!   digest, and resimulates at most one segment's worth of ticks
+//!
+//! Writing resimulates the match once to capture segment states, so only a
+//! replay that verifies can be segmented. `read_replay_auto` reassembles
+//! segmented files into a plain artifact.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use flowstate_sim::{Baseline, EntityId, Tick, World};
+use flowstate_wire::{
+    AppliedInputProto, ReplayArtifact, ReplaySegment, ReplaySegmentIndexEntry,
+    SegmentedReplayHeader,
+};
+use prost::Message;
+
+use crate::divergence::{Divergence, find_divergence};
+use crate::{
+    VerifyError, VerifyOptions, advance_recorded, artifact_player_id, initial_world,
+    inputs_by_tick, migrate_artifact, recorded_tuning, validate_input_stream,
+    write_new_replay_file,
+};
+
+/// Magic bytes opening a segmented replay file.
+pub const SEGMENTED_MAGIC: [u8; 4] = *b"FSRS";
+
+/// Layout version, the byte after `SEGMENTED_MAGIC`.
+pub const SEGMENTED_VERSION: u8 = 1;
+
+/// Default ticks per segment (10 s at 60 Hz).
+pub const DEFAULT_SEGMENT_TICKS: u64 = 600;
+
+/// Largest header or segment the reader accepts (64 MiB).
+pub const MAX_SECTION_LEN: usize = 64 * 1024 * 1024;
+
+/// Magic, version, and header length.
+const PREFIX_LEN: usize = 9;
+
+/// Why a segmented replay could not be read.
+#[derive(Debug)]
+pub enum SegmentError {
+    Io(io::Error),
+    /// Not a segmented replay, or a malformed one.
+    InvalidFormat {
+        reason: String,
+    },
+    /// `tick` is outside the recorded ticks `[start, end]`.
+    OutOfRange {
+        tick: Tick,
+        start: Tick,
+        end: Tick,
+    },
+    /// A segment's state did not restore to its recorded digest.
+    Verify(VerifyError),
+}
+
+impl std::fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Segmented replay I/O failed: {e}"),
+            Self::InvalidFormat { reason } => write!(f, "Invalid segmented replay: {reason}"),
+            Self::OutOfRange { tick, start, end } => {
+                write!(f, "Tick {tick} outside the replay's ticks {start}..={end}")
+            }
+            Self::Verify(e) => write!(f, "Segment does not verify: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SegmentError {}
+
+impl From<io::Error> for SegmentError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<VerifyError> for SegmentError {
+    fn from(e: VerifyError) -> Self {
+        Self::Verify(e)
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> SegmentError {
+    SegmentError::InvalidFormat {
+        reason: reason.into(),
+    }
+}
+
+/// Encode `artifact` as a segmented replay of `segment_ticks` ticks per
+/// segment (at least 1).
+pub fn encode_segmented(
+    artifact: &ReplayArtifact,
+    segment_ticks: u64,
+) -> Result<Vec<u8>, VerifyError> {
+    let segment_ticks = segment_ticks.max(1);
+    validate_input_stream(artifact)?;
+    let mut world = initial_world(artifact)?;
+    let by_tick = inputs_by_tick(&artifact.inputs)?;
+    let start = world.tick();
+    let end = artifact.checkpoint_tick;
+
+    // Partition in recording order, so reassembly is byte-identical
+    let segment_count = end.saturating_sub(start).div_ceil(segment_ticks) as usize;
+    let mut segment_inputs: Vec<Vec<AppliedInputProto>> = vec![Vec::new(); segment_count];
+    for input in &artifact.inputs {
+        // Validated above: every input lies in [start, end)
+        let index = ((input.tick - start) / segment_ticks) as usize;
+        segment_inputs[index].push(input.clone());
+    }
+
+    let mut index = Vec::with_capacity(segment_count);
+    let mut body = Vec::new();
+    for (i, inputs) in segment_inputs.into_iter().enumerate() {
+        let start_tick = start + i as u64 * segment_ticks;
+        let end_tick = (start_tick + segment_ticks).min(end);
+        let segment = ReplaySegment {
+            state: Some(world.baseline().into()),
+            inputs,
+        };
+        let encoded = segment.encode_to_vec();
+        index.push(ReplaySegmentIndexEntry {
+            start_tick,
+            end_tick,
+            offset: body.len() as u64,
+            len: encoded.len() as u32,
+        });
+        body.extend_from_slice(&encoded);
+        for tick in start_tick..end_tick {
+            advance_recorded(&mut world, tick, &by_tick);
+        }
+    }
+    if world.tick() != end {
+        return Err(VerifyError::CheckpointTickMismatch {
+            expected: end,
+            actual: world.tick(),
+        });
+    }
+    if world.state_digest() != artifact.final_digest {
+        return Err(VerifyError::FinalDigestMismatch {
+            expected: artifact.final_digest,
+            actual: world.state_digest(),
+        });
+    }
+
+    let header = SegmentedReplayHeader {
+        artifact: Some(ReplayArtifact {
+            inputs: Vec::new(),
+            ..artifact.clone()
+        }),
+        segments: index,
+    }
+    .encode_to_vec();
+    let mut bytes = Vec::with_capacity(PREFIX_LEN + header.len() + body.len());
+    bytes.extend_from_slice(&SEGMENTED_MAGIC);
+    bytes.push(SEGMENTED_VERSION);
+    bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&header);
+    bytes.extend_from_slice(&body);
+    Ok(bytes)
+}
+
+/// Write `artifact` as a segmented replay file (see `encode_segmented`).
+pub fn write_replay_segmented(
+    artifact: &ReplayArtifact,
+    path: &Path,
+    segment_ticks: u64,
+) -> io::Result<()> {
+    let bytes = encode_segmented(artifact, segment_ticks)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    write_new_replay_file(path, &bytes)
+}
+
+/// Reader for a segmented replay; only the header is read up front.
+pub struct SegmentedReplayReader<R> {
+    reader: R,
+    /// Header artifact (no inputs), migrated to `REPLAY_FORMAT_VERSION`.
+    artifact: ReplayArtifact,
+    segments: Vec<ReplaySegmentIndexEntry>,
+    body_start: u64,
+}
+
+impl SegmentedReplayReader<BufReader<File>> {
+    /// Open the segmented replay file at `path`.
+    pub fn open(path: &Path) -> Result<Self, SegmentError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> SegmentedReplayReader<R> {
+    /// Read the header from the start of `reader`.
+    pub fn new(mut reader: R) -> Result<Self, SegmentError> {
+        let mut prefix = [0u8; PREFIX_LEN];
+        reader.read_exact(&mut prefix)?;
+        if prefix[..4] != SEGMENTED_MAGIC {
+            return Err(invalid("missing segmented replay magic"));
+        }
+        if prefix[4] != SEGMENTED_VERSION {
+            return Err(invalid(format!("unsupported version {}", prefix[4])));
+        }
+        let header_len = u32::from_le_bytes(prefix[5..].try_into().expect("4 bytes")) as usize;
+        let header: SegmentedReplayHeader = decode_section(&mut reader, header_len)?;
+        let mut artifact = header
+            .artifact
+            .ok_or_else(|| invalid("header has no artifact"))?;
+        migrate_artifact(&mut artifact);
+        Ok(Self {
+            reader,
+            artifact,
+            segments: header.segments,
+            body_start: (PREFIX_LEN + header_len) as u64,
+        })
+    }
+
+    /// The artifact without its inputs.
+    pub fn artifact_header(&self) -> &ReplayArtifact {
+        &self.artifact
+    }
+
+    /// The segment index, in tick order.
+    pub fn segments(&self) -> &[ReplaySegmentIndexEntry] {
+        &self.segments
+    }
+
+    /// Read and decode segment `index`.
+    pub fn read_segment(&mut self, index: usize) -> Result<ReplaySegment, SegmentError> {
+        let entry = self
+            .segments
+            .get(index)
+            .ok_or_else(|| invalid(format!("no segment {index}")))?;
+        let len = entry.len as usize;
+        self.reader
+            .seek(SeekFrom::Start(self.body_start + entry.offset))?;
+        decode_section(&mut self.reader, len)
+    }
+
+    /// The World once it has advanced to `tick`, rebuilt from the nearest
+    /// segment state at or before it.
+    pub fn world_at(&mut self, tick: Tick) -> Result<World, SegmentError> {
+        let start = self
+            .artifact
+            .initial_baseline
+            .as_ref()
+            .ok_or(VerifyError::MissingBaseline)?
+            .tick;
+        let end = self.artifact.checkpoint_tick;
+        if tick < start || tick > end {
+            return Err(SegmentError::OutOfRange { tick, start, end });
+        }
+        // Last segment starting at or before `tick` (`end` falls in the last)
+        let Some(index) = self
+            .segments
+            .partition_point(|s| s.start_tick <= tick)
+            .checked_sub(1)
+        else {
+            // No segments: a match that never advanced
+            return Ok(initial_world(&self.artifact)?);
+        };
+        let segment = self.read_segment(index)?;
+        let mut world = self.restore(&segment)?;
+        let by_tick = inputs_by_tick(&segment.inputs)?;
+        for t in world.tick()..tick {
+            advance_recorded(&mut world, t, &by_tick);
+        }
+        Ok(world)
+    }
+
+    /// Reassemble the full artifact.
+    pub fn to_artifact(&mut self) -> Result<ReplayArtifact, SegmentError> {
+        let mut artifact = self.artifact.clone();
+        for index in 0..self.segments.len() {
+            artifact.inputs.extend(self.read_segment(index)?.inputs);
+        }
+        Ok(artifact)
+    }
+
+    /// `find_first_divergent_tick` for the reassembled artifact, also
+    /// comparing each segment's recorded state entity by entity, so the
+    /// divergence is placed within one segment and its first differing
+    /// entity named.
+    pub fn find_first_divergence(
+        &mut self,
+        options: &VerifyOptions,
+    ) -> Result<Option<Divergence>, SegmentError> {
+        let artifact = self.to_artifact()?;
+        let mut states = BTreeMap::new();
+        for index in 0..self.segments.len() {
+            let state = self
+                .read_segment(index)?
+                .state
+                .ok_or_else(|| invalid("segment has no state"))?;
+            let state = Baseline::try_from(state).map_err(|e| invalid(e.to_string()))?;
+            states.insert(state.tick, state);
+        }
+        Ok(find_divergence(&artifact, options, &states)?)
+    }
+
+    fn restore(&self, segment: &ReplaySegment) -> Result<World, SegmentError> {
+        let state = segment
+            .state
+            .clone()
+            .ok_or_else(|| invalid("segment has no state"))?;
+        let state = Baseline::try_from(state).map_err(|e| invalid(e.to_string()))?;
+        let players = self
+            .artifact
+            .player_entity_mapping
+            .iter()
+            .map(|m| Ok((artifact_player_id(m.player_id)?, EntityId(m.entity_id))))
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        let world = World::restore(
+            self.artifact.seed,
+            self.artifact.tick_rate_hz,
+            recorded_tuning(&self.artifact)?,
+            &state,
+            &players,
+        )
+        .ok_or_else(|| invalid("segment entity without player mapping"))?;
+        if world.state_digest() != state.digest {
+            return Err(VerifyError::InitializationAnchorMismatch {
+                expected: state.digest,
+                actual: world.state_digest(),
+            }
+            .into());
+        }
+        Ok(world)
+    }
+}
+
+fn decode_section<M: Message + Default>(
+    reader: &mut impl Read,
+    len: usize,
+) -> Result<M, SegmentError> {
+    if len > MAX_SECTION_LEN {
+        return Err(invalid(format!(
+            "section of {len} bytes exceeds {MAX_SECTION_LEN}"
+        )));
+    }
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    M::decode(bytes.as_slice()).map_err(|e| invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_replay_auto;
+    use crate::test_support::{RecordedMatch, recorded_match};
+
+    #[test]
+    fn test_world_at_matches_full_resimulation() {
+        let RecordedMatch {
+            artifact, digests, ..
+        } = recorded_match(50, 2, 0);
+        let bytes = encode_segmented(&artifact, 16).unwrap();
+        let mut reader = SegmentedReplayReader::new(io::Cursor::new(bytes.clone())).unwrap();
+        let starts: Vec<_> = reader.segments().iter().map(|s| s.start_tick).collect();
+        assert_eq!(starts, vec![0, 16, 32, 48]);
+
+        for tick in [0, 15, 16, 37, 50] {
+            let world = reader.world_at(tick).unwrap();
+            assert_eq!(world.tick(), tick);
+            assert_eq!(world.state_digest(), digests[tick as usize], "tick {tick}");
+        }
+        assert!(matches!(
+            reader.world_at(51),
+            Err(SegmentError::OutOfRange { end: 50, .. })
+        ));
+
+        assert_eq!(reader.to_artifact().unwrap(), artifact);
+        assert_eq!(decode_replay_auto(&bytes).unwrap(), artifact);
+    }
+
+    #[test]
+    fn test_find_first_divergence_names_entity() {
+        let artifact = recorded_match(50, 2, 0).artifact;
+        let mut bytes = encode_segmented(&artifact, 16).unwrap();
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        let mut reader = SegmentedReplayReader::new(io::Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(reader.find_first_divergence(&options).unwrap(), None);
+
+        // Segment 1's state as recorded by a build that simulated differently
+        let mut segment = reader.read_segment(1).unwrap();
+        let state = segment.state.as_mut().unwrap();
+        let entity_id = EntityId(state.entities[1].entity_id);
+        state.entities[1].velocity = vec![0.5, 0.5];
+        state.digest ^= 1;
+        let encoded = segment.encode_to_vec();
+        let entry = &reader.segments()[1];
+        assert_eq!(encoded.len(), entry.len as usize);
+        let offset = (reader.body_start + entry.offset) as usize;
+        bytes[offset..offset + encoded.len()].copy_from_slice(&encoded);
+
+        let mut reader = SegmentedReplayReader::new(io::Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            reader.find_first_divergence(&options).unwrap(),
+            Some(Divergence {
+                tick: 16,
+                last_matching_tick: Some(0),
+                entity_id: Some(entity_id),
+            })
+        );
+    }
+
+    #[test]
+    fn test_unverifiable_replay_not_segmented() {
+        let mut artifact = recorded_match(50, 2, 0).artifact;
+        artifact.final_digest ^= 1;
+        assert!(matches!(
+            encode_segmented(&artifact, 16),
+            Err(VerifyError::FinalDigestMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+//! On-disk spill of recorded inputs for long matches.
+//!
+//! Ref: DM-0017 (ReplayArtifact), DM-0024 (AppliedInput)
+//! - After `ReplayRecorder::spill_to`, the recorder keeps at most
+//!   `SPILL_SEGMENT_INPUTS` inputs in memory; each full segment is appended
+//!   to the spill file as length-delimited `AppliedInputProto`s
+//! - The artifact is assembled by reading the file back, so it is identical
+//!   to one recorded in memory
+//! - A failed write keeps the segment in memory and stops spilling, so no
+//!   input is lost; `ReplayRecorder::spill_error` reports it
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use flowstate_wire::AppliedInputProto;
+use prost::Message;
+
+use crate::AppliedInput;
+
+/// Inputs held in memory between appends to the spill file.
+pub const SPILL_SEGMENT_INPUTS: usize = 4096;
+
+/// Append-only file of recorded inputs.
+#[derive(Debug)]
+pub(crate) struct InputSpill {
+    file: File,
+    /// Bytes of complete segments; anything past it is a torn write.
+    len: u64,
+}
+
+impl InputSpill {
+    /// Create (or truncate) the spill file at `path`.
+    pub(crate) fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Self { file, len: 0 })
+    }
+
+    /// Append one segment of inputs, all or nothing.
+    pub(crate) fn append(&mut self, inputs: &[AppliedInput]) -> io::Result<()> {
+        let mut buf = Vec::new();
+        for input in inputs {
+            AppliedInputProto::from(input.clone())
+                .encode_length_delimited(&mut buf)
+                .expect("Vec grows as needed");
+        }
+        self.file.seek(SeekFrom::Start(self.len))?;
+        if let Err(e) = self.file.write_all(&buf) {
+            // Drop the torn tail so a later read stops at the last segment
+            let _ = self.file.set_len(self.len);
+            return Err(e);
+        }
+        self.len += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Every spilled input, in recording order.
+    pub(crate) fn read_back(&self) -> io::Result<Vec<AppliedInputProto>> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(0))?;
+        let mut data = Vec::new();
+        file.take(self.len).read_to_end(&mut data)?;
+        let mut remaining = data.as_slice();
+        let mut inputs = Vec::new();
+        while !remaining.is_empty() {
+            let input = AppliedInputProto::decode_length_delimited(&mut remaining)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            inputs.push(input);
+        }
+        Ok(inputs)
+    }
+
+    /// Forget every spilled input (e.g., when the recorder starts a rematch).
+    pub(crate) fn clear(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use flowstate_sim::{PlayerId, StepInput, World};
+
+    use super::*;
+    use crate::{MatchEndReason, ReplayConfig, ReplayRecorder};
+
+    fn spill_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("flowstate-spill-{name}-{}", std::process::id()))
+    }
+
+    fn inputs(ticks: std::ops::Range<u64>) -> Vec<AppliedInput> {
+        ticks
+            .map(|tick| AppliedInput {
+                tick,
+                player_id: PlayerId(0),
+                move_dir: [1.0, 0.0],
+                is_fallback: false,
+            })
+            .collect()
+    }
+
+    fn protos(inputs: &[AppliedInput]) -> Vec<AppliedInputProto> {
+        inputs
+            .iter()
+            .cloned()
+            .map(AppliedInputProto::from)
+            .collect()
+    }
+
+    /// Record `ticks` of one player walking east.
+    fn record(recorder: &mut ReplayRecorder, ticks: u64) -> World {
+        let mut world = World::new(0, 60);
+        let entity_id = world.spawn_character(PlayerId(0));
+        recorder.record_spawn(PlayerId(0), entity_id);
+        recorder.record_baseline(world.baseline());
+        for input in inputs(0..ticks) {
+            let step = StepInput {
+                player_id: input.player_id,
+                move_dir: input.move_dir,
+            };
+            let tick = input.tick;
+            recorder.record_input(input);
+            world.advance(tick, &[step]);
+        }
+        world
+    }
+
+    #[test]
+    fn test_append_read_back_round_trip() {
+        let path = spill_path("round-trip");
+        let mut spill = InputSpill::create(&path).unwrap();
+        assert!(spill.read_back().unwrap().is_empty());
+        let first = inputs(0..3);
+        let second = inputs(3..5);
+        spill.append(&first).unwrap();
+        spill.append(&second).unwrap();
+        let expected = protos(&[first, second].concat());
+        assert_eq!(spill.read_back().unwrap(), expected);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_write_is_ignored_and_overwritten() {
+        let path = spill_path("torn");
+        let mut spill = InputSpill::create(&path).unwrap();
+        let first = inputs(0..3);
+        spill.append(&first).unwrap();
+        // A torn tail past the last complete segment
+        spill.file.seek(SeekFrom::End(0)).unwrap();
+        spill.file.write_all(&[0xff; 7]).unwrap();
+        assert_eq!(spill.read_back().unwrap(), protos(&first));
+
+        let second = inputs(3..4);
+        spill.append(&second).unwrap();
+        assert_eq!(
+            spill.read_back().unwrap(),
+            protos(&[first, second].concat())
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_clear_forgets_spilled_inputs() {
+        let path = spill_path("clear");
+        let mut spill = InputSpill::create(&path).unwrap();
+        spill.append(&inputs(0..3)).unwrap();
+        spill.clear().unwrap();
+        assert!(spill.read_back().unwrap().is_empty());
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+
+        let after = inputs(10..12);
+        spill.append(&after).unwrap();
+        assert_eq!(spill.read_back().unwrap(), protos(&after));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_write_keeps_inputs_in_memory() {
+        let path = spill_path("read-only");
+        File::create(&path).unwrap();
+        let mut recorder = ReplayRecorder::new(ReplayConfig::default());
+        // Writes to a read-only handle fail
+        recorder.spill = Some(InputSpill {
+            file: File::open(&path).unwrap(),
+            len: 0,
+        });
+
+        let ticks = SPILL_SEGMENT_INPUTS as u64 + 10;
+        let world = record(&mut recorder, ticks);
+        assert!(recorder.spill_error().is_some());
+        let artifact = recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap();
+        assert_eq!(artifact.inputs, protos(&inputs(0..ticks)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_spill_fails_finalize() {
+        let path = spill_path("corrupt");
+        let mut recorder = ReplayRecorder::new(ReplayConfig::default());
+        recorder.spill_to(&path).unwrap();
+        let world = record(&mut recorder, SPILL_SEGMENT_INPUTS as u64);
+        assert!(recorder.spill_error().is_none());
+        // Overwrite the first length prefix with an invalid varint
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.write_all(&[0xff; 10]).unwrap();
+
+        let err = recorder
+            .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+//! Recorded matches shared by the crate's tests.
+
+use flowstate_sim::{Baseline, PlayerId, Snapshot, StepInput, Tick, World};
+use flowstate_wire::{MatchEndReason, ReplayArtifact};
+
+use crate::{AppliedInput, ReplayConfig, ReplayRecorder};
+
+/// A finalized recording and what the simulation did along the way.
+pub(crate) struct RecordedMatch {
+    pub artifact: ReplayArtifact,
+    /// Full state at each tick, from the initial baseline (index 0) on.
+    pub states: Vec<Baseline>,
+    /// StateDigest at each tick, from the initial state (index 0) on.
+    pub digests: Vec<u64>,
+    /// `World::advance` output of each step, in order.
+    pub snapshots: Vec<Snapshot>,
+}
+
+/// Record `ticks` of `players` walking a square (each turns every 7 ticks,
+/// a quarter turn apart from the previous player), with a digest every
+/// `digest_interval` ticks (0 = none).
+///
+/// Each tick's inputs are recorded in descending player order, so readers
+/// that rely on (tick, player_id) order are exercised too.
+pub(crate) fn recorded_match(ticks: Tick, players: u8, digest_interval: Tick) -> RecordedMatch {
+    let mut recorder = ReplayRecorder::new(ReplayConfig::default());
+    let mut world = World::new(0, 60);
+    let player_ids: Vec<PlayerId> = (0..players).map(PlayerId).collect();
+    for &player_id in &player_ids {
+        let entity_id = world.spawn_character(player_id);
+        recorder.record_spawn(player_id, entity_id);
+    }
+    recorder.record_baseline(world.baseline());
+
+    let mut states = vec![world.baseline()];
+    let mut digests = vec![world.state_digest()];
+    let mut snapshots = Vec::new();
+    let dirs = [[1.0, 0.0], [0.0, 1.0], [-1.0, 0.0], [0.0, -1.0]];
+    for tick in 0..ticks {
+        let inputs: Vec<StepInput> = player_ids
+            .iter()
+            .map(|&player_id| StepInput {
+                player_id,
+                move_dir: dirs[(tick as usize / 7 + usize::from(player_id.0)) % 4],
+            })
+            .collect();
+        for input in inputs.iter().rev() {
+            recorder.record_input(AppliedInput {
+                tick,
+                player_id: input.player_id,
+                move_dir: input.move_dir,
+                is_fallback: false,
+            });
+        }
+        let snapshot = world.advance(tick, &inputs);
+        if digest_interval > 0 && snapshot.tick.is_multiple_of(digest_interval) {
+            recorder.record_digest(snapshot.tick, snapshot.digest);
+        }
+        states.push(world.baseline());
+        digests.push(world.state_digest());
+        snapshots.push(snapshot);
+    }
+
+    let artifact = recorder
+        .finalize(world.state_digest(), world.tick(), MatchEndReason::Complete)
+        .unwrap();
+    RecordedMatch {
+        artifact,
+        states,
+        digests,
+        snapshots,
+    }
+}
@@ -0,0 +1,221 @@
+//! Pre-session connection admission (flood protection).
+//!
+//! Ref: ADR-0005 (Control Channel), DM-0008 (Session)
+//! - Handshake attempts are capped per source IP per one-second window
+//!   (keyed by IP, not port, so port cycling does not evade the cap)
+//! - Unauthenticated (pending) connections are capped globally and expire
+//!
+//! Runs in the transport before `Server::accept_hello`, so a UDP flood cannot
+//! consume session slots or PlayerIds. Time is injected in microseconds
+//! (see `clock::Clock`).
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+use flowstate_wire::{ErrorCode, ErrorResponse};
+
+const WINDOW_MICROS: u64 = 1_000_000;
+
+/// Admission limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdmissionConfig {
+    /// Handshake attempts allowed per source IP per second.
+    pub max_handshakes_per_source_per_sec: u32,
+    /// Concurrent connections that have not completed authentication.
+    pub max_pending_connections: usize,
+    /// Pending connections older than this are dropped.
+    pub pending_timeout_micros: u64,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            max_handshakes_per_source_per_sec: 5,
+            max_pending_connections: 16,
+            pending_timeout_micros: 5_000_000,
+        }
+    }
+}
+
+/// Why a handshake was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdmissionError {
+    /// The source IP exceeded its handshake rate.
+    SourceRateLimited,
+    /// Too many unauthenticated connections are open.
+    TooManyPending,
+}
+
+impl std::fmt::Display for AdmissionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SourceRateLimited => write!(f, "Handshake rate exceeded for source"),
+            Self::TooManyPending => write!(f, "Too many pending connections"),
+        }
+    }
+}
+
+impl std::error::Error for AdmissionError {}
+
+impl AdmissionError {
+    /// The refusal sent to the client; both causes clear with time.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        let code = match self {
+            Self::SourceRateLimited => ErrorCode::RateLimited,
+            Self::TooManyPending => ErrorCode::ServerBusy,
+        };
+        ErrorResponse {
+            code: code as i32,
+            message: self.to_string(),
+            retryable: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SourceWindow {
+    window_start_micros: u64,
+    attempts: u32,
+}
+
+/// Tracks handshake attempts and pending connections.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionGuard {
+    config: AdmissionConfig,
+    sources: HashMap<IpAddr, SourceWindow>,
+    /// Pending connection → first handshake time.
+    pending: HashMap<SocketAddr, u64>,
+}
+
+impl ConnectionGuard {
+    pub fn new(config: AdmissionConfig) -> Self {
+        Self {
+            config,
+            sources: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record a handshake attempt from `source`; `Ok` means proceed to authentication.
+    pub fn on_handshake(
+        &mut self,
+        source: SocketAddr,
+        now_micros: u64,
+    ) -> Result<(), AdmissionError> {
+        let window = self.sources.entry(source.ip()).or_insert(SourceWindow {
+            window_start_micros: now_micros,
+            attempts: 0,
+        });
+        if now_micros.saturating_sub(window.window_start_micros) >= WINDOW_MICROS {
+            window.window_start_micros = now_micros;
+            window.attempts = 0;
+        }
+        window.attempts += 1;
+        if window.attempts > self.config.max_handshakes_per_source_per_sec {
+            return Err(AdmissionError::SourceRateLimited);
+        }
+
+        if !self.pending.contains_key(&source) {
+            self.expire(now_micros);
+            if self.pending.len() >= self.config.max_pending_connections {
+                return Err(AdmissionError::TooManyPending);
+            }
+            self.pending.insert(source, now_micros);
+        }
+        Ok(())
+    }
+
+    /// The connection authenticated; it no longer counts as pending.
+    pub fn on_authenticated(&mut self, source: SocketAddr) {
+        self.pending.remove(&source);
+    }
+
+    /// The connection closed or failed authentication.
+    pub fn on_closed(&mut self, source: SocketAddr) {
+        self.pending.remove(&source);
+    }
+
+    /// Drop timed-out pending connections and stale rate windows.
+    pub fn expire(&mut self, now_micros: u64) {
+        let timeout = self.config.pending_timeout_micros;
+        self.pending
+            .retain(|_, &mut started| now_micros.saturating_sub(started) < timeout);
+        self.sources
+            .retain(|_, w| now_micros.saturating_sub(w.window_start_micros) < WINDOW_MICROS);
+    }
+
+    /// Unauthenticated connections currently open.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(ip: [u8; 4], port: u16) -> SocketAddr {
+        SocketAddr::from((ip, port))
+    }
+
+    #[test]
+    fn test_per_source_rate_ignores_port() {
+        let mut guard = ConnectionGuard::new(AdmissionConfig {
+            max_handshakes_per_source_per_sec: 3,
+            ..Default::default()
+        });
+        for port in 0..3 {
+            assert!(guard.on_handshake(addr([10, 0, 0, 1], port), 0).is_ok());
+        }
+        assert_eq!(
+            guard.on_handshake(addr([10, 0, 0, 1], 99), 500_000),
+            Err(AdmissionError::SourceRateLimited)
+        );
+        // Other sources are unaffected; the window resets after a second
+        assert!(guard.on_handshake(addr([10, 0, 0, 2], 1), 500_000).is_ok());
+        assert!(
+            guard
+                .on_handshake(addr([10, 0, 0, 1], 99), 1_000_000)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_pending_cap_and_release() {
+        let mut guard = ConnectionGuard::new(AdmissionConfig {
+            max_pending_connections: 2,
+            ..Default::default()
+        });
+        let a = addr([10, 0, 0, 1], 1);
+        let b = addr([10, 0, 0, 2], 1);
+        let c = addr([10, 0, 0, 3], 1);
+        assert!(guard.on_handshake(a, 0).is_ok());
+        assert!(guard.on_handshake(b, 0).is_ok());
+        assert_eq!(
+            guard.on_handshake(c, 0),
+            Err(AdmissionError::TooManyPending)
+        );
+
+        // Retransmitted hello from an already-pending source is fine
+        assert!(guard.on_handshake(a, 10).is_ok());
+
+        guard.on_authenticated(a);
+        assert!(guard.on_handshake(c, 20).is_ok());
+        assert_eq!(guard.pending_count(), 2);
+    }
+
+    #[test]
+    fn test_pending_timeout() {
+        let mut guard = ConnectionGuard::new(AdmissionConfig {
+            max_pending_connections: 1,
+            pending_timeout_micros: 1_000,
+            ..Default::default()
+        });
+        assert!(guard.on_handshake(addr([10, 0, 0, 1], 1), 0).is_ok());
+        assert_eq!(
+            guard.on_handshake(addr([10, 0, 0, 2], 1), 500),
+            Err(AdmissionError::TooManyPending)
+        );
+        assert!(guard.on_handshake(addr([10, 0, 0, 2], 1), 1_000).is_ok());
+    }
+}
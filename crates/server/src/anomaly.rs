@@ -0,0 +1,206 @@
+//! Per-session input anomaly detection.
+//!
+//! Ref: DM-0008 (Session), FS-0007 Validation Rules
+//! - Perfectly periodic arrival timing (no human or network produces zero jitter)
+//! - Near-reversal direction flips at an implausible rate
+//! - Analog directions pinned at maximum magnitude for a full window
+//!
+//! Soft signals for moderation: they complement the hard rate limit and never
+//! cause inputs to be dropped or altered.
+
+use std::collections::VecDeque;
+
+/// Inputs per detection window.
+pub const ANOMALY_WINDOW_INPUTS: usize = 120;
+
+/// Inter-arrival standard deviation below which timing counts as periodic.
+pub const PERIODIC_STDDEV_MICROS: f64 = 50.0;
+
+/// Fraction of window inputs that may be near-reversals before flagging.
+pub const MAX_FLIP_RATE: f64 = 0.5;
+
+/// Magnitude at or above which a direction counts as maximum.
+const MAX_MAGNITUDE: f64 = 0.999;
+
+/// Cosine below which consecutive directions count as a reversal.
+const FLIP_COSINE: f64 = -0.9;
+
+/// Kind of input anomaly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnomalyKind {
+    PeriodicTiming,
+    DirectionFlipRate,
+    AlwaysMaxMagnitude,
+}
+
+impl AnomalyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PeriodicTiming => "periodic_timing",
+            Self::DirectionFlipRate => "direction_flip_rate",
+            Self::AlwaysMaxMagnitude => "always_max_magnitude",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    interarrival_micros: Option<u64>,
+    flipped: bool,
+    /// Max magnitude in a direction a keyboard cannot produce (not 8-way).
+    max_analog: bool,
+}
+
+/// Sliding-window anomaly detector for one session.
+#[derive(Debug, Clone, Default)]
+pub struct InputAnomalyDetector {
+    window: VecDeque<Sample>,
+    last_arrival_micros: Option<u64>,
+    last_dir: Option<[f64; 2]>,
+    raised: Vec<AnomalyKind>,
+}
+
+impl InputAnomalyDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observe one input. Returns anomalies raised for the first time.
+    pub fn observe(&mut self, arrival_micros: u64, move_dir: [f64; 2]) -> Vec<AnomalyKind> {
+        let interarrival_micros = self
+            .last_arrival_micros
+            .map(|last| arrival_micros.saturating_sub(last));
+        self.last_arrival_micros = Some(arrival_micros);
+
+        let magnitude = move_dir[0].hypot(move_dir[1]);
+        let flipped = self.last_dir.is_some_and(|last| {
+            let last_mag = last[0].hypot(last[1]);
+            last_mag > 0.0
+                && magnitude > 0.0
+                && (last[0] * move_dir[0] + last[1] * move_dir[1]) / (last_mag * magnitude)
+                    < FLIP_COSINE
+        });
+        self.last_dir = Some(move_dir);
+
+        self.window.push_back(Sample {
+            interarrival_micros,
+            flipped,
+            max_analog: magnitude >= MAX_MAGNITUDE && !is_eight_way(move_dir),
+        });
+        if self.window.len() > ANOMALY_WINDOW_INPUTS {
+            self.window.pop_front();
+        }
+        if self.window.len() < ANOMALY_WINDOW_INPUTS {
+            return Vec::new();
+        }
+
+        let mut detected = Vec::new();
+        if self
+            .interarrival_stddev()
+            .is_some_and(|sd| sd < PERIODIC_STDDEV_MICROS)
+        {
+            detected.push(AnomalyKind::PeriodicTiming);
+        }
+        let flips = self.window.iter().filter(|s| s.flipped).count();
+        if flips as f64 / self.window.len() as f64 > MAX_FLIP_RATE {
+            detected.push(AnomalyKind::DirectionFlipRate);
+        }
+        if self.window.iter().all(|s| s.max_analog) {
+            detected.push(AnomalyKind::AlwaysMaxMagnitude);
+        }
+
+        detected.retain(|kind| !self.raised.contains(kind));
+        self.raised.extend(detected.iter().copied());
+        detected
+    }
+
+    /// Anomalies raised so far, in the order they were first raised.
+    pub fn raised(&self) -> &[AnomalyKind] {
+        &self.raised
+    }
+
+    fn interarrival_stddev(&self) -> Option<f64> {
+        let values: Vec<f64> = self
+            .window
+            .iter()
+            .filter_map(|s| s.interarrival_micros)
+            .map(|v| v as f64)
+            .collect();
+        if values.len() < 2 {
+            return None;
+        }
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance =
+            values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / values.len() as f64;
+        Some(variance.sqrt())
+    }
+}
+
+/// Whether a direction lies on one of the eight digital-pad axes.
+fn is_eight_way(move_dir: [f64; 2]) -> bool {
+    const EPS: f64 = 1e-6;
+    let (x, y) = (move_dir[0].abs(), move_dir[1].abs());
+    x < EPS || y < EPS || (x - y).abs() < EPS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Arrival times with deterministic, human-like jitter.
+    fn jittered_arrival(i: u64) -> u64 {
+        i * 16_667 + (i * 7919 % 4000)
+    }
+
+    #[test]
+    fn test_human_like_input_raises_nothing() {
+        let mut detector = InputAnomalyDetector::new();
+        for i in 0..500u64 {
+            let dir = if (i / 40) % 2 == 0 {
+                [1.0, 0.0]
+            } else {
+                [0.0, 1.0]
+            };
+            assert!(detector.observe(jittered_arrival(i), dir).is_empty());
+        }
+        assert!(detector.raised().is_empty());
+    }
+
+    #[test]
+    fn test_periodic_timing_raised_once() {
+        let mut detector = InputAnomalyDetector::new();
+        let mut raised = Vec::new();
+        for i in 0..300u64 {
+            raised.extend(detector.observe(i * 16_667, [1.0, 0.0]));
+        }
+        assert_eq!(raised, vec![AnomalyKind::PeriodicTiming]);
+    }
+
+    #[test]
+    fn test_direction_flip_rate() {
+        let mut detector = InputAnomalyDetector::new();
+        for i in 0..200u64 {
+            let dir = if i % 2 == 0 { [1.0, 0.0] } else { [-1.0, 0.0] };
+            detector.observe(jittered_arrival(i), dir);
+        }
+        assert_eq!(detector.raised(), &[AnomalyKind::DirectionFlipRate]);
+    }
+
+    #[test]
+    fn test_always_max_analog_magnitude() {
+        let mut detector = InputAnomalyDetector::new();
+        for i in 0..200u64 {
+            let angle = 0.3 + (i % 10) as f64 * 0.01;
+            detector.observe(jittered_arrival(i), [angle.cos(), angle.sin()]);
+        }
+        assert_eq!(detector.raised(), &[AnomalyKind::AlwaysMaxMagnitude]);
+
+        // Full-magnitude 8-way input (keyboard) is not anomalous
+        let mut keyboard = InputAnomalyDetector::new();
+        let diag = std::f64::consts::FRAC_1_SQRT_2;
+        for i in 0..200u64 {
+            keyboard.observe(jittered_arrival(i), [diag, diag]);
+        }
+        assert!(keyboard.raised().is_empty());
+    }
+}
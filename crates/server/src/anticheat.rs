@@ -0,0 +1,147 @@
+//! Server-side movement plausibility checks.
+//!
+//! Ref: DM-0011 (Server Edge), DM-0024 (AppliedInput)
+//! - After each step, compare every player's realized displacement against the
+//!   maximum allowed per tick (MOVE_SPEED * dt; v0 has no speed effects)
+//! - A single excess may be float noise; a session is flagged once excesses
+//!   accumulate within a sliding window
+//!
+//! Observation only: flags are surfaced as ServerEvents, never fed back into
+//! the Simulation Core.
+
+use std::collections::VecDeque;
+
+use flowstate_sim::{MOVE_SPEED, Tick};
+
+/// Relative slack allowed over the nominal per-tick displacement.
+pub const DISPLACEMENT_TOLERANCE: f64 = 1.01;
+
+/// Sliding window for counting violations, in ticks.
+pub const SPEED_CHECK_WINDOW_TICKS: u64 = 60;
+
+/// Violations within the window that flag a session.
+pub const SPEED_VIOLATION_THRESHOLD: usize = 5;
+
+/// Maximum allowed displacement for one tick at `tick_rate_hz`.
+pub fn max_displacement_per_tick(tick_rate_hz: u32) -> f64 {
+    MOVE_SPEED / f64::from(tick_rate_hz)
+}
+
+/// Per-session movement check state.
+#[derive(Debug, Clone, Default)]
+pub struct MovementCheck {
+    last_position: Option<[f64; 2]>,
+    violation_ticks: VecDeque<Tick>,
+    total_violations: u64,
+    max_ratio: f64,
+    flagged: bool,
+}
+
+impl MovementCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observe the entity position after simulating to `tick`.
+    /// Returns true when this observation causes the session to become flagged.
+    pub fn observe(&mut self, tick: Tick, position: [f64; 2], max_displacement: f64) -> bool {
+        let Some(last) = self.last_position.replace(position) else {
+            return false;
+        };
+        let displacement = (position[0] - last[0]).hypot(position[1] - last[1]);
+        let ratio = displacement / max_displacement;
+        self.max_ratio = self.max_ratio.max(ratio);
+
+        if ratio > DISPLACEMENT_TOLERANCE {
+            self.total_violations += 1;
+            self.violation_ticks.push_back(tick);
+        }
+        while self
+            .violation_ticks
+            .front()
+            .is_some_and(|&t| t + SPEED_CHECK_WINDOW_TICKS <= tick)
+        {
+            self.violation_ticks.pop_front();
+        }
+
+        if !self.flagged && self.violation_ticks.len() >= SPEED_VIOLATION_THRESHOLD {
+            self.flagged = true;
+            return true;
+        }
+        false
+    }
+
+    /// Start over for a new match: the entity respawns and ticks restart,
+    /// so the last position and the violation window are dropped. Totals and
+    /// the flag are kept.
+    pub fn restart(&mut self) {
+        self.last_position = None;
+        self.violation_ticks.clear();
+    }
+
+    /// Whether the session has been flagged.
+    pub fn is_flagged(&self) -> bool {
+        self.flagged
+    }
+
+    /// Total ticks whose displacement exceeded the allowance.
+    pub fn total_violations(&self) -> u64 {
+        self.total_violations
+    }
+
+    /// Largest observed displacement / allowance ratio.
+    pub fn max_ratio(&self) -> f64 {
+        self.max_ratio
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nominal_movement_not_flagged() {
+        let max = max_displacement_per_tick(60);
+        let mut check = MovementCheck::new();
+        for tick in 0..200u64 {
+            assert!(!check.observe(tick, [tick as f64 * max, 0.0], max));
+        }
+        assert!(!check.is_flagged());
+        assert_eq!(check.total_violations(), 0);
+        assert!(check.max_ratio() <= DISPLACEMENT_TOLERANCE);
+    }
+
+    #[test]
+    fn test_sustained_excess_flags_once() {
+        let max = max_displacement_per_tick(60);
+        let mut check = MovementCheck::new();
+        let mut flags = 0;
+        for tick in 0..20u64 {
+            if check.observe(tick, [tick as f64 * max * 2.0, 0.0], max) {
+                flags += 1;
+            }
+        }
+        assert_eq!(flags, 1);
+        assert!(check.is_flagged());
+        assert_eq!(check.total_violations(), 19);
+        assert!((check.max_ratio() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sparse_excess_outside_window_not_flagged() {
+        let max = max_displacement_per_tick(60);
+        let mut check = MovementCheck::new();
+        let mut x = 0.0;
+        for tick in 0..(SPEED_CHECK_WINDOW_TICKS * 10) {
+            // One teleport per window
+            x += if tick % SPEED_CHECK_WINDOW_TICKS == 1 {
+                max * 10.0
+            } else {
+                max
+            };
+            check.observe(tick, [x, 0.0], max);
+        }
+        assert!(!check.is_flagged());
+        assert_eq!(check.total_violations(), 10);
+    }
+}
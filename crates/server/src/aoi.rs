@@ -0,0 +1,223 @@
+//! Interest management (area-of-interest filtering) for Server Edge.
+//!
+//! Ref: DM-0011 (Server Edge), INV-0007
+//! - Relevance is a presentation concern; the Simulation Core is unaffected
+//! - Each session receives entities within `radius` of its controlled entity
+//! - The controlled entity is always included
+//! - Entity order remains entity_id ascending (INV-0007)
+//! - Under a size limit, entities are kept in priority order (controlled
+//!   entity, then nearest first) and the snapshot is flagged `truncated`
+
+use flowstate_sim::{EntityId, World};
+use flowstate_wire::{EntitySnapshotProto, SnapshotProto};
+
+/// Filter a full snapshot down to the entities relevant to one session.
+///
+/// Relevance is computed with the Simulation Core's spatial query
+/// (`World::entities_within`). The digest and floor are carried through
+/// unchanged: the digest still describes the full World state, so clients
+/// receiving a filtered view MUST NOT verify it against their partial state.
+pub fn filter_for_session(
+    world: &World,
+    snapshot: &SnapshotProto,
+    controlled_entity_id: EntityId,
+    radius: f64,
+) -> SnapshotProto {
+    let Some(center) = world.entity_position(controlled_entity_id) else {
+        // No controlled entity (defensive): nothing is relevant
+        return SnapshotProto {
+            entities: Vec::new(),
+            ..snapshot.clone()
+        };
+    };
+
+    let mut relevant = world.entities_within(center, radius);
+    if !relevant.contains(&controlled_entity_id) {
+        relevant.push(controlled_entity_id);
+        relevant.sort_unstable();
+    }
+
+    SnapshotProto {
+        entities: snapshot
+            .entities
+            .iter()
+            .filter(|e| relevant.binary_search(&EntityId(e.entity_id)).is_ok())
+            .cloned()
+            .collect(),
+        ..snapshot.clone()
+    }
+}
+
+/// Cut `snapshot` to at most `max_bytes` encoded, keeping entities by
+/// priority: the controlled entity (always kept, even alone over the limit),
+/// then nearest to it, ties by lower EntityId. Kept entities stay in
+/// entity_id order, and `truncated` is set if any were dropped. A snapshot
+/// that already fits is returned unchanged.
+pub fn fit_to_size(
+    snapshot: &SnapshotProto,
+    controlled_entity_id: EntityId,
+    max_bytes: usize,
+) -> SnapshotProto {
+    if prost::Message::encoded_len(snapshot) <= max_bytes {
+        return snapshot.clone();
+    }
+    let controlled_entity_id = u64::from(controlled_entity_id);
+    let position = |e: &EntitySnapshotProto| match e.position[..] {
+        [x, y] => [x, y],
+        _ => [0.0, 0.0],
+    };
+    let center = snapshot
+        .entities
+        .iter()
+        .find(|e| e.entity_id == controlled_entity_id)
+        .map(position);
+    let mut by_priority: Vec<&EntitySnapshotProto> = snapshot.entities.iter().collect();
+    by_priority.sort_by(|a, b| {
+        let own = |e: &EntitySnapshotProto| e.entity_id != controlled_entity_id;
+        let distance = |e: &EntitySnapshotProto| {
+            center.map_or(0.0, |[cx, cy]| {
+                let [x, y] = position(e);
+                (x - cx).powi(2) + (y - cy).powi(2)
+            })
+        };
+        own(a)
+            .cmp(&own(b))
+            .then(distance(a).total_cmp(&distance(b)))
+            .then(a.entity_id.cmp(&b.entity_id))
+    });
+
+    let mut fitted = SnapshotProto {
+        entities: Vec::new(),
+        truncated: true,
+        ..snapshot.clone()
+    };
+    let mut size = prost::Message::encoded_len(&fitted);
+    for entity in by_priority {
+        let cost = prost::encoding::message::encoded_len(2, entity);
+        if size + cost > max_bytes && entity.entity_id != controlled_entity_id {
+            break;
+        }
+        size += cost;
+        fitted.entities.push(entity.clone());
+    }
+    fitted.entities.sort_by_key(|e| e.entity_id);
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstate_sim::{PlayerId, StepInput};
+
+    fn world_with_spread_characters() -> (World, EntityId, EntityId) {
+        let mut world = World::new(0, 60);
+        let a = world.spawn_character(PlayerId(0));
+        let b = world.spawn_character(PlayerId(1));
+
+        // Player 1 walks 5.0 units along +x over 60 ticks
+        for tick in 0..60 {
+            world.advance(
+                tick,
+                &[StepInput {
+                    player_id: PlayerId(1),
+                    move_dir: [1.0, 0.0],
+                }],
+            );
+        }
+        (world, a, b)
+    }
+
+    fn full_snapshot(world: &World) -> SnapshotProto {
+        let baseline = world.baseline();
+        SnapshotProto {
+            tick: baseline.tick,
+            entities: baseline.entities.into_iter().map(Into::into).collect(),
+            digest: baseline.digest,
+            target_tick_floor: baseline.tick + 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_excludes_distant_entities() {
+        let (world, a, _b) = world_with_spread_characters();
+        let full = full_snapshot(&world);
+
+        let filtered = filter_for_session(&world, &full, a, 1.0);
+        let ids: Vec<_> = filtered
+            .entities
+            .iter()
+            .map(|e| EntityId(e.entity_id))
+            .collect();
+        assert_eq!(ids, vec![a]);
+        assert_eq!(filtered.digest, full.digest);
+        assert_eq!(filtered.target_tick_floor, full.target_tick_floor);
+    }
+
+    #[test]
+    fn test_filter_includes_entities_in_radius() {
+        let (world, a, b) = world_with_spread_characters();
+        let full = full_snapshot(&world);
+
+        let filtered = filter_for_session(&world, &full, b, 10.0);
+        let ids: Vec<_> = filtered
+            .entities
+            .iter()
+            .map(|e| EntityId(e.entity_id))
+            .collect();
+        assert_eq!(ids, vec![a, b]);
+    }
+
+    #[test]
+    fn test_filter_always_includes_controlled_entity() {
+        let (world, _a, b) = world_with_spread_characters();
+        let full = full_snapshot(&world);
+
+        // Zero radius still includes the controlled entity itself
+        let filtered = filter_for_session(&world, &full, b, 0.0);
+        let ids: Vec<_> = filtered
+            .entities
+            .iter()
+            .map(|e| EntityId(e.entity_id))
+            .collect();
+        assert_eq!(ids, vec![b]);
+    }
+
+    #[test]
+    fn test_fit_to_size_keeps_priority_order() {
+        let entity = |entity_id, x| EntitySnapshotProto {
+            entity_id,
+            position: vec![x, 0.0],
+            velocity: vec![0.0, 0.0],
+            ..Default::default()
+        };
+        let full = SnapshotProto {
+            tick: 1,
+            entities: vec![
+                entity(1, 9.0),
+                entity(2, 0.0),
+                entity(3, 5.0),
+                entity(4, 1.0),
+            ],
+            digest: 7,
+            target_tick_floor: 2,
+            ..Default::default()
+        };
+        let full_len = prost::Message::encoded_len(&full);
+        assert_eq!(fit_to_size(&full, EntityId(2), full_len), full);
+
+        // Room for two of four entities (plus the 2-byte flag): own (2), then nearest (4)
+        let entity_len = prost::encoding::message::encoded_len(2, &full.entities[0]);
+        let max_bytes = full_len - 2 * entity_len + 2;
+        let fitted = fit_to_size(&full, EntityId(2), max_bytes);
+        let ids: Vec<_> = fitted.entities.iter().map(|e| e.entity_id).collect();
+        assert_eq!(ids, vec![2, 4]);
+        assert!(fitted.truncated);
+        assert_eq!(prost::Message::encoded_len(&fitted), max_bytes);
+
+        // The controlled entity survives even when nothing fits
+        let fitted = fit_to_size(&full, EntityId(3), 0);
+        let ids: Vec<_> = fitted.entities.iter().map(|e| e.entity_id).collect();
+        assert_eq!(ids, vec![3]);
+    }
+}
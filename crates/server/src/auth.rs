@@ -0,0 +1,134 @@
+//! Handshake authentication for Server Edge.
+//!
+//! Ref: ADR-0005 (Control Channel), DM-0008 (Session)
+//! - ClientHello carries an opaque auth token
+//! - An `Authenticator` validates it before a Session is created
+//! - The default `AllowAll` keeps v0 first-come-first-served behavior
+
+use std::collections::HashMap;
+
+use flowstate_wire::{ErrorCode, ErrorResponse};
+
+/// Stable external identity of an authenticated player (e.g., account id).
+pub type PlayerIdentity = String;
+
+/// Handshake authentication failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// ClientHello carried no token but one is required.
+    MissingToken,
+    /// Token was not recognized.
+    InvalidToken,
+    /// Identity already has an active session (`DuplicateIdentityPolicy::RejectNew`).
+    AlreadyConnected,
+    /// ClientHello carried a protocol version this server does not speak.
+    UnsupportedProtocolVersion(u32),
+    /// ClientHello carried a schema hash different from this server's
+    /// (mismatched wire crate builds, T0.19).
+    SchemaMismatch(u64),
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingToken => write!(f, "Missing auth token"),
+            Self::InvalidToken => write!(f, "Invalid auth token"),
+            Self::AlreadyConnected => write!(f, "Identity already has an active session"),
+            Self::UnsupportedProtocolVersion(version) => {
+                write!(f, "Unsupported protocol version {version}")
+            }
+            Self::SchemaMismatch(hash) => write!(
+                f,
+                "Schema hash {hash:#018x} does not match server {:#018x}",
+                flowstate_wire::SCHEMA_HASH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl AuthError {
+    /// The refusal sent to the client.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        let code = match self {
+            Self::MissingToken => ErrorCode::MissingToken,
+            Self::InvalidToken => ErrorCode::InvalidToken,
+            Self::AlreadyConnected => ErrorCode::AlreadyConnected,
+            Self::UnsupportedProtocolVersion(_) => ErrorCode::UnsupportedProtocolVersion,
+            Self::SchemaMismatch(_) => ErrorCode::SchemaMismatch,
+        };
+        ErrorResponse {
+            code: code as i32,
+            message: self.to_string(),
+            // The other session may close; nothing else changes on retry
+            retryable: matches!(self, Self::AlreadyConnected),
+        }
+    }
+}
+
+/// Validates ClientHello auth tokens.
+pub trait Authenticator: Send {
+    /// Returns the player's identity, `None` for anonymous sessions.
+    fn authenticate(&self, token: &str) -> Result<Option<PlayerIdentity>, AuthError>;
+}
+
+/// Accepts every connection anonymously (v0 behavior).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl Authenticator for AllowAll {
+    fn authenticate(&self, _token: &str) -> Result<Option<PlayerIdentity>, AuthError> {
+        Ok(None)
+    }
+}
+
+/// Accepts only tokens issued to invited players.
+#[derive(Debug, Clone, Default)]
+pub struct InviteList {
+    invites: HashMap<String, PlayerIdentity>,
+}
+
+impl InviteList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue `token` to `identity`.
+    pub fn invite(&mut self, token: impl Into<String>, identity: impl Into<PlayerIdentity>) {
+        self.invites.insert(token.into(), identity.into());
+    }
+}
+
+impl Authenticator for InviteList {
+    fn authenticate(&self, token: &str) -> Result<Option<PlayerIdentity>, AuthError> {
+        if token.is_empty() {
+            return Err(AuthError::MissingToken);
+        }
+        self.invites
+            .get(token)
+            .cloned()
+            .map(Some)
+            .ok_or(AuthError::InvalidToken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_all_is_anonymous() {
+        assert_eq!(AllowAll.authenticate(""), Ok(None));
+        assert_eq!(AllowAll.authenticate("anything"), Ok(None));
+    }
+
+    #[test]
+    fn test_invite_list() {
+        let mut invites = InviteList::new();
+        invites.invite("tok-a", "alice");
+        assert_eq!(invites.authenticate("tok-a"), Ok(Some("alice".to_string())));
+        assert_eq!(invites.authenticate("tok-b"), Err(AuthError::InvalidToken));
+        assert_eq!(invites.authenticate(""), Err(AuthError::MissingToken));
+    }
+}
@@ -0,0 +1,408 @@
+//! Outbound bandwidth budgeting per session.
+//!
+//! Ref: DM-0008 (Session), DM-0011 (Server Edge), ADR-0005
+//! - Each session has a token bucket of `bytes_per_sec`, up to `burst_bytes`
+//! - Queued messages leave in priority order: snapshots, then events
+//!   (Control messages such as welcomes and pongs), then chat, then bulk
+//!   downloads (replay chunks)
+//! - Only the newest queued snapshot is kept: a snapshot is full state, so an
+//!   older one still waiting is superseded rather than sent late
+//! - Per-session queues are bounded by `max_queued_bytes` (backpressure): on
+//!   overflow the oldest droppable payloads (bulk, then chat, then
+//!   snapshots) are dropped
+//! - Control messages (`SendClass::Event`) are never dropped; they may push a
+//!   queue past its cap, but are only produced in response to client traffic
+//!   or rare lifecycle changes, so a stalled client cannot grow them unbounded
+//!
+//! A slow, lossy, or stalled link therefore costs at most one bounded queue,
+//! never unbounded buffering in the transport. Server Edge only; nothing here
+//! affects simulation state.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use flowstate_wire::channel::Delivery;
+
+use crate::session::SessionId;
+
+/// Default per-session outbound budget (bytes per second).
+pub const OUTBOUND_BYTES_PER_SEC: u64 = 128 * 1024;
+
+/// Default per-session burst allowance (bytes).
+pub const OUTBOUND_BURST_BYTES: u64 = 16 * 1024;
+
+/// Default cap on bytes waiting in one session's queue.
+pub const OUTBOUND_QUEUE_MAX_BYTES: usize = 64 * 1024;
+
+/// Send priority, highest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SendClass {
+    /// Realtime full state; droppable.
+    Snapshot,
+    /// Control messages; never dropped.
+    Event,
+    /// Realtime chat lines; droppable.
+    Chat,
+    /// Bulk downloads the client re-requests when lost (replay chunks);
+    /// droppable.
+    Bulk,
+}
+
+const CLASSES: [SendClass; 4] = [
+    SendClass::Snapshot,
+    SendClass::Event,
+    SendClass::Chat,
+    SendClass::Bulk,
+];
+
+impl SendClass {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Whether backpressure may drop messages of this class.
+    pub fn is_droppable(self) -> bool {
+        self != SendClass::Event
+    }
+
+    /// Whether this class honors `delivery`: only `Event` is never dropped,
+    /// and only `Snapshot` supersedes older messages.
+    pub fn carries(self, delivery: Delivery) -> bool {
+        match self {
+            SendClass::Event => delivery != Delivery::Latest,
+            SendClass::Snapshot => delivery == Delivery::Latest,
+            SendClass::Chat | SendClass::Bulk => delivery == Delivery::Unreliable,
+        }
+    }
+}
+
+/// Budget applied to every session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BandwidthBudget {
+    /// Sustained rate (0 = unlimited; queued messages always drain).
+    pub bytes_per_sec: u64,
+    pub burst_bytes: u64,
+    pub max_queued_bytes: usize,
+}
+
+impl Default for BandwidthBudget {
+    fn default() -> Self {
+        Self {
+            bytes_per_sec: OUTBOUND_BYTES_PER_SEC,
+            burst_bytes: OUTBOUND_BURST_BYTES,
+            max_queued_bytes: OUTBOUND_QUEUE_MAX_BYTES,
+        }
+    }
+}
+
+/// Outbound accounting for one session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BandwidthStats {
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    /// Bytes currently waiting for budget.
+    pub bytes_queued: usize,
+    /// Messages currently waiting for budget (queue depth).
+    pub messages_queued: usize,
+    /// Largest `bytes_queued` observed.
+    pub peak_bytes_queued: usize,
+    /// Queued snapshots replaced by a newer one before being sent.
+    pub superseded: u64,
+    /// Realtime messages dropped because the queue was full.
+    pub dropped_overflow: u64,
+    /// Polls that left messages queued for lack of budget.
+    pub deferred: u64,
+}
+
+impl BandwidthStats {
+    fn add(&mut self, other: &Self) {
+        self.messages_sent += other.messages_sent;
+        self.bytes_sent += other.bytes_sent;
+        self.bytes_queued += other.bytes_queued;
+        self.messages_queued += other.messages_queued;
+        self.peak_bytes_queued = self.peak_bytes_queued.max(other.peak_bytes_queued);
+        self.superseded += other.superseded;
+        self.dropped_overflow += other.dropped_overflow;
+        self.deferred += other.deferred;
+    }
+}
+
+/// One session's token bucket and priority queues.
+#[derive(Debug)]
+struct SessionQueue {
+    /// May go negative: a message larger than the remaining budget is sent
+    /// once any budget is left, and the debt is repaid before the next send.
+    tokens: i64,
+    last_refill_micros: u64,
+    queues: [VecDeque<Vec<u8>>; 4],
+    stats: BandwidthStats,
+}
+
+impl SessionQueue {
+    fn new(budget: &BandwidthBudget, now_micros: u64) -> Self {
+        Self {
+            tokens: i64::try_from(budget.burst_bytes).unwrap_or(i64::MAX),
+            last_refill_micros: now_micros,
+            queues: Default::default(),
+            stats: BandwidthStats::default(),
+        }
+    }
+
+    fn refill(&mut self, budget: &BandwidthBudget, now_micros: u64) {
+        let elapsed = now_micros.saturating_sub(self.last_refill_micros);
+        self.last_refill_micros = self.last_refill_micros.max(now_micros);
+        let earned = u128::from(elapsed) * u128::from(budget.bytes_per_sec) / 1_000_000;
+        let burst = i64::try_from(budget.burst_bytes).unwrap_or(i64::MAX);
+        let earned = i64::try_from(earned).unwrap_or(i64::MAX);
+        self.tokens = self.tokens.saturating_add(earned).min(burst);
+    }
+
+    fn enqueue(&mut self, budget: &BandwidthBudget, class: SendClass, message: Vec<u8>) {
+        if class == SendClass::Snapshot {
+            let superseded = &mut self.queues[class.index()];
+            self.stats.superseded += superseded.len() as u64;
+            self.stats.messages_queued -= superseded.len();
+            self.stats.bytes_queued -= superseded.iter().map(Vec::len).sum::<usize>();
+            superseded.clear();
+        }
+        // Evict realtime messages (lowest priority, oldest first) to make room.
+        // Realtime messages only evict lower-or-equal priority; control
+        // messages evict any realtime message.
+        while self.stats.bytes_queued + message.len() > budget.max_queued_bytes {
+            let victim = CLASSES
+                .iter()
+                .rev()
+                .filter(|&&c| c.is_droppable() && (!class.is_droppable() || c >= class))
+                .find_map(|&c| self.queues[c.index()].pop_front());
+            match victim {
+                Some(victim) => {
+                    self.stats.bytes_queued -= victim.len();
+                    self.stats.messages_queued -= 1;
+                    self.stats.dropped_overflow += 1;
+                }
+                // Control messages are queued past the cap
+                None if !class.is_droppable() => break,
+                None => {
+                    self.stats.dropped_overflow += 1;
+                    return;
+                }
+            }
+        }
+        self.stats.bytes_queued += message.len();
+        self.stats.messages_queued += 1;
+        self.stats.peak_bytes_queued = self.stats.peak_bytes_queued.max(self.stats.bytes_queued);
+        self.queues[class.index()].push_back(message);
+    }
+
+    fn drain(&mut self, budget: &BandwidthBudget, now_micros: u64) -> Vec<Vec<u8>> {
+        self.refill(budget, now_micros);
+        let mut sent = Vec::new();
+        for class in CLASSES {
+            while budget.bytes_per_sec == 0 || self.tokens > 0 {
+                let Some(message) = self.queues[class.index()].pop_front() else {
+                    break;
+                };
+                let len = message.len();
+                self.tokens = self.tokens.saturating_sub(len as i64);
+                self.stats.bytes_queued -= len;
+                self.stats.messages_queued -= 1;
+                self.stats.bytes_sent += len as u64;
+                self.stats.messages_sent += 1;
+                sent.push(message);
+            }
+        }
+        if self.stats.bytes_queued > 0 {
+            self.stats.deferred += 1;
+        }
+        sent
+    }
+}
+
+/// Per-session outbound shaper used by the host between the Server and the
+/// transport. Sessions are kept in SessionId order.
+#[derive(Debug)]
+pub struct BandwidthShaper {
+    budget: BandwidthBudget,
+    sessions: BTreeMap<SessionId, SessionQueue>,
+}
+
+impl BandwidthShaper {
+    pub fn new(budget: BandwidthBudget) -> Self {
+        Self {
+            budget,
+            sessions: BTreeMap::new(),
+        }
+    }
+
+    pub fn budget(&self) -> BandwidthBudget {
+        self.budget
+    }
+
+    /// Change the budget; existing token balances are clamped on next refill.
+    pub fn set_budget(&mut self, budget: BandwidthBudget) {
+        self.budget = budget;
+    }
+
+    /// Queue `message` for `session_id`. A new session starts with a full burst.
+    pub fn enqueue(
+        &mut self,
+        session_id: SessionId,
+        class: SendClass,
+        message: Vec<u8>,
+        now_micros: u64,
+    ) {
+        let budget = self.budget;
+        self.sessions
+            .entry(session_id)
+            .or_insert_with(|| SessionQueue::new(&budget, now_micros))
+            .enqueue(&budget, class, message);
+    }
+
+    /// Messages `session_id` may send now, in priority order.
+    pub fn drain(&mut self, session_id: SessionId, now_micros: u64) -> Vec<Vec<u8>> {
+        let budget = self.budget;
+        self.sessions
+            .get_mut(&session_id)
+            .map(|queue| queue.drain(&budget, now_micros))
+            .unwrap_or_default()
+    }
+
+    /// Forget a session and anything still queued for it.
+    pub fn remove_session(&mut self, session_id: SessionId) {
+        self.sessions.remove(&session_id);
+    }
+
+    pub fn stats(&self, session_id: SessionId) -> Option<BandwidthStats> {
+        self.sessions.get(&session_id).map(|queue| queue.stats)
+    }
+
+    /// Deepest queue across sessions, in messages (0 with no sessions).
+    pub fn max_queue_depth(&self) -> usize {
+        self.sessions
+            .values()
+            .map(|queue| queue.stats.messages_queued)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Stats summed over all sessions (`peak_bytes_queued` is the maximum).
+    pub fn total_stats(&self) -> BandwidthStats {
+        let mut total = BandwidthStats::default();
+        for queue in self.sessions.values() {
+            total.add(&queue.stats);
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(bytes_per_sec: u64, burst_bytes: u64, max_queued_bytes: usize) -> BandwidthBudget {
+        BandwidthBudget {
+            bytes_per_sec,
+            burst_bytes,
+            max_queued_bytes,
+        }
+    }
+
+    #[test]
+    fn test_priority_order_and_budget() {
+        let mut shaper = BandwidthShaper::new(budget(1_000, 100, 1_000));
+        shaper.enqueue(SessionId(1), SendClass::Chat, vec![3; 50], 0);
+        shaper.enqueue(SessionId(1), SendClass::Event, vec![2; 50], 0);
+        shaper.enqueue(SessionId(1), SendClass::Snapshot, vec![1; 80], 0);
+
+        // Burst of 100: the snapshot, then the event on the remaining budget
+        let sent = shaper.drain(SessionId(1), 0);
+        assert_eq!(sent, vec![vec![1; 80], vec![2; 50]]);
+        assert!(shaper.drain(SessionId(1), 10_000).is_empty());
+        // 30 bytes of debt repaid after 30ms; chat goes out once budget returns
+        assert_eq!(shaper.drain(SessionId(1), 31_000), vec![vec![3; 50]]);
+
+        let stats = shaper.stats(SessionId(1)).unwrap();
+        assert_eq!((stats.messages_sent, stats.bytes_sent), (3, 180));
+        assert_eq!(stats.bytes_queued, 0);
+        assert_eq!(stats.deferred, 2);
+    }
+
+    #[test]
+    fn test_snapshots_superseded_and_queue_bounded() {
+        let mut shaper = BandwidthShaper::new(budget(1, 0, 100));
+        shaper.enqueue(SessionId(1), SendClass::Snapshot, vec![1; 40], 0);
+        shaper.enqueue(SessionId(1), SendClass::Snapshot, vec![2; 40], 0);
+        shaper.enqueue(SessionId(1), SendClass::Chat, vec![3; 40], 0);
+        // Overflow evicts the chat line, never the newer event or snapshot
+        shaper.enqueue(SessionId(1), SendClass::Event, vec![4; 40], 0);
+        // A chat line that cannot fit is itself dropped
+        shaper.enqueue(SessionId(1), SendClass::Chat, vec![5; 40], 0);
+
+        let stats = shaper.stats(SessionId(1)).unwrap();
+        assert_eq!(stats.superseded, 1);
+        assert_eq!(stats.dropped_overflow, 2);
+        assert_eq!(stats.bytes_queued, 80);
+        assert_eq!(stats.messages_queued, 2);
+        assert_eq!(stats.peak_bytes_queued, 80);
+
+        shaper.set_budget(budget(0, 0, 100));
+        assert_eq!(
+            shaper.drain(SessionId(1), 0),
+            vec![vec![2; 40], vec![4; 40]]
+        );
+        assert_eq!(shaper.total_stats().bytes_sent, 80);
+        shaper.remove_session(SessionId(1));
+        assert!(shaper.stats(SessionId(1)).is_none());
+    }
+
+    #[test]
+    fn test_bulk_drains_last_and_drops_first() {
+        let mut shaper = BandwidthShaper::new(budget(1, 0, 100));
+        for i in 0..3 {
+            shaper.enqueue(SessionId(1), SendClass::Bulk, vec![i; 30], 0);
+        }
+        shaper.enqueue(SessionId(1), SendClass::Chat, vec![7; 30], 0);
+        // Chat evicts the oldest bulk chunk; a new chunk only evicts chunks
+        shaper.enqueue(SessionId(1), SendClass::Bulk, vec![8; 30], 0);
+
+        let stats = shaper.stats(SessionId(1)).unwrap();
+        assert_eq!(stats.dropped_overflow, 2);
+        assert_eq!(stats.bytes_queued, 90);
+
+        shaper.set_budget(budget(0, 0, 100));
+        assert_eq!(
+            shaper.drain(SessionId(1), 0),
+            vec![vec![7; 30], vec![2; 30], vec![8; 30]]
+        );
+    }
+
+    #[test]
+    fn test_stalled_session_never_drops_control() {
+        // A client that never drains: no budget, ever
+        let mut shaper = BandwidthShaper::new(budget(1, 0, 100));
+        for i in 0..10 {
+            shaper.enqueue(SessionId(1), SendClass::Snapshot, vec![i; 30], 0);
+            shaper.enqueue(SessionId(1), SendClass::Chat, vec![i; 30], 0);
+        }
+        // Queue stays bounded under realtime traffic
+        let stats = shaper.stats(SessionId(1)).unwrap();
+        assert!(stats.peak_bytes_queued <= 100);
+
+        // Control messages evict realtime payloads, then exceed the cap
+        for i in 0..4 {
+            shaper.enqueue(SessionId(1), SendClass::Event, vec![100 + i; 40], 0);
+        }
+        let stats = shaper.stats(SessionId(1)).unwrap();
+        assert_eq!(stats.messages_queued, 4);
+        assert_eq!(stats.bytes_queued, 160);
+        assert_eq!(shaper.max_queue_depth(), 4);
+
+        // Realtime payloads no longer fit behind the control backlog
+        shaper.enqueue(SessionId(1), SendClass::Snapshot, vec![9; 30], 0);
+        assert_eq!(shaper.stats(SessionId(1)).unwrap().messages_queued, 4);
+
+        shaper.set_budget(budget(0, 0, 100));
+        let sent = shaper.drain(SessionId(1), 0);
+        assert_eq!(sent, (0..4).map(|i| vec![100 + i; 40]).collect::<Vec<_>>());
+        assert_eq!(shaper.max_queue_depth(), 0);
+    }
+}
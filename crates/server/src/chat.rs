@@ -0,0 +1,124 @@
+//! Chat relay for Server Edge.
+//!
+//! Ref: ADR-0005 (Control Channel), DM-0011 (Server Edge)
+//! - Sender identity is bound from the session, never from the message
+//! - Per-session rate limit over a fixed window; length cap; optional filter hook
+//!
+//! Chat is Server Edge only: it is not recorded in the replay and never reaches
+//! the Simulation Core.
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum chat message length in characters.
+pub const MAX_CHAT_CHARS: usize = 256;
+
+/// Chat rate limit: at most `max_messages` per `window_micros`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatRateLimit {
+    pub max_messages: u32,
+    pub window_micros: u64,
+}
+
+impl Default for ChatRateLimit {
+    fn default() -> Self {
+        Self {
+            max_messages: 5,
+            window_micros: 5_000_000,
+        }
+    }
+}
+
+/// Why a chat message was not relayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatError {
+    UnknownSession,
+    Empty,
+    TooLong,
+    RateLimited,
+    /// Rejected by the configured `ChatFilter`.
+    Blocked,
+}
+
+impl std::fmt::Display for ChatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSession => write!(f, "Unknown session"),
+            Self::Empty => write!(f, "Empty chat message"),
+            Self::TooLong => write!(f, "Chat message exceeds {MAX_CHAT_CHARS} characters"),
+            Self::RateLimited => write!(f, "Chat rate limit exceeded"),
+            Self::Blocked => write!(f, "Chat message blocked by filter"),
+        }
+    }
+}
+
+impl std::error::Error for ChatError {}
+
+/// Content filter hook (e.g., profanity masking).
+pub trait ChatFilter: Send {
+    /// Return the text to relay (possibly rewritten), or `None` to block it.
+    fn filter(&self, text: &str) -> Option<String>;
+}
+
+/// Fixed-window chat limiter for one session.
+#[derive(Debug, Clone, Default)]
+pub struct ChatLimiter {
+    window_start_micros: Option<u64>,
+    count: u32,
+}
+
+impl ChatLimiter {
+    /// Count one message at `now_micros`; false if over the limit.
+    pub fn try_send(&mut self, limit: ChatRateLimit, now_micros: u64) -> bool {
+        let expired = self
+            .window_start_micros
+            .is_none_or(|start| now_micros.saturating_sub(start) >= limit.window_micros);
+        if expired {
+            self.window_start_micros = Some(now_micros);
+            self.count = 0;
+        }
+        if self.count >= limit.max_messages {
+            return false;
+        }
+        self.count += 1;
+        true
+    }
+}
+
+/// Validate chat text shape (before rate limiting and filtering).
+pub fn check_text(text: &str) -> Result<&str, ChatError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(ChatError::Empty);
+    }
+    if text.chars().count() > MAX_CHAT_CHARS {
+        return Err(ChatError::TooLong);
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_window() {
+        let limit = ChatRateLimit {
+            max_messages: 2,
+            window_micros: 1_000,
+        };
+        let mut limiter = ChatLimiter::default();
+        assert!(limiter.try_send(limit, 0));
+        assert!(limiter.try_send(limit, 10));
+        assert!(!limiter.try_send(limit, 999));
+        assert!(limiter.try_send(limit, 1_000));
+    }
+
+    #[test]
+    fn test_check_text() {
+        assert_eq!(check_text("  hi  "), Ok("hi"));
+        assert_eq!(check_text("   "), Err(ChatError::Empty));
+        let long = "x".repeat(MAX_CHAT_CHARS + 1);
+        assert_eq!(check_text(&long), Err(ChatError::TooLong));
+    }
+}
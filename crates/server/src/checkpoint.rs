@@ -0,0 +1,99 @@
+//! Crash-recovery checkpoints.
+//!
+//! Ref: DM-0002 (World), DM-0017 (ReplayArtifact), INV-0006
+//! - Every `checkpoint_interval_ticks`, the Server persists a `MatchCheckpoint`:
+//!   World state plus a partial ReplayArtifact up to that tick
+//! - Writes are atomic (temp file + rename): a crash mid-write leaves the
+//!   previous checkpoint intact
+//! - The partial replay verifies on its own, so a match that cannot resume
+//!   still yields a verifiable replay up to the last checkpoint
+//!
+//! Sessions are not checkpointed; after `Server::resume`, clients reconnect
+//! and are bound to their players with `Server::reclaim_player`.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use flowstate_sim::Tick;
+use flowstate_wire::{MatchCheckpoint, ReplayArtifact};
+use prost::Message;
+
+/// Default ticks between checkpoints (10 s at 60 Hz).
+pub const CHECKPOINT_INTERVAL_TICKS: u64 = 600;
+
+/// Why a checkpoint could not be resumed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckpointError {
+    /// No World state in the checkpoint.
+    MissingState,
+    /// No partial replay in the checkpoint.
+    MissingReplay,
+    /// Malformed state or replay contents.
+    InvalidFormat { reason: String },
+    /// The checkpoint was taken with a different seed or tick rate.
+    ConfigMismatch { reason: String },
+    /// World state and replay disagree on the checkpoint tick.
+    TickMismatch { state: Tick, replay: Tick },
+    /// Restored World does not reproduce the recorded StateDigest.
+    DigestMismatch { expected: u64, actual: u64 },
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingState => write!(f, "Checkpoint has no World state"),
+            Self::MissingReplay => write!(f, "Checkpoint has no replay"),
+            Self::InvalidFormat { reason } => write!(f, "Invalid checkpoint: {reason}"),
+            Self::ConfigMismatch { reason } => write!(f, "Checkpoint config mismatch: {reason}"),
+            Self::TickMismatch { state, replay } => {
+                write!(
+                    f,
+                    "Checkpoint tick mismatch: state {state}, replay {replay}"
+                )
+            }
+            Self::DigestMismatch { expected, actual } => write!(
+                f,
+                "Checkpoint digest mismatch: expected {expected:#x}, got {actual:#x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+/// Atomically write a checkpoint to `path` (parent directories are created).
+pub fn write_checkpoint(checkpoint: &MatchCheckpoint, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&checkpoint.encode_to_vec())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+/// Read a checkpoint from `path`; its replay is migrated like `read_replay`'s.
+pub fn read_checkpoint(path: &Path) -> io::Result<MatchCheckpoint> {
+    let data = fs::read(path)?;
+    let mut checkpoint = MatchCheckpoint::decode(data.as_slice()).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to decode checkpoint: {e}"),
+        )
+    })?;
+    if let Some(replay) = &mut checkpoint.replay {
+        flowstate_replay::migrate_artifact(replay);
+    }
+    Ok(checkpoint)
+}
+
+/// The partial replay from a checkpoint file, for when resuming is not possible.
+pub fn salvage_replay(path: &Path) -> io::Result<ReplayArtifact> {
+    read_checkpoint(path)?
+        .replay
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Checkpoint has no replay"))
+}
@@ -0,0 +1,113 @@
+//! Injectable clock for Server Edge.
+//!
+//! Ref: INV-0004, DM-0011
+//! - Wall-clock reads are a Server Edge concern and never reach the Simulation Core
+//! - All Server Edge time reads go through `Clock` so tests can drive time manually
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Monotonic time source in microseconds, plus the wall clock for metadata.
+pub trait Clock: Send {
+    /// Microseconds since an arbitrary, fixed epoch. MUST be monotonic.
+    fn now_micros(&self) -> u64;
+
+    /// UTC wall-clock milliseconds since the Unix epoch. For metadata such
+    /// as replay timestamps only; never for scheduling (it may jump).
+    fn unix_millis(&self) -> u64;
+}
+
+/// Monotonic system clock (epoch = construction time).
+#[derive(Debug, Clone)]
+pub struct SystemClock {
+    epoch: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now_micros(&self) -> u64 {
+        u64::try_from(self.epoch.elapsed().as_micros()).unwrap_or(u64::MAX)
+    }
+
+    fn unix_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+    }
+}
+
+/// Manually driven clock for tests. Its epoch doubles as the Unix epoch, so
+/// `unix_millis` is `now_micros / 1000`.
+///
+/// Clones share the same underlying time, so a test can keep one handle and
+/// give another to the Server.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    micros: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    pub fn new(start_micros: u64) -> Self {
+        Self {
+            micros: Arc::new(AtomicU64::new(start_micros)),
+        }
+    }
+
+    /// Advance time by `micros`.
+    pub fn advance(&self, micros: u64) {
+        self.micros.fetch_add(micros, Ordering::SeqCst);
+    }
+
+    /// Set the absolute time. Callers must keep it monotonic.
+    pub fn set(&self, micros: u64) {
+        self.micros.store(micros, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_micros(&self) -> u64 {
+        self.micros.load(Ordering::SeqCst)
+    }
+
+    fn unix_millis(&self) -> u64 {
+        self.now_micros() / 1000
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_shared_between_clones() {
+        let clock = ManualClock::new(100);
+        let handle = clock.clone();
+        handle.advance(50);
+        assert_eq!(clock.now_micros(), 150);
+        handle.set(1000);
+        assert_eq!(clock.now_micros(), 1000);
+        assert_eq!(clock.unix_millis(), 1);
+    }
+
+    #[test]
+    fn test_system_clock_monotonic() {
+        let clock = SystemClock::new();
+        let a = clock.now_micros();
+        let b = clock.now_micros();
+        assert!(b >= a);
+    }
+}
@@ -0,0 +1,240 @@
+//! Runtime reload of ServerConfig.
+//!
+//! Ref: DM-0011 (Server Edge), INV-0006
+//! - Hot-reloadable: Server Edge policy only (rate limits, timeouts, AOI,
+//!   snapshot size limit, digest/checkpoint intervals); none of it reaches
+//!   the Simulation Core or the AppliedInput stream, so replays are unaffected
+//! - Locked once the match starts: values that shape the match itself
+//!   (`match_duration_ticks`, `input_lead_ticks`)
+//! - Never reloadable: `seed`, `tick_rate_hz` (the World is built from them)
+//!
+//! A patch is applied all-or-nothing: one locked or invalid field rejects it.
+
+use crate::ServerConfig;
+use crate::chat::ChatRateLimit;
+use crate::validation::RateLimit;
+
+/// Partial ServerConfig update; `None` fields are left unchanged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigPatch {
+    pub input_rate_limit_per_sec: Option<u32>,
+    pub input_burst: Option<u32>,
+    pub bot_rate_limit: Option<RateLimit>,
+    pub spectator_rate_limit: Option<RateLimit>,
+    pub chat_rate_limit: Option<ChatRateLimit>,
+    pub connect_timeout_ms: Option<u64>,
+    pub pre_match_idle_timeout_ms: Option<u64>,
+    pub match_idle_timeout_ms: Option<u64>,
+    /// `Some(None)` disables AOI filtering.
+    pub aoi_radius: Option<Option<f64>>,
+    /// `Some(None)` removes the snapshot size limit.
+    pub snapshot_max_bytes: Option<Option<usize>>,
+    pub live_digest_interval_ticks: Option<u64>,
+    pub checkpoint_interval_ticks: Option<u64>,
+    /// Locked once the match starts.
+    pub match_duration_ticks: Option<u64>,
+    /// Locked once the match starts.
+    pub input_lead_ticks: Option<u64>,
+}
+
+/// Why a patch was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReloadError {
+    /// Gameplay-affecting field changed after match start.
+    LockedAfterStart { field: &'static str },
+    /// Field value out of range.
+    Invalid { field: &'static str, reason: String },
+}
+
+impl std::fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LockedAfterStart { field } => {
+                write!(f, "Config field {field} is locked once the match starts")
+            }
+            Self::Invalid { field, reason } => write!(f, "Invalid value for {field}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
+impl ConfigPatch {
+    /// Check the patch against the current match state without applying it.
+    pub fn validate(&self, match_started: bool) -> Result<(), ReloadError> {
+        if match_started {
+            if self.match_duration_ticks.is_some() {
+                return Err(ReloadError::LockedAfterStart {
+                    field: "match_duration_ticks",
+                });
+            }
+            if self.input_lead_ticks.is_some() {
+                return Err(ReloadError::LockedAfterStart {
+                    field: "input_lead_ticks",
+                });
+            }
+        }
+        if let Some(Some(radius)) = self.aoi_radius
+            && !(radius.is_finite() && radius >= 0.0)
+        {
+            return Err(ReloadError::Invalid {
+                field: "aoi_radius",
+                reason: format!("{radius} is not a finite, non-negative radius"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Apply to `config`; returns the names of fields whose value changed.
+    pub(crate) fn apply_to(&self, config: &mut ServerConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        fn set<T: PartialEq + Copy>(
+            changed: &mut Vec<&'static str>,
+            field: &'static str,
+            target: &mut T,
+            value: Option<T>,
+        ) {
+            if let Some(value) = value
+                && *target != value
+            {
+                *target = value;
+                changed.push(field);
+            }
+        }
+        set(
+            &mut changed,
+            "input_rate_limit_per_sec",
+            &mut config.input_rate_limit_per_sec,
+            self.input_rate_limit_per_sec,
+        );
+        set(
+            &mut changed,
+            "input_burst",
+            &mut config.input_burst,
+            self.input_burst,
+        );
+        set(
+            &mut changed,
+            "bot_rate_limit",
+            &mut config.bot_rate_limit,
+            self.bot_rate_limit,
+        );
+        set(
+            &mut changed,
+            "spectator_rate_limit",
+            &mut config.spectator_rate_limit,
+            self.spectator_rate_limit,
+        );
+        set(
+            &mut changed,
+            "chat_rate_limit",
+            &mut config.chat_rate_limit,
+            self.chat_rate_limit,
+        );
+        set(
+            &mut changed,
+            "connect_timeout_ms",
+            &mut config.connect_timeout_ms,
+            self.connect_timeout_ms,
+        );
+        set(
+            &mut changed,
+            "pre_match_idle_timeout_ms",
+            &mut config.pre_match_idle_timeout_ms,
+            self.pre_match_idle_timeout_ms,
+        );
+        set(
+            &mut changed,
+            "match_idle_timeout_ms",
+            &mut config.match_idle_timeout_ms,
+            self.match_idle_timeout_ms,
+        );
+        set(
+            &mut changed,
+            "aoi_radius",
+            &mut config.aoi_radius,
+            self.aoi_radius,
+        );
+        set(
+            &mut changed,
+            "snapshot_max_bytes",
+            &mut config.snapshot_max_bytes,
+            self.snapshot_max_bytes,
+        );
+        set(
+            &mut changed,
+            "live_digest_interval_ticks",
+            &mut config.live_digest_interval_ticks,
+            self.live_digest_interval_ticks,
+        );
+        set(
+            &mut changed,
+            "checkpoint_interval_ticks",
+            &mut config.checkpoint_interval_ticks,
+            self.checkpoint_interval_ticks,
+        );
+        set(
+            &mut changed,
+            "match_duration_ticks",
+            &mut config.match_duration_ticks,
+            self.match_duration_ticks,
+        );
+        set(
+            &mut changed,
+            "input_lead_ticks",
+            &mut config.input_lead_ticks,
+            self.input_lead_ticks,
+        );
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_fields_after_start() {
+        let patch = ConfigPatch {
+            input_burst: Some(2),
+            match_duration_ticks: Some(10),
+            ..Default::default()
+        };
+        assert!(patch.validate(false).is_ok());
+        assert_eq!(
+            patch.validate(true),
+            Err(ReloadError::LockedAfterStart {
+                field: "match_duration_ticks"
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_reports_changed_fields_only() {
+        let mut config = ServerConfig::default();
+        let patch = ConfigPatch {
+            input_burst: Some(3),
+            connect_timeout_ms: Some(config.connect_timeout_ms),
+            aoi_radius: Some(Some(20.0)),
+            ..Default::default()
+        };
+        assert_eq!(
+            patch.apply_to(&mut config),
+            vec!["input_burst", "aoi_radius"]
+        );
+        assert_eq!(config.input_burst, 3);
+        assert_eq!(config.aoi_radius, Some(20.0));
+
+        let invalid = ConfigPatch {
+            aoi_radius: Some(Some(f64::NAN)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            invalid.validate(false),
+            Err(ReloadError::Invalid {
+                field: "aoi_radius",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,37 @@
+//! Server-initiated Control channel messages.
+//!
+//! Ref: ADR-0005 (Control Channel), DM-0008 (Session)
+//! - The Server queues messages for specific sessions while handling inputs
+//!   and stepping; the host drains them with `Server::drain_control` and
+//!   sends them on the Control channel
+//! - Queue order is the order the Server produced them
+//!
+//! Welcomes and baselines are returned directly by `start_match` /
+//! `reclaim_player`; this outbox carries everything sent mid-match.
+
+use flowstate_wire::{
+    EntityRemoved, EntitySpawned, FloorUpdate, InputRejectionReport, InputTimingReport, MatchEnd,
+    PlayerLeft,
+};
+
+/// Control message addressed to one session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlMessage {
+    /// Explicit TargetTickFloor resend for a session whose inputs keep
+    /// landing below the floor (see `ServerConfig::floor_resend_after_drops`).
+    FloorUpdate(FloorUpdate),
+    /// Another player's session left the match (see `Server::remove_session`).
+    PlayerLeft(PlayerLeft),
+    /// The match is over (see `Server::announce_match_end`).
+    MatchEnd(MatchEnd),
+    /// Periodic summary of this session's dropped inputs (see
+    /// `ServerConfig::input_rejection_report_interval_ticks`).
+    InputRejectionReport(InputRejectionReport),
+    /// Periodic input lateness and fallback counts for a player session (see
+    /// `ServerConfig::input_timing_report_interval_ticks`).
+    InputTimingReport(InputTimingReport),
+    /// A new entity, sent to every session (see `lifecycle`).
+    EntitySpawned(EntitySpawned),
+    /// An announced entity is gone, sent to every session (see `lifecycle`).
+    EntityRemoved(EntityRemoved),
+}
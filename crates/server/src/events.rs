@@ -0,0 +1,56 @@
+//! Server Edge events for the host/orchestrator.
+//!
+//! Ref: DM-0011 (Server Edge)
+//! - Events are queued during `step()` and input handling, drained by the host
+//! - Diagnostics and moderation signals only; never simulation input
+
+use flowstate_sim::{PlayerId, Tick};
+
+use crate::anomaly::AnomalyKind;
+use crate::session::SessionId;
+
+/// Event raised by the Server Edge.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerEvent {
+    /// A session's realized movement systematically exceeded the allowed speed.
+    MovementFlagged {
+        session_id: SessionId,
+        player_id: PlayerId,
+        tick: Tick,
+        total_violations: u64,
+        max_ratio: f64,
+    },
+    /// An identity reconnected and its player was rebound to a new session.
+    SessionSuperseded {
+        old_session_id: SessionId,
+        new_session_id: SessionId,
+        player_id: PlayerId,
+    },
+    /// A session's input pattern tripped a soft anomaly heuristic.
+    InputAnomaly {
+        session_id: SessionId,
+        player_id: PlayerId,
+        tick: Tick,
+        kind: AnomalyKind,
+    },
+    /// A pre-match session went silent and was dropped; its slot is free again.
+    SessionExpired {
+        session_id: SessionId,
+        player_id: PlayerId,
+        idle_micros: u64,
+    },
+    /// A periodic crash-recovery checkpoint could not be written.
+    CheckpointFailed { tick: Tick, error: String },
+    /// A client reported a digest mismatch and was sent a fresh baseline.
+    ClientDesync {
+        session_id: SessionId,
+        player_id: PlayerId,
+        tick: Tick,
+        mismatch_tick: Tick,
+    },
+    /// ServerConfig fields were changed at runtime.
+    ConfigReloaded {
+        tick: Tick,
+        fields: Vec<&'static str>,
+    },
+}
@@ -0,0 +1,226 @@
+//! HTTP health and readiness probes for fleet orchestrators.
+//!
+//! Ref: DM-0011 (Server Edge)
+//! - `GET /healthz`: 200 while the process can answer (liveness)
+//! - `GET /readyz`: 200 when a match slot is available and the host loop has
+//!   heartbeated within the stall threshold; otherwise 503 with the reason
+//!
+//! The host loop updates a shared `HealthProbe`; a background thread serves
+//! the probes with a minimal HTTP/1.1 responder (no keep-alive). Server Edge
+//! only; nothing here touches match state.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::clock::Clock;
+
+/// Default time without a heartbeat before the host loop counts as stalled.
+pub const DEFAULT_STALL_THRESHOLD_MICROS: u64 = 2_000_000;
+
+/// Readiness verdict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Readiness {
+    Ready,
+    /// No match slot can accept players.
+    NoSlots,
+    /// The host loop has not heartbeated recently.
+    Stalled {
+        since_micros: u64,
+    },
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Ready)
+    }
+}
+
+/// Liveness/readiness state shared between the host loop and the probe server.
+#[derive(Debug)]
+pub struct HealthProbe {
+    last_heartbeat_micros: AtomicU64,
+    available_slots: AtomicUsize,
+    stall_threshold_micros: u64,
+}
+
+impl HealthProbe {
+    pub fn new(stall_threshold_micros: u64) -> Self {
+        Self {
+            last_heartbeat_micros: AtomicU64::new(0),
+            available_slots: AtomicUsize::new(0),
+            stall_threshold_micros,
+        }
+    }
+
+    /// Called by the host loop on every iteration.
+    pub fn heartbeat(&self, now_micros: u64) {
+        self.last_heartbeat_micros
+            .store(now_micros, Ordering::Relaxed);
+    }
+
+    /// Match slots currently able to accept players.
+    pub fn set_available_slots(&self, slots: usize) {
+        self.available_slots.store(slots, Ordering::Relaxed);
+    }
+
+    pub fn readiness(&self, now_micros: u64) -> Readiness {
+        let last = self.last_heartbeat_micros.load(Ordering::Relaxed);
+        let since = now_micros.saturating_sub(last);
+        if since > self.stall_threshold_micros {
+            return Readiness::Stalled {
+                since_micros: since,
+            };
+        }
+        if self.available_slots.load(Ordering::Relaxed) == 0 {
+            return Readiness::NoSlots;
+        }
+        Readiness::Ready
+    }
+
+    /// HTTP status line and body for a request path.
+    fn respond(&self, path: &str, now_micros: u64) -> (&'static str, String) {
+        match path {
+            "/healthz" => ("200 OK", "ok\n".to_string()),
+            "/readyz" => match self.readiness(now_micros) {
+                Readiness::Ready => ("200 OK", "ready\n".to_string()),
+                Readiness::NoSlots => ("503 Service Unavailable", "no slots\n".to_string()),
+                Readiness::Stalled { since_micros } => (
+                    "503 Service Unavailable",
+                    format!("stalled for {since_micros}us\n"),
+                ),
+            },
+            _ => ("404 Not Found", "not found\n".to_string()),
+        }
+    }
+}
+
+/// Background HTTP responder for a `HealthProbe`.
+pub struct HealthServer {
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HealthServer {
+    /// Bind `addr` and serve probes on a background thread.
+    /// `clock` MUST share its epoch with the clock feeding `heartbeat`.
+    pub fn spawn(
+        addr: impl ToSocketAddrs,
+        probe: Arc<HealthProbe>,
+        clock: Box<dyn Clock>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&shutdown);
+        let thread = std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        // Best effort: a broken probe connection is the prober's problem
+                        let _ = serve(stream, &probe, clock.now_micros());
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                    Err(_) => std::thread::sleep(Duration::from_millis(10)),
+                }
+            }
+        });
+        Ok(Self {
+            addr,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for HealthServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(stream: TcpStream, probe: &HealthProbe, now_micros: u64) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => probe.respond(path, now_micros),
+        _ => ("400 Bad Request", "bad request\n".to_string()),
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::io::Read;
+
+    #[test]
+    fn test_readiness() {
+        let probe = HealthProbe::new(1_000);
+        probe.heartbeat(0);
+        assert_eq!(probe.readiness(10), Readiness::NoSlots);
+        probe.set_available_slots(1);
+        assert_eq!(probe.readiness(1_000), Readiness::Ready);
+        assert_eq!(
+            probe.readiness(1_001),
+            Readiness::Stalled {
+                since_micros: 1_001
+            }
+        );
+        probe.heartbeat(1_001);
+        assert!(probe.readiness(1_500).is_ready());
+    }
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_http_probes() {
+        let clock = ManualClock::new(0);
+        let probe = Arc::new(HealthProbe::new(DEFAULT_STALL_THRESHOLD_MICROS));
+        let server =
+            HealthServer::spawn("127.0.0.1:0", Arc::clone(&probe), Box::new(clock.clone()))
+                .unwrap();
+        let addr = server.local_addr();
+
+        assert!(get(addr, "/healthz").starts_with("HTTP/1.1 200"));
+        assert!(get(addr, "/readyz").starts_with("HTTP/1.1 503"));
+        probe.set_available_slots(1);
+        assert!(get(addr, "/readyz").starts_with("HTTP/1.1 200"));
+        clock.advance(DEFAULT_STALL_THRESHOLD_MICROS + 1);
+        let response = get(addr, "/readyz");
+        assert!(response.starts_with("HTTP/1.1 503"));
+        assert!(response.contains("stalled"));
+        assert!(get(addr, "/nope").starts_with("HTTP/1.1 404"));
+    }
+}
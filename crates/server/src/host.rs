@@ -0,0 +1,406 @@
+//! Multi-match hosting for a Game Server Instance.
+//!
+//! Ref: DM-0011 (Server Edge), INV-0004
+//! - Each match owns an independent `Server` (and therefore its own World)
+//! - Matches never share simulation state; hosting is pure Server Edge bookkeeping
+//! - Iteration is by slot id ascending, so host-level output order is stable
+//! - Finalized matches are persisted through the optional `ReplayStorage`
+//! - `step_all` may step matches in parallel on a pool of `step_workers`
+//!   threads kept for the host's lifetime; each running match is one job per
+//!   tick, stepped by exactly one worker, and results are collected by slot
+//!   id, so output is identical to sequential stepping
+
+use std::collections::BTreeMap;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use flowstate_sim::{Snapshot, Tick};
+use flowstate_wire::ReplayArtifact;
+
+use crate::replay_storage::ReplayStorage;
+use crate::summary::MatchSummary;
+use crate::{EndReason, Server, ServerConfig, SnapshotPayload};
+
+/// Host-local identifier for a match slot.
+pub type MatchSlotId = u64;
+
+/// Output of `MatchHost::finalize_match`.
+#[derive(Debug)]
+pub struct FinalizedMatch {
+    /// The replay, or why it could not be assembled (see `Server::finalize`).
+    pub artifact: io::Result<ReplayArtifact>,
+    pub summary: MatchSummary,
+    /// Stored artifact path or the storage error (`None` without storage or
+    /// artifact). The artifact is returned either way, so a failed write
+    /// loses nothing.
+    pub stored: Option<io::Result<PathBuf>>,
+}
+
+/// One `Server::step` result.
+type StepOutput = (Snapshot, Tick, SnapshotPayload);
+
+/// A stepped match handed back by a worker, with its step result (or panic).
+type StepDone = (MatchSlotId, Server, thread::Result<StepOutput>);
+
+/// Worker threads that step the matches sent to them, one job per match.
+struct StepPool {
+    jobs: Option<Sender<(MatchSlotId, Server)>>,
+    done: Receiver<StepDone>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl StepPool {
+    fn new(workers: usize) -> Self {
+        let (jobs, job_queue) = mpsc::channel::<(MatchSlotId, Server)>();
+        let (done_tx, done) = mpsc::channel();
+        let job_queue = Arc::new(Mutex::new(job_queue));
+        let workers = (0..workers)
+            .map(|i| {
+                let job_queue = Arc::clone(&job_queue);
+                let done_tx = done_tx.clone();
+                thread::Builder::new()
+                    .name(format!("match-step-{i}"))
+                    .spawn(move || {
+                        loop {
+                            // The lock is held only while waiting for a job
+                            let job = job_queue.lock().expect("step queue poisoned").recv();
+                            let Ok((slot_id, mut server)) = job else {
+                                break;
+                            };
+                            let output = panic::catch_unwind(AssertUnwindSafe(|| server.step()));
+                            if done_tx.send((slot_id, server, output)).is_err() {
+                                break;
+                            }
+                        }
+                    })
+                    .expect("spawning a match step worker")
+            })
+            .collect();
+        Self {
+            jobs: Some(jobs),
+            done,
+            workers,
+        }
+    }
+
+    /// Step every match in `running` and return them with their results, in
+    /// completion order.
+    fn step(&self, running: Vec<(MatchSlotId, Server)>) -> Vec<StepDone> {
+        let jobs = self.jobs.as_ref().expect("jobs closed only on drop");
+        let count = running.len();
+        for job in running {
+            jobs.send(job)
+                .expect("step workers live as long as the pool");
+        }
+        (0..count)
+            .map(|_| {
+                self.done
+                    .recv()
+                    .expect("step workers live as long as the pool")
+            })
+            .collect()
+    }
+}
+
+impl Drop for StepPool {
+    fn drop(&mut self) {
+        // Closing the queue ends every worker's loop
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Container for the matches running on one host.
+pub struct MatchHost {
+    matches: BTreeMap<MatchSlotId, Server>,
+    next_slot_id: MatchSlotId,
+    replay_storage: Option<ReplayStorage>,
+    /// Threads used by `step_all` (1 = step on the caller's thread).
+    step_workers: usize,
+    /// Present while `step_workers` is above 1.
+    step_pool: Option<StepPool>,
+}
+
+impl Default for MatchHost {
+    fn default() -> Self {
+        Self {
+            matches: BTreeMap::new(),
+            next_slot_id: 0,
+            replay_storage: None,
+            step_workers: 1,
+            step_pool: None,
+        }
+    }
+}
+
+impl MatchHost {
+    /// Create an empty host.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Step matches on a pool of `workers` threads (e.g., the host's core
+    /// count), replacing any previous pool. Values below 1 are treated as 1,
+    /// which steps on the caller's thread with no pool.
+    pub fn set_step_workers(&mut self, workers: usize) {
+        let workers = workers.max(1);
+        if workers == self.step_workers {
+            return;
+        }
+        self.step_workers = workers;
+        self.step_pool = (workers > 1).then(|| StepPool::new(workers));
+    }
+
+    pub fn step_workers(&self) -> usize {
+        self.step_workers
+    }
+
+    /// Create a new match and return its slot id.
+    pub fn create_match(&mut self, config: ServerConfig) -> MatchSlotId {
+        let slot_id = self.next_slot_id;
+        self.next_slot_id += 1;
+        self.matches.insert(slot_id, Server::new(config));
+        slot_id
+    }
+
+    /// Persist finalized matches through `storage`.
+    pub fn set_replay_storage(&mut self, storage: ReplayStorage) {
+        self.replay_storage = Some(storage);
+    }
+
+    /// Remove, finalize, and (if configured) store a match.
+    pub fn finalize_match(
+        &mut self,
+        slot_id: MatchSlotId,
+        end_reason: EndReason,
+    ) -> Option<FinalizedMatch> {
+        let server = self.matches.remove(&slot_id)?;
+        let (artifact, mut summary) = server.finalize_with_summary(end_reason);
+        let stored = match (&self.replay_storage, &artifact) {
+            (Some(storage), Ok(artifact)) => Some(storage.store(artifact, &mut summary)),
+            _ => None,
+        };
+        Some(FinalizedMatch {
+            artifact,
+            summary,
+            stored,
+        })
+    }
+
+    /// Remove a match from the host (e.g., to finalize it).
+    pub fn remove_match(&mut self, slot_id: MatchSlotId) -> Option<Server> {
+        self.matches.remove(&slot_id)
+    }
+
+    /// Get a match.
+    pub fn get(&self, slot_id: MatchSlotId) -> Option<&Server> {
+        self.matches.get(&slot_id)
+    }
+
+    /// Get a match mutably.
+    pub fn get_mut(&mut self, slot_id: MatchSlotId) -> Option<&mut Server> {
+        self.matches.get_mut(&slot_id)
+    }
+
+    /// Number of hosted matches.
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// All slot ids, ascending.
+    pub fn slot_ids(&self) -> Vec<MatchSlotId> {
+        self.matches.keys().copied().collect()
+    }
+
+    /// Iterate over hosted matches, by slot id ascending.
+    pub fn iter(&self) -> impl Iterator<Item = (MatchSlotId, &Server)> {
+        self.matches.iter().map(|(&id, server)| (id, server))
+    }
+
+    /// Step every running match (started, not yet ended) once.
+    /// Returns each stepped match's `step()` output, by slot id ascending.
+    ///
+    /// With more than one step worker, each running match is sent to the
+    /// pool as one job and returned to its slot once stepped.
+    ///
+    /// # Panics
+    /// If a match's step panics; the panic is resumed on the caller's thread
+    /// once every other match is back in its slot.
+    pub fn step_all(&mut self) -> Vec<(MatchSlotId, StepOutput)> {
+        let running: Vec<MatchSlotId> = self
+            .matches
+            .iter()
+            .filter(|(_, server)| server.is_running())
+            .map(|(&id, _)| id)
+            .collect();
+        let pool = match &self.step_pool {
+            Some(pool) if running.len() > 1 => pool,
+            _ => {
+                return running
+                    .into_iter()
+                    .map(|id| (id, self.matches.get_mut(&id).expect("listed above").step()))
+                    .collect();
+            }
+        };
+
+        let jobs = running
+            .iter()
+            .map(|id| (*id, self.matches.remove(id).expect("listed above")))
+            .collect();
+        let mut outputs = BTreeMap::new();
+        let mut panicked = None;
+        for (slot_id, server, output) in pool.step(jobs) {
+            self.matches.insert(slot_id, server);
+            match output {
+                Ok(output) => {
+                    outputs.insert(slot_id, output);
+                }
+                Err(payload) => panicked = Some(payload),
+            }
+        }
+        if let Some(payload) = panicked {
+            panic::resume_unwind(payload);
+        }
+        outputs.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay_storage::ReplayStorageConfig;
+
+    fn started_match(host: &mut MatchHost, duration: u64) -> MatchSlotId {
+        let slot = host.create_match(ServerConfig {
+            match_duration_ticks: duration,
+            ..Default::default()
+        });
+        let server = host.get_mut(slot).unwrap();
+        server.accept_session();
+        server.accept_session();
+        server.start_match();
+        slot
+    }
+
+    #[test]
+    fn test_step_all_only_steps_running_matches() {
+        let mut host = MatchHost::new();
+        let a = started_match(&mut host, 2);
+        let b = started_match(&mut host, 10);
+        let pending = host.create_match(ServerConfig::default());
+
+        let stepped: Vec<_> = host.step_all().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(stepped, vec![a, b]);
+
+        host.step_all();
+        // Match `a` has reached its duration; only `b` keeps stepping
+        let stepped: Vec<_> = host.step_all().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(stepped, vec![b]);
+        assert_eq!(host.get(pending).unwrap().current_tick(), 0);
+    }
+
+    #[test]
+    fn test_parallel_step_matches_sequential() {
+        let run = |workers: usize| {
+            let mut host = MatchHost::new();
+            host.set_step_workers(workers);
+            let slots: Vec<_> = (0..5).map(|_| started_match(&mut host, 30)).collect();
+            for (i, &slot) in slots.iter().enumerate() {
+                let server = host.get_mut(slot).unwrap();
+                let session_id = server.session_ids()[0];
+                let tick = server.current_tick() + crate::INPUT_LEAD_TICKS;
+                server.receive_input(
+                    session_id,
+                    flowstate_wire::InputCmdProto {
+                        tick,
+                        input_seq: tick,
+                        move_dir: vec![1.0, i as f64 / 5.0],
+                    },
+                );
+            }
+            let mut outputs = Vec::new();
+            for _ in 0..30 {
+                outputs.extend(
+                    host.step_all()
+                        .into_iter()
+                        .map(|(id, (snapshot, floor, _))| (id, snapshot.digest, floor)),
+                );
+            }
+            outputs
+        };
+        let sequential = run(1);
+        assert_eq!(sequential.len(), 5 * 30);
+        assert_eq!(run(2), sequential);
+        assert_eq!(run(8), sequential);
+    }
+
+    #[test]
+    fn test_step_pool_kept_across_ticks() {
+        let mut host = MatchHost::new();
+        assert!(host.step_pool.is_none());
+        host.set_step_workers(3);
+        assert_eq!(host.step_pool.as_ref().unwrap().workers.len(), 3);
+        let slots: Vec<_> = (0..4).map(|_| started_match(&mut host, 10)).collect();
+        for _ in 0..3 {
+            let stepped: Vec<_> = host.step_all().into_iter().map(|(id, _)| id).collect();
+            assert_eq!(stepped, slots);
+        }
+        // Every match is back in its slot after each tick
+        assert_eq!(host.slot_ids(), slots);
+        assert!(
+            slots
+                .iter()
+                .all(|&slot| host.get(slot).unwrap().current_tick() == 3)
+        );
+
+        host.set_step_workers(0);
+        assert_eq!(host.step_workers(), 1);
+        assert!(host.step_pool.is_none());
+    }
+
+    #[test]
+    fn test_matches_are_isolated() {
+        let mut host = MatchHost::new();
+        let a = started_match(&mut host, 100);
+        let b = started_match(&mut host, 100);
+        for _ in 0..5 {
+            host.step_all();
+        }
+        let server_a = host.remove_match(a).unwrap();
+        assert_eq!(server_a.current_tick(), 5);
+        assert_eq!(host.match_count(), 1);
+        assert_eq!(host.get(b).unwrap().current_tick(), 5);
+    }
+
+    #[test]
+    fn test_finalize_match_stores_replay() {
+        let dir =
+            std::env::temp_dir().join(format!("flowstate-host-finalize-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut host = MatchHost::new();
+        host.set_replay_storage(ReplayStorage::new(ReplayStorageConfig::new(&dir)));
+        let a = started_match(&mut host, 2);
+        let b = started_match(&mut host, 2);
+        host.step_all();
+        host.step_all();
+
+        let first = host.finalize_match(a, EndReason::Complete).unwrap();
+        let second = host.finalize_match(b, EndReason::Complete).unwrap();
+        let first_path = first.stored.unwrap().unwrap();
+        let second_path = second.stored.unwrap().unwrap();
+        // Same (empty) match id within the same second: no overwrite
+        assert_ne!(first_path, second_path);
+        assert_eq!(
+            first.summary.replay_path,
+            Some(first_path.display().to_string())
+        );
+        assert!(host.finalize_match(a, EndReason::Complete).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,14 +4,21 @@
 //! - Buffer keyed by (player_id, tick)
 //! - InputSeq selection: greatest wins
 //! - Rate limiting: per-tick limit = ceil(input_rate_limit_per_sec / tick_rate_hz)
+//! - Per-player limits override the default; an optional burst bucket admits
+//!   extra inputs beyond the per-tick limit and refills one per tick
 //! - Buffer cap: one selected InputCmd per (player_id, tick)
+//! - Redundancy: (tick, input_seq) pairs already buffered are detectable as duplicates
+//! - Memory bound: at most `max_buffered_per_player` ticks per player and
+//!   `max_buffered_total` entries overall. A player at its cap evicts its
+//!   furthest-future entry for a nearer tick; otherwise the new tick is dropped.
+//!   The global cap never evicts another player's entries.
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use flowstate_sim::{PlayerId, Tick};
 use flowstate_wire::InputCmdProto;
 
-use crate::validation::{BufferResult, ValidationConfig};
+use crate::validation::{BufferResult, RateLimit, ValidationConfig};
 
 /// Per-(player_id, tick) buffer entry.
 #[derive(Debug, Clone)]
@@ -24,6 +31,40 @@ struct BufferEntry {
     max_seq_tied: bool,
     /// Number of inputs received for this (player_id, tick) in this tick window.
     receive_count: u32,
+    /// InputSeqs accepted for this (player_id, tick), for redundancy dedup.
+    seen_seqs: Vec<u64>,
+}
+
+/// Per-player limiter state.
+#[derive(Debug, Clone)]
+struct PlayerLimit {
+    per_tick_limit: u32,
+    burst_capacity: u32,
+    burst_tokens: u32,
+}
+
+/// Per-player cap counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapCounters {
+    /// Far-future entries evicted to admit a nearer tick.
+    pub evicted: u64,
+    /// New ticks dropped at the per-player cap.
+    pub rejected_player_cap: u64,
+    /// New ticks dropped at the global cap.
+    pub rejected_global_cap: u64,
+}
+
+/// Snapshot of input buffer occupancy.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BufferOccupancy {
+    pub total_entries: usize,
+    pub max_total: usize,
+    pub max_per_player: usize,
+    /// Buffered entries per player (players with entries only).
+    pub entries_per_player: BTreeMap<PlayerId, usize>,
+    /// Cap counters per player (players that hit a cap only).
+    pub cap_counters: BTreeMap<PlayerId, CapCounters>,
+    pub estimated_bytes: usize,
 }
 
 /// Input buffer for Server Edge.
@@ -31,10 +72,14 @@ struct BufferEntry {
 /// Buffers inputs by (player_id, tick) within the InputTickWindow.
 pub struct InputBuffer {
     config: ValidationConfig,
-    /// Buffer keyed by (player_id, tick).
-    buffer: HashMap<(PlayerId, Tick), BufferEntry>,
-    /// Per-tick rate limit = ceil(input_rate_limit_per_sec / tick_rate_hz).
+    /// Buffer keyed by (player_id, tick); ordered so each player's ticks form a range.
+    buffer: BTreeMap<(PlayerId, Tick), BufferEntry>,
+    /// Cap counters, keyed by player.
+    cap_counters: BTreeMap<PlayerId, CapCounters>,
+    /// Default per-tick rate limit = ceil(input_rate_limit_per_sec / tick_rate_hz).
     per_tick_limit: u32,
+    /// Per-player overrides (role-specific limits and burst buckets).
+    player_limits: BTreeMap<PlayerId, PlayerLimit>,
 }
 
 impl InputBuffer {
@@ -47,8 +92,42 @@ impl InputBuffer {
 
         Self {
             config,
-            buffer: HashMap::new(),
+            buffer: BTreeMap::new(),
+            cap_counters: BTreeMap::new(),
             per_tick_limit,
+            player_limits: BTreeMap::new(),
+        }
+    }
+
+    /// Override the rate limit for one player. The burst bucket starts full.
+    pub fn set_rate_limit(&mut self, player_id: PlayerId, limit: RateLimit) {
+        self.player_limits.insert(
+            player_id,
+            PlayerLimit {
+                per_tick_limit: limit.per_tick_limit(self.config.tick_rate_hz),
+                burst_capacity: limit.burst,
+                burst_tokens: limit.burst,
+            },
+        );
+    }
+
+    /// Refill every burst bucket by one token (called once per simulated tick).
+    pub fn refill_burst_tokens(&mut self) {
+        for limit in self.player_limits.values_mut() {
+            limit.burst_tokens = (limit.burst_tokens + 1).min(limit.burst_capacity);
+        }
+    }
+
+    /// Apply the player's limit to one more input at `receive_count` prior receipts.
+    fn admit(&mut self, player_id: PlayerId, receive_count: u32) -> bool {
+        match self.player_limits.get_mut(&player_id) {
+            None => receive_count < self.per_tick_limit,
+            Some(limit) if receive_count < limit.per_tick_limit => true,
+            Some(limit) if limit.burst_tokens > 0 => {
+                limit.burst_tokens -= 1;
+                true
+            }
+            Some(_) => false,
         }
     }
 
@@ -64,13 +143,18 @@ impl InputBuffer {
         let key = (player_id, input.tick);
         let input_seq = input.input_seq;
 
+        // Rate limiting: check receive count for this (player_id, tick)
+        let receive_count = self.buffer.get(&key).map_or(0, |e| e.receive_count);
+        if !self.admit(player_id, receive_count) {
+            return BufferResult::RateLimited;
+        }
+
         // Check if we already have an entry for this (player_id, tick)
         if let Some(entry) = self.buffer.get_mut(&key) {
-            // Rate limiting: check receive count
-            if entry.receive_count >= self.per_tick_limit {
-                return BufferResult::RateLimited;
-            }
             entry.receive_count += 1;
+            if !entry.seen_seqs.contains(&input_seq) {
+                entry.seen_seqs.push(input_seq);
+            }
 
             // InputSeq tie-breaking per spec:
             // - seq > max: update to new max, clear tie flag
@@ -93,7 +177,10 @@ impl InputBuffer {
 
             BufferResult::Accepted { clamped }
         } else {
-            // First input for this (player_id, tick)
+            // First input for this (player_id, tick): enforce memory caps
+            if !self.make_room(player_id, input.tick) {
+                return BufferResult::BufferFull;
+            }
             let clamped = needs_magnitude_clamp(&input.move_dir);
             let mut input = input;
             if clamped {
@@ -105,6 +192,7 @@ impl InputBuffer {
                 max_input_seq: input_seq,
                 max_seq_tied: false,
                 receive_count: 1,
+                seen_seqs: vec![input_seq],
             };
             self.buffer.insert(key, entry);
 
@@ -112,6 +200,45 @@ impl InputBuffer {
         }
     }
 
+    /// Ensure a new (player_id, tick) entry fits under both caps, evicting the
+    /// player's furthest-future entry if `tick` is nearer. Returns false if the
+    /// entry must be dropped.
+    fn make_room(&mut self, player_id: PlayerId, tick: Tick) -> bool {
+        let player_entries = self.buffer.range((player_id, 0)..=(player_id, Tick::MAX));
+        if player_entries.clone().count() >= self.config.max_buffered_per_player {
+            let furthest = player_entries.map(|(&key, _)| key).next_back();
+            let counters = self.cap_counters.entry(player_id).or_default();
+            match furthest {
+                Some(key) if key.1 > tick => {
+                    self.buffer.remove(&key);
+                    counters.evicted += 1;
+                }
+                _ => {
+                    counters.rejected_player_cap += 1;
+                    return false;
+                }
+            }
+        }
+        if self.buffer.len() >= self.config.max_buffered_total {
+            self.cap_counters
+                .entry(player_id)
+                .or_default()
+                .rejected_global_cap += 1;
+            return false;
+        }
+        true
+    }
+
+    /// Check whether this (tick, input_seq) was already accepted for the player.
+    ///
+    /// Used to drop redundant copies (InputBundle) before they reach the rate
+    /// limiter or InputSeq tie detection.
+    pub fn is_duplicate(&self, player_id: PlayerId, input: &InputCmdProto) -> bool {
+        self.buffer
+            .get(&(player_id, input.tick))
+            .is_some_and(|entry| entry.seen_seqs.contains(&input.input_seq))
+    }
+
     /// Take the selected input for a (player_id, tick), removing it from the buffer.
     ///
     /// Returns `None` if:
@@ -134,6 +261,45 @@ impl InputBuffer {
         self.buffer.retain(|&(_, t), _| t >= tick);
     }
 
+    /// Number of buffered (player_id, tick) entries.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Check if the buffer holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Estimated memory held by buffered entries, in bytes (diagnostics only).
+    pub fn estimated_bytes(&self) -> usize {
+        let entry_size = std::mem::size_of::<((PlayerId, Tick), BufferEntry)>();
+        self.buffer
+            .values()
+            .map(|entry| {
+                entry_size
+                    + entry.selected.move_dir.capacity() * std::mem::size_of::<f64>()
+                    + entry.seen_seqs.capacity() * std::mem::size_of::<u64>()
+            })
+            .sum()
+    }
+
+    /// Occupancy per player and cap counters.
+    pub fn occupancy(&self) -> BufferOccupancy {
+        let mut entries_per_player = BTreeMap::new();
+        for &(player_id, _) in self.buffer.keys() {
+            *entries_per_player.entry(player_id).or_insert(0) += 1;
+        }
+        BufferOccupancy {
+            total_entries: self.buffer.len(),
+            max_total: self.config.max_buffered_total,
+            max_per_player: self.config.max_buffered_per_player,
+            entries_per_player,
+            cap_counters: self.cap_counters.clone(),
+            estimated_bytes: self.estimated_bytes(),
+        }
+    }
+
     /// Check if an entry exists (for testing).
     #[cfg(test)]
     pub fn has_entry(&self, player_id: PlayerId, tick: Tick) -> bool {
@@ -180,9 +346,9 @@ mod tests {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
         let input = make_input(5, 1, 1.0, 0.0);
 
-        let result = buffer.try_buffer(0, input);
+        let result = buffer.try_buffer(PlayerId(0), input);
         assert_eq!(result, BufferResult::Accepted { clamped: false });
-        assert!(buffer.has_entry(0, 5));
+        assert!(buffer.has_entry(PlayerId(0), 5));
     }
 
     #[test]
@@ -190,13 +356,13 @@ mod tests {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
 
         // First input with seq 1
-        buffer.try_buffer(0, make_input(5, 1, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 1, 1.0, 0.0));
 
         // Second input with seq 2 (higher)
-        buffer.try_buffer(0, make_input(5, 2, 0.0, 1.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 2, 0.0, 1.0));
 
         // Should have the second input
-        let taken = buffer.take_input(0, 5).unwrap();
+        let taken = buffer.take_input(PlayerId(0), 5).unwrap();
         assert_eq!(taken.input_seq, 2);
         assert_eq!(taken.move_dir, vec![0.0, 1.0]);
     }
@@ -206,13 +372,13 @@ mod tests {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
 
         // First input with seq 5
-        buffer.try_buffer(0, make_input(5, 5, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 5, 1.0, 0.0));
 
         // Second input with seq 3 (lower)
-        buffer.try_buffer(0, make_input(5, 3, 0.0, 1.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 3, 0.0, 1.0));
 
         // Should still have first input
-        let taken = buffer.take_input(0, 5).unwrap();
+        let taken = buffer.take_input(PlayerId(0), 5).unwrap();
         assert_eq!(taken.input_seq, 5);
         assert_eq!(taken.move_dir, vec![1.0, 0.0]);
     }
@@ -222,13 +388,13 @@ mod tests {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
 
         // First input with seq 5
-        buffer.try_buffer(0, make_input(5, 5, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 5, 1.0, 0.0));
 
         // Second input with seq 5 (same - tie!)
-        buffer.try_buffer(0, make_input(5, 5, 0.0, 1.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 5, 0.0, 1.0));
 
         // Should return None (tie → use LKI)
-        let taken = buffer.take_input(0, 5);
+        let taken = buffer.take_input(PlayerId(0), 5);
         assert!(taken.is_none());
     }
 
@@ -239,18 +405,19 @@ mod tests {
             max_future_ticks: 120,
             input_rate_limit_per_sec: 180, // 3 per tick at 60hz
             tick_rate_hz: 60,
+            ..Default::default()
         };
         let mut buffer = InputBuffer::new(config);
 
         // Create a tie
-        buffer.try_buffer(0, make_input(5, 5, 1.0, 0.0));
-        buffer.try_buffer(0, make_input(5, 5, 0.0, 1.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 5, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 5, 0.0, 1.0));
 
         // Now send a higher seq
-        buffer.try_buffer(0, make_input(5, 8, 0.5, 0.5));
+        buffer.try_buffer(PlayerId(0), make_input(5, 8, 0.5, 0.5));
 
         // Should have the seq 8 input (tie cleared)
-        let taken = buffer.take_input(0, 5).unwrap();
+        let taken = buffer.take_input(PlayerId(0), 5).unwrap();
         assert_eq!(taken.input_seq, 8);
     }
 
@@ -261,6 +428,7 @@ mod tests {
             max_future_ticks: 120,
             input_rate_limit_per_sec: 120,
             tick_rate_hz: 60,
+            ..Default::default()
         };
         let mut buffer = InputBuffer::new(config);
 
@@ -270,7 +438,7 @@ mod tests {
         let mut dropped = 0;
 
         for seq in 1..=5 {
-            let result = buffer.try_buffer(0, make_input(5, seq, 1.0, 0.0));
+            let result = buffer.try_buffer(PlayerId(0), make_input(5, seq, 1.0, 0.0));
             if result == BufferResult::RateLimited {
                 dropped += 1;
             } else {
@@ -283,17 +451,79 @@ mod tests {
         assert_eq!(dropped, 3);
     }
 
+    #[test]
+    fn test_burst_allowance_and_refill() {
+        let mut buffer = InputBuffer::new(ValidationConfig::default());
+        buffer.set_rate_limit(
+            PlayerId(0),
+            RateLimit {
+                inputs_per_sec: 120,
+                burst: 2,
+            },
+        );
+
+        // per_tick_limit 2 + burst 2
+        let accepted = (1..=5)
+            .filter(|&seq| {
+                buffer.try_buffer(PlayerId(0), make_input(5, seq, 1.0, 0.0))
+                    != BufferResult::RateLimited
+            })
+            .count();
+        assert_eq!(accepted, 4);
+
+        // Bucket empty: a third input for another tick is dropped
+        buffer.try_buffer(PlayerId(0), make_input(6, 1, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(6, 2, 1.0, 0.0));
+        assert_eq!(
+            buffer.try_buffer(PlayerId(0), make_input(6, 3, 1.0, 0.0)),
+            BufferResult::RateLimited
+        );
+
+        // One token per tick, capped at capacity
+        for _ in 0..5 {
+            buffer.refill_burst_tokens();
+        }
+        let accepted = (4..=7)
+            .filter(|&seq| {
+                buffer.try_buffer(PlayerId(0), make_input(6, seq, 1.0, 0.0))
+                    != BufferResult::RateLimited
+            })
+            .count();
+        assert_eq!(accepted, 2);
+    }
+
+    #[test]
+    fn test_per_player_limit_override() {
+        let mut buffer = InputBuffer::new(ValidationConfig::default());
+        buffer.set_rate_limit(
+            PlayerId(1),
+            RateLimit {
+                inputs_per_sec: 0,
+                burst: 0,
+            },
+        );
+        assert_eq!(
+            buffer.try_buffer(PlayerId(1), make_input(5, 1, 1.0, 0.0)),
+            BufferResult::RateLimited
+        );
+        // Other players keep the default limit
+        assert!(matches!(
+            buffer.try_buffer(PlayerId(0), make_input(5, 1, 1.0, 0.0)),
+            BufferResult::Accepted { .. }
+        ));
+    }
+
     #[test]
     fn test_magnitude_clamping() {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
 
         // Input with magnitude > 1
         let input = make_input(5, 1, 2.0, 0.0);
-        let result = buffer.try_buffer(0, input);
+        let result = buffer.try_buffer(PlayerId(0), input);
 
         assert_eq!(result, BufferResult::Accepted { clamped: true });
 
-        let taken = buffer.take_input(0, 5).unwrap();
+        let taken = buffer.take_input(PlayerId(0), 5).unwrap();
         // Should be clamped to unit length
         let mag = (taken.move_dir[0].powi(2) + taken.move_dir[1].powi(2)).sqrt();
         assert!((mag - 1.0).abs() < 1e-10);
@@ -303,16 +533,101 @@ mod tests {
     fn test_eviction() {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
 
-        buffer.try_buffer(0, make_input(5, 1, 1.0, 0.0));
-        buffer.try_buffer(0, make_input(10, 1, 1.0, 0.0));
-        buffer.try_buffer(0, make_input(15, 1, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 1, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(10, 1, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(15, 1, 1.0, 0.0));
 
         // Evict before tick 10
         buffer.evict_before(10);
 
-        assert!(!buffer.has_entry(0, 5));
-        assert!(buffer.has_entry(0, 10));
-        assert!(buffer.has_entry(0, 15));
+        assert!(!buffer.has_entry(PlayerId(0), 5));
+        assert!(buffer.has_entry(PlayerId(0), 10));
+        assert!(buffer.has_entry(PlayerId(0), 15));
+    }
+
+    #[test]
+    fn test_is_duplicate() {
+        let mut buffer = InputBuffer::new(ValidationConfig::default());
+        let input = make_input(5, 1, 1.0, 0.0);
+
+        assert!(!buffer.is_duplicate(PlayerId(0), &input));
+        buffer.try_buffer(PlayerId(0), input.clone());
+        assert!(buffer.is_duplicate(PlayerId(0), &input));
+
+        // Different seq, tick, or player is not a duplicate
+        assert!(!buffer.is_duplicate(PlayerId(0), &make_input(5, 2, 1.0, 0.0)));
+        assert!(!buffer.is_duplicate(PlayerId(0), &make_input(6, 1, 1.0, 0.0)));
+        assert!(!buffer.is_duplicate(PlayerId(1), &input));
+    }
+
+    #[test]
+    fn test_per_player_cap_evicts_furthest_tick() {
+        let mut buffer = InputBuffer::new(ValidationConfig {
+            max_buffered_per_player: 3,
+            ..Default::default()
+        });
+        for tick in [10, 20, 30] {
+            buffer.try_buffer(PlayerId(0), make_input(tick, 1, 1.0, 0.0));
+        }
+        // Further than everything buffered: dropped
+        assert_eq!(
+            buffer.try_buffer(PlayerId(0), make_input(40, 1, 1.0, 0.0)),
+            BufferResult::BufferFull
+        );
+        // Nearer: evicts tick 30
+        assert!(matches!(
+            buffer.try_buffer(PlayerId(0), make_input(5, 1, 1.0, 0.0)),
+            BufferResult::Accepted { .. }
+        ));
+        assert!(!buffer.has_entry(PlayerId(0), 30));
+        // Existing entries still take updates at the cap
+        assert!(matches!(
+            buffer.try_buffer(PlayerId(0), make_input(10, 2, 1.0, 0.0)),
+            BufferResult::Accepted { .. }
+        ));
+        // Other players are unaffected
+        assert!(matches!(
+            buffer.try_buffer(PlayerId(1), make_input(40, 1, 1.0, 0.0)),
+            BufferResult::Accepted { .. }
+        ));
+
+        let occupancy = buffer.occupancy();
+        assert_eq!(occupancy.total_entries, 4);
+        assert_eq!(occupancy.entries_per_player[&PlayerId(0)], 3);
+        assert_eq!(
+            occupancy.cap_counters[&PlayerId(0)],
+            CapCounters {
+                evicted: 1,
+                rejected_player_cap: 1,
+                rejected_global_cap: 0,
+            }
+        );
+        assert!(!occupancy.cap_counters.contains_key(&PlayerId(1)));
+    }
+
+    #[test]
+    fn test_global_cap_never_evicts_other_players() {
+        let mut buffer = InputBuffer::new(ValidationConfig {
+            max_buffered_total: 2,
+            ..Default::default()
+        });
+        buffer.try_buffer(PlayerId(0), make_input(10, 1, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(11, 1, 1.0, 0.0));
+        assert_eq!(
+            buffer.try_buffer(PlayerId(1), make_input(5, 1, 1.0, 0.0)),
+            BufferResult::BufferFull
+        );
+        assert!(buffer.has_entry(PlayerId(0), 10) && buffer.has_entry(PlayerId(0), 11));
+        assert_eq!(
+            buffer.occupancy().cap_counters[&PlayerId(1)].rejected_global_cap,
+            1
+        );
+
+        buffer.take_input(PlayerId(0), 10);
+        assert!(matches!(
+            buffer.try_buffer(PlayerId(1), make_input(5, 1, 1.0, 0.0)),
+            BufferResult::Accepted { .. }
+        ));
     }
 
     /// T0.11: Future input non-interference.
@@ -321,16 +636,16 @@ mod tests {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
 
         // Buffer input for tick 5 (future)
-        buffer.try_buffer(0, make_input(5, 1, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 1, 1.0, 0.0));
 
         // Should still be there
-        assert!(buffer.has_entry(0, 5));
+        assert!(buffer.has_entry(PlayerId(0), 5));
 
         // Taking input for tick 0 should return None (not 5)
-        assert!(buffer.take_input(0, 0).is_none());
+        assert!(buffer.take_input(PlayerId(0), 0).is_none());
 
         // Tick 5 should still be available
-        assert!(buffer.take_input(0, 5).is_some());
+        assert!(buffer.take_input(PlayerId(0), 5).is_some());
     }
 
     /// T0.13: InputSeq selection (tied → LKI fallback).
@@ -339,11 +654,11 @@ mod tests {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
 
         // Send two inputs with same seq
-        buffer.try_buffer(0, make_input(5, 10, 1.0, 0.0));
-        buffer.try_buffer(0, make_input(5, 10, 0.0, 1.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 10, 1.0, 0.0));
+        buffer.try_buffer(PlayerId(0), make_input(5, 10, 0.0, 1.0));
 
         // take_input should return None (use LKI)
-        let result = buffer.take_input(0, 5);
+        let result = buffer.take_input(PlayerId(0), 5);
         assert!(result.is_none());
     }
 }
@@ -24,18 +24,82 @@
 
 #![deny(unsafe_code)]
 
+pub mod admission;
+pub mod anomaly;
+pub mod anticheat;
+pub mod aoi;
+pub mod auth;
+pub mod bandwidth;
+pub mod chat;
+pub mod checkpoint;
+pub mod clock;
+pub mod config_reload;
+pub mod control;
+pub mod events;
+pub mod health;
+pub mod host;
 pub mod input_buffer;
+pub mod lifecycle;
+pub mod lki;
+pub mod load_test;
+pub mod match_id;
+pub mod net_stats;
+#[cfg(any(test, feature = "test-support"))]
+pub mod netsim;
+pub mod player_stats;
+pub mod rejections;
+pub mod replay_chunks;
+pub mod replay_storage;
+pub mod resync;
+pub mod roster;
+pub mod scheduler;
+#[cfg(any(test, feature = "test-support"))]
+pub mod script;
+pub mod secure_channel;
+pub mod seed;
 pub mod session;
+pub mod summary;
+pub mod teams;
+pub mod time_sync;
+pub mod transport;
 pub mod validation;
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use auth::{AllowAll, AuthError, Authenticator};
+use bandwidth::BandwidthBudget;
+use chat::{ChatError, ChatFilter, ChatRateLimit};
+use checkpoint::{CHECKPOINT_INTERVAL_TICKS, CheckpointError};
+use clock::{Clock, SystemClock};
+use config_reload::{ConfigPatch, ReloadError};
+use control::ControlMessage;
+use events::ServerEvent;
+use flowstate_replay::live::ReplaySink;
 use flowstate_replay::{AppliedInput, BuildFingerprintData, ReplayConfig, ReplayRecorder};
-use flowstate_sim::{Baseline, PlayerId, Snapshot, StepInput, Tick, World};
-use flowstate_wire::{InputCmdProto, JoinBaseline, ReplayArtifact, ServerWelcome, SnapshotProto};
-use input_buffer::InputBuffer;
-use session::{Session, SessionId};
-use validation::{ValidationConfig, ValidationResult, validate_input};
+use flowstate_sim::{Baseline, EntityId, PlayerId, Snapshot, StepInput, TeamId, Tick, World};
+use flowstate_wire::compression::{self, Codec};
+use flowstate_wire::{
+    BaselineRequest, BaselineUpdate, ChatBroadcast, ChatSend, ClientHello, EntityKind,
+    EntitySnapshotProto, FloorUpdate, Heartbeat, InputBundle, InputCmdProto, JoinBaseline,
+    KeyframeRequest, LeaveReason, MatchCheckpoint, MatchConfig, MatchEnd, MatchEndReason,
+    PlayerDisplayName, PlayerLeft, PlayerResult, PlayerRoster, RedundantInputCmd, RejectedInput,
+    ReplayArtifact, ReplayChunk, ReplayChunkRequest, ReplayMetadata, ServerWelcome, SnapshotAck,
+    SnapshotProto, TimeSyncPing, TimeSyncPong, TimeSyncReport,
+};
+use input_buffer::{BufferOccupancy, InputBuffer};
+use lifecycle::EntityLifecycle;
+use lki::LkiDecay;
+use player_stats::PlayerStats;
+use replay_chunks::{EncodedArtifact, ReplayChunkError};
+use resync::BaselineRequestError;
+use roster::PlayerProfile;
+use seed::SeedSource;
+use serde::{Deserialize, Serialize};
+use session::{DuplicateIdentityPolicy, Session, SessionId, SessionRole};
+use summary::{MatchSummary, NetworkSummary, PlayerSummary};
+use teams::TeamAssignment;
+use validation::{RateLimit, ValidationConfig, ValidationResult, validate_input};
 
 // ============================================================================
 // v0 Parameters (from docs/networking/v0-parameters.md)
@@ -59,6 +123,48 @@ pub const MATCH_DURATION_TICKS: u64 = 3600;
 /// Connection timeout in milliseconds.
 pub const CONNECT_TIMEOUT_MS: u64 = 30000;
 
+/// Default spectator input rate limit (spectators do not send inputs).
+pub const SPECTATOR_RATE_LIMIT: RateLimit = RateLimit {
+    inputs_per_sec: 0,
+    burst: 0,
+};
+
+/// Maximum inputs processed from a single InputBundle (redundancy depth K).
+pub const MAX_INPUT_BUNDLE_LEN: usize = 8;
+
+/// Interval between StateDigests streamed to live replay sinks.
+pub const LIVE_DIGEST_INTERVAL_TICKS: u64 = 60;
+
+/// Idle time after which a pre-match session is dropped.
+pub const PRE_MATCH_IDLE_TIMEOUT_MS: u64 = 15_000;
+
+/// Idle time after which a session in a running match is timed out, ending
+/// the match as a disconnect.
+pub const MATCH_IDLE_TIMEOUT_MS: u64 = 5_000;
+
+/// Maximum buffered (tick) entries per player across the InputTickWindow.
+pub const MAX_BUFFERED_INPUTS_PER_PLAYER: usize = 32;
+
+/// Maximum buffered (player_id, tick) entries across all players.
+pub const MAX_BUFFERED_INPUTS_TOTAL: usize = 512;
+
+/// Consecutive below-floor drops before the floor is resent on the Control channel.
+pub const FLOOR_RESEND_AFTER_DROPS: u32 = 3;
+
+/// Optional features this server can use (`flowstate_wire::capabilities`).
+pub const SERVER_CAPABILITIES: u64 = flowstate_wire::capabilities::REALTIME_CHECKSUMS;
+
+/// Codecs this server compresses frames with, most preferred first
+/// (`transport::compress`).
+pub const SERVER_COMPRESSION_CODECS: &[Codec] = &[Codec::Lz4, Codec::Zstd];
+
+/// Interval between InputRejectionReports to a session whose inputs are dropped.
+pub const INPUT_REJECTION_REPORT_INTERVAL_TICKS: u64 = 60;
+
+/// Interval between InputTimingReports to each player session (one second
+/// at the default tick rate).
+pub const INPUT_TIMING_REPORT_INTERVAL_TICKS: u64 = 60;
+
 // ============================================================================
 // Match End Reason
 // ============================================================================
@@ -68,6 +174,8 @@ pub const CONNECT_TIMEOUT_MS: u64 = 30000;
 pub enum EndReason {
     Complete,
     Disconnect,
+    /// Ended early by `Server::restart_match`.
+    Rematch,
 }
 
 impl EndReason {
@@ -75,6 +183,16 @@ impl EndReason {
         match self {
             Self::Complete => "complete",
             Self::Disconnect => "disconnect",
+            Self::Rematch => "rematch",
+        }
+    }
+
+    /// Wire form, recorded in the ReplayArtifact and MatchEnd.
+    pub fn to_proto(self) -> MatchEndReason {
+        match self {
+            Self::Complete => MatchEndReason::Complete,
+            Self::Disconnect => MatchEndReason::Disconnect,
+            Self::Rematch => MatchEndReason::Rematch,
         }
     }
 }
@@ -84,104 +202,354 @@ impl EndReason {
 // ============================================================================
 
 /// Server configuration.
-#[derive(Debug, Clone)]
+///
+/// Deserializable from JSON; omitted fields take their default values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ServerConfig {
+    /// MatchId (DM-0021). Empty means `Server::new` generates one.
+    pub match_id: String,
+    /// Match seed. 0 means `Server::new` draws one from OS entropy, except in
+    /// test mode where 0 is used as-is (see `seed`).
     pub seed: u64,
     pub tick_rate_hz: u32,
     pub max_future_ticks: u64,
     pub input_lead_ticks: u64,
+    /// Player input rate limit (FS-0007 `input_rate_limit_per_sec`).
     pub input_rate_limit_per_sec: u32,
+    /// Player burst allowance beyond the per-tick limit (0 = strict v0 limiter).
+    pub input_burst: u32,
+    /// Rate limit for `SessionRole::Bot` sessions.
+    pub bot_rate_limit: RateLimit,
+    /// Rate limit for `SessionRole::Spectator` sessions.
+    pub spectator_rate_limit: RateLimit,
     pub match_duration_ticks: u64,
+    /// Map announced in `MatchConfig` (empty = the v0 open arena).
+    pub map_id: String,
     pub connect_timeout_ms: u64,
+    /// Consecutive below-floor drops from a session before its floor is resent
+    /// as a `FloorUpdate` (0 = never).
+    pub floor_resend_after_drops: u32,
+    /// Ticks between `InputRejectionReport`s to sessions with dropped inputs
+    /// (0 = never).
+    pub input_rejection_report_interval_ticks: u64,
+    /// Ticks between `InputTimingReport`s to player sessions (0 = never).
+    pub input_timing_report_interval_ticks: u64,
+    /// Idle time before `collect_stale_sessions` drops a pre-match session (0 = never).
+    pub pre_match_idle_timeout_ms: u64,
+    /// Idle time before `collect_stale_sessions` times out a session once the
+    /// match has started, ending it with `EndReason::Disconnect` (0 = never).
+    pub match_idle_timeout_ms: u64,
     pub test_mode: bool,
     pub test_player_ids: Option<(PlayerId, PlayerId)>,
+    /// Cap on buffered input ticks per player (see `InputBuffer`).
+    pub input_buffer_max_per_player: usize,
+    /// Cap on buffered input entries across all players.
+    pub input_buffer_max_total: usize,
+    /// LastKnownIntent decay for players whose inputs stop arriving.
+    pub lki_decay: LkiDecay,
+    /// Area-of-interest radius. `None` disables AOI filtering (full broadcast).
+    pub aoi_radius: Option<f64>,
+    /// Encoded snapshot size limit (e.g., MTU-derived). Oversized snapshots are
+    /// cut per session by priority (`aoi::fit_to_size`); `None` = unlimited.
+    pub snapshot_max_bytes: Option<usize>,
+    /// Per-session chat rate limit.
+    pub chat_rate_limit: ChatRateLimit,
+    /// Handling of a second connection from an already-connected identity.
+    pub duplicate_identity_policy: DuplicateIdentityPolicy,
+    /// Ticks between intermediate digests recorded in the replay and streamed
+    /// to live replay sinks (0 = final digest only).
+    pub live_digest_interval_ticks: u64,
+    /// Ticks between full World states recorded in the replay as snapshot
+    /// anchors, which verification compares entity by entity (0 = none).
+    pub snapshot_anchor_interval_ticks: u64,
+    /// Ticks between crash-recovery checkpoints (0 = disabled).
+    /// Only written once a path is set via `Server::set_checkpoint_path`.
+    pub checkpoint_interval_ticks: u64,
+    /// How players are split into teams at match start.
+    pub team_assignment: TeamAssignment,
+    /// Per-session outbound budget (0 = unlimited; see `bandwidth`).
+    pub outbound_bytes_per_sec: u64,
+    pub outbound_burst_bytes: u64,
+    /// Cap on bytes queued for one session before messages are dropped.
+    pub outbound_queue_max_bytes: usize,
+    /// Prefix outbound realtime datagrams with a CRC32C (see `transport`).
+    pub realtime_checksums: bool,
+    /// Refuse ClientHellos without a key exchange, and Realtime datagrams
+    /// that are not sealed (see `transport::unframe_secure`).
+    pub require_encryption: bool,
+    /// Serve the running match's replay to `ReplayChunkRequest`s.
+    pub serve_in_progress_replays: bool,
+    /// Most `ReplayChunkRequest`s answered per session per second; further
+    /// requests are dropped.
+    pub replay_chunk_requests_per_sec: u32,
+    /// Minimum ticks between `BaselineUpdate`s to one session (see `resync`).
+    pub baseline_request_min_interval_ticks: u64,
+    /// Dropped InputCmds kept in the replay's audit track per match
+    /// (0 = no audit track); further drops are only counted.
+    pub rejected_input_audit_limit: usize,
+}
+
+impl ServerConfig {
+    /// Input rate limit for sessions of `role`.
+    pub fn rate_limit_for(&self, role: SessionRole) -> RateLimit {
+        match role {
+            SessionRole::Player => RateLimit {
+                inputs_per_sec: self.input_rate_limit_per_sec,
+                burst: self.input_burst,
+            },
+            SessionRole::Spectator => self.spectator_rate_limit,
+            SessionRole::Bot => self.bot_rate_limit,
+        }
+    }
+
+    /// Outbound budget for the host's `BandwidthShaper`.
+    pub fn bandwidth_budget(&self) -> BandwidthBudget {
+        BandwidthBudget {
+            bytes_per_sec: self.outbound_bytes_per_sec,
+            burst_bytes: self.outbound_burst_bytes,
+            max_queued_bytes: self.outbound_queue_max_bytes,
+        }
+    }
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
+            match_id: String::new(),
             seed: 0,
             tick_rate_hz: TICK_RATE_HZ,
             max_future_ticks: MAX_FUTURE_TICKS,
             input_lead_ticks: INPUT_LEAD_TICKS,
             input_rate_limit_per_sec: INPUT_RATE_LIMIT_PER_SEC,
+            input_burst: 0,
+            bot_rate_limit: RateLimit {
+                inputs_per_sec: INPUT_RATE_LIMIT_PER_SEC,
+                burst: 0,
+            },
+            spectator_rate_limit: SPECTATOR_RATE_LIMIT,
             match_duration_ticks: MATCH_DURATION_TICKS,
+            map_id: String::new(),
             connect_timeout_ms: CONNECT_TIMEOUT_MS,
+            floor_resend_after_drops: FLOOR_RESEND_AFTER_DROPS,
+            input_rejection_report_interval_ticks: INPUT_REJECTION_REPORT_INTERVAL_TICKS,
+            input_timing_report_interval_ticks: INPUT_TIMING_REPORT_INTERVAL_TICKS,
+            pre_match_idle_timeout_ms: PRE_MATCH_IDLE_TIMEOUT_MS,
+            match_idle_timeout_ms: MATCH_IDLE_TIMEOUT_MS,
             test_mode: false,
             test_player_ids: None,
+            input_buffer_max_per_player: MAX_BUFFERED_INPUTS_PER_PLAYER,
+            input_buffer_max_total: MAX_BUFFERED_INPUTS_TOTAL,
+            lki_decay: LkiDecay::default(),
+            aoi_radius: None,
+            snapshot_max_bytes: None,
+            chat_rate_limit: ChatRateLimit::default(),
+            duplicate_identity_policy: DuplicateIdentityPolicy::RejectNew,
+            live_digest_interval_ticks: LIVE_DIGEST_INTERVAL_TICKS,
+            snapshot_anchor_interval_ticks: 0,
+            checkpoint_interval_ticks: CHECKPOINT_INTERVAL_TICKS,
+            team_assignment: TeamAssignment::None,
+            outbound_bytes_per_sec: bandwidth::OUTBOUND_BYTES_PER_SEC,
+            outbound_burst_bytes: bandwidth::OUTBOUND_BURST_BYTES,
+            outbound_queue_max_bytes: bandwidth::OUTBOUND_QUEUE_MAX_BYTES,
+            realtime_checksums: true,
+            require_encryption: false,
+            serve_in_progress_replays: false,
+            replay_chunk_requests_per_sec: replay_chunks::REPLAY_CHUNK_REQUESTS_PER_SEC,
+            baseline_request_min_interval_ticks: resync::BASELINE_REQUEST_MIN_INTERVAL_TICKS,
+            rejected_input_audit_limit: 0,
+        }
+    }
+}
+
+/// Serialized snapshot payload(s) produced by `Server::step()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotPayload {
+    /// One payload, byte-identical for all sessions (T0.18). Used when AOI is disabled.
+    Broadcast(Vec<u8>),
+    /// AOI-filtered payload per session, ordered by SessionId ascending.
+    PerSession(Vec<(SessionId, Vec<u8>)>),
+}
+
+impl SnapshotPayload {
+    /// Get the bytes to send to a given session.
+    pub fn bytes_for(&self, session_id: SessionId) -> Option<&[u8]> {
+        match self {
+            Self::Broadcast(bytes) => Some(bytes),
+            Self::PerSession(payloads) => payloads
+                .iter()
+                .find(|(sid, _)| *sid == session_id)
+                .map(|(_, bytes)| bytes.as_slice()),
         }
     }
 }
 
+/// Replay recording configuration for a match.
+fn replay_config(config: &ServerConfig) -> ReplayConfig {
+    ReplayConfig {
+        match_id: config.match_id.clone(),
+        seed: config.seed,
+        tick_rate_hz: config.tick_rate_hz,
+        rng_algorithm: "none".to_string(),
+        test_mode: config.test_mode,
+        test_player_ids: config
+            .test_player_ids
+            .map(|(a, b)| vec![a, b])
+            .unwrap_or_default(),
+        tuning: config
+            .lki_decay
+            .tuning_parameters()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+        rejected_input_limit: config.rejected_input_audit_limit,
+    }
+}
+
 /// Server state for running a match.
+///
+/// Every per-session and per-player map is ordered (BTreeMap), so anything
+/// iterating them (welcomes, floors, AOI payloads, summaries) runs in
+/// SessionId / PlayerId order, never hash order.
 pub struct Server {
     config: ServerConfig,
     world: World,
-    sessions: HashMap<SessionId, Session>,
+    sessions: BTreeMap<SessionId, Session>,
     next_session_id: SessionId,
     /// PlayerId → SessionId mapping
-    player_sessions: HashMap<PlayerId, SessionId>,
+    player_sessions: BTreeMap<PlayerId, SessionId>,
     /// SessionId → PlayerId mapping (for convenience)
-    session_players: HashMap<SessionId, PlayerId>,
+    session_players: BTreeMap<SessionId, PlayerId>,
     /// Input buffer per (player_id, tick)
     input_buffer: InputBuffer,
-    /// Last known intent per player
-    last_known_intent: HashMap<PlayerId, [f64; 2]>,
+    /// Last received intent per player (LastKnownIntent, before decay)
+    last_known_intent: BTreeMap<PlayerId, [f64; 2]>,
+    /// Consecutive LastKnownIntent fallback ticks per player
+    fallback_streak: BTreeMap<PlayerId, u64>,
     /// Last emitted target tick floor per session
-    last_emitted_floor: HashMap<SessionId, Tick>,
+    last_emitted_floor: BTreeMap<SessionId, Tick>,
+    /// InputCmds received so far (the receive order in the input audit)
+    inputs_received: u64,
     /// Replay recorder
     replay_recorder: ReplayRecorder,
+    /// The running match's encoded artifact, reused within a tick.
+    replay_chunk_cache: Option<EncodedArtifact>,
     /// Entity spawn order (player_ids in order)
     entity_spawn_order: Vec<PlayerId>,
     /// Player → Entity mapping
-    player_entity_mapping: HashMap<PlayerId, flowstate_sim::EntityId>,
+    player_entity_mapping: BTreeMap<PlayerId, EntityId>,
     /// Initial tick (set after match starts)
     initial_tick: Tick,
     /// Match started flag
     match_started: bool,
+    /// A session left after match start (ends the match as a disconnect)
+    session_left: bool,
     /// Build fingerprint
     build_fingerprint: Option<BuildFingerprintData>,
+    /// Server Edge time source (never passed to the Simulation Core)
+    clock: Box<dyn Clock>,
+    /// Events queued for the host (drained via `drain_events`)
+    events: Vec<ServerEvent>,
+    /// Control messages queued for sessions (drained via `drain_control`)
+    control_outbox: Vec<(SessionId, ControlMessage)>,
+    /// Handshake token validation
+    authenticator: Box<dyn Authenticator>,
+    /// Optional chat content filter
+    chat_filter: Option<Box<dyn ChatFilter>>,
+    /// Per-player gameplay/netcode stats (keyed by player, survives rebinds)
+    player_stats: BTreeMap<PlayerId, PlayerStats>,
+    /// Host-provided display names/cosmetics (keyed by player, survives rebinds)
+    player_profiles: BTreeMap<PlayerId, PlayerProfile>,
+    /// Entities announced to clients (JoinBaseline or EntitySpawned)
+    entity_lifecycle: EntityLifecycle,
+    /// Where periodic checkpoints are written (`None` = disabled)
+    checkpoint_path: Option<PathBuf>,
+    /// Where `config.seed` came from
+    seed_source: SeedSource,
 }
 
 impl Server {
     /// Create a new server with the given configuration.
-    pub fn new(config: ServerConfig) -> Self {
+    pub fn new(mut config: ServerConfig) -> Self {
+        if config.match_id.is_empty() {
+            config.match_id = match_id::generate_match_id();
+        }
+        let (seed, seed_source) = seed::provision_seed(config.seed, config.test_mode);
+        config.seed = seed;
         let validation_config = ValidationConfig {
             max_future_ticks: config.max_future_ticks,
             input_rate_limit_per_sec: config.input_rate_limit_per_sec,
             tick_rate_hz: config.tick_rate_hz,
-        };
-
-        let replay_config = ReplayConfig {
-            seed: config.seed,
-            tick_rate_hz: config.tick_rate_hz,
-            rng_algorithm: "none".to_string(),
-            test_mode: config.test_mode,
-            test_player_ids: config
-                .test_player_ids
-                .map(|(a, b)| vec![a, b])
-                .unwrap_or_default(),
+            max_buffered_per_player: config.input_buffer_max_per_player,
+            max_buffered_total: config.input_buffer_max_total,
         };
 
         Self {
             world: World::new(config.seed, config.tick_rate_hz),
-            sessions: HashMap::new(),
-            next_session_id: 1,
-            player_sessions: HashMap::new(),
-            session_players: HashMap::new(),
+            sessions: BTreeMap::new(),
+            next_session_id: SessionId(1),
+            player_sessions: BTreeMap::new(),
+            session_players: BTreeMap::new(),
             input_buffer: InputBuffer::new(validation_config),
-            last_known_intent: HashMap::new(),
-            last_emitted_floor: HashMap::new(),
-            replay_recorder: ReplayRecorder::new(replay_config),
+            last_known_intent: BTreeMap::new(),
+            fallback_streak: BTreeMap::new(),
+            last_emitted_floor: BTreeMap::new(),
+            inputs_received: 0,
+            replay_recorder: ReplayRecorder::new(replay_config(&config)),
+            replay_chunk_cache: None,
             entity_spawn_order: Vec::new(),
-            player_entity_mapping: HashMap::new(),
+            player_entity_mapping: BTreeMap::new(),
             initial_tick: 0,
             match_started: false,
+            session_left: false,
             build_fingerprint: None,
+            clock: Box::new(SystemClock::new()),
+            events: Vec::new(),
+            control_outbox: Vec::new(),
+            authenticator: Box::new(AllowAll),
+            chat_filter: None,
+            player_stats: BTreeMap::new(),
+            player_profiles: BTreeMap::new(),
+            entity_lifecycle: EntityLifecycle::default(),
+            checkpoint_path: None,
+            seed_source,
             config,
         }
     }
 
+    /// Replace the Server Edge clock (e.g., with a `ManualClock` in tests).
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Stream AppliedInputs and periodic digests to `sink` while the match runs.
+    /// Attach before `start_match()` so the sink receives the match start record.
+    pub fn add_replay_sink(&mut self, sink: Box<dyn ReplaySink>) {
+        self.replay_recorder.add_sink(sink);
+    }
+
+    /// Replace the handshake authenticator (default: `AllowAll`).
+    pub fn set_authenticator(&mut self, authenticator: Box<dyn Authenticator>) {
+        self.authenticator = authenticator;
+    }
+
+    /// Install a chat content filter (e.g., profanity masking).
+    pub fn set_chat_filter(&mut self, filter: Box<dyn ChatFilter>) {
+        self.chat_filter = Some(filter);
+    }
+
+    /// Write a crash-recovery checkpoint to `path` every
+    /// `checkpoint_interval_ticks` (each write replaces the previous one).
+    pub fn set_checkpoint_path(&mut self, path: impl Into<PathBuf>) {
+        self.checkpoint_path = Some(path.into());
+    }
+
+    /// Spill recorded inputs to `path` during the match instead of holding
+    /// them all in memory (see `flowstate_replay::spill`).
+    pub fn set_replay_spill_path(&mut self, path: &Path) -> std::io::Result<()> {
+        self.replay_recorder.spill_to(path)
+    }
+
     /// Set the build fingerprint.
     pub fn set_build_fingerprint(&mut self, fingerprint: BuildFingerprintData) {
         self.build_fingerprint = Some(fingerprint.clone());
@@ -193,6 +561,71 @@ impl Server {
         self.world.tick()
     }
 
+    /// Get the server configuration.
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// MatchId (DM-0021): host-supplied or generated at creation.
+    pub fn match_id(&self) -> &str {
+        &self.config.match_id
+    }
+
+    /// Match seed and where it came from.
+    pub fn seed(&self) -> (u64, SeedSource) {
+        (self.config.seed, self.seed_source)
+    }
+
+    /// Apply a runtime config update (e.g., from the admin interface).
+    ///
+    /// Server Edge policy takes effect immediately, including for in-flight
+    /// matches; gameplay-affecting fields are rejected once the match has
+    /// started. Returns the fields that changed.
+    pub fn reload_config(&mut self, patch: &ConfigPatch) -> Result<Vec<&'static str>, ReloadError> {
+        patch.validate(self.match_started)?;
+        let changed = patch.apply_to(&mut self.config);
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+
+        // Re-derive per-player input limits from the (possibly new) role limits
+        for &player_id in &self.entity_spawn_order {
+            let role = self
+                .player_sessions
+                .get(&player_id)
+                .and_then(|sid| self.sessions.get(sid))
+                .map_or(SessionRole::Player, |s| s.role);
+            self.input_buffer
+                .set_rate_limit(player_id, self.config.rate_limit_for(role));
+        }
+
+        self.events.push(ServerEvent::ConfigReloaded {
+            tick: self.world.tick(),
+            fields: changed.clone(),
+        });
+        Ok(changed)
+    }
+
+    /// Check if the match has started and has not yet reached its end condition.
+    pub fn is_running(&self) -> bool {
+        self.match_started && self.should_end_match().is_none()
+    }
+
+    /// Number of buffered (player_id, tick) input entries.
+    pub fn buffered_input_count(&self) -> usize {
+        self.input_buffer.len()
+    }
+
+    /// Estimated heap + inline bytes held by the input buffer.
+    pub fn buffered_input_bytes(&self) -> usize {
+        self.input_buffer.estimated_bytes()
+    }
+
+    /// Input buffer occupancy and cap counters.
+    pub fn input_buffer_occupancy(&self) -> BufferOccupancy {
+        self.input_buffer.occupancy()
+    }
+
     /// Get number of connected sessions.
     pub fn session_count(&self) -> usize {
         self.sessions.len()
@@ -209,7 +642,7 @@ impl Server {
     ///
     /// # Panics
     /// If more than 2 sessions try to connect (v0 limit).
-    pub fn accept_session(&mut self) -> (SessionId, PlayerId, flowstate_sim::EntityId) {
+    pub fn accept_session(&mut self) -> (SessionId, PlayerId, EntityId) {
         assert!(self.sessions.len() < 2, "v0: Only 2 sessions allowed");
         assert!(
             !self.match_started,
@@ -217,23 +650,37 @@ impl Server {
         );
 
         let session_id = self.next_session_id;
-        self.next_session_id += 1;
+        self.next_session_id.0 += 1;
+
+        let player_id = self.player_id_for_slot(self.sessions.len());
+        let entity_id = self.spawn_player(session_id, player_id, SessionRole::Player);
 
-        // Assign player ID
-        let player_id = if let Some((id1, id2)) = self.config.test_player_ids {
+        let mut session = Session::new(session_id, player_id, entity_id);
+        session.last_activity_micros = self.clock.now_micros();
+        self.sessions.insert(session_id, session);
+
+        (session_id, player_id, entity_id)
+    }
+
+    /// PlayerId for the `slot`-th connected session.
+    fn player_id_for_slot(&self, slot: usize) -> PlayerId {
+        if let Some((id1, id2)) = self.config.test_player_ids {
             // Test mode: use configured IDs
-            if self.sessions.is_empty() { id1 } else { id2 }
+            if slot == 0 { id1 } else { id2 }
         } else {
             // Normal mode: 0 for first, 1 for second
-            self.sessions.len() as PlayerId
-        };
+            PlayerId(u8::try_from(slot).expect("v0 has at most 2 slots"))
+        }
+    }
 
-        // Spawn character
+    /// Spawn a player's character and bind it to `session_id` (pre-match).
+    fn spawn_player(
+        &mut self,
+        session_id: SessionId,
+        player_id: PlayerId,
+        role: SessionRole,
+    ) -> EntityId {
         let entity_id = self.world.spawn_character(player_id);
-
-        // Create session
-        let session = Session::new(session_id, player_id, entity_id);
-        self.sessions.insert(session_id, session);
         self.player_sessions.insert(player_id, session_id);
         self.session_players.insert(session_id, player_id);
 
@@ -244,8 +691,206 @@ impl Server {
 
         // Initialize last known intent
         self.last_known_intent.insert(player_id, [0.0, 0.0]);
+        self.player_stats.insert(player_id, PlayerStats::new());
+        self.input_buffer
+            .set_rate_limit(player_id, self.config.rate_limit_for(role));
+        entity_id
+    }
 
-        (session_id, player_id, entity_id)
+    /// Rebuild the pre-match World from the remaining sessions, in their
+    /// original spawn order, so PlayerIds and EntityIds stay dense and
+    /// deterministic after a session leaves. Clients learn their ids from
+    /// ServerWelcome at match start, so reassignment is invisible to them.
+    fn rebuild_pre_match(&mut self) {
+        let survivors: Vec<SessionId> = self
+            .entity_spawn_order
+            .iter()
+            .filter_map(|player_id| self.player_sessions.get(player_id).copied())
+            .filter(|session_id| self.sessions.contains_key(session_id))
+            .collect();
+
+        self.world = World::new(self.config.seed, self.config.tick_rate_hz);
+        self.replay_recorder.clear_spawns();
+        self.entity_spawn_order.clear();
+        self.player_entity_mapping.clear();
+        self.player_sessions.clear();
+        self.session_players.clear();
+        self.last_known_intent.clear();
+        self.fallback_streak.clear();
+        self.player_stats.clear();
+
+        for (slot, session_id) in survivors.into_iter().enumerate() {
+            let player_id = self.player_id_for_slot(slot);
+            let role = self.sessions[&session_id].role;
+            let entity_id = self.spawn_player(session_id, player_id, role);
+            let session = self
+                .sessions
+                .get_mut(&session_id)
+                .expect("survivor session exists");
+            session.player_id = player_id;
+            session.controlled_entity_id = entity_id;
+        }
+    }
+
+    /// Drop sessions silent for longer than their idle timeout. Returns the
+    /// dropped SessionIds so the host can forget their transport addresses.
+    ///
+    /// Before the match starts, `pre_match_idle_timeout_ms` applies and the
+    /// freed slots are reassigned. Once it has started,
+    /// `match_idle_timeout_ms` applies: the session leaves as timed out, and
+    /// the match ends with `EndReason::Disconnect` (see `should_end_match`).
+    pub fn collect_stale_sessions(&mut self) -> Vec<SessionId> {
+        let timeout_ms = if self.match_started {
+            self.config.match_idle_timeout_ms
+        } else {
+            self.config.pre_match_idle_timeout_ms
+        };
+        if timeout_ms == 0 {
+            return Vec::new();
+        }
+        let now = self.clock.now_micros();
+        let timeout_micros = timeout_ms * 1_000;
+        let stale: Vec<(SessionId, PlayerId, u64)> = self
+            .sessions
+            .values()
+            .map(|s| {
+                (
+                    s.id,
+                    s.player_id,
+                    now.saturating_sub(s.last_activity_micros),
+                )
+            })
+            .filter(|&(_, _, idle)| idle > timeout_micros)
+            .collect();
+        if stale.is_empty() {
+            return Vec::new();
+        }
+
+        for &(session_id, player_id, idle_micros) in &stale {
+            if self.match_started {
+                self.remove_session(session_id, LeaveReason::TimedOut);
+            } else {
+                self.sessions.remove(&session_id);
+            }
+            self.events.push(ServerEvent::SessionExpired {
+                session_id,
+                player_id,
+                idle_micros,
+            });
+        }
+        if !self.match_started {
+            self.rebuild_pre_match();
+        }
+        let mut dropped: Vec<SessionId> = stale.into_iter().map(|(id, _, _)| id).collect();
+        dropped.sort_unstable();
+        dropped
+    }
+
+    /// Note activity from a session (keeps it from idle timeouts).
+    fn touch_session(&mut self, session_id: SessionId) {
+        let now = self.clock.now_micros();
+        if let Some(session) = self.sessions.get_mut(&session_id) {
+            session.last_activity_micros = now;
+        }
+    }
+
+    /// Change a session's role, applying that role's input rate limit.
+    pub fn set_session_role(&mut self, session_id: SessionId, role: SessionRole) {
+        if let Some(session) = self.sessions.get_mut(&session_id) {
+            session.role = role;
+            self.input_buffer
+                .set_rate_limit(session.player_id, self.config.rate_limit_for(role));
+        }
+    }
+
+    /// Authenticate a ClientHello and, on success, accept its session.
+    /// No session or PlayerId is allocated for rejected handshakes, including
+    /// hellos from an unsupported nonzero `protocol_version` or with a nonzero
+    /// `schema_hash` other than `flowstate_wire::SCHEMA_HASH`.
+    ///
+    /// If the identity already has a session, `duplicate_identity_policy`
+    /// either rejects the hello or rebinds the existing PlayerId and entity
+    /// to a new SessionId (never a second PlayerId).
+    ///
+    /// # Panics
+    /// Same as `accept_session` when a new player is admitted.
+    pub fn accept_hello(
+        &mut self,
+        hello: &ClientHello,
+    ) -> Result<(SessionId, PlayerId, EntityId), AuthError> {
+        if hello.protocol_version != 0 && hello.protocol_version != flowstate_wire::PROTOCOL_VERSION
+        {
+            return Err(AuthError::UnsupportedProtocolVersion(
+                hello.protocol_version,
+            ));
+        }
+        if hello.schema_hash != 0 && hello.schema_hash != flowstate_wire::SCHEMA_HASH {
+            return Err(AuthError::SchemaMismatch(hello.schema_hash));
+        }
+        let identity = self.authenticator.authenticate(&hello.auth_token)?;
+        let capabilities =
+            flowstate_wire::capabilities::negotiate(hello.capabilities, SERVER_CAPABILITIES);
+        let compression = compression::negotiate(
+            hello.compression.as_ref(),
+            hello.capabilities,
+            SERVER_COMPRESSION_CODECS,
+            compression::DEFAULT_MIN_PAYLOAD_LEN,
+        );
+
+        let existing = identity.as_ref().and_then(|identity| {
+            self.sessions
+                .values()
+                .find(|s| s.identity.as_ref() == Some(identity))
+                .map(|s| s.id)
+        });
+        if let Some(old_session_id) = existing {
+            let rebound = match self.config.duplicate_identity_policy {
+                DuplicateIdentityPolicy::RejectNew => return Err(AuthError::AlreadyConnected),
+                DuplicateIdentityPolicy::SupersedeOld => self.rebind_session(old_session_id),
+            };
+            if let Some(session) = self.sessions.get_mut(&rebound.0) {
+                session.capabilities = capabilities;
+                session.compression = compression;
+            }
+            return Ok(rebound);
+        }
+
+        let accepted = self.accept_session();
+        if let Some(session) = self.sessions.get_mut(&accepted.0) {
+            session.identity = identity;
+            session.capabilities = capabilities;
+            session.compression = compression;
+        }
+        Ok(accepted)
+    }
+
+    /// Move an existing session's player to a fresh SessionId.
+    fn rebind_session(&mut self, old_session_id: SessionId) -> (SessionId, PlayerId, EntityId) {
+        let new_session_id = self.next_session_id;
+        self.next_session_id.0 += 1;
+
+        let mut session = self
+            .sessions
+            .remove(&old_session_id)
+            .expect("rebind of known session");
+        session.rebind(new_session_id);
+        session.last_activity_micros = self.clock.now_micros();
+        let (player_id, entity_id) = (session.player_id, session.controlled_entity_id);
+        self.sessions.insert(new_session_id, session);
+
+        self.session_players.remove(&old_session_id);
+        self.session_players.insert(new_session_id, player_id);
+        self.player_sessions.insert(player_id, new_session_id);
+        if let Some(floor) = self.last_emitted_floor.remove(&old_session_id) {
+            self.last_emitted_floor.insert(new_session_id, floor);
+        }
+
+        self.events.push(ServerEvent::SessionSuperseded {
+            old_session_id,
+            new_session_id,
+            player_id,
+        });
+        (new_session_id, player_id, entity_id)
     }
 
     /// Start the match (after 2 clients connected).
@@ -260,10 +905,23 @@ impl Server {
 
         self.match_started = true;
         self.initial_tick = self.world.tick();
+        self.assign_teams();
+        // Time spent waiting for the match does not count against the
+        // match idle timeout
+        let now = self.clock.now_micros();
+        for session in self.sessions.values_mut() {
+            session.last_activity_micros = now;
+        }
 
         // Record baseline
         let baseline = self.world.baseline();
         self.replay_recorder.record_baseline(baseline.clone());
+        let metadata = self.replay_metadata(self.clock.unix_millis(), 0);
+        self.replay_recorder.set_metadata(metadata);
+        self.entity_lifecycle
+            .reset(baseline.entities.iter().map(|e| e.entity_id));
+        self.observe_movement();
+        self.record_positions();
 
         // Compute initial target tick floor
         let target_tick_floor = self.initial_tick + self.config.input_lead_ticks;
@@ -283,7 +941,12 @@ impl Server {
                     target_tick_floor,
                     tick_rate_hz: self.config.tick_rate_hz,
                     player_id: u32::from(session.player_id),
-                    controlled_entity_id: session.controlled_entity_id,
+                    controlled_entity_id: session.controlled_entity_id.into(),
+                    match_id: self.config.match_id.clone(),
+                    team_id: self.world.team_of(session.player_id).map(u32::from),
+                    schema_hash: flowstate_wire::SCHEMA_HASH,
+                    capabilities: session.capabilities,
+                    compression: session.compression,
                 };
                 (session.id, welcome)
             })
@@ -292,12 +955,109 @@ impl Server {
         (baseline, welcomes)
     }
 
+    /// Resolve `config.team_assignment` over the spawned players and apply it
+    /// to the World and the replay (Ref: `teams`).
+    fn assign_teams(&mut self) {
+        let players: Vec<_> = self
+            .entity_spawn_order
+            .iter()
+            .map(|&player_id| {
+                let identity = self
+                    .player_sessions
+                    .get(&player_id)
+                    .and_then(|sid| self.sessions.get(sid))
+                    .and_then(|s| s.identity.as_ref());
+                (player_id, identity)
+            })
+            .collect();
+        let teams = teams::assign_teams(&self.config.team_assignment, &players);
+        for (player_id, team) in teams {
+            self.world.set_team(player_id, team);
+            self.replay_recorder.record_team(player_id, team);
+        }
+    }
+
+    /// Team of `player_id`, if teams are in use.
+    pub fn team_of(&self, player_id: PlayerId) -> Option<TeamId> {
+        self.world.team_of(player_id)
+    }
+
+    /// Finalize the current match and start a rematch with the same sessions.
+    ///
+    /// Players keep their PlayerIds and are respawned in their original spawn
+    /// order into a fresh World seeded with `new_seed` (0 = provisioned as in
+    /// `Server::new`). The rematch gets its own MatchId and replay; teams are
+    /// reassigned. Returns the finished match's replay, then the new baseline
+    /// and welcomes exactly as `start_match` does.
+    ///
+    /// # Panics
+    /// If the match has not started, or fewer than 2 players remain connected.
+    pub fn restart_match(
+        &mut self,
+        new_seed: u64,
+    ) -> (
+        std::io::Result<ReplayArtifact>,
+        Baseline,
+        Vec<(SessionId, ServerWelcome)>,
+    ) {
+        assert!(self.match_started, "Match not started");
+        let end_reason = self.should_end_match().unwrap_or(EndReason::Rematch);
+        let roster: Vec<(SessionId, PlayerId)> = self
+            .entity_spawn_order
+            .iter()
+            .filter_map(|&player_id| Some((*self.player_sessions.get(&player_id)?, player_id)))
+            .collect();
+
+        let (seed, seed_source) = seed::provision_seed(new_seed, self.config.test_mode);
+        self.config.seed = seed;
+        self.seed_source = seed_source;
+        self.config.match_id = match_id::generate_match_id();
+        self.record_match_end_metadata();
+        let artifact = self.replay_recorder.rotate(
+            replay_config(&self.config),
+            self.world.state_digest(),
+            self.world.tick(),
+            end_reason.to_proto(),
+        );
+
+        self.world = World::new(self.config.seed, self.config.tick_rate_hz);
+        self.input_buffer = InputBuffer::new(*self.input_buffer.config());
+        self.entity_spawn_order.clear();
+        self.player_entity_mapping.clear();
+        self.player_sessions.clear();
+        self.session_players.clear();
+        self.last_known_intent.clear();
+        self.fallback_streak.clear();
+        self.last_emitted_floor.clear();
+        self.player_stats.clear();
+        self.replay_chunk_cache = None;
+        self.match_started = false;
+        self.session_left = false;
+        for (session_id, player_id) in roster {
+            let role = self.sessions[&session_id].role;
+            let entity_id = self.spawn_player(session_id, player_id, role);
+            let session = self
+                .sessions
+                .get_mut(&session_id)
+                .expect("roster session exists");
+            session.controlled_entity_id = entity_id;
+            session.begin_rematch();
+        }
+
+        let (baseline, welcomes) = self.start_match();
+        (artifact, baseline, welcomes)
+    }
+
     /// Check if match should end.
     pub fn should_end_match(&self) -> Option<EndReason> {
         if !self.match_started {
             return None;
         }
 
+        if self.has_disconnect() {
+            return Some(EndReason::Disconnect);
+        }
+
         // Check duration
         if self.world.tick() >= self.initial_tick + self.config.match_duration_ticks {
             return Some(EndReason::Complete);
@@ -306,27 +1066,80 @@ impl Server {
         None
     }
 
-    /// Handle session disconnect.
+    /// Handle session disconnect. Before match start the slot is freed and
+    /// the remaining players are reassigned (see `collect_stale_sessions`).
     pub fn disconnect_session(&mut self, session_id: SessionId) {
-        if let Some(session) = self.sessions.remove(&session_id) {
-            self.player_sessions.remove(&session.player_id);
-            self.session_players.remove(&session_id);
+        self.remove_session(session_id, LeaveReason::Disconnected);
+    }
+
+    /// Remove a session at the host's or an operator's request.
+    pub fn kick_session(&mut self, session_id: SessionId) {
+        self.remove_session(session_id, LeaveReason::Kicked);
+    }
+
+    /// Remove a session for `reason`. Once the match has started, every
+    /// remaining session is sent a `PlayerLeft` Control message; before
+    /// that, PlayerIds are not yet known to clients, so nothing is sent.
+    pub fn remove_session(&mut self, session_id: SessionId, reason: LeaveReason) {
+        let Some(session) = self.sessions.remove(&session_id) else {
+            return;
+        };
+        self.player_sessions.remove(&session.player_id);
+        self.session_players.remove(&session_id);
+        if !self.match_started {
+            self.rebuild_pre_match();
+            return;
+        }
+        self.session_left = true;
+        let left = PlayerLeft {
+            player_id: u32::from(session.player_id),
+            reason: reason as i32,
+            tick: self.world.tick(),
+        };
+        for &remaining in self.sessions.keys() {
+            self.control_outbox
+                .push((remaining, ControlMessage::PlayerLeft(left)));
         }
     }
 
-    /// Check if any session has disconnected.
+    /// Check if any session has left since the match started.
     pub fn has_disconnect(&self) -> bool {
-        // In v0, we check if we started with 2 and now have fewer
-        self.match_started && self.sessions.len() < 2
+        self.match_started && self.session_left
     }
 
     /// Receive and buffer an input from a client.
     /// Returns validation result.
+    ///
+    /// Drops are kept in the replay's input audit track when
+    /// `rejected_input_audit_limit` allows.
     pub fn receive_input(
         &mut self,
         session_id: SessionId,
         input: InputCmdProto,
     ) -> ValidationResult {
+        let receive_seq = self.inputs_received;
+        self.inputs_received += 1;
+        let result = self.validate_received(session_id, &input);
+        if !result.is_accepted() && self.config.rejected_input_audit_limit > 0 {
+            self.replay_recorder.record_rejected_input(RejectedInput {
+                receive_seq,
+                server_tick: self.world.tick(),
+                session_id: session_id.into(),
+                player_id: self.session_players.get(&session_id).map(|&p| p.into()),
+                input: Some(input),
+                reason: result.reason().to_string(),
+            });
+        }
+        result
+    }
+
+    fn validate_received(
+        &mut self,
+        session_id: SessionId,
+        input: &InputCmdProto,
+    ) -> ValidationResult {
+        self.touch_session(session_id);
+
         // Pre-Welcome input drop
         if !self.match_started {
             return ValidationResult::DroppedPreWelcome;
@@ -345,20 +1158,266 @@ impl Server {
             .unwrap_or(0);
 
         // Validate input
-        validate_input(
-            &input,
+        let result = validate_input(
+            input,
             self.world.tick(),
             floor,
             &mut self.input_buffer,
             player_id,
-        )
+        );
+
+        if let Some(stats) = self.player_stats.get_mut(&player_id) {
+            stats.record_input(&result, input.tick, self.world.tick());
+        }
+
+        let now = self.clock.now_micros();
+        if let Some(session) = self.sessions.get_mut(&session_id) {
+            session.network_stats_mut().record_input(&result, now);
+            session.input_rejections.record(&result, input.tick, floor);
+            match result {
+                ValidationResult::DroppedBelowFloor { .. } => session.below_floor_streak += 1,
+                ref accepted if accepted.is_accepted() => session.below_floor_streak = 0,
+                _ => {}
+            }
+            let resend_after = self.config.floor_resend_after_drops;
+            let tick = self.world.tick();
+            if resend_after > 0
+                && session.below_floor_streak >= resend_after
+                && session.last_floor_resend_tick != Some(tick)
+            {
+                session.below_floor_streak = 0;
+                session.last_floor_resend_tick = Some(tick);
+                self.control_outbox.push((
+                    session_id,
+                    ControlMessage::FloorUpdate(FloorUpdate {
+                        target_tick_floor: floor,
+                        server_tick: tick,
+                    }),
+                ));
+            }
+            if let [x, y] = input.move_dir[..]
+                && x.is_finite()
+                && y.is_finite()
+            {
+                for kind in session.anomaly_detector_mut().observe(now, [x, y]) {
+                    self.events.push(ServerEvent::InputAnomaly {
+                        session_id,
+                        player_id,
+                        tick: self.world.tick(),
+                        kind,
+                    });
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Receive a redundant input bundle from a client.
+    /// Returns one validation result per processed input, in bundle order.
+    ///
+    /// Copies whose (tick, input_seq) is already buffered for this session are
+    /// reported as `DroppedDuplicate` without touching the rate limiter or
+    /// InputSeq tie detection. Inputs beyond `MAX_INPUT_BUNDLE_LEN` are ignored.
+    pub fn receive_input_bundle(
+        &mut self,
+        session_id: SessionId,
+        bundle: InputBundle,
+    ) -> Vec<ValidationResult> {
+        self.touch_session(session_id);
+        bundle
+            .inputs
+            .into_iter()
+            .take(MAX_INPUT_BUNDLE_LEN)
+            .map(|input| {
+                let is_duplicate = self
+                    .session_players
+                    .get(&session_id)
+                    .is_some_and(|&player_id| self.input_buffer.is_duplicate(player_id, &input));
+                if self.match_started && is_duplicate {
+                    // Expected redundancy: counted in the receive order, not audited
+                    self.inputs_received += 1;
+                    let result = ValidationResult::DroppedDuplicate;
+                    if let Some(stats) = self
+                        .session_players
+                        .get(&session_id)
+                        .and_then(|player_id| self.player_stats.get_mut(player_id))
+                    {
+                        stats.record_input(&result, input.tick, self.world.tick());
+                    }
+                    result
+                } else {
+                    self.receive_input(session_id, input)
+                }
+            })
+            .collect()
+    }
+
+    /// Answer a TimeSync ping and update the session's RTT/offset estimate.
+    /// Returns `None` for unknown sessions.
+    ///
+    /// Valid before and during the match; never affects simulation state.
+    pub fn handle_time_sync(
+        &mut self,
+        session_id: SessionId,
+        ping: &TimeSyncPing,
+    ) -> Option<TimeSyncPong> {
+        let now = self.clock.now_micros();
+        let server_tick = self.world.tick();
+        let session = self.sessions.get_mut(&session_id)?;
+        session.last_activity_micros = now;
+
+        if let Some(sample) = time_sync::estimate(ping, server_tick, now) {
+            session.record_time_sync(sample);
+        }
+
+        Some(time_sync::build_pong(ping, server_tick, now))
+    }
+
+    /// Record a client's SnapshotAck. Returns `false` for unknown sessions,
+    /// for acks at or below the last acknowledged tick (duplicates or
+    /// reordering), and for ticks not yet simulated.
+    ///
+    /// Server Edge bookkeeping only; never affects simulation state.
+    pub fn receive_snapshot_ack(&mut self, session_id: SessionId, ack: &SnapshotAck) -> bool {
+        let now = self.clock.now_micros();
+        let current_tick = self.world.tick();
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return false;
+        };
+        session.last_activity_micros = now;
+        if ack.tick > current_tick
+            || session
+                .last_acked_snapshot_tick
+                .is_some_and(|last| ack.tick <= last)
+        {
+            return false;
+        }
+        let previous = session.last_acked_snapshot_tick.replace(ack.tick);
+        session
+            .network_stats_mut()
+            .record_snapshot_ack(previous, ack.tick);
+        true
+    }
+
+    /// Record a client's Heartbeat (either channel) as session activity.
+    /// Returns `false` for unknown sessions and for counters at or below the
+    /// last one received (duplicates or reordering), which do not count.
+    pub fn receive_heartbeat(&mut self, session_id: SessionId, heartbeat: &Heartbeat) -> bool {
+        let now = self.clock.now_micros();
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return false;
+        };
+        if session
+            .last_heartbeat
+            .is_some_and(|last| heartbeat.counter <= last)
+        {
+            return false;
+        }
+        session.last_heartbeat = Some(heartbeat.counter);
+        session.last_activity_micros = now;
+        true
+    }
+
+    /// Record a client's self-reported clock quality. Returns `false` for
+    /// unknown sessions.
+    ///
+    /// Diagnostics alongside the server's own `RttEstimator`; never affects
+    /// simulation state.
+    pub fn receive_time_sync_report(
+        &mut self,
+        session_id: SessionId,
+        report: &TimeSyncReport,
+    ) -> bool {
+        let now = self.clock.now_micros();
+        let tick = self.world.tick();
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return false;
+        };
+        session.client_time_sync = Some((*report, tick));
+        session.last_activity_micros = now;
+        true
+    }
+
+    /// Record a client's KeyframeRequest; the next snapshot built for the
+    /// session is a keyframe. Returns `false` for unknown sessions.
+    ///
+    /// v0 snapshots are always full, so every broadcast already satisfies the
+    /// request; the flag is what a delta encoder consults.
+    pub fn receive_keyframe_request(
+        &mut self,
+        session_id: SessionId,
+        _request: &KeyframeRequest,
+    ) -> bool {
+        let now = self.clock.now_micros();
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return false;
+        };
+        session.last_activity_micros = now;
+        session.keyframe_requested = true;
+        true
+    }
+
+    /// Receive a delta-encoded input with history. Expands it and validates
+    /// each input as in `receive_input_bundle`, keeping only the newest
+    /// `MAX_INPUT_BUNDLE_LEN`. Returns `None` if the deltas are malformed.
+    pub fn receive_redundant_input(
+        &mut self,
+        session_id: SessionId,
+        input: &RedundantInputCmd,
+    ) -> Option<Vec<ValidationResult>> {
+        let mut bundle = input.to_bundle()?;
+        let excess = bundle.inputs.len().saturating_sub(MAX_INPUT_BUNDLE_LEN);
+        bundle.inputs.drain(..excess);
+        Some(self.receive_input_bundle(session_id, bundle))
+    }
+
+    /// Validate a chat message and build the relay for all sessions.
+    /// The host sends the returned `ChatBroadcast` to every connected session.
+    pub fn receive_chat(
+        &mut self,
+        session_id: SessionId,
+        chat: &ChatSend,
+    ) -> Result<ChatBroadcast, ChatError> {
+        let now = self.clock.now_micros();
+        let limit = self.config.chat_rate_limit;
+        let session = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or(ChatError::UnknownSession)?;
+        session.last_activity_micros = now;
+        let text = chat::check_text(&chat.text)?;
+        if !session.chat_limiter.try_send(limit, now) {
+            return Err(ChatError::RateLimited);
+        }
+        let text = match &self.chat_filter {
+            Some(filter) => filter.filter(text).ok_or(ChatError::Blocked)?,
+            None => text.to_string(),
+        };
+        Ok(ChatBroadcast {
+            player_id: u32::from(session.player_id),
+            text,
+            server_tick: self.world.tick(),
+        })
+    }
+
+    /// Gameplay/netcode stats for a player.
+    pub fn player_stats(&self, player_id: PlayerId) -> Option<&PlayerStats> {
+        self.player_stats.get(&player_id)
+    }
+
+    /// Get a session by id.
+    pub fn session(&self, session_id: SessionId) -> Option<&Session> {
+        self.sessions.get(&session_id)
     }
 
     /// Process a single tick.
-    /// Returns (snapshot, target_tick_floor, serialized_snapshot_bytes).
+    /// Returns (snapshot, target_tick_floor, serialized snapshot payload).
     ///
-    /// The serialized bytes are identical for all sessions (T0.18).
-    pub fn step(&mut self) -> (Snapshot, Tick, Vec<u8>) {
+    /// Without AOI the payload is a single broadcast, identical for all
+    /// sessions (T0.18). With `aoi_radius` set, each session gets its own
+    /// filtered payload; the target tick floor is still shared.
+    pub fn step(&mut self) -> (Snapshot, Tick, SnapshotPayload) {
         let current_tick = self.world.tick();
 
         // Produce AppliedInput per player
@@ -378,17 +1437,34 @@ impl Server {
                     (move_dir, false)
                 })
                 .unwrap_or_else(|| {
-                    // LastKnownIntent fallback
+                    // LastKnownIntent fallback, decayed per `lki_decay`
                     let lki = self
                         .last_known_intent
                         .get(&player_id)
                         .copied()
                         .unwrap_or([0.0, 0.0]);
-                    (lki, true)
+                    let streak = self.fallback_streak.get(&player_id).copied().unwrap_or(0) + 1;
+                    (self.config.lki_decay.apply(lki, streak), true)
                 });
 
-            // Update last known intent
-            self.last_known_intent.insert(player_id, move_dir);
+            // Update last known intent (only received inputs reset it)
+            if is_fallback {
+                *self.fallback_streak.entry(player_id).or_insert(0) += 1;
+            } else {
+                self.last_known_intent.insert(player_id, move_dir);
+                self.fallback_streak.remove(&player_id);
+            }
+            if let Some(stats) = self.player_stats.get_mut(&player_id) {
+                stats.record_tick(is_fallback);
+            }
+
+            if let Some(session) = self
+                .player_sessions
+                .get(&player_id)
+                .and_then(|sid| self.sessions.get_mut(sid))
+            {
+                session.network_stats_mut().record_tick(is_fallback);
+            }
 
             applied_inputs.push(AppliedInput {
                 tick: current_tick,
@@ -413,6 +1489,35 @@ impl Server {
         // Advance world
         let snapshot = self.world.advance(current_tick, &step_inputs);
 
+        let interval = self.config.live_digest_interval_ticks;
+        if interval > 0 && (snapshot.tick - self.initial_tick).is_multiple_of(interval) {
+            self.replay_recorder
+                .record_digest(snapshot.tick, snapshot.digest);
+        }
+
+        let interval = self.config.snapshot_anchor_interval_ticks;
+        if interval > 0 && (snapshot.tick - self.initial_tick).is_multiple_of(interval) {
+            self.replay_recorder
+                .record_snapshot_anchor(self.world.baseline());
+        }
+
+        let interval = self.config.checkpoint_interval_ticks;
+        if let Some(path) = &self.checkpoint_path
+            && interval > 0
+            && (snapshot.tick - self.initial_tick).is_multiple_of(interval)
+            && let Err(e) = self
+                .checkpoint()
+                .and_then(|checkpoint| checkpoint::write_checkpoint(&checkpoint, path))
+        {
+            self.events.push(ServerEvent::CheckpointFailed {
+                tick: snapshot.tick,
+                error: e.to_string(),
+            });
+        }
+
+        self.observe_movement();
+        self.record_positions();
+
         // Compute new target tick floor (post-step tick + lead)
         let target_tick_floor = self.world.tick() + self.config.input_lead_ticks;
 
@@ -422,8 +1527,33 @@ impl Server {
                 .insert(*session_id, target_tick_floor);
         }
 
-        // Evict old buffered inputs
-        self.input_buffer.evict_before(self.world.tick());
+        let interval = self.config.input_rejection_report_interval_ticks;
+        if interval > 0 && (snapshot.tick - self.initial_tick).is_multiple_of(interval) {
+            for session in self.sessions.values_mut() {
+                if let Some(report) = session.input_rejections.take_report(snapshot.tick) {
+                    self.control_outbox
+                        .push((session.id, ControlMessage::InputRejectionReport(report)));
+                }
+            }
+        }
+
+        let interval = self.config.input_timing_report_interval_ticks;
+        if interval > 0 && (snapshot.tick - self.initial_tick).is_multiple_of(interval) {
+            for session in self.sessions.values_mut() {
+                if session.role != SessionRole::Player {
+                    continue;
+                }
+                let report = session
+                    .network_stats_mut()
+                    .take_input_timing_report(snapshot.tick, target_tick_floor);
+                self.control_outbox
+                    .push((session.id, ControlMessage::InputTimingReport(report)));
+            }
+        }
+
+        // Evict old buffered inputs
+        self.input_buffer.evict_before(self.world.tick());
+        self.input_buffer.refill_burst_tokens();
 
         // Serialize snapshot (identical for all sessions - T0.18)
         let snapshot_proto = SnapshotProto {
@@ -431,36 +1561,571 @@ impl Server {
             entities: snapshot
                 .entities
                 .iter()
-                .map(|e| flowstate_wire::EntitySnapshotProto {
-                    entity_id: e.entity_id,
-                    position: e.position.to_vec(),
-                    velocity: e.velocity.to_vec(),
-                })
+                .map(|e| self.entity_proto(e))
                 .collect(),
             digest: snapshot.digest,
             target_tick_floor,
+            truncated: false,
+            server_time_micros: self.clock.now_micros(),
+        };
+        let (removed, spawned) = self
+            .entity_lifecycle
+            .update(snapshot.tick, &snapshot_proto.entities);
+        let announcements = removed
+            .into_iter()
+            .map(ControlMessage::EntityRemoved)
+            .chain(spawned.into_iter().map(ControlMessage::EntitySpawned));
+        for message in announcements {
+            for &session_id in self.sessions.keys() {
+                self.control_outbox.push((session_id, message.clone()));
+            }
+        }
+        let max_bytes = self.config.snapshot_max_bytes;
+        let oversized =
+            max_bytes.is_some_and(|max| prost::Message::encoded_len(&snapshot_proto) > max);
+        let payload = if self.config.aoi_radius.is_none() && !oversized {
+            SnapshotPayload::Broadcast(prost::Message::encode_to_vec(&snapshot_proto))
+        } else {
+            SnapshotPayload::PerSession(
+                self.sessions
+                    .values()
+                    .map(|session| {
+                        let mut view = match self.config.aoi_radius {
+                            Some(radius) => aoi::filter_for_session(
+                                &self.world,
+                                &snapshot_proto,
+                                session.controlled_entity_id,
+                                radius,
+                            ),
+                            None => snapshot_proto.clone(),
+                        };
+                        if let Some(max) = max_bytes {
+                            view = aoi::fit_to_size(&view, session.controlled_entity_id, max);
+                        }
+                        (session.id, prost::Message::encode_to_vec(&view))
+                    })
+                    .collect(),
+            )
+        };
+        // Every payload above is a full snapshot (keyframe)
+        for session in self.sessions.values_mut() {
+            session.keyframe_requested = false;
+        }
+
+        (snapshot, target_tick_floor, payload)
+    }
+
+    /// Take all queued Control messages, oldest first.
+    pub fn drain_control(&mut self) -> Vec<(SessionId, ControlMessage)> {
+        std::mem::take(&mut self.control_outbox)
+    }
+
+    /// Take all queued Server Edge events, oldest first.
+    pub fn drain_events(&mut self) -> Vec<ServerEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Feed post-step positions to each session's movement check.
+    fn observe_movement(&mut self) {
+        let tick = self.world.tick();
+        let max_displacement = anticheat::max_displacement_per_tick(self.config.tick_rate_hz);
+        let mut sessions: Vec<&mut Session> = self.sessions.values_mut().collect();
+        sessions.sort_by_key(|s| s.id);
+        for session in sessions {
+            let Some(position) = self.world.entity_position(session.controlled_entity_id) else {
+                continue;
+            };
+            if session
+                .movement_check_mut()
+                .observe(tick, position, max_displacement)
+            {
+                let check = session.movement_check();
+                self.events.push(ServerEvent::MovementFlagged {
+                    session_id: session.id,
+                    player_id: session.player_id,
+                    tick,
+                    total_violations: check.total_violations(),
+                    max_ratio: check.max_ratio(),
+                });
+            }
+        }
+    }
+
+    /// Feed post-step positions to each player's stats.
+    fn record_positions(&mut self) {
+        for (player_id, stats) in &mut self.player_stats {
+            let entity_id = self.player_entity_mapping[player_id];
+            if let Some(position) = self.world.entity_position(entity_id) {
+                stats.record_position(position);
+            }
+        }
+    }
+
+    /// Capture World state and recorder progress at the current tick.
+    /// Fails only when spilled replay inputs cannot be read back.
+    pub fn checkpoint(&self) -> std::io::Result<MatchCheckpoint> {
+        let state = self.world.baseline();
+        Ok(MatchCheckpoint {
+            replay: Some(self.replay_recorder.partial_artifact(
+                state.digest,
+                state.tick,
+                MatchEndReason::Checkpoint,
+            )?),
+            state: Some(state.into()),
+            match_id: self.config.match_id.clone(),
+        })
+    }
+
+    /// Answer a desynced client's BaselineRequest with the current state
+    /// (see `resync`). Raises `ServerEvent::ClientDesync` when served.
+    pub fn baseline_update(
+        &mut self,
+        session_id: SessionId,
+        request: &BaselineRequest,
+    ) -> Result<BaselineUpdate, BaselineRequestError> {
+        let now = self.clock.now_micros();
+        let tick = self.world.tick();
+        let min_interval = self.config.baseline_request_min_interval_ticks;
+        let session = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or(BaselineRequestError::UnknownSession)?;
+        session.last_activity_micros = now;
+        if !self.match_started {
+            return Err(BaselineRequestError::MatchNotStarted);
+        }
+        if let Some(last) = session.last_baseline_tick {
+            let next = last.saturating_add(min_interval);
+            if tick < next {
+                return Err(BaselineRequestError::TooFrequent {
+                    retry_after_ticks: next - tick,
+                });
+            }
+        }
+        session.last_baseline_tick = Some(tick);
+        self.events.push(ServerEvent::ClientDesync {
+            session_id,
+            player_id: session.player_id,
+            tick,
+            mismatch_tick: request.mismatch_tick,
+        });
+        Ok(BaselineUpdate {
+            baseline: Some(self.baseline_proto()),
+            target_tick_floor: tick + self.config.input_lead_ticks,
+        })
+    }
+
+    /// Serve a chunk of the running match's replay to `session_id`, encoded
+    /// as of the current tick (see `replay_chunks`). The encoding is reused
+    /// for every request within a tick, so audit entries recorded since it
+    /// was built appear from the next tick.
+    pub fn replay_chunk(
+        &mut self,
+        session_id: SessionId,
+        request: &ReplayChunkRequest,
+    ) -> Result<ReplayChunk, ReplayChunkError> {
+        let now = self.clock.now_micros();
+        let max_per_sec = self.config.replay_chunk_requests_per_sec;
+        let session = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or(ReplayChunkError::UnknownSession)?;
+        session.last_activity_micros = now;
+        if !session.replay_chunk_limiter.try_request(max_per_sec, now) {
+            return Err(ReplayChunkError::RateLimited);
+        }
+        if !request.match_id.is_empty() && request.match_id != self.config.match_id {
+            return Err(ReplayChunkError::UnknownMatch);
+        }
+        if !self.config.serve_in_progress_replays {
+            return Err(ReplayChunkError::InProgressNotPermitted);
+        }
+        let tick = self.world.tick();
+        let cached = match self.replay_chunk_cache.take() {
+            Some(cached) if cached.tick == tick => cached,
+            _ => {
+                let artifact = self
+                    .replay_recorder
+                    .partial_artifact(self.world.state_digest(), tick, MatchEndReason::InProgress)
+                    .map_err(|_| ReplayChunkError::ReadFailed)?;
+                EncodedArtifact::new(prost::Message::encode_to_vec(&artifact), tick)
+            }
+        };
+        let chunk = replay_chunks::chunk(&cached, &self.config.match_id, false, request);
+        self.replay_chunk_cache = Some(cached);
+        chunk
+    }
+
+    /// Rebuild a running match from a checkpoint.
+    ///
+    /// The match resumes at the checkpoint tick with no sessions; reconnecting
+    /// clients are bound with `reclaim_player`. Until then, players' last
+    /// applied intent continues (LastKnownIntent).
+    pub fn resume(
+        mut config: ServerConfig,
+        checkpoint: &MatchCheckpoint,
+    ) -> Result<Self, CheckpointError> {
+        let state = checkpoint
+            .state
+            .clone()
+            .ok_or(CheckpointError::MissingState)?;
+        let state = Baseline::try_from(state).map_err(|e| CheckpointError::InvalidFormat {
+            reason: e.to_string(),
+        })?;
+        let replay = checkpoint
+            .replay
+            .as_ref()
+            .ok_or(CheckpointError::MissingReplay)?;
+        // 0 means "unset": take the checkpoint's seed rather than drawing anew
+        if config.seed == 0 {
+            config.seed = replay.seed;
+        }
+        if replay.seed != config.seed || replay.tick_rate_hz != config.tick_rate_hz {
+            return Err(CheckpointError::ConfigMismatch {
+                reason: format!(
+                    "checkpoint seed {} at {} Hz, config seed {} at {} Hz",
+                    replay.seed, replay.tick_rate_hz, config.seed, config.tick_rate_hz
+                ),
+            });
+        }
+        if replay.checkpoint_tick != state.tick {
+            return Err(CheckpointError::TickMismatch {
+                state: state.tick,
+                replay: replay.checkpoint_tick,
+            });
+        }
+        let initial_tick = replay
+            .initial_baseline
+            .as_ref()
+            .map(|b| b.tick)
+            .ok_or(CheckpointError::MissingReplay)?;
+
+        // A resumed match keeps its MatchId unless the host overrides it
+        if config.match_id.is_empty() {
+            config.match_id = checkpoint.match_id.clone();
+        }
+        let mut server = Self::new(config);
+        let recorder =
+            ReplayRecorder::resume(replay_config(&server.config), replay).map_err(|e| {
+                CheckpointError::InvalidFormat {
+                    reason: e.to_string(),
+                }
+            })?;
+
+        let player_id = |player_id: u32| {
+            PlayerId::try_from(player_id).map_err(|_| CheckpointError::InvalidFormat {
+                reason: format!("player_id {player_id} out of range"),
+            })
         };
-        let snapshot_bytes = prost::Message::encode_to_vec(&snapshot_proto);
+        let spawns = replay
+            .player_entity_mapping
+            .iter()
+            .map(|m| Ok((player_id(m.player_id)?, EntityId(m.entity_id))))
+            .collect::<Result<Vec<_>, CheckpointError>>()?;
+        let world = World::restore(
+            server.config.seed,
+            server.config.tick_rate_hz,
+            server.world.tuning(),
+            &state,
+            &spawns,
+        )
+        .ok_or_else(|| CheckpointError::InvalidFormat {
+            reason: "entity without player mapping".to_string(),
+        })?;
+        if world.state_digest() != state.digest {
+            return Err(CheckpointError::DigestMismatch {
+                expected: state.digest,
+                actual: world.state_digest(),
+            });
+        }
+
+        server.world = world;
+        server.replay_recorder = recorder;
+        server.initial_tick = initial_tick;
+        server.match_started = true;
+        server
+            .entity_lifecycle
+            .reset(server.world.baseline().entities.iter().map(|e| e.entity_id));
+        server.entity_spawn_order = replay
+            .entity_spawn_order
+            .iter()
+            .map(|&p| player_id(p))
+            .collect::<Result<_, _>>()?;
+        for &(player_id, entity_id) in &spawns {
+            server.player_entity_mapping.insert(player_id, entity_id);
+            server.player_stats.insert(player_id, PlayerStats::new());
+            server
+                .input_buffer
+                .set_rate_limit(player_id, server.config.rate_limit_for(SessionRole::Player));
+            // LKI is the last received input; trailing fallbacks are the streak
+            let history: Vec<_> = replay
+                .inputs
+                .iter()
+                .rev()
+                .filter(|i| i.player_id == u32::from(player_id))
+                .collect();
+            let streak = history.iter().take_while(|i| i.is_fallback).count() as u64;
+            let lki = history
+                .iter()
+                .find(|i| !i.is_fallback && i.move_dir.len() == 2)
+                .map_or([0.0, 0.0], |i| [i.move_dir[0], i.move_dir[1]]);
+            server.last_known_intent.insert(player_id, lki);
+            if streak > 0 {
+                server.fallback_streak.insert(player_id, streak);
+            }
+        }
+        for mapping in &replay.player_entity_mapping {
+            if let Some(team) = mapping.team_id {
+                server
+                    .world
+                    .set_team(player_id(mapping.player_id)?, team as TeamId);
+            }
+        }
+        server.record_positions();
+        Ok(server)
+    }
+
+    /// Bind a new session to a player that has none (reconnect after `resume`).
+    /// Returns `None` if the player is unknown or already has a session.
+    pub fn reclaim_player(
+        &mut self,
+        player_id: PlayerId,
+    ) -> Option<(SessionId, EntityId, ServerWelcome)> {
+        let &entity_id = self.player_entity_mapping.get(&player_id)?;
+        if self.player_sessions.contains_key(&player_id) {
+            return None;
+        }
+        let session_id = self.next_session_id;
+        self.next_session_id.0 += 1;
+        let mut session = Session::new(session_id, player_id, entity_id);
+        session.last_activity_micros = self.clock.now_micros();
+        self.sessions.insert(session_id, session);
+        self.player_sessions.insert(player_id, session_id);
+        self.session_players.insert(session_id, player_id);
 
-        (snapshot, target_tick_floor, snapshot_bytes)
+        let target_tick_floor = self.world.tick() + self.config.input_lead_ticks;
+        self.last_emitted_floor
+            .insert(session_id, target_tick_floor);
+        let welcome = ServerWelcome {
+            target_tick_floor,
+            tick_rate_hz: self.config.tick_rate_hz,
+            player_id: u32::from(player_id),
+            controlled_entity_id: entity_id.into(),
+            match_id: self.config.match_id.clone(),
+            team_id: self.world.team_of(player_id).map(u32::from),
+            schema_hash: flowstate_wire::SCHEMA_HASH,
+            capabilities: 0,
+            compression: None,
+        };
+        Some((session_id, entity_id, welcome))
     }
 
-    /// Finalize the match and produce a replay artifact.
-    pub fn finalize(self, end_reason: EndReason) -> ReplayArtifact {
+    /// Finalize the match and produce a replay artifact. Fails only when
+    /// spilled replay inputs cannot be read back (see
+    /// `set_replay_spill_path`).
+    pub fn finalize(mut self, end_reason: EndReason) -> std::io::Result<ReplayArtifact> {
+        self.record_match_end_metadata();
         let final_digest = self.world.state_digest();
         let checkpoint_tick = self.world.tick();
 
         self.replay_recorder
-            .finalize(final_digest, checkpoint_tick, end_reason.as_str())
+            .finalize(final_digest, checkpoint_tick, end_reason.to_proto())
+    }
+
+    /// Replay metadata with the current map and display names.
+    fn replay_metadata(&self, start_unix_ms: u64, end_unix_ms: u64) -> ReplayMetadata {
+        ReplayMetadata {
+            start_unix_ms,
+            end_unix_ms,
+            map_id: self.config.map_id.clone(),
+            players: self
+                .player_roster()
+                .players
+                .into_iter()
+                .map(|p| PlayerDisplayName {
+                    player_id: p.player_id,
+                    display_name: p.display_name,
+                })
+                .collect(),
+        }
+    }
+
+    /// Stamp the replay metadata with the end time and final display names.
+    fn record_match_end_metadata(&mut self) {
+        let start_unix_ms = self
+            .replay_recorder
+            .metadata()
+            .map_or(0, |m| m.start_unix_ms);
+        let metadata = self.replay_metadata(start_unix_ms, self.clock.unix_millis());
+        self.replay_recorder.set_metadata(metadata);
+    }
+
+    /// Queue a `MatchEnd` for every session. Call just before finalizing,
+    /// then drain and send the control outbox.
+    pub fn announce_match_end(&mut self, end_reason: EndReason) {
+        let results = self
+            .entity_spawn_order
+            .iter()
+            .map(|&player_id| PlayerResult {
+                player_id: u32::from(player_id),
+                team_id: self.world.team_of(player_id).map(u32::from),
+                distance_moved: self
+                    .player_stats
+                    .get(&player_id)
+                    .map_or(0.0, |stats| stats.distance_moved()),
+            })
+            .collect();
+        let match_end = MatchEnd {
+            end_reason: end_reason.to_proto() as i32,
+            end_reason_detail: String::new(),
+            final_tick: self.world.tick(),
+            final_digest: self.world.state_digest(),
+            match_id: self.config.match_id.clone(),
+            results,
+        };
+        for &session_id in self.sessions.keys() {
+            self.control_outbox
+                .push((session_id, ControlMessage::MatchEnd(match_end.clone())));
+        }
+    }
+
+    /// Finalize the match, producing the replay artifact (see `finalize`)
+    /// and its summary.
+    pub fn finalize_with_summary(
+        self,
+        end_reason: EndReason,
+    ) -> (std::io::Result<ReplayArtifact>, MatchSummary) {
+        let summary = self.match_summary(end_reason);
+        (self.finalize(end_reason), summary)
+    }
+
+    /// Headline summary of the match so far.
+    pub fn match_summary(&self, end_reason: EndReason) -> MatchSummary {
+        let end_tick = self.world.tick();
+        let players = self
+            .entity_spawn_order
+            .iter()
+            .map(|&player_id| {
+                let entity_id = self.player_entity_mapping[&player_id];
+                let session = self
+                    .player_sessions
+                    .get(&player_id)
+                    .and_then(|sid| self.sessions.get(sid));
+                let stats = self
+                    .player_stats
+                    .get(&player_id)
+                    .cloned()
+                    .unwrap_or_default();
+                PlayerSummary {
+                    player_id,
+                    entity_id,
+                    identity: session.and_then(|s| s.identity.clone()),
+                    final_position: self.world.entity_position(entity_id),
+                    distance_moved: stats.distance_moved(),
+                    inputs_received: stats.inputs_received(),
+                    inputs_accepted: stats.inputs_accepted(),
+                    inputs_dropped: stats
+                        .drops_by_reason()
+                        .iter()
+                        .map(|(reason, &count)| (reason.to_string(), count))
+                        .collect(),
+                    fallback_ticks: stats.fallback_ticks(),
+                    average_input_lead_ticks: stats.average_input_lead_ticks(),
+                    movement_flagged: session.is_some_and(|s| s.movement_check().is_flagged()),
+                    input_anomalies: session
+                        .map(|s| {
+                            s.anomaly_detector()
+                                .raised()
+                                .iter()
+                                .map(|k| k.as_str().to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    network: session.map(|s| NetworkSummary::from(s.network_stats())),
+                }
+            })
+            .collect();
+
+        MatchSummary {
+            summary_version: summary::MATCH_SUMMARY_VERSION,
+            match_id: self.config.match_id.clone(),
+            seed: self.config.seed,
+            seed_source: self.seed_source,
+            tick_rate_hz: self.config.tick_rate_hz,
+            start_tick: self.initial_tick,
+            end_tick,
+            duration_ticks: end_tick - self.initial_tick,
+            end_reason: end_reason.as_str().to_string(),
+            final_digest: summary::format_digest(self.world.state_digest()),
+            replay_path: None,
+            players,
+        }
     }
 
-    /// Get the baseline for JoinBaseline message.
+    /// Get the baseline for JoinBaseline message, with entity metadata.
     pub fn baseline_proto(&self) -> JoinBaseline {
         let baseline = self.world.baseline();
-        baseline.into()
+        JoinBaseline {
+            tick: baseline.tick,
+            entities: baseline
+                .entities
+                .iter()
+                .map(|e| self.entity_proto(e))
+                .collect(),
+            digest: baseline.digest,
+        }
+    }
+
+    /// Authoritative match parameters for the MatchConfig message, sent
+    /// before the JoinBaseline so clients predict with the server's values.
+    pub fn match_config(&self) -> MatchConfig {
+        MatchConfig {
+            tick_rate_hz: self.config.tick_rate_hz,
+            match_duration_ticks: self.config.match_duration_ticks,
+            map_id: self.config.map_id.clone(),
+            tuning_parameters: replay_config(&self.config).tuning_parameters(),
+        }
+    }
+
+    /// Wire form of a sim entity, tagged with its kind and owner.
+    ///
+    /// Presentation metadata held by the Server Edge; the Simulation Core and
+    /// StateDigest never see it. Every v0 entity is a Character.
+    fn entity_proto(&self, entity: &flowstate_sim::EntitySnapshot) -> EntitySnapshotProto {
+        let owner = self
+            .player_entity_mapping
+            .iter()
+            .find(|&(_, &entity_id)| entity_id == entity.entity_id)
+            .map(|(&player_id, _)| u32::from(player_id));
+        EntitySnapshotProto {
+            entity_kind: EntityKind::Character as i32,
+            owner_player_id: owner,
+            ..entity.clone().into()
+        }
+    }
+
+    /// Set the display name and cosmetics announced for `player_id`.
+    /// Takes effect in the next `player_roster`.
+    pub fn set_player_profile(&mut self, player_id: PlayerId, profile: PlayerProfile) {
+        self.player_profiles.insert(player_id, profile);
+    }
+
+    /// Display name and cosmetics of every spawned player, for the host to
+    /// send after the JoinBaseline at match start and after `reclaim_player`
+    /// (or a mid-match rebind).
+    pub fn player_roster(&self) -> PlayerRoster {
+        PlayerRoster {
+            players: self
+                .player_entity_mapping
+                .iter()
+                .map(|(&player_id, &entity_id)| {
+                    roster::player_info(player_id, entity_id, self.player_profiles.get(&player_id))
+                })
+                .collect(),
+        }
     }
 
-    /// Get all connected session IDs.
+    /// Get all connected session IDs, ascending.
     pub fn session_ids(&self) -> Vec<SessionId> {
         self.sessions.keys().copied().collect()
     }
@@ -481,14 +2146,14 @@ mod tests {
 
         // Accept first session
         let (session1, player1, entity1) = server.accept_session();
-        assert_eq!(player1, 0);
-        assert!(entity1 > 0);
+        assert_eq!(player1, PlayerId(0));
+        assert!(entity1 > EntityId(0));
         assert_eq!(server.session_count(), 1);
 
         // Accept second session
         let (_session2, player2, entity2) = server.accept_session();
-        assert_eq!(player2, 1);
-        assert!(entity2 > 0);
+        assert_eq!(player2, PlayerId(1));
+        assert!(entity2 > EntityId(0));
         assert_ne!(entity1, entity2);
         assert_eq!(server.session_count(), 2);
 
@@ -503,10 +2168,10 @@ mod tests {
             assert_eq!(welcome.tick_rate_hz, TICK_RATE_HZ);
             if *sid == session1 {
                 assert_eq!(welcome.player_id, 0);
-                assert_eq!(welcome.controlled_entity_id, entity1);
+                assert_eq!(welcome.controlled_entity_id, u64::from(entity1));
             } else {
                 assert_eq!(welcome.player_id, 1);
-                assert_eq!(welcome.controlled_entity_id, entity2);
+                assert_eq!(welcome.controlled_entity_id, u64::from(entity2));
             }
         }
     }
@@ -526,6 +2191,72 @@ mod tests {
         assert!(baseline.digest != 0);
     }
 
+    /// Wire entities carry kind and owner; the digest does not depend on them.
+    #[test]
+    fn test_entity_metadata_on_wire() {
+        let mut server = Server::new(ServerConfig::default());
+        let (_, player1, entity1) = server.accept_session();
+        let (_, player2, entity2) = server.accept_session();
+        let (baseline, _) = server.start_match();
+
+        let proto = server.baseline_proto();
+        assert_eq!(proto.digest, baseline.digest);
+        let (_, _, payload) = server.step();
+        let SnapshotPayload::Broadcast(bytes) = payload else {
+            panic!("broadcast without AOI");
+        };
+        let snapshot: SnapshotProto = prost::Message::decode(bytes.as_slice()).unwrap();
+        for entities in [&proto.entities, &snapshot.entities] {
+            let metadata: Vec<_> = entities
+                .iter()
+                .map(|e| (EntityId(e.entity_id), e.entity_kind(), e.owner_player_id))
+                .collect();
+            assert_eq!(
+                metadata,
+                vec![
+                    (entity1, EntityKind::Character, Some(u32::from(player1))),
+                    (entity2, EntityKind::Character, Some(u32::from(player2))),
+                ]
+            );
+        }
+    }
+
+    /// The roster maps every player to its Character and announced name.
+    #[test]
+    fn test_player_roster() {
+        let mut server = Server::new(ServerConfig::default());
+        let (_, player1, entity1) = server.accept_session();
+        let (_, player2, entity2) = server.accept_session();
+        server.set_player_profile(
+            player2,
+            PlayerProfile {
+                display_name: "Ada".to_string(),
+                ..Default::default()
+            },
+        );
+        let (baseline, _) = server.start_match();
+
+        let roster: Vec<_> = server
+            .player_roster()
+            .players
+            .into_iter()
+            .map(|p| (p.player_id, p.entity_id, p.display_name))
+            .collect();
+        assert_eq!(
+            roster,
+            vec![
+                (
+                    u32::from(player1),
+                    u64::from(entity1),
+                    format!("Player {player1}")
+                ),
+                (u32::from(player2), u64::from(entity2), "Ada".to_string()),
+            ]
+        );
+        // Presentation only
+        assert_eq!(server.baseline_proto().digest, baseline.digest);
+    }
+
     /// T0.5a: Tick/floor relationship assertion.
     #[test]
     fn test_t0_05a_tick_floor_relationship() {
@@ -561,6 +2292,67 @@ mod tests {
 
         assert!(server.has_disconnect());
         assert_eq!(server.session_count(), 1);
+        assert_eq!(server.should_end_match(), Some(EndReason::Disconnect));
+    }
+
+    /// Remaining sessions are told who left and why.
+    #[test]
+    fn test_player_left_broadcast() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, player1, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+        server.step();
+
+        server.kick_session(session1);
+        let expected = PlayerLeft {
+            player_id: u32::from(player1),
+            reason: LeaveReason::Kicked as i32,
+            tick: 1,
+        };
+        assert_eq!(
+            server.drain_control(),
+            vec![(session2, ControlMessage::PlayerLeft(expected))]
+        );
+        server.kick_session(session1);
+        assert!(server.drain_control().is_empty());
+    }
+
+    #[test]
+    fn test_match_end_matches_replay() {
+        let mut server = Server::new(ServerConfig {
+            match_id: "m-1".to_string(),
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+        for _ in 0..3 {
+            server.step();
+        }
+
+        server.announce_match_end(EndReason::Complete);
+        let outbox = server.drain_control();
+        assert_eq!(
+            outbox.iter().map(|(s, _)| *s).collect::<Vec<_>>(),
+            vec![session1, session2]
+        );
+        let ControlMessage::MatchEnd(match_end) = outbox[0].1.clone() else {
+            panic!("expected MatchEnd");
+        };
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        assert_eq!(match_end.end_reason, artifact.end_reason);
+        assert_eq!(match_end.final_tick, artifact.checkpoint_tick);
+        assert_eq!(match_end.final_digest, artifact.final_digest);
+        assert_eq!(match_end.match_id, "m-1");
+        assert_eq!(
+            match_end
+                .results
+                .iter()
+                .map(|r| r.player_id)
+                .collect::<Vec<_>>(),
+            artifact.entity_spawn_order
+        );
     }
 
     /// T0.15: Match termination.
@@ -589,7 +2381,7 @@ mod tests {
     fn test_t0_17_playerid_test_mode() {
         let config = ServerConfig {
             test_mode: true,
-            test_player_ids: Some((17, 99)),
+            test_player_ids: Some((PlayerId(17), PlayerId(99))),
             match_duration_ticks: 10,
             ..Default::default()
         };
@@ -598,8 +2390,8 @@ mod tests {
         let (_, player1, _) = server.accept_session();
         let (_, player2, _) = server.accept_session();
 
-        assert_eq!(player1, 17);
-        assert_eq!(player2, 99);
+        assert_eq!(player1, PlayerId(17));
+        assert_eq!(player2, PlayerId(99));
 
         server.start_match();
 
@@ -609,7 +2401,7 @@ mod tests {
         }
 
         // Finalize and check artifact
-        let artifact = server.finalize(EndReason::Complete);
+        let artifact = server.finalize(EndReason::Complete).unwrap();
         assert!(artifact.test_mode);
         assert_eq!(artifact.test_player_ids, vec![17, 99]);
         assert_eq!(artifact.entity_spawn_order, vec![17, 99]);
@@ -624,15 +2416,21 @@ mod tests {
         server.start_match();
 
         // Step and get serialized snapshot
-        let (_, floor1, bytes1) = server.step();
+        let (_, floor1, payload1) = server.step();
 
         // The bytes would be sent to all sessions identically
+        let SnapshotPayload::Broadcast(bytes1) = payload1 else {
+            panic!("AOI disabled: expected a single broadcast payload");
+        };
         // Decode to verify floor is consistent
         let decoded: SnapshotProto = prost::Message::decode(bytes1.as_slice()).unwrap();
         assert_eq!(decoded.target_tick_floor, floor1);
 
         // Step again
-        let (_, floor2, bytes2) = server.step();
+        let (_, floor2, payload2) = server.step();
+        let SnapshotPayload::Broadcast(bytes2) = payload2 else {
+            panic!("AOI disabled: expected a single broadcast payload");
+        };
         let decoded2: SnapshotProto = prost::Message::decode(bytes2.as_slice()).unwrap();
         assert_eq!(decoded2.target_tick_floor, floor2);
         assert!(floor2 > floor1, "Floor should be monotonic increasing");
@@ -659,7 +2457,7 @@ mod tests {
         }
 
         // Now finalize and verify artifact has fallback inputs
-        let artifact = server.finalize(EndReason::Complete);
+        let artifact = server.finalize(EndReason::Complete).unwrap();
 
         // All inputs should be fallback since we didn't send any
         assert!(artifact.inputs.iter().all(|i| i.is_fallback));
@@ -682,13 +2480,16 @@ mod tests {
             server.step();
         }
 
-        let artifact = server.finalize(EndReason::Complete);
+        let artifact = server.finalize(EndReason::Complete).unwrap();
 
-        assert_eq!(artifact.replay_format_version, 1);
+        assert_eq!(
+            artifact.replay_format_version,
+            flowstate_replay::REPLAY_FORMAT_VERSION
+        );
         assert!(artifact.initial_baseline.is_some());
         assert_eq!(artifact.tick_rate_hz, 60);
         assert_eq!(artifact.checkpoint_tick, 5);
-        assert_eq!(artifact.end_reason, "complete");
+        assert_eq!(artifact.end_reason(), MatchEndReason::Complete);
         // 5 ticks * 2 players = 10 inputs
         assert_eq!(artifact.inputs.len(), 10);
     }
@@ -782,4 +2583,2017 @@ mod tests {
         // If that condition is true, orchestrator would exit with non-zero.
         // The server exposes enough state for this check.
     }
+
+    #[test]
+    fn test_stale_pre_match_session_collected() {
+        let clock = clock::ManualClock::new(0);
+        let mut server = Server::new(ServerConfig::default());
+        server.set_clock(Box::new(clock.clone()));
+        let (silent, _, _) = server.accept_session();
+        clock.advance(5_000_000);
+        let (active, _, _) = server.accept_session();
+        assert_eq!(server.session(active).unwrap().player_id, PlayerId(1));
+
+        clock.advance(PRE_MATCH_IDLE_TIMEOUT_MS * 1_000 - 5_000_000);
+        assert!(server.collect_stale_sessions().is_empty());
+        clock.advance(5_000_000);
+        assert_eq!(server.collect_stale_sessions(), vec![silent]);
+        assert!(matches!(
+            server.drain_events().as_slice(),
+            [ServerEvent::SessionExpired { session_id, player_id: PlayerId(0), .. }] if *session_id == silent
+        ));
+
+        // The survivor takes slot 0; the freed slot goes to the next session
+        assert_eq!(server.session_count(), 1);
+        assert_eq!(server.session(active).unwrap().player_id, PlayerId(0));
+        let (_, late_player, _) = server.accept_session();
+        assert_eq!(late_player, PlayerId(1));
+        assert!(server.collect_stale_sessions().is_empty());
+
+        let (_, welcomes) = server.start_match();
+        assert_eq!(welcomes.len(), 2);
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        assert_eq!(artifact.entity_spawn_order, vec![0, 1]);
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(flowstate_replay::verify_replay(&artifact, &options).is_ok());
+    }
+
+    /// AOI: per-session payloads only carry relevant entities.
+    #[test]
+    fn test_aoi_per_session_payloads() {
+        let config = ServerConfig {
+            aoi_radius: Some(1.0),
+            ..Default::default()
+        };
+        let mut server = Server::new(config);
+        let (session1, _, entity1) = server.accept_session();
+        let (session2, _, entity2) = server.accept_session();
+        server.start_match();
+
+        // Player 0 walks away along +x; player 1 stays at the origin
+        for _ in 0..60 {
+            let tick = server.current_tick();
+            let floor = tick + INPUT_LEAD_TICKS;
+            server.receive_input(
+                session1,
+                InputCmdProto {
+                    tick: floor,
+                    input_seq: floor,
+                    move_dir: vec![1.0, 0.0],
+                },
+            );
+            server.step();
+        }
+
+        let (snapshot, floor, payload) = server.step();
+        assert_eq!(
+            snapshot.entities.len(),
+            2,
+            "Simulation is unaffected by AOI"
+        );
+
+        let SnapshotPayload::PerSession(ref payloads) = payload else {
+            panic!("AOI enabled: expected per-session payloads");
+        };
+        assert_eq!(payloads.len(), 2);
+
+        for (session_id, expected_entity) in [(session1, entity1), (session2, entity2)] {
+            let bytes = payload.bytes_for(session_id).unwrap();
+            let decoded: SnapshotProto = prost::Message::decode(bytes).unwrap();
+            let ids: Vec<_> = decoded
+                .entities
+                .iter()
+                .map(|e| EntityId(e.entity_id))
+                .collect();
+            assert_eq!(ids, vec![expected_entity]);
+            assert_eq!(decoded.target_tick_floor, floor);
+            assert_eq!(decoded.digest, snapshot.digest);
+        }
+    }
+
+    /// Size limit: oversized snapshots are cut per session and flagged.
+    #[test]
+    fn test_snapshot_size_limit_truncates_per_session() {
+        let mut server = Server::new(ServerConfig {
+            snapshot_max_bytes: Some(1_000),
+            ..Default::default()
+        });
+        let (session1, _, entity1) = server.accept_session();
+        let (session2, _, entity2) = server.accept_session();
+        server.start_match();
+        let (_, _, payload) = server.step();
+        assert!(matches!(payload, SnapshotPayload::Broadcast(_)));
+
+        server.config.snapshot_max_bytes = Some(40);
+        let (_, _, payload) = server.step();
+        for (session_id, own_entity) in [(session1, entity1), (session2, entity2)] {
+            let bytes = payload.bytes_for(session_id).unwrap();
+            let decoded: SnapshotProto = prost::Message::decode(bytes).unwrap();
+            let ids: Vec<_> = decoded
+                .entities
+                .iter()
+                .map(|e| EntityId(e.entity_id))
+                .collect();
+            assert_eq!(ids, vec![own_entity]);
+            assert!(decoded.truncated);
+        }
+    }
+
+    /// Repeated below-floor drops trigger a FloorUpdate on the Control channel.
+    #[test]
+    fn test_floor_resent_after_repeated_below_floor_drops() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        for _ in 0..5 {
+            server.step();
+        }
+        let stale = |seq| InputCmdProto {
+            tick: 2,
+            input_seq: seq,
+            move_dir: vec![1.0, 0.0],
+        };
+
+        for seq in 1..FLOOR_RESEND_AFTER_DROPS as u64 {
+            server.receive_input(session1, stale(seq));
+        }
+        assert!(server.drain_control().is_empty());
+        server.receive_input(session1, stale(10));
+        let expected = FloorUpdate {
+            target_tick_floor: 5 + INPUT_LEAD_TICKS,
+            server_tick: 5,
+        };
+        assert_eq!(
+            server.drain_control(),
+            vec![(session1, ControlMessage::FloorUpdate(expected))]
+        );
+
+        // At most one resend per tick
+        for seq in 11..20 {
+            server.receive_input(session1, stale(seq));
+        }
+        assert!(server.drain_control().is_empty());
+        server.step();
+        for seq in 20..20 + FLOOR_RESEND_AFTER_DROPS as u64 {
+            server.receive_input(session1, stale(seq));
+        }
+        assert_eq!(server.drain_control().len(), 1);
+    }
+
+    /// Dropped inputs are summarized per session at the report interval.
+    #[test]
+    fn test_input_rejection_report_at_interval() {
+        let mut server = Server::new(ServerConfig {
+            input_rejection_report_interval_ticks: 4,
+            floor_resend_after_drops: 0,
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        server.step();
+        server.receive_input(
+            session1,
+            InputCmdProto {
+                tick: 0,
+                input_seq: 1,
+                move_dir: vec![1.0, 0.0],
+            },
+        );
+        server.receive_input(
+            session1,
+            InputCmdProto {
+                tick: 3,
+                input_seq: 2,
+                move_dir: vec![f64::NAN, 0.0],
+            },
+        );
+        for _ in 0..2 {
+            server.step();
+        }
+        assert!(server.drain_control().is_empty());
+
+        server.step();
+        let control = server.drain_control();
+        let [(session_id, ControlMessage::InputRejectionReport(report))] = &control[..] else {
+            panic!("expected one report, got {control:?}");
+        };
+        assert_eq!(*session_id, session1);
+        let reasons: Vec<_> = report
+            .drops
+            .iter()
+            .map(|d| (d.reason.as_str(), d.count))
+            .collect();
+        assert_eq!(reasons, vec![("below_floor", 1), ("nan_inf", 1)]);
+        assert_eq!(report.last_rejected_tick, 3);
+        assert_eq!(report.server_tick, 4);
+
+        // Nothing dropped since: no report at the next interval
+        for _ in 0..4 {
+            server.step();
+        }
+        assert!(server.drain_control().is_empty());
+    }
+
+    #[test]
+    fn test_input_timing_report_at_interval() {
+        let mut server = Server::new(ServerConfig {
+            input_rejection_report_interval_ticks: 0,
+            input_timing_report_interval_ticks: 4,
+            floor_resend_after_drops: 0,
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+        server.step();
+        server.receive_input(
+            session1,
+            InputCmdProto {
+                tick: 0,
+                input_seq: 1,
+                move_dir: vec![1.0, 0.0],
+            },
+        );
+        for _ in 0..2 {
+            server.step();
+        }
+        assert!(server.drain_control().is_empty());
+
+        // Every player session gets a report, drops or not
+        server.step();
+        let control = server.drain_control();
+        let reports: BTreeMap<_, _> = control
+            .iter()
+            .map(|(session_id, message)| {
+                let ControlMessage::InputTimingReport(report) = message else {
+                    panic!("expected timing reports, got {control:?}");
+                };
+                (*session_id, report)
+            })
+            .collect();
+        assert_eq!(reports.len(), 2);
+        let report = reports[&session1];
+        assert_eq!(report.server_tick, 4);
+        assert_eq!(report.window_ticks, 4);
+        assert_eq!(report.inputs_received, 1);
+        assert_eq!(report.late_drops + report.below_floor_drops, 1);
+        assert_eq!(report.fallback_ticks, 4);
+        assert_eq!(report.target_tick_floor, 4 + server.config.input_lead_ticks);
+        assert_eq!(reports[&session2].inputs_received, 0);
+
+        // The next report covers only its own window
+        for _ in 0..4 {
+            server.step();
+        }
+        let control = server.drain_control();
+        let Some((_, ControlMessage::InputTimingReport(report))) = control
+            .iter()
+            .find(|(session_id, _)| *session_id == session1)
+        else {
+            panic!("expected a report for session1, got {control:?}");
+        };
+        assert_eq!(report.inputs_received, 0);
+        assert_eq!(report.late_drops + report.below_floor_drops, 0);
+        assert_eq!(report.window_ticks, 4);
+    }
+
+    #[test]
+    fn test_mid_match_spawn_announced_to_every_session() {
+        let mut server = Server::new(ServerConfig {
+            input_timing_report_interval_ticks: 0,
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+        server.step();
+        // Baseline entities are not announced
+        assert!(server.drain_control().is_empty());
+
+        let entity_id = server.world.spawn_character(PlayerId(7));
+        server.step();
+        let expected = flowstate_wire::EntitySpawned {
+            entity_id: entity_id.into(),
+            entity_kind: EntityKind::Character as i32,
+            owner_player_id: None,
+            tick: 2,
+        };
+        assert_eq!(
+            server.drain_control(),
+            vec![
+                (session1, ControlMessage::EntitySpawned(expected)),
+                (session2, ControlMessage::EntitySpawned(expected)),
+            ]
+        );
+        server.step();
+        assert!(server.drain_control().is_empty());
+    }
+
+    /// Redundant input bundles: copies are deduplicated before rate limiting.
+    #[test]
+    fn test_input_bundle_dedup() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+
+        let cmd = |tick: Tick, seq: u64| InputCmdProto {
+            tick,
+            input_seq: seq,
+            move_dir: vec![1.0, 0.0],
+        };
+
+        // First packet: inputs for ticks 1..=3
+        let results = server.receive_input_bundle(
+            session1,
+            InputBundle {
+                inputs: vec![cmd(1, 1), cmd(2, 2), cmd(3, 3)],
+            },
+        );
+        assert!(results.iter().all(ValidationResult::is_accepted));
+
+        // Second packet resends 2..=3 and adds 4; resent copies are duplicates.
+        // Without dedup, 3 copies of tick 2 would exceed the per-tick limit of 2.
+        for _ in 0..2 {
+            let results = server.receive_input_bundle(
+                session1,
+                InputBundle {
+                    inputs: vec![cmd(2, 2), cmd(3, 3), cmd(4, 4)],
+                },
+            );
+            assert_eq!(results[0], ValidationResult::DroppedDuplicate);
+            assert_eq!(results[1], ValidationResult::DroppedDuplicate);
+        }
+
+        // Redundant copies must not create an InputSeq tie (no LKI fallback)
+        server.step();
+        let (snapshot, _, _) = server.step();
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        assert!(
+            artifact
+                .inputs
+                .iter()
+                .filter(|i| i.player_id == 0 && i.tick >= 1)
+                .all(|i| !i.is_fallback)
+        );
+        assert!(snapshot.entities[0].position[0] > 0.0);
+    }
+
+    /// Bundles longer than MAX_INPUT_BUNDLE_LEN are truncated.
+    #[test]
+    fn test_redundant_input_keeps_newest() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+
+        let cmd = |t: Tick| InputCmdProto {
+            tick: t,
+            input_seq: t,
+            move_dir: vec![0.0, 1.0],
+        };
+        let previous: Vec<_> = (1..=11).map(cmd).collect();
+        let redundant = RedundantInputCmd::new(cmd(12), &previous);
+        let results = server
+            .receive_redundant_input(session1, &redundant)
+            .unwrap();
+        assert_eq!(results.len(), MAX_INPUT_BUNDLE_LEN);
+        assert!(results.iter().all(ValidationResult::is_accepted));
+        // Ticks 5..=12 were buffered; the oldest history did not fit
+        assert!(
+            server
+                .input_buffer
+                .is_duplicate(server.session_players[&session1], &cmd(12))
+        );
+        assert!(
+            !server
+                .input_buffer
+                .is_duplicate(server.session_players[&session1], &cmd(4))
+        );
+
+        let malformed = RedundantInputCmd::default();
+        assert!(
+            server
+                .receive_redundant_input(session1, &malformed)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_input_bundle_length_cap() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+
+        let inputs = (1..=20)
+            .map(|t| InputCmdProto {
+                tick: t,
+                input_seq: t,
+                move_dir: vec![0.0, 1.0],
+            })
+            .collect();
+        let results = server.receive_input_bundle(session1, InputBundle { inputs });
+        assert_eq!(results.len(), MAX_INPUT_BUNDLE_LEN);
+    }
+
+    /// TimeSync: pong is filled from the server clock and RTT is stored per session.
+    #[test]
+    fn test_time_sync_handler() {
+        let clock = clock::ManualClock::new(1_000_000);
+        let mut server = Server::new(ServerConfig::default());
+        server.set_clock(Box::new(clock.clone()));
+        let (session1, _, _) = server.accept_session();
+
+        // First ping: pong returned, no RTT estimate yet
+        let ping = TimeSyncPing {
+            client_timestamp: 50,
+            ..Default::default()
+        };
+        let pong = server.handle_time_sync(session1, &ping).unwrap();
+        assert_eq!(pong.server_tick, 0);
+        assert_eq!(pong.server_timestamp, 1_000_000);
+        assert_eq!(pong.ping_timestamp_echo, 50);
+        assert!(server.session(session1).unwrap().time_sync.is_none());
+
+        // Second ping echoes the pong after 30ms, of which the client held 10ms
+        clock.advance(30_000);
+        let ping = TimeSyncPing {
+            client_timestamp: 30_050,
+            server_timestamp_echo: pong.server_timestamp,
+            client_hold_micros: 10_000,
+        };
+        server.handle_time_sync(session1, &ping).unwrap();
+        let sample = server.session(session1).unwrap().time_sync.unwrap();
+        assert_eq!(sample.rtt_micros, 20_000);
+        assert_eq!(server.session(session1).unwrap().rtt(), Some(20_000));
+
+        // Unknown session gets no answer
+        assert!(server.handle_time_sync(SessionId(999), &ping).is_none());
+    }
+
+    /// Snapshots carry the server clock at build time, on the TimeSync clock.
+    #[test]
+    fn test_snapshot_carries_server_time() {
+        let clock = clock::ManualClock::new(5_000_000);
+        let mut server = Server::new(ServerConfig::default());
+        server.set_clock(Box::new(clock.clone()));
+        server.accept_session();
+        server.accept_session();
+        server.start_match();
+
+        let mut times = Vec::new();
+        for _ in 0..2 {
+            clock.advance(16_667);
+            let (_, _, payload) = server.step();
+            let SnapshotPayload::Broadcast(bytes) = payload else {
+                panic!("expected a broadcast snapshot");
+            };
+            let snapshot: SnapshotProto = prost::Message::decode(bytes.as_slice()).unwrap();
+            times.push(snapshot.server_time_micros);
+        }
+        assert_eq!(times, vec![5_016_667, 5_033_334]);
+    }
+
+    #[test]
+    fn test_snapshot_ack_tracking() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        let (_session2, _, _) = server.accept_session();
+        server.start_match();
+        for _ in 0..6 {
+            server.step();
+        }
+
+        let ack = |tick| SnapshotAck { tick };
+        assert!(server.receive_snapshot_ack(session1, &ack(2)));
+        // Duplicate, reordered, and future acks are ignored
+        assert!(!server.receive_snapshot_ack(session1, &ack(2)));
+        assert!(!server.receive_snapshot_ack(session1, &ack(1)));
+        assert!(!server.receive_snapshot_ack(session1, &ack(7)));
+        assert!(server.receive_snapshot_ack(session1, &ack(5)));
+        assert!(!server.receive_snapshot_ack(SessionId(999), &ack(6)));
+
+        let session = server.session(session1).unwrap();
+        assert_eq!(session.last_acked_snapshot_tick, Some(5));
+        assert_eq!(session.network_stats().snapshots_acked(), 2);
+        assert_eq!(session.network_stats().snapshots_unacked(), 2);
+    }
+
+    #[test]
+    fn test_heartbeat_keeps_session_alive() {
+        let clock = clock::ManualClock::new(0);
+        let mut server = Server::new(ServerConfig {
+            pre_match_idle_timeout_ms: 100,
+            ..Default::default()
+        });
+        server.set_clock(Box::new(clock.clone()));
+        let (session1, _, _) = server.accept_session();
+
+        clock.advance(80_000);
+        assert!(server.receive_heartbeat(session1, &Heartbeat { counter: 2 }));
+        // Stale counters are not activity
+        clock.advance(80_000);
+        assert!(!server.receive_heartbeat(session1, &Heartbeat { counter: 1 }));
+        assert!(!server.receive_heartbeat(SessionId(999), &Heartbeat { counter: 3 }));
+        assert!(server.collect_stale_sessions().is_empty());
+
+        clock.advance(30_000);
+        assert_eq!(server.collect_stale_sessions(), vec![session1]);
+    }
+
+    /// A session silent during the match times out and ends it as a
+    /// disconnect at the current tick.
+    #[test]
+    fn test_match_idle_timeout_ends_match() {
+        let clock = clock::ManualClock::new(0);
+        let mut server = Server::new(ServerConfig {
+            match_idle_timeout_ms: 100,
+            ..Default::default()
+        });
+        server.set_clock(Box::new(clock.clone()));
+        let (session1, _, _) = server.accept_session();
+        let (session2, player2, _) = server.accept_session();
+        // Time spent waiting for the match does not count
+        clock.advance(1_000_000);
+        server.start_match();
+        server.step();
+        server.drain_control();
+
+        clock.advance(100_000);
+        assert!(server.receive_heartbeat(session1, &Heartbeat { counter: 1 }));
+        assert!(server.collect_stale_sessions().is_empty());
+        assert_eq!(server.should_end_match(), None);
+
+        clock.advance(1_000);
+        assert_eq!(server.collect_stale_sessions(), vec![session2]);
+        assert!(server.drain_events().iter().any(|event| matches!(
+            event,
+            ServerEvent::SessionExpired { session_id, .. } if *session_id == session2
+        )));
+        let left = PlayerLeft {
+            player_id: u32::from(player2),
+            reason: LeaveReason::TimedOut as i32,
+            tick: 1,
+        };
+        assert_eq!(
+            server.drain_control(),
+            vec![(session1, ControlMessage::PlayerLeft(left))]
+        );
+        assert_eq!(server.should_end_match(), Some(EndReason::Disconnect));
+
+        let artifact = server.finalize(EndReason::Disconnect).unwrap();
+        assert_eq!(artifact.end_reason(), MatchEndReason::Disconnect);
+        assert_eq!(artifact.checkpoint_tick, 1);
+    }
+
+    #[test]
+    fn test_time_sync_report_stored_per_session() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+        server.step();
+
+        let report = TimeSyncReport {
+            clock_offset_micros: -1_500,
+            rtt_micros: 40_000,
+            jitter_micros: 3_000,
+            sample_count: 8,
+        };
+        assert!(server.receive_time_sync_report(session1, &report));
+        assert!(!server.receive_time_sync_report(SessionId(999), &report));
+        assert_eq!(
+            server.session(session1).unwrap().client_time_sync,
+            Some((report, 1))
+        );
+        assert_eq!(server.session(session2).unwrap().client_time_sync, None);
+    }
+
+    #[test]
+    fn test_in_progress_replay_chunks() {
+        let mut server = Server::new(ServerConfig {
+            replay_chunk_requests_per_sec: u32::MAX,
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        server.step();
+        let request = |offset| ReplayChunkRequest {
+            offset,
+            max_len: 64,
+            ..Default::default()
+        };
+        assert_eq!(
+            server.replay_chunk(session1, &request(0)),
+            Err(ReplayChunkError::InProgressNotPermitted)
+        );
+        assert_eq!(
+            server.replay_chunk(SessionId(999), &request(0)),
+            Err(ReplayChunkError::UnknownSession)
+        );
+
+        server.config.serve_in_progress_replays = true;
+        let mut bytes = Vec::new();
+        loop {
+            let chunk = server
+                .replay_chunk(session1, &request(bytes.len() as u64))
+                .unwrap();
+            assert!(!chunk.complete);
+            assert_eq!(chunk.artifact_tick, 1);
+            if chunk.data.is_empty() {
+                break;
+            }
+            bytes.extend_from_slice(&chunk.data);
+        }
+        let artifact: ReplayArtifact = prost::Message::decode(bytes.as_slice()).unwrap();
+        assert_eq!(artifact.checkpoint_tick, 1);
+        assert_eq!(artifact.end_reason(), MatchEndReason::InProgress);
+        // Every chunk of the tick was cut from one encoding
+        assert_eq!(server.replay_chunk_cache.as_ref().unwrap().bytes, bytes);
+
+        server.step();
+        let chunk = server.replay_chunk(session1, &request(0)).unwrap();
+        assert_eq!(chunk.artifact_tick, 2);
+        assert_eq!(server.replay_chunk_cache.as_ref().unwrap().tick, 2);
+    }
+
+    #[test]
+    fn test_replay_chunks_rate_limited_per_session() {
+        let clock = clock::ManualClock::new(0);
+        let mut server = Server::new(ServerConfig {
+            serve_in_progress_replays: true,
+            replay_chunk_requests_per_sec: 2,
+            ..Default::default()
+        });
+        server.set_clock(Box::new(clock.clone()));
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+        server.step();
+
+        let request = ReplayChunkRequest::default();
+        assert!(server.replay_chunk(session1, &request).is_ok());
+        assert!(server.replay_chunk(session1, &request).is_ok());
+        let limited = server.replay_chunk(session1, &request);
+        assert_eq!(limited, Err(ReplayChunkError::RateLimited));
+        assert!(limited.unwrap_err().to_error_response().retryable);
+        // Other sessions have their own budget
+        assert!(server.replay_chunk(session2, &request).is_ok());
+
+        clock.advance(1_000_000);
+        assert!(server.replay_chunk(session1, &request).is_ok());
+    }
+
+    #[test]
+    fn test_keyframe_request_cleared_by_next_snapshot() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+        server.step();
+
+        let request = KeyframeRequest {
+            last_received_tick: 0,
+        };
+        assert!(server.receive_keyframe_request(session1, &request));
+        assert!(!server.receive_keyframe_request(SessionId(999), &request));
+        assert!(server.session(session1).unwrap().keyframe_requested);
+        assert!(!server.session(session2).unwrap().keyframe_requested);
+
+        server.step();
+        assert!(!server.session(session1).unwrap().keyframe_requested);
+    }
+
+    #[test]
+    fn test_baseline_update_rate_limited() {
+        let mut server = Server::new(ServerConfig {
+            baseline_request_min_interval_ticks: 3,
+            ..Default::default()
+        });
+        let (session1, player1, _) = server.accept_session();
+        server.accept_session();
+        let request = BaselineRequest {
+            mismatch_tick: 2,
+            client_digest: 7,
+        };
+        assert_eq!(
+            server.baseline_update(session1, &request),
+            Err(BaselineRequestError::MatchNotStarted)
+        );
+        server.start_match();
+        for _ in 0..2 {
+            server.step();
+        }
+
+        let update = server.baseline_update(session1, &request).unwrap();
+        let baseline = update.baseline.unwrap();
+        assert_eq!(baseline, server.baseline_proto());
+        assert_eq!(baseline.tick, 2);
+        assert_eq!(update.target_tick_floor, 2 + server.config.input_lead_ticks);
+        assert_eq!(
+            server.drain_events(),
+            vec![ServerEvent::ClientDesync {
+                session_id: session1,
+                player_id: player1,
+                tick: 2,
+                mismatch_tick: 2,
+            }]
+        );
+
+        server.step();
+        assert_eq!(
+            server.baseline_update(session1, &request),
+            Err(BaselineRequestError::TooFrequent {
+                retry_after_ticks: 2
+            })
+        );
+        assert_eq!(
+            server.baseline_update(SessionId(999), &request),
+            Err(BaselineRequestError::UnknownSession)
+        );
+        for _ in 0..2 {
+            server.step();
+        }
+        assert!(server.baseline_update(session1, &request).is_ok());
+
+        // A rematch restarts at tick 0; the previous match's requests do not
+        // hold back the new one's
+        for _ in 0..20 {
+            server.step();
+        }
+        assert!(server.baseline_update(session1, &request).is_ok());
+        server.restart_match(1).0.unwrap();
+        server.step();
+        assert!(server.baseline_update(session1, &request).is_ok());
+    }
+
+    /// Network stats: drops and fallbacks are counted per session.
+    #[test]
+    fn test_session_network_stats() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+
+        for _ in 0..5 {
+            server.step();
+        }
+
+        // Below floor (floor is now 6)
+        server.receive_input(
+            session1,
+            InputCmdProto {
+                tick: 2,
+                input_seq: 1,
+                move_dir: vec![1.0, 0.0],
+            },
+        );
+        // Valid input for the next simulated tick
+        server.receive_input(
+            session1,
+            InputCmdProto {
+                tick: 6,
+                input_seq: 2,
+                move_dir: vec![1.0, 0.0],
+            },
+        );
+        server.step(); // tick 5: fallback for both
+        server.step(); // tick 6: session1 has input
+
+        let stats1 = server.session(session1).unwrap().network_stats();
+        assert_eq!(stats1.inputs_received(), 2);
+        assert_eq!(stats1.below_floor_drops(), 1);
+        assert_eq!(stats1.ticks_observed(), 7);
+        assert_eq!(stats1.fallback_ticks(), 6);
+
+        let stats2 = server.session(session2).unwrap().network_stats();
+        assert_eq!(stats2.inputs_received(), 0);
+        assert_eq!(stats2.fallback_rate(), 1.0);
+    }
+
+    /// The summary carries each session's network quality.
+    #[test]
+    fn test_match_summary_network_stats() {
+        let clock = clock::ManualClock::new(0);
+        let mut server = Server::new(ServerConfig::default());
+        server.set_clock(Box::new(clock.clone()));
+        let (session1, _, _) = server.accept_session();
+        let (_session2, _, _) = server.accept_session();
+        server.start_match();
+        for _ in 0..8 {
+            server.step();
+        }
+
+        // Uneven arrivals: two accepted inputs and two below the floor
+        let send = |server: &mut Server, tick, input_seq| {
+            server.receive_input(
+                session1,
+                InputCmdProto {
+                    tick,
+                    input_seq,
+                    move_dir: vec![1.0, 0.0],
+                },
+            );
+        };
+        send(&mut server, 9, 1);
+        clock.advance(10_000);
+        send(&mut server, 10, 2);
+        clock.advance(40_000);
+        send(&mut server, 0, 3);
+        clock.advance(5_000);
+        send(&mut server, 5, 4);
+        // The emitted floor never trails the current tick, so an input cannot
+        // reach the late check through `receive_input`; count one directly
+        server
+            .sessions
+            .get_mut(&session1)
+            .unwrap()
+            .network_stats_mut()
+            .record_input(
+                &ValidationResult::DroppedLate {
+                    tick: 7,
+                    current: 8,
+                },
+                55_000,
+            );
+        server.step();
+        server.step();
+        for tick in [2, 5] {
+            server.receive_snapshot_ack(session1, &SnapshotAck { tick });
+        }
+
+        let summary = server.match_summary(EndReason::Complete);
+        let network = summary.players[0].network.as_ref().unwrap();
+        assert!(network.jitter_micros > 0.0);
+        assert_eq!(network.late_input_rate, 0.2);
+        assert_eq!(network.below_floor_drops, 2);
+        assert!(network.fallback_rate > 0.0);
+        assert_eq!(network.snapshot_loss_rate, 0.5);
+    }
+
+    #[test]
+    fn test_live_replay_sink_follows_match() {
+        use flowstate_replay::live::{LiveFollower, LiveRecord};
+
+        let config = ServerConfig {
+            match_duration_ticks: 50,
+            live_digest_interval_ticks: 10,
+            ..Default::default()
+        };
+        let mut server = Server::new(config);
+        let (tx, rx) = std::sync::mpsc::channel();
+        server.add_replay_sink(Box::new(tx));
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+
+        let mut follower = LiveFollower::new();
+        while server.should_end_match().is_none() {
+            let tick = server.current_tick();
+            server.receive_input(
+                session1,
+                InputCmdProto {
+                    tick: tick + INPUT_LEAD_TICKS,
+                    input_seq: tick + 1,
+                    move_dir: vec![0.5, 0.5],
+                },
+            );
+            server.step();
+            // Follow along mid-match
+            for record in rx.try_iter() {
+                follower.apply(record).unwrap();
+            }
+        }
+        assert_eq!(follower.verified_tick(), Some(50));
+
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        let digest_ticks: Vec<_> = artifact.checkpoint_digests.iter().map(|d| d.tick).collect();
+        assert_eq!(digest_ticks, vec![10, 20, 30, 40]);
+        let records: Vec<_> = rx.try_iter().collect();
+        assert_eq!(
+            records,
+            vec![LiveRecord::Digest {
+                tick: 50,
+                digest: artifact.final_digest
+            }]
+        );
+    }
+
+    #[test]
+    fn test_nominal_play_raises_no_movement_flags() {
+        let mut server = Server::new(ServerConfig {
+            match_duration_ticks: 120,
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        let (session2, _, _) = server.accept_session();
+        server.start_match();
+
+        while server.should_end_match().is_none() {
+            let tick = server.current_tick();
+            for (session_id, dir) in [(session1, [1.0, 0.0]), (session2, [0.6, -0.8])] {
+                server.receive_input(
+                    session_id,
+                    InputCmdProto {
+                        tick: tick + INPUT_LEAD_TICKS,
+                        input_seq: tick + 1,
+                        move_dir: dir.to_vec(),
+                    },
+                );
+            }
+            server.step();
+        }
+
+        assert!(
+            !server
+                .drain_events()
+                .iter()
+                .any(|e| matches!(e, ServerEvent::MovementFlagged { .. }))
+        );
+        let check = server.session(session1).unwrap().movement_check();
+        assert!(!check.is_flagged());
+        assert!(check.max_ratio() > 0.99);
+        assert_eq!(check.total_violations(), 0);
+    }
+
+    #[test]
+    fn test_input_anomaly_event() {
+        use crate::anomaly::{ANOMALY_WINDOW_INPUTS, AnomalyKind};
+        use crate::clock::ManualClock;
+
+        let mut server = Server::new(ServerConfig::default());
+        let clock = ManualClock::new(0);
+        server.set_clock(Box::new(clock.clone()));
+        let (session1, player1, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+
+        // Bot-like: exactly one input every 16_667us
+        for _ in 0..ANOMALY_WINDOW_INPUTS {
+            let tick = server.current_tick();
+            server.receive_input(
+                session1,
+                InputCmdProto {
+                    tick: tick + INPUT_LEAD_TICKS,
+                    input_seq: tick + 1,
+                    move_dir: vec![1.0, 0.0],
+                },
+            );
+            clock.advance(16_667);
+            server.step();
+        }
+
+        let events = server.drain_events();
+        assert_eq!(
+            events,
+            vec![ServerEvent::InputAnomaly {
+                session_id: session1,
+                player_id: player1,
+                tick: ANOMALY_WINDOW_INPUTS as Tick - 1,
+                kind: AnomalyKind::PeriodicTiming,
+            }]
+        );
+        assert!(server.drain_events().is_empty());
+    }
+
+    /// Welcomes carry the intersection of client and server capabilities.
+    #[test]
+    fn test_capabilities_negotiated_in_welcome() {
+        use flowstate_wire::capabilities::{DELTA_SNAPSHOTS, REALTIME_CHECKSUMS};
+
+        let mut server = Server::new(ServerConfig::default());
+        let (modern, _, _) = server
+            .accept_hello(&ClientHello {
+                capabilities: REALTIME_CHECKSUMS | DELTA_SNAPSHOTS,
+                ..Default::default()
+            })
+            .unwrap();
+        let (legacy, _, _) = server.accept_hello(&ClientHello::default()).unwrap();
+        let (_, welcomes) = server.start_match();
+        let negotiated: BTreeMap<_, _> = welcomes
+            .iter()
+            .map(|(session_id, welcome)| (*session_id, welcome.capabilities))
+            .collect();
+        assert_eq!(negotiated[&modern], REALTIME_CHECKSUMS);
+        assert_eq!(negotiated[&legacy], 0);
+        assert_eq!(
+            server.session(modern).unwrap().capabilities,
+            REALTIME_CHECKSUMS
+        );
+    }
+
+    /// A compression offer is answered in the welcome with the client's
+    /// most preferred supported codec; no offer means uncompressed.
+    #[test]
+    fn test_compression_negotiated_in_welcome() {
+        use flowstate_wire::{CompressionCodec, CompressionOffer};
+
+        let mut server = Server::new(ServerConfig::default());
+        server
+            .accept_hello(&ClientHello {
+                compression: Some(CompressionOffer {
+                    codecs: vec![CompressionCodec::Zstd as i32, CompressionCodec::Lz4 as i32],
+                    max_raw_len: 0,
+                }),
+                ..Default::default()
+            })
+            .unwrap();
+        let (plain, _, _) = server.accept_hello(&ClientHello::default()).unwrap();
+        let (_, welcomes) = server.start_match();
+        for (session_id, welcome) in welcomes {
+            if session_id == plain {
+                assert!(welcome.compression.is_none());
+            } else {
+                let params = welcome.compression.unwrap();
+                assert_eq!(params.codec(), CompressionCodec::Zstd);
+                assert_eq!(params.max_raw_len, compression::DEFAULT_MAX_RAW_LEN as u32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_accept_hello_checks_protocol_version() {
+        let mut server = Server::new(ServerConfig::default());
+        let hello = |protocol_version| ClientHello {
+            protocol_version,
+            ..Default::default()
+        };
+        assert_eq!(
+            server.accept_hello(&hello(flowstate_wire::PROTOCOL_VERSION + 1)),
+            Err(AuthError::UnsupportedProtocolVersion(
+                flowstate_wire::PROTOCOL_VERSION + 1
+            ))
+        );
+        assert_eq!(server.session_count(), 0);
+        assert!(server.accept_hello(&hello(0)).is_ok());
+        assert!(
+            server
+                .accept_hello(&hello(flowstate_wire::PROTOCOL_VERSION))
+                .is_ok()
+        );
+
+        let mismatched = ClientHello {
+            schema_hash: flowstate_wire::SCHEMA_HASH ^ 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            server.accept_hello(&mismatched),
+            Err(AuthError::SchemaMismatch(flowstate_wire::SCHEMA_HASH ^ 1))
+        );
+
+        let response = AuthError::UnsupportedProtocolVersion(9).to_error_response();
+        assert_eq!(
+            response.code(),
+            flowstate_wire::ErrorCode::UnsupportedProtocolVersion
+        );
+        assert!(!response.retryable);
+    }
+
+    #[test]
+    fn test_accept_hello_with_invites() {
+        use crate::auth::InviteList;
+
+        let mut invites = InviteList::new();
+        invites.invite("tok-a", "alice");
+        invites.invite("tok-b", "bob");
+        let mut server = Server::new(ServerConfig::default());
+        server.set_authenticator(Box::new(invites));
+
+        let rejected = server.accept_hello(&ClientHello {
+            auth_token: "forged".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(rejected, Err(AuthError::InvalidToken));
+        assert_eq!(
+            server.accept_hello(&ClientHello::default()),
+            Err(AuthError::MissingToken)
+        );
+        assert_eq!(server.session_count(), 0);
+
+        let (session_a, player_a, _) = server
+            .accept_hello(&ClientHello {
+                auth_token: "tok-a".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(player_a, PlayerId(0));
+        assert_eq!(
+            server.session(session_a).unwrap().identity.as_deref(),
+            Some("alice")
+        );
+    }
+
+    #[test]
+    fn test_accept_hello_default_is_anonymous() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session_id, _, _) = server.accept_hello(&ClientHello::default()).unwrap();
+        assert_eq!(server.session(session_id).unwrap().identity, None);
+    }
+
+    #[test]
+    fn test_role_rate_limits() {
+        let mut server = Server::new(ServerConfig {
+            input_burst: 1,
+            ..Default::default()
+        });
+        let (player, _, _) = server.accept_session();
+        let (spectator, _, _) = server.accept_session();
+        server.set_session_role(spectator, SessionRole::Spectator);
+        server.start_match();
+
+        let send = |server: &mut Server, session_id, seq| {
+            server.receive_input(
+                session_id,
+                InputCmdProto {
+                    tick: 3,
+                    input_seq: seq,
+                    move_dir: vec![1.0, 0.0],
+                },
+            )
+        };
+        // Player: per-tick limit 2 plus burst 1
+        let accepted = (1..=4)
+            .filter(|&seq| send(&mut server, player, seq).is_accepted())
+            .count();
+        assert_eq!(accepted, 3);
+        // Spectator: no inputs
+        assert_eq!(
+            send(&mut server, spectator, 1),
+            ValidationResult::DroppedRateLimit
+        );
+        assert_eq!(
+            server.session(spectator).unwrap().role,
+            SessionRole::Spectator
+        );
+    }
+
+    fn invited_server(policy: DuplicateIdentityPolicy) -> Server {
+        let mut invites = crate::auth::InviteList::new();
+        invites.invite("tok-a", "alice");
+        invites.invite("tok-b", "bob");
+        let mut server = Server::new(ServerConfig {
+            duplicate_identity_policy: policy,
+            ..Default::default()
+        });
+        server.set_authenticator(Box::new(invites));
+        server
+    }
+
+    fn hello(token: &str) -> ClientHello {
+        ClientHello {
+            auth_token: token.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_duplicate_identity_rejected() {
+        let mut server = invited_server(DuplicateIdentityPolicy::RejectNew);
+        let (session_a, _, _) = server.accept_hello(&hello("tok-a")).unwrap();
+        assert_eq!(
+            server.accept_hello(&hello("tok-a")),
+            Err(AuthError::AlreadyConnected)
+        );
+        assert_eq!(server.session_ids(), vec![session_a]);
+
+        // The second slot is still available to a different identity
+        let (_, player_b, _) = server.accept_hello(&hello("tok-b")).unwrap();
+        assert_eq!(player_b, PlayerId(1));
+    }
+
+    #[test]
+    fn test_duplicate_identity_supersedes_mid_match() {
+        let mut server = invited_server(DuplicateIdentityPolicy::SupersedeOld);
+        let (old_a, player_a, entity_a) = server.accept_hello(&hello("tok-a")).unwrap();
+        server.accept_hello(&hello("tok-b")).unwrap();
+        server.start_match();
+        server.step();
+
+        let (new_a, rebound_player, rebound_entity) = server.accept_hello(&hello("tok-a")).unwrap();
+        assert_ne!(new_a, old_a);
+        assert_eq!((rebound_player, rebound_entity), (player_a, entity_a));
+        assert_eq!(server.session_count(), 2);
+        assert!(server.session(old_a).is_none());
+        assert_eq!(
+            server.drain_events(),
+            vec![ServerEvent::SessionSuperseded {
+                old_session_id: old_a,
+                new_session_id: new_a,
+                player_id: player_a,
+            }]
+        );
+
+        // Old session's inputs are refused; the new one drives the same player
+        let input = |seq| InputCmdProto {
+            tick: 2,
+            input_seq: seq,
+            move_dir: vec![1.0, 0.0],
+        };
+        assert_eq!(
+            server.receive_input(old_a, input(1)),
+            ValidationResult::DroppedUnknownSession
+        );
+        assert!(server.receive_input(new_a, input(2)).is_accepted());
+        server.step();
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        assert_eq!(artifact.player_entity_mapping.len(), 2);
+    }
+
+    #[test]
+    fn test_chat_relay() {
+        use crate::chat::ChatFilter;
+        use crate::clock::ManualClock;
+
+        struct Mask;
+        impl ChatFilter for Mask {
+            fn filter(&self, text: &str) -> Option<String> {
+                if text.contains("spam.example") {
+                    None
+                } else {
+                    Some(text.replace("darn", "****"))
+                }
+            }
+        }
+
+        let mut server = Server::new(ServerConfig {
+            chat_rate_limit: ChatRateLimit {
+                max_messages: 2,
+                window_micros: 1_000_000,
+            },
+            ..Default::default()
+        });
+        let clock = ManualClock::new(0);
+        server.set_clock(Box::new(clock.clone()));
+        server.set_chat_filter(Box::new(Mask));
+        let (session1, player1, _) = server.accept_session();
+
+        let send = |text: &str| ChatSend {
+            text: text.to_string(),
+        };
+        let relayed = server.receive_chat(session1, &send(" darn, gl ")).unwrap();
+        assert_eq!(relayed.player_id, u32::from(player1));
+        assert_eq!(relayed.text, "****, gl");
+        assert_eq!(
+            server.receive_chat(session1, &send("visit spam.example")),
+            Err(ChatError::Blocked)
+        );
+        assert_eq!(
+            server.receive_chat(session1, &send("hf")),
+            Err(ChatError::RateLimited)
+        );
+        clock.advance(1_000_000);
+        assert!(server.receive_chat(session1, &send("hf")).is_ok());
+        assert_eq!(
+            server.receive_chat(SessionId(99), &send("hi")),
+            Err(ChatError::UnknownSession)
+        );
+    }
+
+    #[test]
+    fn test_match_id_propagates_to_welcome_and_replay() {
+        let mut server = Server::new(ServerConfig::default());
+        let match_id = server.match_id().to_string();
+        assert!(!match_id.is_empty());
+        assert_ne!(Server::new(ServerConfig::default()).match_id(), match_id);
+
+        server.accept_session();
+        server.accept_session();
+        let (_, welcomes) = server.start_match();
+        assert!(welcomes.iter().all(|(_, w)| w.match_id == match_id));
+        let (artifact, summary) = server.finalize_with_summary(EndReason::Complete);
+        let artifact = artifact.unwrap();
+        assert_eq!(artifact.match_id, match_id);
+        assert_eq!(summary.match_id, match_id);
+    }
+
+    #[test]
+    fn test_team_assignment_surfaced_and_resumed() {
+        let config = ServerConfig {
+            seed: 3,
+            team_assignment: TeamAssignment::RoundRobin { teams: 2 },
+            ..Default::default()
+        };
+        let mut server = Server::new(config.clone());
+        server.accept_session();
+        server.accept_session();
+        let (_, welcomes) = server.start_match();
+        let teams: Vec<_> = welcomes.iter().map(|(_, w)| w.team_id).collect();
+        assert_eq!(teams, vec![Some(0), Some(1)]);
+        assert_eq!(server.team_of(PlayerId(1)), Some(1));
+        server.step();
+
+        let mut resumed = Server::resume(config, &server.checkpoint().unwrap()).unwrap();
+        assert_eq!(resumed.team_of(PlayerId(0)), Some(0));
+        let (_, _, welcome) = resumed.reclaim_player(PlayerId(1)).unwrap();
+        assert_eq!(welcome.team_id, Some(1));
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        let recorded: Vec<_> = artifact
+            .player_entity_mapping
+            .iter()
+            .map(|m| m.team_id)
+            .collect();
+        assert_eq!(recorded, vec![Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn test_restart_match_keeps_sessions() {
+        let mut server = Server::new(ServerConfig {
+            seed: 5,
+            match_duration_ticks: 10,
+            ..Default::default()
+        });
+        let (s1, p1, e1) = server.accept_session();
+        let (s2, p2, _) = server.accept_session();
+        server.start_match();
+        for _ in 0..10 {
+            send_move(&mut server, s1, [1.0, 0.0]);
+            server.step();
+            let tick = server.current_tick();
+            assert!(server.receive_snapshot_ack(s1, &SnapshotAck { tick }));
+        }
+        let first_match = server.match_id().to_string();
+
+        let (artifact, baseline, welcomes) = server.restart_match(6);
+        let artifact = artifact.unwrap();
+        assert_eq!(artifact.end_reason(), MatchEndReason::Complete);
+        assert_eq!(artifact.match_id, first_match);
+        assert_eq!(artifact.seed, 5);
+        assert_ne!(server.match_id(), first_match);
+        assert_eq!(server.seed(), (6, SeedSource::Configured));
+        assert_eq!(baseline.tick, 0);
+        assert_eq!(server.world.entity_position(e1), Some([0.0, 0.0]));
+        let bound: Vec<_> = welcomes
+            .iter()
+            .map(|(sid, w)| (*sid, PlayerId::try_from(w.player_id).unwrap()))
+            .collect();
+        assert_eq!(bound, vec![(s1, p1), (s2, p2)]);
+
+        // Acks start over with the new match's ticks
+        assert!(
+            server
+                .session(s1)
+                .unwrap()
+                .last_acked_snapshot_tick
+                .is_none()
+        );
+        for _ in 0..10 {
+            send_move(&mut server, s1, [-1.0, 0.0]);
+            send_move(&mut server, s2, [0.0, 1.0]);
+            server.step();
+            let tick = server.current_tick();
+            assert!(server.receive_snapshot_ack(s1, &SnapshotAck { tick }));
+        }
+        assert_eq!(
+            server.session(s1).unwrap().last_acked_snapshot_tick,
+            Some(10)
+        );
+        let rematch = server.finalize(EndReason::Complete).unwrap();
+        assert_eq!(rematch.seed, 6);
+        assert_eq!(rematch.inputs.len(), 20);
+        // Input sequencing starts over too
+        assert!(
+            rematch
+                .inputs
+                .iter()
+                .any(|i| i.player_id == u32::from(p1) && !i.is_fallback)
+        );
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(flowstate_replay::verify_replay(&artifact, &options).is_ok());
+        assert!(flowstate_replay::verify_replay(&rematch, &options).is_ok());
+    }
+
+    #[test]
+    fn test_match_config_matches_replay_tuning() {
+        let mut server = Server::new(ServerConfig {
+            match_duration_ticks: 4,
+            map_id: "dunes".to_string(),
+            lki_decay: LkiDecay {
+                after_ticks: 3,
+                ramp_ticks: 6,
+            },
+            ..Default::default()
+        });
+        server.accept_session();
+        server.accept_session();
+        server.start_match();
+        let config = server.match_config();
+        assert_eq!(config.tick_rate_hz, TICK_RATE_HZ);
+        assert_eq!(config.match_duration_ticks, 4);
+        assert_eq!(config.map_id, "dunes");
+        assert!(
+            config
+                .tuning_parameters
+                .iter()
+                .any(|t| t.key == "move_speed" && t.value == flowstate_sim::MOVE_SPEED)
+        );
+
+        while server.should_end_match().is_none() {
+            server.step();
+        }
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        assert_eq!(config.tuning_parameters, artifact.tuning_parameters);
+    }
+
+    #[test]
+    fn test_snapshot_anchors_recorded_at_interval() {
+        let mut server = Server::new(ServerConfig {
+            match_duration_ticks: 45,
+            snapshot_anchor_interval_ticks: 20,
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        while server.should_end_match().is_none() {
+            let tick = server.current_tick();
+            server.receive_input(
+                session1,
+                InputCmdProto {
+                    tick: tick + INPUT_LEAD_TICKS,
+                    input_seq: tick + 1,
+                    move_dir: vec![0.0, -1.0],
+                },
+            );
+            server.step();
+        }
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        let anchor_ticks: Vec<_> = artifact.snapshot_anchors.iter().map(|a| a.tick).collect();
+        assert_eq!(anchor_ticks, vec![20, 40]);
+        assert!(
+            artifact
+                .snapshot_anchors
+                .iter()
+                .all(|a| a.entities.len() == 2)
+        );
+
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        let report = flowstate_replay::verify_replay_report(&artifact, &options);
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.anchors_checked, 2);
+
+        // Off by default
+        let mut server = Server::new(ServerConfig {
+            match_duration_ticks: 45,
+            ..Default::default()
+        });
+        server.accept_session();
+        server.accept_session();
+        server.start_match();
+        while server.should_end_match().is_none() {
+            server.step();
+        }
+        assert!(
+            server
+                .finalize(EndReason::Complete)
+                .unwrap()
+                .snapshot_anchors
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_rejected_inputs_audited_in_replay() {
+        let mut server = Server::new(ServerConfig {
+            rejected_input_audit_limit: 2,
+            ..Default::default()
+        });
+        let (session1, player1, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        let tick = server.current_tick() + INPUT_LEAD_TICKS;
+        let input = |input_seq, move_dir: Vec<f64>| InputCmdProto {
+            tick,
+            input_seq,
+            move_dir,
+        };
+        assert!(
+            server
+                .receive_input(session1, input(1, vec![1.0, 0.0]))
+                .is_accepted()
+        );
+        assert_eq!(
+            server.receive_input(session1, input(2, vec![2.0, f64::INFINITY])),
+            ValidationResult::DroppedNanInf
+        );
+        assert_eq!(
+            server.receive_input(SessionId(99), input(3, vec![0.0, 1.0])),
+            ValidationResult::DroppedUnknownSession
+        );
+        // Past the limit: only counted
+        server.receive_input(session1, input(4, vec![f64::NAN, 0.0]));
+        server.step();
+
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        let audit = artifact.input_audit.clone().unwrap();
+        assert_eq!(audit.omitted, 1);
+        let summary: Vec<_> = audit
+            .rejected
+            .iter()
+            .map(|r| (r.receive_seq, r.session_id, r.player_id, r.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, session1.0, Some(player1.into()), "nan_inf"),
+                (2, 99, None, "unknown_session"),
+            ]
+        );
+        assert_eq!(audit.rejected[1].input, Some(input(3, vec![0.0, 1.0])));
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(flowstate_replay::verify_replay(&artifact, &options).is_ok());
+
+        // Off by default
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        server.receive_input(session1, input(1, vec![f64::NAN, 0.0]));
+        assert_eq!(
+            server.finalize(EndReason::Complete).unwrap().input_audit,
+            None
+        );
+    }
+
+    #[test]
+    fn test_replay_metadata_recorded_outside_verification() {
+        let clock = clock::ManualClock::new(1_700_000_000_000_000);
+        let mut server = Server::new(ServerConfig {
+            match_duration_ticks: 30,
+            map_id: "dunes".to_string(),
+            ..Default::default()
+        });
+        server.set_clock(Box::new(clock.clone()));
+        server.accept_session();
+        let (_, player2, _) = server.accept_session();
+        server.start_match();
+        server.set_player_profile(
+            player2,
+            PlayerProfile {
+                display_name: "Ada".to_string(),
+                ..Default::default()
+            },
+        );
+        while server.should_end_match().is_none() {
+            server.step();
+        }
+        clock.advance(500_000);
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+
+        let metadata = artifact.metadata.clone().unwrap();
+        assert_eq!(metadata.start_unix_ms, 1_700_000_000_000);
+        assert_eq!(metadata.end_unix_ms, 1_700_000_000_500);
+        assert_eq!(metadata.map_id, "dunes");
+        let names: Vec<_> = metadata
+            .players
+            .iter()
+            .map(|p| p.display_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Player 0", "Ada"]);
+
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        let stripped = ReplayArtifact {
+            metadata: None,
+            ..artifact.clone()
+        };
+        assert!(flowstate_replay::verify_replay(&artifact, &options).is_ok());
+        assert!(flowstate_replay::verify_replay(&stripped, &options).is_ok());
+    }
+
+    #[test]
+    fn test_lki_decay_recorded_and_verifiable() {
+        let config = ServerConfig {
+            seed: 4,
+            match_duration_ticks: 8,
+            lki_decay: LkiDecay {
+                after_ticks: 2,
+                ramp_ticks: 2,
+            },
+            ..Default::default()
+        };
+        // Pinned, so both runs stamp the same replay end time
+        let clock = clock::ManualClock::new(0);
+        let mut server = Server::new(config.clone());
+        server.set_clock(Box::new(clock.clone()));
+        let (s1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        send_move(&mut server, s1, [1.0, 0.0]);
+        for _ in 0..5 {
+            server.step();
+        }
+        let mid_run = server.checkpoint().unwrap();
+        for _ in 5..8 {
+            server.step();
+        }
+
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        let player0: Vec<f64> = artifact
+            .inputs
+            .iter()
+            .filter(|i| i.player_id == 0)
+            .map(|i| i.move_dir[0])
+            .collect();
+        assert_eq!(player0, vec![0.0, 1.0, 1.0, 1.0, 0.5, 0.0, 0.0, 0.0]);
+        let tuning: Vec<_> = artifact
+            .tuning_parameters
+            .iter()
+            .map(|t| (t.key.as_str(), t.value))
+            .collect();
+        assert!(tuning.contains(&(lki::TUNING_KEY_AFTER_TICKS, 2.0)));
+        assert!(tuning.contains(&(lki::TUNING_KEY_RAMP_TICKS, 2.0)));
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        // The verifier knows every recorded tuning key
+        let report = flowstate_replay::verify_replay_report(&artifact, &options);
+        assert!(report.is_ok(), "{report:?}");
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+        // A resumed server continues the decay where it left off
+        let mut resumed = Server::resume(config, &mid_run).unwrap();
+        resumed.set_clock(Box::new(clock));
+        for _ in 5..8 {
+            resumed.step();
+        }
+        assert_eq!(resumed.finalize(EndReason::Complete).unwrap(), artifact);
+    }
+
+    #[test]
+    fn test_seed_provisioned_and_recorded() {
+        let mut server = Server::new(ServerConfig::default());
+        let (seed, source) = server.seed();
+        assert_ne!(seed, 0);
+        assert_eq!(source, SeedSource::Entropy);
+        server.accept_session();
+        server.accept_session();
+        server.start_match();
+        let (artifact, summary) = server.finalize_with_summary(EndReason::Complete);
+        let artifact = artifact.unwrap();
+        assert_eq!(artifact.seed, seed);
+        assert_eq!((summary.seed, summary.seed_source), (seed, source));
+
+        let test_server = Server::new(ServerConfig {
+            test_mode: true,
+            ..Default::default()
+        });
+        assert_eq!(test_server.seed(), (0, SeedSource::Configured));
+    }
+
+    #[test]
+    fn test_finalize_with_summary() {
+        let mut server = Server::new(ServerConfig {
+            match_id: "m-42".to_string(),
+            match_duration_ticks: 30,
+            ..Default::default()
+        });
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        while server.should_end_match().is_none() {
+            let tick = server.current_tick();
+            server.receive_input(
+                session1,
+                InputCmdProto {
+                    tick: tick + INPUT_LEAD_TICKS,
+                    input_seq: tick + 1,
+                    move_dir: vec![1.0, 0.0],
+                },
+            );
+            server.step();
+        }
+
+        let (artifact, summary) = server.finalize_with_summary(EndReason::Complete);
+        let artifact = artifact.unwrap();
+        assert_eq!(summary.match_id, "m-42");
+        assert_eq!(summary.duration_ticks, 30);
+        assert_eq!(summary.end_reason, "complete");
+        assert_eq!(
+            summary.final_digest,
+            summary::format_digest(artifact.final_digest)
+        );
+        assert_eq!(summary.players.len(), 2);
+        assert_eq!(summary.players[0].inputs_received, 30);
+        assert_eq!(summary.players[1].fallback_ticks, 30);
+        assert!(summary.players[0].final_position.unwrap()[0] > 0.0);
+
+        let parsed = MatchSummary::from_json(&summary.to_json()).unwrap();
+        assert_eq!(parsed, summary);
+    }
+
+    fn send_move(server: &mut Server, session_id: SessionId, move_dir: [f64; 2]) {
+        let tick = server.current_tick();
+        server.receive_input(
+            session_id,
+            InputCmdProto {
+                tick: tick + INPUT_LEAD_TICKS,
+                input_seq: tick + 1,
+                move_dir: move_dir.to_vec(),
+            },
+        );
+    }
+
+    /// Outputs must not depend on the order sessions' messages arrive in, or on
+    /// map iteration order.
+    #[test]
+    fn test_outputs_independent_of_session_message_order() {
+        fn run(reverse: bool, aoi_radius: Option<f64>) -> (Vec<u8>, Vec<Vec<u8>>, Vec<u8>) {
+            let mut server = Server::new(ServerConfig {
+                match_id: "m-order".to_string(),
+                seed: 3,
+                match_duration_ticks: 12,
+                aoi_radius,
+                ..Default::default()
+            });
+            // Snapshots carry the server clock; pin it
+            server.set_clock(Box::new(clock::ManualClock::new(0)));
+            let (s1, _, _) = server.accept_session();
+            let (s2, _, _) = server.accept_session();
+            let (_, welcomes) = server.start_match();
+            let welcome_ids: Vec<SessionId> = welcomes.iter().map(|(id, _)| *id).collect();
+            assert_eq!(welcome_ids, vec![s1, s2]);
+            let welcomes: Vec<u8> = welcomes
+                .iter()
+                .flat_map(|(_, w)| prost::Message::encode_to_vec(w))
+                .collect();
+
+            let mut payloads = Vec::new();
+            while server.should_end_match().is_none() {
+                let moves = [(s1, [1.0, 0.0]), (s2, [0.0, -1.0])];
+                let order: Vec<_> = if reverse {
+                    moves.iter().rev().collect()
+                } else {
+                    moves.iter().collect()
+                };
+                for &(session_id, dir) in order {
+                    send_move(&mut server, session_id, dir);
+                }
+                let (_, _, payload) = server.step();
+                payloads.push(match payload {
+                    SnapshotPayload::Broadcast(bytes) => bytes,
+                    SnapshotPayload::PerSession(per_session) => per_session
+                        .into_iter()
+                        .flat_map(|(id, bytes)| id.0.to_le_bytes().into_iter().chain(bytes))
+                        .collect(),
+                });
+            }
+            let (artifact, summary) = server.finalize_with_summary(EndReason::Complete);
+            let artifact = artifact.unwrap();
+            let mut artifact_bytes = prost::Message::encode_to_vec(&artifact);
+            artifact_bytes.extend(summary.to_json().into_bytes());
+            (welcomes, payloads, artifact_bytes)
+        }
+
+        for aoi_radius in [None, Some(100.0)] {
+            let forward = run(false, aoi_radius);
+            for _ in 0..4 {
+                assert_eq!(run(true, aoi_radius), forward);
+                assert_eq!(run(false, aoi_radius), forward);
+            }
+        }
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint_matches_uninterrupted_run() {
+        let config = ServerConfig {
+            seed: 9,
+            match_duration_ticks: 20,
+            ..Default::default()
+        };
+        let mut original = Server::new(config.clone());
+        let (s1, _, _) = original.accept_session();
+        let (s2, _, _) = original.accept_session();
+        original.start_match();
+        for _ in 0..10 {
+            send_move(&mut original, s1, [1.0, 0.0]);
+            send_move(&mut original, s2, [0.0, 1.0]);
+            original.step();
+        }
+
+        let checkpoint = original.checkpoint().unwrap();
+        let mut resumed = Server::resume(config, &checkpoint).unwrap();
+        assert_eq!(resumed.current_tick(), 10);
+        assert_eq!(resumed.match_id(), original.match_id());
+        let (r1, _, welcome) = resumed.reclaim_player(PlayerId(0)).unwrap();
+        assert_eq!(welcome.target_tick_floor, 10 + INPUT_LEAD_TICKS);
+        let (r2, _, _) = resumed.reclaim_player(PlayerId(1)).unwrap();
+        assert!(resumed.reclaim_player(PlayerId(1)).is_none());
+
+        for _ in 10..20 {
+            send_move(&mut original, s1, [-1.0, 0.0]);
+            send_move(&mut resumed, r1, [-1.0, 0.0]);
+            send_move(&mut original, s2, [0.0, 1.0]);
+            send_move(&mut resumed, r2, [0.0, 1.0]);
+            original.step();
+            resumed.step();
+        }
+        assert!(resumed.should_end_match().is_some());
+        // Inputs buffered for tick 10 were lost with the "crashed" process, so
+        // the resumed run applied them via LastKnownIntent (same direction).
+        let resumed = resumed.finalize(EndReason::Complete).unwrap();
+        let original = original.finalize(EndReason::Complete).unwrap();
+        assert_eq!(resumed.final_digest, original.final_digest);
+        assert_eq!(resumed.inputs.len(), original.inputs.len());
+        assert_eq!(resumed.match_id, original.match_id);
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(flowstate_replay::verify_replay(&resumed, &options).is_ok());
+    }
+
+    #[test]
+    fn test_resume_rejects_mismatched_config() {
+        let mut server = Server::new(ServerConfig::default());
+        server.accept_session();
+        server.accept_session();
+        server.start_match();
+        server.step();
+        let checkpoint = server.checkpoint().unwrap();
+        let result = Server::resume(
+            ServerConfig {
+                seed: 1,
+                ..Default::default()
+            },
+            &checkpoint,
+        );
+        assert!(matches!(
+            result,
+            Err(CheckpointError::ConfigMismatch { .. })
+        ));
+
+        let mut tampered = checkpoint;
+        tampered.state.as_mut().unwrap().digest ^= 1;
+        let result = Server::resume(ServerConfig::default(), &tampered);
+        assert!(matches!(
+            result,
+            Err(CheckpointError::DigestMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_periodic_checkpoint_salvages_verifiable_replay() {
+        let dir = std::env::temp_dir().join(format!("flowstate-checkpoint-{}", std::process::id()));
+        let path = dir.join("match.checkpoint");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut server = Server::new(ServerConfig {
+            checkpoint_interval_ticks: 5,
+            ..Default::default()
+        });
+        server.set_checkpoint_path(&path);
+        let (s1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+        for _ in 0..12 {
+            send_move(&mut server, s1, [1.0, 0.0]);
+            server.step();
+        }
+        // Process "crashes" here; the last checkpoint is at tick 10
+        drop(server);
+
+        let replay = checkpoint::salvage_replay(&path).unwrap();
+        assert_eq!(replay.checkpoint_tick, 10);
+        assert_eq!(replay.end_reason(), MatchEndReason::Checkpoint);
+        let options = flowstate_replay::VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(flowstate_replay::verify_replay(&replay, &options).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload_config_mid_match() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, _, _) = server.accept_session();
+        server.accept_session();
+        server.start_match();
+
+        let locked = ConfigPatch {
+            input_rate_limit_per_sec: Some(60),
+            match_duration_ticks: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(
+            server.reload_config(&locked),
+            Err(ReloadError::LockedAfterStart {
+                field: "match_duration_ticks"
+            })
+        );
+        // Rejected patches apply nothing
+        assert_eq!(
+            server.config().input_rate_limit_per_sec,
+            INPUT_RATE_LIMIT_PER_SEC
+        );
+
+        let patch = ConfigPatch {
+            input_rate_limit_per_sec: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            server.reload_config(&patch),
+            Ok(vec!["input_rate_limit_per_sec"])
+        );
+        assert!(server.drain_events().iter().any(|e| matches!(
+            e,
+            ServerEvent::ConfigReloaded { fields, .. } if fields == &["input_rate_limit_per_sec"]
+        )));
+
+        // 60/s at 60 Hz: one input per tick
+        let tick = server.current_tick() + INPUT_LEAD_TICKS;
+        let results: Vec<_> = (1..=2)
+            .map(|seq| {
+                server.receive_input(
+                    session1,
+                    InputCmdProto {
+                        tick,
+                        input_seq: seq,
+                        move_dir: vec![1.0, 0.0],
+                    },
+                )
+            })
+            .collect();
+        assert!(results[0].is_accepted());
+        assert_eq!(results[1], ValidationResult::DroppedRateLimit);
+    }
+
+    #[test]
+    fn test_player_stats_aggregation() {
+        let mut server = Server::new(ServerConfig::default());
+        let (session1, player1, _) = server.accept_session();
+        let (_, player2, _) = server.accept_session();
+        server.start_match();
+        for _ in 0..10 {
+            let tick = server.current_tick();
+            server.receive_input(
+                session1,
+                InputCmdProto {
+                    tick: tick + INPUT_LEAD_TICKS,
+                    input_seq: tick + 1,
+                    move_dir: vec![1.0, 0.0],
+                },
+            );
+            server.step();
+        }
+        // Target tick already simulated
+        server.receive_input(
+            session1,
+            InputCmdProto {
+                tick: 0,
+                input_seq: 100,
+                move_dir: vec![1.0, 0.0],
+            },
+        );
+
+        let stats = server.player_stats(player1).unwrap();
+        assert_eq!(stats.inputs_received(), 11);
+        assert_eq!(stats.inputs_accepted(), 10);
+        assert_eq!(stats.inputs_dropped(), 1);
+        assert_eq!(
+            stats.average_input_lead_ticks(),
+            Some(INPUT_LEAD_TICKS as f64)
+        );
+        assert_eq!(stats.ticks_applied(), 10);
+        // First INPUT_LEAD_TICKS ticks had no input yet
+        assert_eq!(stats.fallback_ticks(), INPUT_LEAD_TICKS);
+        let expected =
+            (10 - INPUT_LEAD_TICKS) as f64 * flowstate_sim::MOVE_SPEED / TICK_RATE_HZ as f64;
+        assert!((stats.distance_moved() - expected).abs() < 1e-9);
+
+        let idle = server.player_stats(player2).unwrap();
+        assert_eq!(idle.distance_moved(), 0.0);
+        assert_eq!(idle.fallback_ticks(), 10);
+
+        let summary = server.match_summary(EndReason::Complete);
+        assert_eq!(summary.players[0].inputs_dropped.values().sum::<u64>(), 1);
+        assert_eq!(summary.players[0].distance_moved, stats.distance_moved());
+    }
 }
@@ -0,0 +1,106 @@
+//! Entity spawn and removal announcements.
+//!
+//! Ref: DM-0020 (EntityId), ADR-0005 (Control Channel)
+//! - `EntityLifecycle` remembers the entities clients already know about:
+//!   the JoinBaseline's at match start, then its own announcements
+//! - `update` compares each post-step snapshot with them and returns an
+//!   `EntityRemoved` per vanished entity and an `EntitySpawned` per new
+//!   one, in EntityId order (snapshots are ordered per INV-0007)
+//!
+//! Announcements go out reliably on the Control channel, so clients do not
+//! have to diff consecutive snapshots, which misses changes when snapshots
+//! are lost.
+
+use std::collections::BTreeSet;
+
+use flowstate_sim::{EntityId, Tick};
+use flowstate_wire::{EntityRemoved, EntitySnapshotProto, EntitySpawned};
+
+/// Entities announced to clients so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityLifecycle {
+    known: BTreeSet<EntityId>,
+}
+
+impl EntityLifecycle {
+    /// Forget every announcement; `entities` are known from a baseline.
+    pub fn reset(&mut self, entities: impl IntoIterator<Item = EntityId>) {
+        self.known = entities.into_iter().collect();
+    }
+
+    /// Announcements for the snapshot at `tick` holding `entities`.
+    pub fn update(
+        &mut self,
+        tick: Tick,
+        entities: &[EntitySnapshotProto],
+    ) -> (Vec<EntityRemoved>, Vec<EntitySpawned>) {
+        let current: BTreeSet<EntityId> = entities.iter().map(|e| EntityId(e.entity_id)).collect();
+        let removed = self
+            .known
+            .difference(&current)
+            .map(|&entity_id| EntityRemoved {
+                entity_id: entity_id.into(),
+                tick,
+            })
+            .collect();
+        let spawned = entities
+            .iter()
+            .filter(|e| !self.known.contains(&EntityId(e.entity_id)))
+            .map(|e| EntitySpawned {
+                entity_id: e.entity_id,
+                entity_kind: e.entity_kind,
+                owner_player_id: e.owner_player_id,
+                tick,
+            })
+            .collect();
+        self.known = current;
+        (removed, spawned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstate_wire::EntityKind;
+
+    fn entity(entity_id: EntityId) -> EntitySnapshotProto {
+        EntitySnapshotProto {
+            entity_id: entity_id.into(),
+            entity_kind: EntityKind::Projectile as i32,
+            owner_player_id: Some(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_update_announces_changes_once() {
+        let mut lifecycle = EntityLifecycle::default();
+        lifecycle.reset([EntityId(1), EntityId(2)]);
+        assert_eq!(
+            lifecycle.update(5, &[entity(EntityId(1)), entity(EntityId(2))]),
+            (vec![], vec![])
+        );
+
+        let (removed, spawned) = lifecycle.update(6, &[entity(EntityId(2)), entity(EntityId(3))]);
+        assert_eq!(
+            removed,
+            vec![EntityRemoved {
+                entity_id: 1,
+                tick: 6
+            }]
+        );
+        assert_eq!(
+            spawned,
+            vec![EntitySpawned {
+                entity_id: 3,
+                entity_kind: EntityKind::Projectile as i32,
+                owner_player_id: Some(1),
+                tick: 6,
+            }]
+        );
+        assert_eq!(
+            lifecycle.update(7, &[entity(EntityId(2)), entity(EntityId(3))]),
+            (vec![], vec![])
+        );
+    }
+}
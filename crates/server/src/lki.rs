@@ -0,0 +1,91 @@
+//! LastKnownIntent decay policy.
+//!
+//! Ref: DM-0023 (LastKnownIntent), DM-0024 (AppliedInput), INV-0006
+//! - v0 reuses a player's last move_dir for every missing tick, so a player
+//!   who drops off glides forever
+//! - With decay, after `after_ticks` consecutive fallback ticks the intent
+//!   ramps linearly to zero over `ramp_ticks` (0 = drops to zero at once)
+//! - Decay always starts from the last *received* intent, never from an
+//!   already-decayed value
+//!
+//! The decayed move_dir is what gets recorded as the fallback AppliedInput,
+//! so replays reproduce it without knowing the policy; the policy itself is
+//! recorded in the artifact's tuning parameters so verifiers can check the
+//! fallback stream against it.
+
+use serde::{Deserialize, Serialize};
+
+/// Tuning-parameter key for `LkiDecay::after_ticks`.
+pub const TUNING_KEY_AFTER_TICKS: &str = "lki_decay_after_ticks";
+
+/// Tuning-parameter key for `LkiDecay::ramp_ticks`.
+pub const TUNING_KEY_RAMP_TICKS: &str = "lki_decay_ramp_ticks";
+
+/// LastKnownIntent decay policy. The default never decays (v0 behavior).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LkiDecay {
+    /// Consecutive fallback ticks at full intent before decay starts
+    /// (0 = never decay).
+    pub after_ticks: u64,
+    /// Ticks over which intent ramps down to zero (0 = immediately).
+    pub ramp_ticks: u64,
+}
+
+impl LkiDecay {
+    /// Intent scale for the `streak`-th consecutive fallback tick (1-based).
+    pub fn scale(&self, streak: u64) -> f64 {
+        if self.after_ticks == 0 || streak <= self.after_ticks {
+            return 1.0;
+        }
+        let decayed = streak - self.after_ticks;
+        if decayed >= self.ramp_ticks {
+            return 0.0;
+        }
+        1.0 - decayed as f64 / self.ramp_ticks as f64
+    }
+
+    /// Fallback move_dir for the `streak`-th consecutive missing tick.
+    pub fn apply(&self, intent: [f64; 2], streak: u64) -> [f64; 2] {
+        let scale = self.scale(streak);
+        if scale == 1.0 {
+            intent
+        } else {
+            [intent[0] * scale, intent[1] * scale]
+        }
+    }
+
+    /// Policy as replay tuning parameters.
+    pub fn tuning_parameters(&self) -> [(&'static str, f64); 2] {
+        [
+            (TUNING_KEY_AFTER_TICKS, self.after_ticks as f64),
+            (TUNING_KEY_RAMP_TICKS, self.ramp_ticks as f64),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decay_curve() {
+        let never = LkiDecay::default();
+        assert_eq!(never.apply([1.0, 0.0], 1_000_000), [1.0, 0.0]);
+
+        let ramp = LkiDecay {
+            after_ticks: 2,
+            ramp_ticks: 4,
+        };
+        let scales: Vec<f64> = (1..=7).map(|streak| ramp.scale(streak)).collect();
+        assert_eq!(scales, vec![1.0, 1.0, 0.75, 0.5, 0.25, 0.0, 0.0]);
+        assert_eq!(ramp.apply([0.0, -1.0], 4), [0.0, -0.5]);
+
+        let cut = LkiDecay {
+            after_ticks: 3,
+            ramp_ticks: 0,
+        };
+        assert_eq!(cut.apply([1.0, 0.0], 3), [1.0, 0.0]);
+        assert_eq!(cut.apply([1.0, 0.0], 4), [0.0, 0.0]);
+    }
+}
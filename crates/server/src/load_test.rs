@@ -0,0 +1,202 @@
+//! Headless load testing against a `MatchHost`.
+//!
+//! Ref: DM-0011 (Server Edge)
+//! - Spins up N two-player matches with simulated clients (no sockets)
+//! - Steps all matches on a wall-clock budget of one tick period
+//! - Reports tick overruns, input drop rates, and input buffer memory
+//!
+//! Wall-clock reads here measure host performance only; they never reach the
+//! Simulation Core (INV-0004).
+
+use std::time::{Duration, Instant};
+
+use flowstate_wire::InputCmdProto;
+
+use crate::host::MatchHost;
+use crate::{ServerConfig, TICK_RATE_HZ};
+
+/// Load test parameters.
+#[derive(Debug, Clone)]
+pub struct LoadTestConfig {
+    /// Number of concurrent matches (two simulated clients each).
+    pub matches: usize,
+    /// Ticks to run every match for.
+    pub ticks: u64,
+    /// Tick rate; defines the per-tick time budget.
+    pub tick_rate_hz: u32,
+    /// Inputs each client sends per tick.
+    pub inputs_per_client_per_tick: u32,
+}
+
+impl Default for LoadTestConfig {
+    fn default() -> Self {
+        Self {
+            matches: 100,
+            ticks: 600,
+            tick_rate_hz: TICK_RATE_HZ,
+            inputs_per_client_per_tick: 1,
+        }
+    }
+}
+
+/// Load test results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadTestReport {
+    pub matches: usize,
+    pub clients: usize,
+    pub ticks_run: u64,
+    /// Wall-clock budget per host tick (1 / tick_rate_hz).
+    pub tick_budget: Duration,
+    /// Host ticks whose processing exceeded `tick_budget`.
+    pub tick_overruns: u64,
+    pub max_tick_time: Duration,
+    pub mean_tick_time: Duration,
+    pub inputs_sent: u64,
+    pub inputs_accepted: u64,
+    pub inputs_dropped: u64,
+    /// Peak buffered input entries across all matches.
+    pub peak_buffered_inputs: usize,
+    /// Estimated peak input buffer memory across all matches.
+    pub peak_buffer_bytes: usize,
+}
+
+impl LoadTestReport {
+    /// Fraction of sent inputs that were dropped (0.0 if none sent).
+    pub fn drop_rate(&self) -> f64 {
+        if self.inputs_sent == 0 {
+            0.0
+        } else {
+            self.inputs_dropped as f64 / self.inputs_sent as f64
+        }
+    }
+}
+
+/// Run a load test to completion.
+pub fn run_load_test(config: &LoadTestConfig) -> LoadTestReport {
+    let tick_budget = Duration::from_secs_f64(1.0 / f64::from(config.tick_rate_hz));
+
+    let mut host = MatchHost::new();
+    let mut clients = Vec::new();
+    for _ in 0..config.matches {
+        let slot = host.create_match(ServerConfig {
+            tick_rate_hz: config.tick_rate_hz,
+            match_duration_ticks: config.ticks,
+            ..Default::default()
+        });
+        let server = host.get_mut(slot).expect("slot just created");
+        let (s1, _, _) = server.accept_session();
+        let (s2, _, _) = server.accept_session();
+        server.start_match();
+        clients.push((slot, s1));
+        clients.push((slot, s2));
+    }
+
+    let mut report = LoadTestReport {
+        matches: config.matches,
+        clients: clients.len(),
+        ticks_run: 0,
+        tick_budget,
+        tick_overruns: 0,
+        max_tick_time: Duration::ZERO,
+        mean_tick_time: Duration::ZERO,
+        inputs_sent: 0,
+        inputs_accepted: 0,
+        inputs_dropped: 0,
+        peak_buffered_inputs: 0,
+        peak_buffer_bytes: 0,
+    };
+    let mut total_time = Duration::ZERO;
+    let mut input_seq: u64 = 0;
+
+    for tick_index in 0..config.ticks {
+        let started = Instant::now();
+
+        for (client_index, &(slot, session_id)) in clients.iter().enumerate() {
+            let server = host.get_mut(slot).expect("slot exists");
+            let target = server.current_tick() + server.config().input_lead_ticks;
+            for _ in 0..config.inputs_per_client_per_tick {
+                input_seq += 1;
+                // Deterministic, varied movement per client
+                let angle = (tick_index + client_index as u64) as f64 * 0.1;
+                let result = server.receive_input(
+                    session_id,
+                    InputCmdProto {
+                        tick: target,
+                        input_seq,
+                        move_dir: vec![angle.cos(), angle.sin()],
+                    },
+                );
+                report.inputs_sent += 1;
+                if result.is_accepted() {
+                    report.inputs_accepted += 1;
+                } else {
+                    report.inputs_dropped += 1;
+                }
+            }
+        }
+
+        let (buffered, bytes) = host.iter().fold((0, 0), |(n, b), (_, server)| {
+            (
+                n + server.buffered_input_count(),
+                b + server.buffered_input_bytes(),
+            )
+        });
+        report.peak_buffered_inputs = report.peak_buffered_inputs.max(buffered);
+        report.peak_buffer_bytes = report.peak_buffer_bytes.max(bytes);
+
+        host.step_all();
+
+        let elapsed = started.elapsed();
+        total_time += elapsed;
+        report.max_tick_time = report.max_tick_time.max(elapsed);
+        if elapsed > tick_budget {
+            report.tick_overruns += 1;
+        }
+        report.ticks_run += 1;
+    }
+
+    if report.ticks_run > 0 {
+        report.mean_tick_time = total_time / u32::try_from(report.ticks_run).unwrap_or(u32::MAX);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_test_report_counts() {
+        let config = LoadTestConfig {
+            matches: 4,
+            ticks: 30,
+            ..Default::default()
+        };
+        let report = run_load_test(&config);
+
+        assert_eq!(report.matches, 4);
+        assert_eq!(report.clients, 8);
+        assert_eq!(report.ticks_run, 30);
+        assert_eq!(report.inputs_sent, 8 * 30);
+        assert_eq!(report.inputs_dropped, 0);
+        assert_eq!(report.drop_rate(), 0.0);
+        assert!(report.peak_buffered_inputs > 0);
+        assert!(report.peak_buffer_bytes > 0);
+        assert!(report.max_tick_time >= report.mean_tick_time);
+    }
+
+    #[test]
+    fn test_load_test_reports_rate_limit_drops() {
+        // 3 inputs per tick exceeds the v0 per-tick limit of 2
+        let config = LoadTestConfig {
+            matches: 2,
+            ticks: 10,
+            inputs_per_client_per_tick: 3,
+            ..Default::default()
+        };
+        let report = run_load_test(&config);
+        assert_eq!(report.inputs_sent, 4 * 10 * 3);
+        assert_eq!(report.inputs_dropped, 4 * 10);
+        assert!(report.drop_rate() > 0.3);
+    }
+}
@@ -0,0 +1,1009 @@
+//! `flowstate-server`: runs one match over UDP.
+//!
+//! Wires together config loading, the UDP transport, `MatchHost`,
+//! `ReplayStorage`, a once-per-second metrics line on stderr, and optional
+//! HTTP health/readiness probes. Outbound messages go through a per-session
+//! `BandwidthShaper` before reaching the socket. Clients that complete a key
+//! exchange get their Realtime traffic sealed (`secure_channel`), under keys
+//! authenticated by the server's static key: read from `--key-file` (64 hex
+//! characters), or generated per run and logged at startup.
+//!
+//! ```text
+//! flowstate-server [--config FILE] [--port N] [--seed N] [--players N]
+//!                  [--replay-dir DIR] [--match-id ID] [--health-port N]
+//!                  [--key-file FILE]
+//! ```
+//!
+//! Flags override values from the JSON config file. Every stderr line carries
+//! a `match=<MatchId>` label (DM-0021) for correlation with the replay and
+//! summary.
+
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use flowstate_server::admission::{AdmissionConfig, ConnectionGuard};
+use flowstate_server::bandwidth::{BandwidthShaper, SendClass};
+use flowstate_server::clock::{Clock, SystemClock};
+use flowstate_server::control::ControlMessage;
+use flowstate_server::health::{DEFAULT_STALL_THRESHOLD_MICROS, HealthProbe, HealthServer};
+use flowstate_server::host::{MatchHost, MatchSlotId};
+use flowstate_server::replay_chunks::ReplayChunkError;
+use flowstate_server::replay_storage::{ReplayStorage, ReplayStorageConfig};
+use flowstate_server::scheduler::{SchedulerStats, TickScheduler};
+use flowstate_server::secure_channel::{PeerKeys, ServerKey};
+use flowstate_server::session::SessionId;
+use flowstate_server::transport::{self, Datagram, UdpTransport};
+use flowstate_server::{EndReason, ServerConfig};
+use flowstate_wire::control_message::Payload as ControlPayload;
+use flowstate_wire::realtime_message::Payload as RealtimePayload;
+use flowstate_wire::sequence::SequenceWindow;
+use flowstate_wire::{ClientHello, ErrorCode, ErrorResponse, capabilities};
+
+const DEFAULT_PORT: u16 = 7777;
+const DEFAULT_REPLAY_DIR: &str = "replays";
+/// v0 matches are exactly two players (FS-0007).
+const SUPPORTED_PLAYERS: usize = 2;
+
+const USAGE: &str = "Usage: flowstate-server [--config FILE] [--port N] [--seed N] \
+[--players N] [--replay-dir DIR] [--match-id ID] [--health-port N] [--key-file FILE]";
+
+/// Parsed command-line flags.
+#[derive(Debug, Clone, PartialEq)]
+struct CliArgs {
+    config: Option<PathBuf>,
+    port: u16,
+    seed: Option<u64>,
+    players: usize,
+    replay_dir: PathBuf,
+    match_id: Option<String>,
+    health_port: Option<u16>,
+    key_file: Option<PathBuf>,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            config: None,
+            port: DEFAULT_PORT,
+            seed: None,
+            players: SUPPORTED_PLAYERS,
+            replay_dir: PathBuf::from(DEFAULT_REPLAY_DIR),
+            match_id: None,
+            health_port: None,
+            key_file: None,
+        }
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+    let mut parsed = CliArgs::default();
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{flag} requires a value"))
+        };
+        match flag.as_str() {
+            "--config" => parsed.config = Some(PathBuf::from(value()?)),
+            "--port" => parsed.port = parse_number(&flag, &value()?)?,
+            "--seed" => parsed.seed = Some(parse_number(&flag, &value()?)?),
+            "--players" => parsed.players = parse_number(&flag, &value()?)?,
+            "--replay-dir" => parsed.replay_dir = PathBuf::from(value()?),
+            "--match-id" => parsed.match_id = Some(value()?),
+            "--health-port" => parsed.health_port = Some(parse_number(&flag, &value()?)?),
+            "--key-file" => parsed.key_file = Some(PathBuf::from(value()?)),
+            _ => return Err(format!("Unknown argument {flag}")),
+        }
+    }
+    if parsed.players != SUPPORTED_PLAYERS {
+        return Err(format!(
+            "--players {} is not supported; v0 matches have exactly {SUPPORTED_PLAYERS} players",
+            parsed.players
+        ));
+    }
+    Ok(parsed)
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{flag}: invalid number {value:?}"))
+}
+
+/// Load the config file (if any) and apply flag overrides.
+fn load_config(args: &CliArgs) -> Result<ServerConfig, String> {
+    let mut config = match &args.config {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .map_err(|e| format!("Reading {}: {e}", path.display()))?;
+            serde_json::from_str(&json).map_err(|e| format!("Parsing {}: {e}", path.display()))?
+        }
+        None => ServerConfig::default(),
+    };
+    if let Some(seed) = args.seed {
+        config.seed = seed;
+    }
+    if let Some(match_id) = &args.match_id {
+        config.match_id = match_id.clone();
+    }
+    Ok(config)
+}
+
+/// Load the server's static key from a file of 64 hex characters, or
+/// generate one for this run.
+fn load_server_key(path: Option<&std::path::Path>) -> Result<ServerKey, String> {
+    let Some(path) = path else {
+        return ServerKey::generate().map_err(|e| format!("Generating server key: {e}"));
+    };
+    let hex =
+        std::fs::read_to_string(path).map_err(|e| format!("Reading {}: {e}", path.display()))?;
+    let hex = hex.trim();
+    let invalid = || format!("{}: expected 64 hex characters", path.display());
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let mut secret = [0u8; 32];
+    for (i, byte) in secret.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(ServerKey::from_secret_bytes(secret))
+}
+
+/// Counters reported once per second.
+#[derive(Debug, Default)]
+struct Metrics {
+    datagrams_in: u64,
+    datagrams_out: u64,
+    bytes_in: u64,
+    bytes_out: u64,
+    malformed: u64,
+    /// Receive errors tied to one datagram (e.g., an ICMP unreachable
+    /// surfacing as `ConnectionReset`) that did not stop the loop.
+    recv_errors: u64,
+    /// Sequenced realtime datagrams dropped as duplicate or stale.
+    duplicates: u64,
+    refused_handshakes: u64,
+}
+
+impl Metrics {
+    fn report(
+        &self,
+        match_id: &str,
+        tick: u64,
+        sessions: usize,
+        buffered: usize,
+        schedule: SchedulerStats,
+        shaper: &BandwidthShaper,
+    ) {
+        let outbound = shaper.total_stats();
+        eprintln!(
+            "match={match_id} tick={tick} sessions={sessions} buffered={buffered} in={}/{}B out={}/{}B queued={}B peak_queued={}B depth={} superseded={} shaped_drops={} malformed={} recv_errors={} duplicates={} refused={} overruns={} skipped={}",
+            self.datagrams_in,
+            self.bytes_in,
+            self.datagrams_out,
+            self.bytes_out,
+            outbound.bytes_queued,
+            outbound.peak_bytes_queued,
+            shaper.max_queue_depth(),
+            outbound.superseded,
+            outbound.dropped_overflow,
+            self.malformed,
+            self.recv_errors,
+            self.duplicates,
+            self.refused_handshakes,
+            schedule.overruns,
+            schedule.ticks_skipped
+        );
+    }
+}
+
+/// One match over UDP: the host loop and its peer bookkeeping.
+struct ServerApp {
+    transport: UdpTransport,
+    host: MatchHost,
+    slot: MatchSlotId,
+    match_id: String,
+    guard: ConnectionGuard,
+    clock: SystemClock,
+    scheduler: TickScheduler,
+    peers: BTreeMap<SocketAddr, SessionId>,
+    /// Inbound realtime sequence numbers per peer address.
+    sequences: BTreeMap<SocketAddr, SequenceWindow>,
+    server_key: ServerKey,
+    /// Realtime Channel keys per peer address that sent a KeyExchangeInit.
+    keys: BTreeMap<SocketAddr, PeerKeys>,
+    shaper: BandwidthShaper,
+    metrics: Metrics,
+}
+
+impl ServerApp {
+    /// Create the match on `host` and serve it over `transport`.
+    fn new(
+        transport: UdpTransport,
+        mut host: MatchHost,
+        config: ServerConfig,
+        server_key: ServerKey,
+    ) -> Self {
+        let clock = SystemClock::new();
+        let scheduler = TickScheduler::new(config.tick_rate_hz, clock.now_micros());
+        let shaper = BandwidthShaper::new(config.bandwidth_budget());
+        let slot = host.create_match(config);
+        let match_id = host
+            .get(slot)
+            .map(|server| server.match_id().to_string())
+            .unwrap_or_default();
+        Self {
+            transport,
+            host,
+            slot,
+            match_id,
+            guard: ConnectionGuard::new(AdmissionConfig::default()),
+            clock,
+            scheduler,
+            peers: BTreeMap::new(),
+            sequences: BTreeMap::new(),
+            server_key,
+            keys: BTreeMap::new(),
+            shaper,
+            metrics: Metrics::default(),
+        }
+    }
+
+    fn send(&mut self, to: SocketAddr, datagram: &[u8]) {
+        if self.transport.send(to, datagram).is_ok() {
+            self.metrics.datagrams_out += 1;
+            self.metrics.bytes_out += datagram.len() as u64;
+        }
+    }
+
+    /// Queue a datagram for a session; `flush` sends it when budget allows.
+    fn send_to_session(&mut self, session_id: SessionId, class: SendClass, datagram: Vec<u8>) {
+        if cfg!(debug_assertions)
+            && let Err(e) = transport::check_send_class(&datagram, class)
+        {
+            panic!("refusing to queue for session {session_id}: {e}");
+        }
+        let datagram = match self
+            .server()
+            .session(session_id)
+            .and_then(|s| s.compression)
+        {
+            Some(params) => transport::compress(datagram, &params),
+            None => datagram,
+        };
+        let addr = self
+            .peers
+            .iter()
+            .find_map(|(&addr, &peer)| (peer == session_id).then_some(addr));
+        let datagram = match addr.and_then(|addr| self.keys.get_mut(&addr)) {
+            Some(keys) => transport::seal(datagram, keys),
+            None => datagram,
+        };
+        let now = self.clock.now_micros();
+        self.shaper.enqueue(session_id, class, datagram, now);
+    }
+
+    /// Queue the Server's pending Control messages.
+    fn queue_control(&mut self) {
+        for (session_id, message) in self.server().drain_control() {
+            let datagram = match message {
+                ControlMessage::FloorUpdate(update) => {
+                    transport::frame_control(ControlPayload::FloorUpdate(update))
+                }
+                ControlMessage::PlayerLeft(left) => {
+                    transport::frame_control(ControlPayload::PlayerLeft(left))
+                }
+                ControlMessage::MatchEnd(match_end) => {
+                    transport::frame_control(ControlPayload::MatchEnd(match_end))
+                }
+                ControlMessage::InputRejectionReport(report) => {
+                    transport::frame_control(ControlPayload::InputRejectionReport(report))
+                }
+                ControlMessage::InputTimingReport(report) => {
+                    transport::frame_control(ControlPayload::InputTimingReport(report))
+                }
+                ControlMessage::EntitySpawned(spawned) => {
+                    transport::frame_control(ControlPayload::EntitySpawned(spawned))
+                }
+                ControlMessage::EntityRemoved(removed) => {
+                    transport::frame_control(ControlPayload::EntityRemoved(removed))
+                }
+            };
+            self.send_to_session(session_id, SendClass::Event, datagram);
+        }
+    }
+
+    /// Send whatever each session's budget allows.
+    fn flush(&mut self) {
+        let now = self.clock.now_micros();
+        let peers: Vec<_> = self.peers.iter().map(|(&a, &s)| (a, s)).collect();
+        for (addr, session_id) in peers {
+            for datagram in self.shaper.drain(session_id, now) {
+                self.send(addr, &datagram);
+            }
+        }
+    }
+
+    /// Handle every datagram waiting on the socket.
+    fn poll(&mut self) -> std::io::Result<()> {
+        loop {
+            let (from, datagram) = match self.transport.recv() {
+                Ok(Some(received)) => received,
+                Ok(None) => break,
+                // Only that datagram is lost; the socket is still usable
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        ErrorKind::ConnectionReset | ErrorKind::Interrupted
+                    ) =>
+                {
+                    self.metrics.recv_errors += 1;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            self.metrics.datagrams_in += 1;
+            self.metrics.bytes_in += datagram.len() as u64;
+            if self.handle(from, &datagram).is_none() {
+                self.metrics.malformed += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// `None` if the datagram was malformed.
+    fn handle(&mut self, from: SocketAddr, datagram: &[u8]) -> Option<()> {
+        let require_encryption = self.server().config().require_encryption;
+        let (sequence, datagram) =
+            transport::unframe_secure(datagram, self.keys.get_mut(&from), require_encryption)?;
+        // Windows are kept for known peers only, so spoofed sources cannot grow the map
+        if let Some(sequence) = sequence
+            && self.peers.contains_key(&from)
+            && !self
+                .sequences
+                .entry(from)
+                .or_default()
+                .accept(sequence)
+                .is_fresh()
+        {
+            self.metrics.duplicates += 1;
+            return Some(());
+        }
+        match datagram {
+            Datagram::Control(ControlPayload::ClientHello(hello)) => {
+                self.handle_hello(from, &hello);
+            }
+            // Only a rekey sealed under the peer's current keys gets here
+            Datagram::Control(ControlPayload::KeyExchangeInit(init)) => {
+                let session_id = *self.peers.get(&from)?;
+                let keys = self.keys.get_mut(&from)?;
+                let payload = match keys.respond(&init, &self.server_key) {
+                    Ok(response) => ControlPayload::KeyExchangeResponse(response),
+                    Err(e) => ControlPayload::ErrorResponse(e.to_error_response()),
+                };
+                let datagram = transport::frame_control(payload);
+                self.send_to_session(session_id, SendClass::Event, datagram);
+            }
+            Datagram::Realtime(RealtimePayload::InputCmd(input)) => {
+                let session_id = *self.peers.get(&from)?;
+                self.server().receive_input(session_id, input);
+            }
+            Datagram::Realtime(RealtimePayload::InputBundle(bundle)) => {
+                let session_id = *self.peers.get(&from)?;
+                self.server().receive_input_bundle(session_id, bundle);
+            }
+            Datagram::Realtime(RealtimePayload::RedundantInputCmd(input)) => {
+                let session_id = *self.peers.get(&from)?;
+                self.server().receive_redundant_input(session_id, &input)?;
+            }
+            Datagram::Realtime(RealtimePayload::SnapshotAck(ack)) => {
+                let session_id = *self.peers.get(&from)?;
+                self.server().receive_snapshot_ack(session_id, &ack);
+            }
+            Datagram::Control(ControlPayload::Heartbeat(heartbeat))
+            | Datagram::Realtime(RealtimePayload::Heartbeat(heartbeat)) => {
+                let session_id = *self.peers.get(&from)?;
+                self.server().receive_heartbeat(session_id, &heartbeat);
+            }
+            Datagram::Realtime(RealtimePayload::KeyframeRequest(request)) => {
+                let session_id = *self.peers.get(&from)?;
+                self.server().receive_keyframe_request(session_id, &request);
+            }
+            Datagram::Control(ControlPayload::TimeSyncReport(report)) => {
+                let session_id = *self.peers.get(&from)?;
+                self.server().receive_time_sync_report(session_id, &report);
+            }
+            Datagram::Control(ControlPayload::ReplayChunkRequest(request)) => {
+                let session_id = *self.peers.get(&from)?;
+                // Chunks are droppable; refusals are not, so a flood gets no reply
+                let (payload, class) = match self.server().replay_chunk(session_id, &request) {
+                    Ok(chunk) => (ControlPayload::ReplayChunk(chunk), SendClass::Bulk),
+                    Err(ReplayChunkError::RateLimited) => return Some(()),
+                    Err(e) => (
+                        ControlPayload::ErrorResponse(e.to_error_response()),
+                        SendClass::Event,
+                    ),
+                };
+                let datagram = transport::frame_control(payload);
+                self.send_to_session(session_id, class, datagram);
+            }
+            Datagram::Control(ControlPayload::BaselineRequest(request)) => {
+                let session_id = *self.peers.get(&from)?;
+                let payload = match self.server().baseline_update(session_id, &request) {
+                    Ok(update) => ControlPayload::BaselineUpdate(update),
+                    Err(e) => ControlPayload::ErrorResponse(e.to_error_response()),
+                };
+                let datagram = transport::frame_control(payload);
+                self.send_to_session(session_id, SendClass::Event, datagram);
+            }
+            Datagram::Control(ControlPayload::ChatSend(chat)) => {
+                let session_id = *self.peers.get(&from)?;
+                // Rejected chat (rate limit, filter) is dropped, not malformed
+                if let Ok(broadcast) = self.server().receive_chat(session_id, &chat) {
+                    let datagram =
+                        transport::frame_control(ControlPayload::ChatBroadcast(broadcast));
+                    let sessions: Vec<SessionId> = self.peers.values().copied().collect();
+                    for session_id in sessions {
+                        self.send_to_session(session_id, SendClass::Chat, datagram.clone());
+                    }
+                }
+            }
+            Datagram::Control(ControlPayload::TimeSyncPing(ping)) => {
+                let session_id = *self.peers.get(&from)?;
+                let pong = self.server().handle_time_sync(session_id, &ping)?;
+                let datagram = transport::frame_control(ControlPayload::TimeSyncPong(pong));
+                self.send_to_session(session_id, SendClass::Event, datagram);
+            }
+            // Server-to-client kinds (and unhandled ones) are not accepted from clients
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Tell a refused peer why, instead of dropping it silently.
+    fn refuse(&mut self, to: SocketAddr, error: ErrorResponse) {
+        self.metrics.refused_handshakes += 1;
+        self.send(
+            to,
+            &transport::frame_control(ControlPayload::ErrorResponse(error)),
+        );
+    }
+
+    fn handle_hello(&mut self, from: SocketAddr, hello: &ClientHello) {
+        let now = self.clock.now_micros();
+        // A repeated hello from a connected peer is a retransmit, not a new handshake
+        if self.peers.contains_key(&from) {
+            self.metrics.refused_handshakes += 1;
+            return;
+        }
+        if let Err(e) = self.guard.on_handshake(from, now) {
+            self.refuse(from, e.to_error_response());
+            return;
+        }
+        let server = self.server();
+        if server.is_running() || server.is_ready_to_start() {
+            self.guard.on_closed(from);
+            self.refuse(
+                from,
+                ErrorResponse {
+                    code: ErrorCode::MatchInProgress as i32,
+                    message: "Match already in progress".to_string(),
+                    retryable: false,
+                },
+            );
+            return;
+        }
+        // Keys are agreed with the hello that creates the session, so no
+        // other source can install them
+        let require_encryption = server.config().require_encryption;
+        let mut keys = PeerKeys::default();
+        let key_exchange = match &hello.key_exchange {
+            Some(init) => match keys.respond(init, &self.server_key) {
+                Ok(response) => Some(response),
+                Err(e) => {
+                    self.guard.on_closed(from);
+                    self.refuse(from, e.to_error_response());
+                    return;
+                }
+            },
+            None if require_encryption => {
+                self.guard.on_closed(from);
+                self.refuse(
+                    from,
+                    ErrorResponse {
+                        code: ErrorCode::KeyExchangeFailed as i32,
+                        message: "Encryption required".to_string(),
+                        retryable: false,
+                    },
+                );
+                return;
+            }
+            None => None,
+        };
+        match self.server().accept_hello(hello) {
+            Ok((session_id, player_id, _)) => {
+                self.guard.on_authenticated(from);
+                // A rebound identity leaves its old address without a session
+                let server = self
+                    .host
+                    .get(self.slot)
+                    .expect("match slot lives until finalize");
+                let superseded: Vec<SocketAddr> = self
+                    .peers
+                    .iter()
+                    .filter(|&(_, &old)| server.session(old).is_none())
+                    .map(|(&addr, _)| addr)
+                    .collect();
+                for addr in superseded {
+                    self.forget_peer(addr);
+                    eprintln!(
+                        "match={} session from {addr} superseded by {from}",
+                        self.match_id
+                    );
+                }
+                self.peers.insert(from, session_id);
+                self.sequences.remove(&from);
+                match key_exchange {
+                    Some(response) => {
+                        self.keys.insert(from, keys);
+                        let datagram =
+                            transport::frame_control(ControlPayload::KeyExchangeResponse(response));
+                        self.send_to_session(session_id, SendClass::Event, datagram);
+                    }
+                    None => {
+                        self.keys.remove(&from);
+                    }
+                }
+                eprintln!(
+                    "match={} session {session_id} (player {player_id}) connected from {from}",
+                    self.match_id
+                );
+            }
+            Err(e) => {
+                self.guard.on_closed(from);
+                self.refuse(from, e.to_error_response());
+                eprintln!("match={} handshake from {from} refused: {e}", self.match_id);
+                return;
+            }
+        }
+
+        if self.server().is_ready_to_start() {
+            self.start_match();
+        }
+    }
+
+    fn start_match(&mut self) {
+        let (_, welcomes) = self.server().start_match();
+        let match_config =
+            transport::frame_control(ControlPayload::MatchConfig(self.server().match_config()));
+        let baseline =
+            transport::frame_control(ControlPayload::JoinBaseline(self.server().baseline_proto()));
+        let roster =
+            transport::frame_control(ControlPayload::PlayerRoster(self.server().player_roster()));
+        for (session_id, welcome) in welcomes {
+            let welcome = transport::frame_control(ControlPayload::ServerWelcome(welcome));
+            self.send_to_session(session_id, SendClass::Event, welcome);
+            self.send_to_session(session_id, SendClass::Event, match_config.clone());
+            self.send_to_session(session_id, SendClass::Event, baseline.clone());
+            self.send_to_session(session_id, SendClass::Event, roster.clone());
+        }
+        let (seed, source) = self.server().seed();
+        // Tick 1 is due one interval after start, however long the lobby took
+        self.scheduler.reset(self.clock.now_micros());
+        eprintln!(
+            "match={} started seed={seed} seed_source={}",
+            self.match_id,
+            source.as_str()
+        );
+    }
+
+    /// Step the match and queue snapshot payloads.
+    fn tick(&mut self) {
+        let checksums = self.server().config().realtime_checksums;
+        for (_, (_, _, payload)) in self.host.step_all() {
+            let sessions: Vec<SessionId> = self.peers.values().copied().collect();
+            for session_id in sessions {
+                if let Some(bytes) = payload.bytes_for(session_id) {
+                    // Clients that never advertised checksums get plain frames
+                    let checked = checksums
+                        && self.server().session(session_id).is_some_and(|s| {
+                            capabilities::has(s.capabilities, capabilities::REALTIME_CHECKSUMS)
+                        });
+                    let datagram = transport::frame_snapshot_bytes(bytes, checked);
+                    self.send_to_session(session_id, SendClass::Snapshot, datagram);
+                }
+            }
+        }
+    }
+
+    /// Drop sessions that went silent, freeing their addresses. Once the
+    /// match has started, this ends it with `EndReason::Disconnect`.
+    fn collect_stale_sessions(&mut self) {
+        let dropped = self.server().collect_stale_sessions();
+        if dropped.is_empty() {
+            return;
+        }
+        let stale: Vec<SocketAddr> = self
+            .peers
+            .iter()
+            .filter(|(_, session_id)| dropped.contains(session_id))
+            .map(|(&addr, _)| addr)
+            .collect();
+        for &session_id in &dropped {
+            self.shaper.remove_session(session_id);
+        }
+        for addr in stale {
+            self.forget_peer(addr);
+            eprintln!("match={} session from {addr} timed out", self.match_id);
+        }
+    }
+
+    /// Drop an address's peer state once its session is gone.
+    fn forget_peer(&mut self, addr: SocketAddr) {
+        if let Some(session_id) = self.peers.remove(&addr) {
+            self.shaper.remove_session(session_id);
+        }
+        self.sequences.remove(&addr);
+        self.keys.remove(&addr);
+        self.guard.on_closed(addr);
+    }
+
+    fn server(&mut self) -> &mut flowstate_server::Server {
+        self.host
+            .get_mut(self.slot)
+            .expect("match slot lives until finalize")
+    }
+}
+
+fn run(args: CliArgs) -> Result<(), String> {
+    let config = load_config(&args)?;
+    let mut host = MatchHost::new();
+    host.set_replay_storage(ReplayStorage::new(ReplayStorageConfig::new(
+        &args.replay_dir,
+    )));
+    let server_key = load_server_key(args.key_file.as_deref())?;
+    let transport = UdpTransport::bind(("0.0.0.0", args.port))
+        .map_err(|e| format!("Binding UDP port {}: {e}", args.port))?;
+    let mut app = ServerApp::new(transport, host, config, server_key);
+    let match_id = app.match_id.clone();
+    let server_key = &app.server_key;
+    let public_key: String = server_key
+        .public_key()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    eprintln!(
+        "match={match_id} listening on {} key={public_key}, waiting for {SUPPORTED_PLAYERS} players",
+        app.transport.local_addr().map_err(|e| e.to_string())?
+    );
+
+    let clock = app.clock.clone();
+    let probe = Arc::new(HealthProbe::new(DEFAULT_STALL_THRESHOLD_MICROS));
+    probe.heartbeat(clock.now_micros());
+    let _health = match args.health_port {
+        Some(port) => {
+            let server = HealthServer::spawn(
+                ("0.0.0.0", port),
+                Arc::clone(&probe),
+                Box::new(clock.clone()),
+            )
+            .map_err(|e| format!("Binding health port {port}: {e}"))?;
+            eprintln!("health probes on http://{}", server.local_addr());
+            Some(server)
+        }
+        None => None,
+    };
+
+    let mut last_report = app.clock.now_micros();
+    loop {
+        app.poll().map_err(|e| format!("Socket error: {e}"))?;
+
+        let now = app.clock.now_micros();
+        for _ in 0..app.scheduler.poll(now) {
+            app.tick();
+            if app.server().should_end_match().is_some() {
+                break;
+            }
+        }
+        app.queue_control();
+        app.flush();
+
+        if let Some(end_reason) = app.server().should_end_match() {
+            return finish(app, end_reason);
+        }
+
+        if now.saturating_sub(last_report) >= 1_000_000 {
+            last_report = now;
+            let (tick, sessions, buffered) = {
+                let server = app.server();
+                (
+                    server.current_tick(),
+                    server.session_count(),
+                    server.buffered_input_count(),
+                )
+            };
+            app.metrics.report(
+                &app.match_id,
+                tick,
+                sessions,
+                buffered,
+                app.scheduler.stats(),
+                &app.shaper,
+            );
+        }
+        app.guard.expire(app.clock.now_micros());
+        app.collect_stale_sessions();
+        // The single slot accepts players until its match starts
+        let accepting = !app.server().is_ready_to_start();
+        probe.set_available_slots(usize::from(accepting));
+        probe.heartbeat(app.clock.now_micros());
+
+        let wait = app.scheduler.micros_until_next(app.clock.now_micros());
+        std::thread::sleep(Duration::from_micros(wait.min(1_000)));
+    }
+}
+
+fn finish(mut app: ServerApp, end_reason: EndReason) -> Result<(), String> {
+    app.server().announce_match_end(end_reason);
+    app.queue_control();
+    app.flush();
+    let finalized = app
+        .host
+        .finalize_match(app.slot, end_reason)
+        .expect("match slot lives until finalize");
+    if let Err(e) = &finalized.artifact {
+        eprintln!("match={} replay lost: {e}", app.match_id);
+    }
+    match finalized.stored {
+        Some(Ok(path)) => eprintln!(
+            "match={} replay written to {}",
+            app.match_id,
+            path.display()
+        ),
+        Some(Err(e)) => return Err(format!("Writing replay: {e}")),
+        None => {}
+    }
+    println!("{}", finalized.summary.to_json());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let result = parse_args(args).and_then(run);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstate_server::secure_channel::{KeyExchange, SecureChannel};
+    use flowstate_wire::{KeyExchangeInit, SnapshotAck};
+
+    fn args(list: &[&str]) -> Result<CliArgs, String> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(args(&[]).unwrap(), CliArgs::default());
+        let parsed = args(&["--port", "9000", "--seed", "42", "--replay-dir", "/tmp/r"]).unwrap();
+        assert_eq!(parsed.port, 9000);
+        assert_eq!(parsed.seed, Some(42));
+        assert_eq!(parsed.replay_dir, PathBuf::from("/tmp/r"));
+
+        assert!(args(&["--port"]).is_err());
+        assert!(args(&["--port", "x"]).is_err());
+        assert!(args(&["--players", "4"]).is_err());
+        assert!(args(&["--bogus"]).is_err());
+        assert_eq!(
+            args(&["--key-file", "server.key"]).unwrap().key_file,
+            Some(PathBuf::from("server.key"))
+        );
+    }
+
+    #[test]
+    fn test_load_server_key() {
+        let path =
+            std::env::temp_dir().join(format!("flowstate-server-key-{}.hex", std::process::id()));
+        std::fs::write(&path, format!("{}\n", "07".repeat(32))).unwrap();
+        let key = load_server_key(Some(&path)).unwrap();
+        assert_eq!(
+            key.public_key(),
+            ServerKey::from_secret_bytes([7; 32]).public_key()
+        );
+
+        for bad in ["07".repeat(31), "zz".repeat(32), "é".repeat(32)] {
+            std::fs::write(&path, bad).unwrap();
+            assert!(load_server_key(Some(&path)).is_err());
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert!(load_server_key(Some(&path)).is_err());
+        assert!(load_server_key(None).is_ok());
+    }
+
+    #[test]
+    fn test_load_config_applies_overrides() {
+        let path = std::env::temp_dir().join(format!(
+            "flowstate-server-config-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"{ "seed": 1, "match_duration_ticks": 120 }"#).unwrap();
+        let config = load_config(&CliArgs {
+            config: Some(path.clone()),
+            seed: Some(7),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.match_duration_ticks, 120);
+
+        std::fs::write(&path, r#"{ "sead": 1 }"#).unwrap();
+        let args = CliArgs {
+            config: Some(path.clone()),
+            ..Default::default()
+        };
+        assert!(load_config(&args).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+    fn app(config: ServerConfig) -> ServerApp {
+        let transport = UdpTransport::bind("127.0.0.1:0").unwrap();
+        let server_key = ServerKey::from_secret_bytes([9; 32]);
+        ServerApp::new(transport, MatchHost::new(), config, server_key)
+    }
+
+    /// The Control messages queued for `session_id`.
+    fn sent_control(app: &mut ServerApp, session_id: SessionId) -> Vec<ControlPayload> {
+        let now = app.clock.now_micros();
+        app.shaper
+            .drain(session_id, now)
+            .iter()
+            .filter_map(|datagram| match transport::unframe(datagram) {
+                Some(Datagram::Control(payload)) => Some(payload),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Connect a client at `addr` that opens encryption in its hello.
+    fn connect_sealed(
+        app: &mut ServerApp,
+        addr: SocketAddr,
+        secret: u8,
+    ) -> (SessionId, u32, SecureChannel) {
+        let exchange = KeyExchange::from_secret_bytes([secret; 32]);
+        let hello = ClientHello {
+            key_exchange: Some(KeyExchangeInit {
+                public_key: exchange.public_key().to_vec(),
+                algorithms: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        app.handle(
+            addr,
+            &transport::frame_control(ControlPayload::ClientHello(hello)),
+        )
+        .unwrap();
+        let session_id = app.peers[&addr];
+        let response = sent_control(app, session_id)
+            .into_iter()
+            .find_map(|payload| match payload {
+                ControlPayload::KeyExchangeResponse(response) => Some(response),
+                _ => None,
+            })
+            .expect("hello with a key exchange is answered");
+        let channel = exchange
+            .finish_client(&response.public_key, &app.server_key.public_key())
+            .unwrap();
+        (session_id, response.key_id, channel)
+    }
+
+    fn ack(tick: u64) -> Vec<u8> {
+        transport::frame_realtime(RealtimePayload::SnapshotAck(SnapshotAck { tick }), false)
+    }
+
+    #[test]
+    fn test_spoofed_rekey_cannot_replace_keys() {
+        let mut app = app(ServerConfig::default());
+        let victim: SocketAddr = "127.0.0.1:40001".parse().unwrap();
+        let (session_id, key_id, mut channel) = connect_sealed(&mut app, victim, 1);
+
+        // A spoofer repeating plaintext Inits from the victim's address is dropped
+        for secret in [2, 3] {
+            let init = KeyExchangeInit {
+                public_key: KeyExchange::from_secret_bytes([secret; 32])
+                    .public_key()
+                    .to_vec(),
+                algorithms: Vec::new(),
+            };
+            let spoofed = transport::frame_control(ControlPayload::KeyExchangeInit(init));
+            assert!(app.handle(victim, &spoofed).is_none());
+        }
+        // So is a second hello carrying one
+        let hello = ClientHello {
+            key_exchange: Some(KeyExchangeInit {
+                public_key: KeyExchange::from_secret_bytes([4; 32])
+                    .public_key()
+                    .to_vec(),
+                algorithms: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        app.handle(
+            victim,
+            &transport::frame_control(ControlPayload::ClientHello(hello)),
+        )
+        .unwrap();
+        assert!(sent_control(&mut app, session_id).is_empty());
+
+        // The victim's own keys still work both ways
+        let sealed = transport::frame_encrypted(channel.seal_frame(key_id, &ack(1)));
+        assert!(app.handle(victim, &sealed).is_some());
+        let snapshot = transport::seal(ack(2), app.keys.get_mut(&victim).unwrap());
+        let Some(Datagram::Realtime(RealtimePayload::EncryptedFrame(frame))) =
+            transport::unframe(&snapshot)
+        else {
+            panic!("outbound realtime is sealed");
+        };
+        assert_eq!(channel.open_frame(&frame).unwrap(), ack(2));
+
+        // A rekey sealed under the current keys is accepted
+        let next = KeyExchange::from_secret_bytes([5; 32]);
+        let init = KeyExchangeInit {
+            public_key: next.public_key().to_vec(),
+            algorithms: Vec::new(),
+        };
+        let rekey = transport::frame_control(ControlPayload::KeyExchangeInit(init));
+        let sealed = transport::frame_encrypted(channel.seal_frame(key_id, &rekey));
+        assert!(app.handle(victim, &sealed).is_some());
+        let sent = sent_control(&mut app, session_id);
+        let [ControlPayload::KeyExchangeResponse(response)] = sent.as_slice() else {
+            panic!("rekey answered: {sent:?}");
+        };
+        assert_eq!(response.key_id, key_id + 1);
+    }
+
+    #[test]
+    fn test_require_encryption_refuses_plaintext() {
+        let mut app = app(ServerConfig {
+            require_encryption: true,
+            ..Default::default()
+        });
+
+        // A hello without a key exchange is refused
+        let plain: SocketAddr = "127.0.0.1:40002".parse().unwrap();
+        let hello = transport::frame_control(ControlPayload::ClientHello(ClientHello::default()));
+        app.handle(plain, &hello).unwrap();
+        assert!(app.peers.is_empty());
+        assert_eq!(app.metrics.refused_handshakes, 1);
+
+        // A connected peer's plaintext realtime is dropped, sealed is not
+        let addr: SocketAddr = "127.0.0.1:40003".parse().unwrap();
+        let (_, key_id, mut channel) = connect_sealed(&mut app, addr, 1);
+        assert!(app.handle(addr, &ack(1)).is_none());
+        let sealed = transport::frame_encrypted(channel.seal_frame(key_id, &ack(1)));
+        assert!(app.handle(addr, &sealed).is_some());
+
+        // Without the policy, a peer that never exchanged keys may send plaintext
+        let mut app = self::app(ServerConfig::default());
+        app.handle(plain, &hello).unwrap();
+        assert!(app.handle(plain, &ack(1)).is_some());
+        // ...until it has keys
+        connect_sealed(&mut app, addr, 1);
+        assert!(app.handle(addr, &ack(1)).is_none());
+    }
+}
@@ -0,0 +1,64 @@
+//! MatchId generation.
+//!
+//! Ref: DM-0021 (MatchId)
+//! - Assigned by the Server Edge when the Server is created, unless the host
+//!   supplies one (e.g., a matchmaker-issued id)
+//! - Carried in ServerWelcome, the ReplayArtifact, the MatchSummary, and the
+//!   host's log and metrics lines so all artifacts of a match correlate
+//!
+//! Format: 32 lowercase hex characters (128 random bits) in UUID grouping,
+//! e.g. `3f2a9c1e-7b4d-4e2a-9c1f-0a8b7c6d5e4f`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Generate a new collision-resistant MatchId.
+///
+/// Draws from OS entropy; if that is unavailable, falls back to wall clock
+/// time mixed with the process id (unique per host, not unpredictable).
+pub fn generate_match_id() -> String {
+    let mut bytes = [0u8; 16];
+    if getrandom::getrandom(&mut bytes).is_err() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        bytes = (nanos ^ (u128::from(std::process::id()) << 96)).to_be_bytes();
+    }
+    // RFC 4122 version 4, variant 1
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    format_match_id(&bytes)
+}
+
+fn format_match_id(bytes: &[u8; 16]) -> String {
+    let mut out = String::with_capacity(36);
+    for (i, byte) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            out.push('-');
+        }
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_ids_are_unique_and_well_formed() {
+        let a = generate_match_id();
+        let b = generate_match_id();
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 36);
+        assert_eq!(&a[14..15], "4");
+        assert!(
+            a.chars()
+                .all(|c| c == '-' || c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+        );
+        assert_eq!(
+            format_match_id(&[0xab; 16]),
+            "abababab-abab-abab-abab-abababababab"
+        );
+    }
+}
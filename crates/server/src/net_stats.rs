@@ -0,0 +1,289 @@
+//! Per-session network quality statistics for Server Edge.
+//!
+//! Ref: DM-0008 (Session), DM-0023 (LastKnownIntent), DM-0025 (TargetTickFloor)
+//! - Input arrival jitter: smoothed variation of inter-arrival times (RFC 3550 style)
+//! - Late-input rate: inputs dropped as late / inputs received
+//! - Below-floor drops: inputs targeting ticks below the emitted floor
+//! - Fallback frequency: ticks applied via LastKnownIntent / ticks observed
+//! - Snapshot loss: ticks skipped between successive SnapshotAcks / ticks spanned
+//! - `take_input_timing_report` reports the input counters accumulated
+//!   since the previous report, for the client's lead tuning
+//!
+//! Diagnostics only; nothing here feeds back into simulation state.
+
+use flowstate_sim::Tick;
+use flowstate_wire::InputTimingReport;
+
+use crate::validation::ValidationResult;
+
+/// Jitter smoothing divisor (RFC 3550 uses 1/16).
+const JITTER_GAIN_DIVISOR: f64 = 16.0;
+
+/// Network quality counters for one session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetworkStats {
+    inputs_received: u64,
+    late_drops: u64,
+    below_floor_drops: u64,
+    ticks_observed: u64,
+    fallback_ticks: u64,
+    snapshots_acked: u64,
+    snapshots_unacked: u64,
+    last_arrival_micros: Option<u64>,
+    last_interarrival_micros: Option<u64>,
+    jitter_micros: f64,
+    reported: ReportedCounters,
+}
+
+/// Counters as of the previous `InputTimingReport`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ReportedCounters {
+    inputs_received: u64,
+    late_drops: u64,
+    below_floor_drops: u64,
+    ticks_observed: u64,
+    fallback_ticks: u64,
+}
+
+impl NetworkStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an input arrival and its validation outcome.
+    pub fn record_input(&mut self, result: &ValidationResult, arrival_micros: u64) {
+        self.inputs_received += 1;
+        match result {
+            ValidationResult::DroppedLate { .. } => self.late_drops += 1,
+            ValidationResult::DroppedBelowFloor { .. } => self.below_floor_drops += 1,
+            _ => {}
+        }
+
+        if let Some(last) = self.last_arrival_micros {
+            let interarrival = arrival_micros.saturating_sub(last);
+            if let Some(prev) = self.last_interarrival_micros {
+                let deviation = interarrival.abs_diff(prev) as f64;
+                self.jitter_micros += (deviation - self.jitter_micros) / JITTER_GAIN_DIVISOR;
+            }
+            self.last_interarrival_micros = Some(interarrival);
+        }
+        self.last_arrival_micros = Some(arrival_micros);
+    }
+
+    /// Record one simulated tick for this session's player.
+    pub fn record_tick(&mut self, is_fallback: bool) {
+        self.ticks_observed += 1;
+        if is_fallback {
+            self.fallback_ticks += 1;
+        }
+    }
+
+    /// Record a SnapshotAck for `tick`, newer than the `previous` acked tick.
+    pub fn record_snapshot_ack(&mut self, previous: Option<Tick>, tick: Tick) {
+        self.snapshots_acked += 1;
+        if let Some(previous) = previous {
+            self.snapshots_unacked += tick.saturating_sub(previous).saturating_sub(1);
+        }
+    }
+
+    /// Total inputs received (any outcome).
+    pub fn inputs_received(&self) -> u64 {
+        self.inputs_received
+    }
+
+    /// Inputs dropped because their tick had already been simulated.
+    pub fn late_drops(&self) -> u64 {
+        self.late_drops
+    }
+
+    /// Inputs dropped for targeting a tick below TargetTickFloor.
+    pub fn below_floor_drops(&self) -> u64 {
+        self.below_floor_drops
+    }
+
+    /// Ticks for which an AppliedInput was produced for this player.
+    pub fn ticks_observed(&self) -> u64 {
+        self.ticks_observed
+    }
+
+    /// Ticks applied via LastKnownIntent fallback.
+    pub fn fallback_ticks(&self) -> u64 {
+        self.fallback_ticks
+    }
+
+    /// Smoothed input arrival jitter in microseconds.
+    pub fn jitter_micros(&self) -> f64 {
+        self.jitter_micros
+    }
+
+    /// Snapshots acknowledged by the client.
+    pub fn snapshots_acked(&self) -> u64 {
+        self.snapshots_acked
+    }
+
+    /// Snapshot ticks skipped between successive acks (lost snapshots or acks).
+    pub fn snapshots_unacked(&self) -> u64 {
+        self.snapshots_unacked
+    }
+
+    /// Fraction of snapshot ticks spanned by acks that went unacknowledged
+    /// (0.0 if none acked).
+    pub fn snapshot_loss_rate(&self) -> f64 {
+        ratio(
+            self.snapshots_unacked,
+            self.snapshots_acked + self.snapshots_unacked,
+        )
+    }
+
+    /// Fraction of received inputs that were dropped as late (0.0 if none received).
+    pub fn late_input_rate(&self) -> f64 {
+        ratio(self.late_drops, self.inputs_received)
+    }
+
+    /// Fraction of observed ticks that used LastKnownIntent (0.0 if none observed).
+    pub fn fallback_rate(&self) -> f64 {
+        ratio(self.fallback_ticks, self.ticks_observed)
+    }
+
+    /// Report the input counters since the previous report and start a new
+    /// window.
+    pub fn take_input_timing_report(
+        &mut self,
+        server_tick: Tick,
+        target_tick_floor: Tick,
+    ) -> InputTimingReport {
+        let now = ReportedCounters {
+            inputs_received: self.inputs_received,
+            late_drops: self.late_drops,
+            below_floor_drops: self.below_floor_drops,
+            ticks_observed: self.ticks_observed,
+            fallback_ticks: self.fallback_ticks,
+        };
+        let then = std::mem::replace(&mut self.reported, now);
+        InputTimingReport {
+            server_tick,
+            window_ticks: now.ticks_observed - then.ticks_observed,
+            inputs_received: now.inputs_received - then.inputs_received,
+            late_drops: now.late_drops - then.late_drops,
+            below_floor_drops: now.below_floor_drops - then.below_floor_drops,
+            fallback_ticks: now.fallback_ticks - then.fallback_ticks,
+            target_tick_floor,
+        }
+    }
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_counters_and_rates() {
+        let mut stats = NetworkStats::new();
+        stats.record_input(&ValidationResult::Accepted, 0);
+        stats.record_input(
+            &ValidationResult::DroppedLate {
+                tick: 1,
+                current: 2,
+            },
+            0,
+        );
+        stats.record_input(
+            &ValidationResult::DroppedBelowFloor { tick: 1, floor: 3 },
+            0,
+        );
+        stats.record_input(&ValidationResult::DroppedRateLimit, 0);
+
+        assert_eq!(stats.inputs_received(), 4);
+        assert_eq!(stats.late_drops(), 1);
+        assert_eq!(stats.below_floor_drops(), 1);
+        assert_eq!(stats.late_input_rate(), 0.25);
+    }
+
+    #[test]
+    fn test_input_timing_report_windows() {
+        let mut stats = NetworkStats::new();
+        stats.record_input(&ValidationResult::Accepted, 0);
+        stats.record_input(
+            &ValidationResult::DroppedLate {
+                tick: 1,
+                current: 2,
+            },
+            0,
+        );
+        stats.record_tick(false);
+        stats.record_tick(true);
+        let report = stats.take_input_timing_report(2, 4);
+        assert_eq!(report.window_ticks, 2);
+        assert_eq!(report.inputs_received, 2);
+        assert_eq!(report.late_drops, 1);
+        assert_eq!(report.fallback_ticks, 1);
+        assert_eq!(report.target_tick_floor, 4);
+
+        stats.record_input(
+            &ValidationResult::DroppedBelowFloor { tick: 3, floor: 5 },
+            0,
+        );
+        stats.record_tick(true);
+        let report = stats.take_input_timing_report(3, 5);
+        assert_eq!(report.window_ticks, 1);
+        assert_eq!(report.inputs_received, 1);
+        assert_eq!(report.late_drops, 0);
+        assert_eq!(report.below_floor_drops, 1);
+        assert_eq!(report.fallback_ticks, 1);
+        // Cumulative counters are unaffected
+        assert_eq!(stats.late_drops(), 1);
+    }
+
+    #[test]
+    fn test_fallback_rate() {
+        let mut stats = NetworkStats::new();
+        assert_eq!(stats.fallback_rate(), 0.0);
+        stats.record_tick(true);
+        stats.record_tick(false);
+        stats.record_tick(false);
+        stats.record_tick(true);
+        assert_eq!(stats.ticks_observed(), 4);
+        assert_eq!(stats.fallback_ticks(), 2);
+        assert_eq!(stats.fallback_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_snapshot_loss_from_ack_gaps() {
+        let mut stats = NetworkStats::new();
+        assert_eq!(stats.snapshot_loss_rate(), 0.0);
+        stats.record_snapshot_ack(None, 5);
+        stats.record_snapshot_ack(Some(5), 6);
+        // Ticks 7-8 never acked
+        stats.record_snapshot_ack(Some(6), 9);
+        assert_eq!(stats.snapshots_acked(), 3);
+        assert_eq!(stats.snapshots_unacked(), 2);
+        assert_eq!(stats.snapshot_loss_rate(), 0.4);
+    }
+
+    #[test]
+    fn test_jitter_zero_for_periodic_arrivals() {
+        let mut stats = NetworkStats::new();
+        for i in 0..10 {
+            stats.record_input(&ValidationResult::Accepted, i * 16_667);
+        }
+        assert_eq!(stats.jitter_micros(), 0.0);
+    }
+
+    #[test]
+    fn test_jitter_grows_with_irregular_arrivals() {
+        let mut stats = NetworkStats::new();
+        let arrivals = [0, 10_000, 40_000, 45_000, 90_000, 95_000];
+        for t in arrivals {
+            stats.record_input(&ValidationResult::Accepted, t);
+        }
+        assert!(stats.jitter_micros() > 0.0);
+    }
+}
@@ -0,0 +1,366 @@
+//! Network condition simulation for Server Edge tests.
+//!
+//! Ref: ADR-0005 (Realtime Channel is unreliable), ADR-0006 (floor recovery)
+//! - Injects latency, jitter, loss, duplication, and reordering between a fake
+//!   client and the Server
+//! - Time is measured in ticks and the PRNG is seeded, so every scenario is
+//!   reproducible without sockets or wall-clock pacing (FS-0007 manual-step mode)
+//!
+//! Available in unit tests and behind the `test-support` feature.
+
+/// Adverse network conditions for one direction of a link.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkConditions {
+    /// Base one-way latency in ticks.
+    pub latency_ticks: u64,
+    /// Additional uniformly distributed delay in `[0, jitter_ticks]`.
+    pub jitter_ticks: u64,
+    /// Probability a message is dropped.
+    pub loss_rate: f64,
+    /// Probability a delivered message is delivered twice.
+    pub duplicate_rate: f64,
+    /// Probability a message is held back so later messages overtake it.
+    pub reorder_rate: f64,
+}
+
+impl LinkConditions {
+    /// A perfect link: zero latency, no loss.
+    pub const IDEAL: Self = Self {
+        latency_ticks: 0,
+        jitter_ticks: 0,
+        loss_rate: 0.0,
+        duplicate_rate: 0.0,
+        reorder_rate: 0.0,
+    };
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self::IDEAL
+    }
+}
+
+/// SplitMix64 PRNG (deterministic, seedable; test infrastructure only).
+#[derive(Debug, Clone)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [0, max].
+    fn next_up_to(&mut self, max: u64) -> u64 {
+        if max == 0 {
+            0
+        } else {
+            self.next_u64() % (max + 1)
+        }
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InFlight<T> {
+    deliver_at: u64,
+    seq: u64,
+    message: T,
+}
+
+/// One direction of a simulated, lossy link.
+#[derive(Debug, Clone)]
+pub struct SimulatedLink<T> {
+    conditions: LinkConditions,
+    rng: SplitMix64,
+    in_flight: Vec<InFlight<T>>,
+    next_seq: u64,
+    sent: u64,
+    dropped: u64,
+    duplicated: u64,
+}
+
+impl<T: Clone> SimulatedLink<T> {
+    /// Create a link with the given conditions and PRNG seed.
+    pub fn new(conditions: LinkConditions, seed: u64) -> Self {
+        Self {
+            conditions,
+            rng: SplitMix64::new(seed),
+            in_flight: Vec::new(),
+            next_seq: 0,
+            sent: 0,
+            dropped: 0,
+            duplicated: 0,
+        }
+    }
+
+    /// Send a message at `now_tick`.
+    pub fn send(&mut self, now_tick: u64, message: T) {
+        self.sent += 1;
+        if self.rng.chance(self.conditions.loss_rate) {
+            self.dropped += 1;
+            return;
+        }
+
+        let copies = if self.rng.chance(self.conditions.duplicate_rate) {
+            self.duplicated += 1;
+            2
+        } else {
+            1
+        };
+
+        for _ in 0..copies {
+            let mut delay =
+                self.conditions.latency_ticks + self.rng.next_up_to(self.conditions.jitter_ticks);
+            if self.rng.chance(self.conditions.reorder_rate) {
+                delay += 1 + self.rng.next_up_to(self.conditions.jitter_ticks);
+            }
+            self.in_flight.push(InFlight {
+                deliver_at: now_tick + delay,
+                seq: self.next_seq,
+                message: message.clone(),
+            });
+            self.next_seq += 1;
+        }
+    }
+
+    /// Deliver all messages due at or before `now_tick`, in arrival order.
+    pub fn deliver(&mut self, now_tick: u64) -> Vec<T> {
+        let (mut due, pending): (Vec<_>, Vec<_>) = self
+            .in_flight
+            .drain(..)
+            .partition(|m| m.deliver_at <= now_tick);
+        self.in_flight = pending;
+        due.sort_by_key(|m| (m.deliver_at, m.seq));
+        due.into_iter().map(|m| m.message).collect()
+    }
+
+    /// Messages still in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Messages passed to `send`.
+    pub fn sent(&self) -> u64 {
+        self.sent
+    }
+
+    /// Messages dropped by simulated loss.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Messages delivered twice.
+    pub fn duplicated(&self) -> u64 {
+        self.duplicated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndReason, INPUT_LEAD_TICKS, Server, ServerConfig, SessionId, SnapshotPayload};
+    use flowstate_replay::{VerifyOptions, verify_replay};
+    use flowstate_wire::{InputCmdProto, SnapshotProto};
+
+    #[test]
+    fn test_ideal_link_delivers_in_order() {
+        let mut link = SimulatedLink::new(LinkConditions::IDEAL, 1);
+        link.send(0, 1);
+        link.send(0, 2);
+        assert_eq!(link.deliver(0), vec![1, 2]);
+        assert_eq!(link.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_latency_delays_delivery() {
+        let conditions = LinkConditions {
+            latency_ticks: 3,
+            ..LinkConditions::IDEAL
+        };
+        let mut link = SimulatedLink::new(conditions, 1);
+        link.send(10, "a");
+        assert!(link.deliver(12).is_empty());
+        assert_eq!(link.deliver(13), vec!["a"]);
+    }
+
+    #[test]
+    fn test_total_loss_and_duplication() {
+        let lossy = LinkConditions {
+            loss_rate: 1.0,
+            ..LinkConditions::IDEAL
+        };
+        let mut link = SimulatedLink::new(lossy, 1);
+        for i in 0..10 {
+            link.send(0, i);
+        }
+        assert!(link.deliver(100).is_empty());
+        assert_eq!(link.dropped(), 10);
+
+        let dup = LinkConditions {
+            duplicate_rate: 1.0,
+            ..LinkConditions::IDEAL
+        };
+        let mut link = SimulatedLink::new(dup, 1);
+        link.send(0, 7);
+        assert_eq!(link.deliver(0), vec![7, 7]);
+        assert_eq!(link.duplicated(), 1);
+    }
+
+    #[test]
+    fn test_same_seed_same_schedule() {
+        let conditions = LinkConditions {
+            latency_ticks: 2,
+            jitter_ticks: 3,
+            loss_rate: 0.2,
+            duplicate_rate: 0.1,
+            reorder_rate: 0.2,
+        };
+        let run = || {
+            let mut link = SimulatedLink::new(conditions, 42);
+            let mut delivered = Vec::new();
+            for tick in 0..100u64 {
+                link.send(tick, tick);
+                delivered.extend(link.deliver(tick).into_iter().map(|m| (tick, m)));
+            }
+            delivered
+        };
+        assert_eq!(run(), run());
+    }
+
+    /// Fake client that targets the newest floor it has seen plus a latency guess.
+    struct FakeClient {
+        session_id: SessionId,
+        known_floor: u64,
+        lead_guess: u64,
+        next_seq: u64,
+    }
+
+    impl FakeClient {
+        fn observe(&mut self, snapshot: &SnapshotProto) {
+            self.known_floor = self.known_floor.max(snapshot.target_tick_floor);
+        }
+
+        fn next_input(&mut self) -> InputCmdProto {
+            self.next_seq += 1;
+            InputCmdProto {
+                tick: self.known_floor + self.lead_guess,
+                input_seq: self.next_seq,
+                move_dir: vec![1.0, 0.0],
+            }
+        }
+    }
+
+    /// Run a two-client match over simulated links; returns the server artifact
+    /// verification result and the number of fallback inputs applied.
+    fn run_adverse_match(conditions: LinkConditions, ticks: u64) -> (bool, usize, usize) {
+        let config = ServerConfig {
+            match_duration_ticks: ticks,
+            ..Default::default()
+        };
+        let mut server = Server::new(config);
+        let (s1, _, _) = server.accept_session();
+        let (s2, _, _) = server.accept_session();
+        let (_, welcomes) = server.start_match();
+
+        let mut clients: Vec<FakeClient> = welcomes
+            .iter()
+            .map(|(sid, w)| FakeClient {
+                session_id: *sid,
+                known_floor: w.target_tick_floor,
+                lead_guess: conditions.latency_ticks * 2 + conditions.jitter_ticks,
+                next_seq: 0,
+            })
+            .collect();
+        let mut uplinks: Vec<SimulatedLink<InputCmdProto>> = (0..2)
+            .map(|i| SimulatedLink::new(conditions, 100 + i))
+            .collect();
+        let mut downlinks: Vec<SimulatedLink<SnapshotProto>> = (0..2)
+            .map(|i| SimulatedLink::new(conditions, 200 + i))
+            .collect();
+
+        let mut accepted = 0;
+        while server.should_end_match().is_none() {
+            let now = server.current_tick();
+            for (i, client) in clients.iter_mut().enumerate() {
+                for snapshot in downlinks[i].deliver(now) {
+                    client.observe(&snapshot);
+                }
+                let input = client.next_input();
+                uplinks[i].send(now, input);
+                for input in uplinks[i].deliver(now) {
+                    if server.receive_input(client.session_id, input).is_accepted() {
+                        accepted += 1;
+                    }
+                }
+            }
+
+            let (_, _, payload) = server.step();
+            let SnapshotPayload::Broadcast(bytes) = payload else {
+                panic!("expected broadcast payload");
+            };
+            let snapshot: SnapshotProto = prost::Message::decode(bytes.as_slice()).unwrap();
+            for link in &mut downlinks {
+                link.send(now, snapshot.clone());
+            }
+        }
+        assert!(server.session(s1).is_some() && server.session(s2).is_some());
+
+        let artifact = server.finalize(EndReason::Complete).unwrap();
+        let fallbacks = artifact.inputs.iter().filter(|i| i.is_fallback).count();
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        (
+            verify_replay(&artifact, &options).is_ok(),
+            fallbacks,
+            accepted,
+        )
+    }
+
+    /// Under loss, jitter, duplication, and reordering the match still
+    /// produces a verifiable replay, with LKI covering the gaps.
+    #[test]
+    fn test_adverse_conditions_replay_verifies() {
+        let conditions = LinkConditions {
+            latency_ticks: 2,
+            jitter_ticks: 2,
+            loss_rate: 0.2,
+            duplicate_rate: 0.05,
+            reorder_rate: 0.1,
+        };
+        let (verified, fallbacks, accepted) = run_adverse_match(conditions, 300);
+        assert!(verified);
+        assert!(fallbacks > 0, "Loss should force LastKnownIntent fallbacks");
+        assert!(accepted > 0, "Clients should recover and land inputs");
+    }
+
+    /// With an ideal link and a correct lead, no tick falls back to LKI after
+    /// the first input can arrive.
+    #[test]
+    fn test_ideal_conditions_no_fallback_after_warmup() {
+        let (verified, fallbacks, _) = run_adverse_match(LinkConditions::IDEAL, 120);
+        assert!(verified);
+        // Inputs target floor + 0 lead; the first INPUT_LEAD_TICKS ticks cannot be targeted.
+        assert!(fallbacks <= 2 * INPUT_LEAD_TICKS as usize);
+    }
+}
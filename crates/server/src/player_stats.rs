@@ -0,0 +1,143 @@
+//! Per-player gameplay and netcode statistics.
+//!
+//! Ref: DM-0011 (Server Edge), DM-0024 (AppliedInput), DM-0023 (LastKnownIntent)
+//! - Keyed by PlayerId, so stats survive session rebinds (reconnects)
+//! - Input lead: `input.tick - current_tick` at receipt, for accepted inputs
+//!
+//! Observation only; nothing here feeds back into simulation state.
+
+use std::collections::BTreeMap;
+
+use flowstate_sim::Tick;
+
+use crate::validation::ValidationResult;
+
+/// Aggregated statistics for one player.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerStats {
+    distance_moved: f64,
+    last_position: Option<[f64; 2]>,
+    inputs_received: u64,
+    inputs_accepted: u64,
+    drops_by_reason: BTreeMap<&'static str, u64>,
+    ticks_applied: u64,
+    fallback_ticks: u64,
+    lead_ticks_sum: u64,
+}
+
+impl PlayerStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one received input and its validation outcome.
+    pub fn record_input(
+        &mut self,
+        result: &ValidationResult,
+        input_tick: Tick,
+        current_tick: Tick,
+    ) {
+        self.inputs_received += 1;
+        if result.is_accepted() {
+            self.inputs_accepted += 1;
+            self.lead_ticks_sum += input_tick.saturating_sub(current_tick);
+        } else {
+            *self.drops_by_reason.entry(result.reason()).or_insert(0) += 1;
+        }
+    }
+
+    /// Record one applied tick.
+    pub fn record_tick(&mut self, is_fallback: bool) {
+        self.ticks_applied += 1;
+        if is_fallback {
+            self.fallback_ticks += 1;
+        }
+    }
+
+    /// Record the entity position after a step.
+    pub fn record_position(&mut self, position: [f64; 2]) {
+        if let Some(last) = self.last_position {
+            self.distance_moved += (position[0] - last[0]).hypot(position[1] - last[1]);
+        }
+        self.last_position = Some(position);
+    }
+
+    /// Total path length travelled by the player's entity.
+    pub fn distance_moved(&self) -> f64 {
+        self.distance_moved
+    }
+
+    pub fn inputs_received(&self) -> u64 {
+        self.inputs_received
+    }
+
+    pub fn inputs_accepted(&self) -> u64 {
+        self.inputs_accepted
+    }
+
+    pub fn inputs_dropped(&self) -> u64 {
+        self.drops_by_reason.values().sum()
+    }
+
+    /// Dropped inputs by `ValidationResult::reason()`, sorted by reason.
+    pub fn drops_by_reason(&self) -> &BTreeMap<&'static str, u64> {
+        &self.drops_by_reason
+    }
+
+    pub fn ticks_applied(&self) -> u64 {
+        self.ticks_applied
+    }
+
+    pub fn fallback_ticks(&self) -> u64 {
+        self.fallback_ticks
+    }
+
+    /// Mean ticks of lead for accepted inputs (`None` if none accepted).
+    pub fn average_input_lead_ticks(&self) -> Option<f64> {
+        (self.inputs_accepted > 0).then(|| self.lead_ticks_sum as f64 / self.inputs_accepted as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_counters_and_lead() {
+        let mut stats = PlayerStats::new();
+        stats.record_input(&ValidationResult::Accepted, 12, 10);
+        stats.record_input(&ValidationResult::AcceptedWithClamp, 14, 10);
+        stats.record_input(&ValidationResult::DroppedRateLimit, 14, 10);
+        stats.record_input(&ValidationResult::DroppedRateLimit, 14, 10);
+        stats.record_input(
+            &ValidationResult::DroppedLate {
+                tick: 5,
+                current: 10,
+            },
+            5,
+            10,
+        );
+
+        assert_eq!(stats.inputs_received(), 5);
+        assert_eq!(stats.inputs_accepted(), 2);
+        assert_eq!(stats.inputs_dropped(), 3);
+        assert_eq!(stats.drops_by_reason()["rate_limit"], 2);
+        assert_eq!(stats.drops_by_reason()["late"], 1);
+        assert_eq!(stats.average_input_lead_ticks(), Some(3.0));
+    }
+
+    #[test]
+    fn test_distance_and_fallback() {
+        let mut stats = PlayerStats::new();
+        assert_eq!(stats.average_input_lead_ticks(), None);
+        stats.record_position([0.0, 0.0]);
+        stats.record_position([3.0, 4.0]);
+        stats.record_position([3.0, 0.0]);
+        assert_eq!(stats.distance_moved(), 9.0);
+
+        stats.record_tick(true);
+        stats.record_tick(false);
+        assert_eq!(stats.ticks_applied(), 2);
+        assert_eq!(stats.fallback_ticks(), 1);
+    }
+}
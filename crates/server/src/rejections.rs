@@ -0,0 +1,98 @@
+//! Per-session input rejection summaries.
+//!
+//! Ref: DM-0006 (InputCmd), ADR-0006 (TargetTickFloor)
+//! - Every dropped input is counted by `ValidationResult::reason()` until
+//!   the next report; `DroppedDuplicate` is expected redundancy and skipped
+//! - `take_report` turns the counts into an `InputRejectionReport` and
+//!   starts a new period; nothing is sent for a period without drops
+//!
+//! Diagnostics only; nothing here feeds back into validation.
+
+use std::collections::BTreeMap;
+
+use flowstate_sim::Tick;
+use flowstate_wire::{InputDropCount, InputRejectionReport};
+
+use crate::validation::ValidationResult;
+
+/// Drops since the last report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRejections {
+    drops: BTreeMap<&'static str, u32>,
+    last_rejected_tick: Tick,
+    last_target_tick_floor: Tick,
+}
+
+impl InputRejections {
+    /// Record the outcome of validating an input targeting `tick` against `floor`.
+    pub fn record(&mut self, result: &ValidationResult, tick: Tick, floor: Tick) {
+        if result.is_accepted() || *result == ValidationResult::DroppedDuplicate {
+            return;
+        }
+        let count = self.drops.entry(result.reason()).or_insert(0);
+        *count = count.saturating_add(1);
+        self.last_rejected_tick = tick;
+        self.last_target_tick_floor = floor;
+    }
+
+    /// Drops recorded since the last report.
+    pub fn pending(&self) -> u32 {
+        self.drops.values().fold(0, |sum, &n| sum.saturating_add(n))
+    }
+
+    /// Report and reset the period's drops; `None` if there were none.
+    pub fn take_report(&mut self, server_tick: Tick) -> Option<InputRejectionReport> {
+        if self.drops.is_empty() {
+            return None;
+        }
+        let drops = std::mem::take(&mut self.drops)
+            .into_iter()
+            .map(|(reason, count)| InputDropCount {
+                reason: reason.to_string(),
+                count,
+            })
+            .collect();
+        Some(InputRejectionReport {
+            drops,
+            last_rejected_tick: self.last_rejected_tick,
+            last_target_tick_floor: self.last_target_tick_floor,
+            server_tick,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_counts_by_reason() {
+        let mut rejections = InputRejections::default();
+        assert_eq!(rejections.take_report(5), None);
+
+        rejections.record(&ValidationResult::Accepted, 10, 8);
+        rejections.record(&ValidationResult::DroppedDuplicate, 10, 8);
+        rejections.record(&ValidationResult::DroppedRateLimit, 11, 8);
+        rejections.record(
+            &ValidationResult::DroppedBelowFloor { tick: 7, floor: 9 },
+            7,
+            9,
+        );
+        rejections.record(&ValidationResult::DroppedRateLimit, 12, 9);
+        assert_eq!(rejections.pending(), 3);
+
+        let report = rejections.take_report(12).unwrap();
+        let drops: Vec<_> = report
+            .drops
+            .iter()
+            .map(|d| (d.reason.as_str(), d.count))
+            .collect();
+        assert_eq!(drops, vec![("below_floor", 1), ("rate_limit", 2)]);
+        assert_eq!(report.last_rejected_tick, 12);
+        assert_eq!(report.last_target_tick_floor, 9);
+        assert_eq!(report.server_tick, 12);
+
+        // A new period starts empty
+        assert_eq!(rejections.take_report(13), None);
+    }
+}
@@ -0,0 +1,224 @@
+//! Replay download over the Control channel.
+//!
+//! Ref: DM-0017 (ReplayArtifact), ADR-0005 (Control Channel)
+//! - A client fetches the encoded artifact as `ReplayChunk`s by offset,
+//!   each at most `MAX_REPLAY_CHUNK_LEN` bytes so it fits one datagram
+//! - Every chunk carries the artifact's total length, tick, and CRC32C, so
+//!   the client can detect when an in-progress artifact changed under it
+//!   and check the reassembled bytes
+//! - In-progress replays are only served when
+//!   `ServerConfig::serve_in_progress_replays` is set (they reveal every
+//!   player's inputs so far)
+//! - Requests are cheap to answer: the running match's artifact is encoded
+//!   (and checksummed) at most once per tick, each session may make at most
+//!   `ServerConfig::replay_chunk_requests_per_sec` requests, and chunks go
+//!   out droppable (`SendClass::Bulk`), so a flood of requests can neither
+//!   burn CPU nor grow an outbound queue; a client re-requests lost offsets
+//!
+//! Finished replays are chunked by the host from its stored artifact with
+//! `chunk`; the Server serves the running match with `Server::replay_chunk`.
+
+use flowstate_sim::Tick;
+use flowstate_wire::{ErrorCode, ErrorResponse, ReplayChunk, ReplayChunkRequest, checksum};
+
+/// Chunk size used when the request does not name one.
+pub const DEFAULT_REPLAY_CHUNK_LEN: u32 = 1024;
+
+/// Largest chunk served, whatever the request asks for.
+pub const MAX_REPLAY_CHUNK_LEN: u32 = 16 * 1024;
+
+/// Default most chunk requests answered per session per second.
+pub const REPLAY_CHUNK_REQUESTS_PER_SEC: u32 = 30;
+
+/// An encoded ReplayArtifact with its checksum, kept for the chunks cut
+/// from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedArtifact {
+    pub bytes: Vec<u8>,
+    /// Tick the artifact was built at.
+    pub tick: Tick,
+    /// CRC32C of `bytes`.
+    pub crc32c: u32,
+}
+
+impl EncodedArtifact {
+    pub fn new(bytes: Vec<u8>, tick: Tick) -> Self {
+        let crc32c = checksum::crc32c(&bytes);
+        Self {
+            bytes,
+            tick,
+            crc32c,
+        }
+    }
+}
+
+/// Fixed one-second window of chunk requests for one session.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayChunkLimiter {
+    window_start_micros: Option<u64>,
+    count: u32,
+}
+
+impl ReplayChunkLimiter {
+    /// Count one request at `now_micros`; false if over `max_per_sec`.
+    pub fn try_request(&mut self, max_per_sec: u32, now_micros: u64) -> bool {
+        let expired = self
+            .window_start_micros
+            .is_none_or(|start| now_micros.saturating_sub(start) >= 1_000_000);
+        if expired {
+            self.window_start_micros = Some(now_micros);
+            self.count = 0;
+        }
+        if self.count >= max_per_sec {
+            return false;
+        }
+        self.count += 1;
+        true
+    }
+}
+
+/// Why a replay chunk was not served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayChunkError {
+    UnknownSession,
+    /// The session exceeded `replay_chunk_requests_per_sec`; the request is
+    /// dropped without a reply.
+    RateLimited,
+    /// The request named a match this server has no replay for.
+    UnknownMatch,
+    /// The match is still running and in-progress replays are not served.
+    InProgressNotPermitted,
+    /// `offset` is past the end of the artifact.
+    OffsetOutOfRange {
+        offset: u64,
+        total_len: u64,
+    },
+    /// The running match's artifact could not be assembled (its spilled
+    /// inputs could not be read back).
+    ReadFailed,
+}
+
+impl std::fmt::Display for ReplayChunkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSession => write!(f, "Unknown session"),
+            Self::RateLimited => write!(f, "Replay chunk rate limit exceeded"),
+            Self::UnknownMatch => write!(f, "No replay for the requested match"),
+            Self::InProgressNotPermitted => {
+                write!(f, "Replays of a running match are not served")
+            }
+            Self::OffsetOutOfRange { offset, total_len } => {
+                write!(f, "Offset {offset} is past the replay's {total_len} bytes")
+            }
+            Self::ReadFailed => write!(f, "The replay could not be read"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayChunkError {}
+
+impl ReplayChunkError {
+    /// The refusal sent to the client.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        let (code, retryable) = match self {
+            Self::RateLimited => (ErrorCode::RateLimited, true),
+            _ => (ErrorCode::ReplayUnavailable, false),
+        };
+        ErrorResponse {
+            code: code as i32,
+            message: self.to_string(),
+            retryable,
+        }
+    }
+}
+
+/// The chunk of `artifact` that `request` asks for.
+/// An offset equal to the length yields an empty final chunk.
+pub fn chunk(
+    artifact: &EncodedArtifact,
+    match_id: &str,
+    complete: bool,
+    request: &ReplayChunkRequest,
+) -> Result<ReplayChunk, ReplayChunkError> {
+    if !request.match_id.is_empty() && request.match_id != match_id {
+        return Err(ReplayChunkError::UnknownMatch);
+    }
+    let bytes = &artifact.bytes;
+    let total_len = bytes.len() as u64;
+    if request.offset > total_len {
+        return Err(ReplayChunkError::OffsetOutOfRange {
+            offset: request.offset,
+            total_len,
+        });
+    }
+    let max_len = match request.max_len {
+        0 => DEFAULT_REPLAY_CHUNK_LEN,
+        len => len.min(MAX_REPLAY_CHUNK_LEN),
+    };
+    let start = request.offset as usize;
+    let end = start.saturating_add(max_len as usize).min(bytes.len());
+    Ok(ReplayChunk {
+        match_id: match_id.to_string(),
+        offset: request.offset,
+        data: bytes[start..end].to_vec(),
+        total_len,
+        artifact_tick: artifact.tick,
+        complete,
+        artifact_crc32c: artifact.crc32c,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_reassemble() {
+        let bytes: Vec<u8> = (0..=255).cycle().take(2_500).collect();
+        let artifact = EncodedArtifact::new(bytes.clone(), 60);
+        let mut received = Vec::new();
+        loop {
+            let request = ReplayChunkRequest {
+                offset: received.len() as u64,
+                ..Default::default()
+            };
+            let chunk = chunk(&artifact, "m-1", true, &request).unwrap();
+            assert_eq!(chunk.total_len, 2_500);
+            assert!(chunk.data.len() <= DEFAULT_REPLAY_CHUNK_LEN as usize);
+            if chunk.data.is_empty() {
+                assert_eq!(checksum::crc32c(&received), chunk.artifact_crc32c);
+                break;
+            }
+            received.extend_from_slice(&chunk.data);
+        }
+        assert_eq!(received, bytes);
+
+        let request = |match_id: &str, offset, max_len| ReplayChunkRequest {
+            match_id: match_id.to_string(),
+            offset,
+            max_len,
+        };
+        let large = chunk(&artifact, "m-1", true, &request("", 0, u32::MAX)).unwrap();
+        assert_eq!(large.data.len(), 2_500);
+        assert_eq!(
+            chunk(&artifact, "m-1", true, &request("m-2", 0, 0)),
+            Err(ReplayChunkError::UnknownMatch)
+        );
+        assert_eq!(
+            chunk(&artifact, "m-1", true, &request("m-1", 2_501, 0)),
+            Err(ReplayChunkError::OffsetOutOfRange {
+                offset: 2_501,
+                total_len: 2_500
+            })
+        );
+    }
+
+    #[test]
+    fn test_limiter_window() {
+        let mut limiter = ReplayChunkLimiter::default();
+        assert!(limiter.try_request(2, 0));
+        assert!(limiter.try_request(2, 500_000));
+        assert!(!limiter.try_request(2, 999_999));
+        assert!(limiter.try_request(2, 1_000_000));
+    }
+}
@@ -0,0 +1,331 @@
+//! Replay persistence for the match host.
+//!
+//! Ref: DM-0017 (ReplayArtifact), DM-0011 (Server Edge), FS-0007 (Replay storage)
+//! - File names: `{match_id}_{YYYYMMDDTHHMMSSZ}.replay` (UTC wall clock at
+//!   finalize); an empty or unsafe MatchId falls back to `match`
+//! - A `{stem}.summary.json` sidecar is written next to each artifact
+//! - Retention: after each write, the oldest artifacts (by modification time)
+//!   beyond `max_replays` are deleted together with their sidecars
+//!
+//! Collision policy: existing artifacts are never overwritten. With
+//! `CollisionPolicy::Suffix` (default) the next free `-1`, `-2`, … suffix is
+//! used; with `CollisionPolicy::Fail` the write fails with `AlreadyExists`,
+//! the strict FS-0007 behavior.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flowstate_replay::write_replay;
+use flowstate_wire::ReplayArtifact;
+
+use crate::summary::MatchSummary;
+
+/// Replay artifact file extension.
+pub const REPLAY_EXTENSION: &str = "replay";
+
+/// Summary sidecar suffix (appended to the artifact stem).
+pub const SUMMARY_SUFFIX: &str = ".summary.json";
+
+/// Upper bound on suffix probing before giving up.
+const MAX_SUFFIX: u32 = 1000;
+
+/// What to do when the target file name is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollisionPolicy {
+    /// Append `-1`, `-2`, … until a free name is found.
+    #[default]
+    Suffix,
+    /// Fail with `io::ErrorKind::AlreadyExists`.
+    Fail,
+}
+
+/// Replay storage configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayStorageConfig {
+    /// Directory artifacts are written to (created if missing).
+    pub dir: PathBuf,
+    /// Keep at most this many artifacts (`None` = unlimited).
+    pub max_replays: Option<usize>,
+    pub collision_policy: CollisionPolicy,
+}
+
+impl ReplayStorageConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_replays: None,
+            collision_policy: CollisionPolicy::default(),
+        }
+    }
+}
+
+/// Writes finalized matches to disk.
+#[derive(Debug, Clone)]
+pub struct ReplayStorage {
+    config: ReplayStorageConfig,
+}
+
+impl ReplayStorage {
+    pub fn new(config: ReplayStorageConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &ReplayStorageConfig {
+        &self.config
+    }
+
+    /// Write an artifact and its summary, stamped with the current UTC time.
+    /// Sets `summary.replay_path` and returns the artifact path.
+    pub fn store(
+        &self,
+        artifact: &ReplayArtifact,
+        summary: &mut MatchSummary,
+    ) -> io::Result<PathBuf> {
+        let unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.store_at(artifact, summary, unix_secs)
+    }
+
+    /// Like `store`, with an explicit timestamp (seconds since the Unix epoch).
+    pub fn store_at(
+        &self,
+        artifact: &ReplayArtifact,
+        summary: &mut MatchSummary,
+        unix_secs: u64,
+    ) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.config.dir)?;
+        let stem = format!(
+            "{}_{}",
+            sanitize_match_id(&summary.match_id),
+            format_utc(unix_secs)
+        );
+        let path = self.resolve_collision(&stem)?;
+
+        write_replay(artifact, &path)?;
+        summary.set_replay_path(&path);
+        summary.write_json(&summary_path(&path))?;
+
+        self.apply_retention()?;
+        Ok(path)
+    }
+
+    /// Pick a free artifact path for `stem` according to the collision policy.
+    fn resolve_collision(&self, stem: &str) -> io::Result<PathBuf> {
+        let candidate = |suffix: u32| {
+            let name = if suffix == 0 {
+                format!("{stem}.{REPLAY_EXTENSION}")
+            } else {
+                format!("{stem}-{suffix}.{REPLAY_EXTENSION}")
+            };
+            self.config.dir.join(name)
+        };
+        let path = candidate(0);
+        if !path.exists() {
+            return Ok(path);
+        }
+        match self.config.collision_policy {
+            CollisionPolicy::Fail => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Replay artifact already exists at {}", path.display()),
+            )),
+            CollisionPolicy::Suffix => (1..=MAX_SUFFIX)
+                .map(candidate)
+                .find(|path| !path.exists())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("No free replay file name for {stem}"),
+                    )
+                }),
+        }
+    }
+
+    /// Stored artifacts, oldest first (modification time, then name).
+    pub fn list(&self) -> io::Result<Vec<PathBuf>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.config.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION) {
+                let modified = fs::metadata(&path)?.modified()?;
+                entries.push((modified, path));
+            }
+        }
+        entries.sort();
+        Ok(entries.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Delete the oldest artifacts (and sidecars) beyond `max_replays`.
+    fn apply_retention(&self) -> io::Result<()> {
+        let Some(max) = self.config.max_replays else {
+            return Ok(());
+        };
+        let stored = self.list()?;
+        let excess = stored.len().saturating_sub(max);
+        for path in &stored[..excess] {
+            fs::remove_file(path)?;
+            match fs::remove_file(summary_path(path)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sidecar summary path for an artifact path.
+pub fn summary_path(replay_path: &Path) -> PathBuf {
+    let stem = replay_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    replay_path.with_file_name(format!("{stem}{SUMMARY_SUFFIX}"))
+}
+
+/// Restrict a MatchId to file-name-safe characters.
+fn sanitize_match_id(match_id: &str) -> String {
+    let sanitized: String = match_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.chars().all(|c| c == '_') {
+        "match".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// Format Unix seconds as `YYYYMMDDTHHMMSSZ` (UTC).
+fn format_utc(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+
+    // Civil-from-days (proleptic Gregorian), days since 1970-01-01
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        secs_of_day / 3_600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndReason, Server, ServerConfig};
+    use flowstate_replay::read_replay;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "flowstate-replay-storage-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn finished_match(match_id: &str) -> (ReplayArtifact, MatchSummary) {
+        let mut server = Server::new(ServerConfig {
+            match_id: match_id.to_string(),
+            match_duration_ticks: 3,
+            ..Default::default()
+        });
+        server.accept_session();
+        server.accept_session();
+        server.start_match();
+        while server.should_end_match().is_none() {
+            server.step();
+        }
+        let (artifact, summary) = server.finalize_with_summary(EndReason::Complete);
+        (artifact.unwrap(), summary)
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "19700101T000000Z");
+        assert_eq!(format_utc(951_782_400), "20000229T000000Z");
+        assert_eq!(format_utc(1_791_000_000), "20261003T040000Z");
+    }
+
+    #[test]
+    fn test_sanitize_match_id() {
+        assert_eq!(sanitize_match_id("m-42"), "m-42");
+        assert_eq!(sanitize_match_id("../etc"), "___etc");
+        assert_eq!(sanitize_match_id(""), "match");
+        assert_eq!(sanitize_match_id("//"), "match");
+    }
+
+    #[test]
+    fn test_store_names_and_suffixes_collisions() {
+        let dir = test_dir("suffix");
+        let storage = ReplayStorage::new(ReplayStorageConfig::new(&dir));
+        let (artifact, mut summary) = finished_match("m-1");
+
+        let first = storage.store_at(&artifact, &mut summary, 0).unwrap();
+        assert_eq!(first, dir.join("m-1_19700101T000000Z.replay"));
+        assert_eq!(read_replay(&first).unwrap(), artifact);
+        let sidecar = fs::read_to_string(summary_path(&first)).unwrap();
+        let parsed = MatchSummary::from_json(&sidecar).unwrap();
+        assert_eq!(parsed.replay_path, Some(first.display().to_string()));
+
+        let second = storage.store_at(&artifact, &mut summary, 0).unwrap();
+        assert_eq!(second, dir.join("m-1_19700101T000000Z-1.replay"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fail_policy_preserves_existing() {
+        let dir = test_dir("fail");
+        let storage = ReplayStorage::new(ReplayStorageConfig {
+            collision_policy: CollisionPolicy::Fail,
+            ..ReplayStorageConfig::new(&dir)
+        });
+        let (artifact, mut summary) = finished_match("m-1");
+        storage.store_at(&artifact, &mut summary, 0).unwrap();
+        let err = storage.store_at(&artifact, &mut summary, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(storage.list().unwrap().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_retention_deletes_oldest_with_sidecar() {
+        let dir = test_dir("retention");
+        let storage = ReplayStorage::new(ReplayStorageConfig {
+            max_replays: Some(2),
+            ..ReplayStorageConfig::new(&dir)
+        });
+        let (artifact, mut summary) = finished_match("m-1");
+        let first = storage.store_at(&artifact, &mut summary, 0).unwrap();
+        // Ensure distinct modification times on coarse-grained filesystems
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        storage.store_at(&artifact, &mut summary, 1).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        storage.store_at(&artifact, &mut summary, 2).unwrap();
+
+        let stored = storage.list().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(!first.exists());
+        assert!(!summary_path(&first).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,57 @@
+//! Mid-match baseline resend.
+//!
+//! Ref: DM-0016 (Baseline), ADR-0007 (StateDigest)
+//! - A client whose reconstructed state disagrees with snapshot digests
+//!   sends a `BaselineRequest`; `Server::baseline_update` answers with the
+//!   current authoritative state as a `BaselineUpdate`
+//! - A session gets at most one update per
+//!   `ServerConfig::baseline_request_min_interval_ticks`, since each one
+//!   carries every entity
+//! - Every update raises `ServerEvent::ClientDesync`: client and server
+//!   disagreeing on the simulation is worth investigating even when the
+//!   resend repairs it
+
+use flowstate_wire::{ErrorCode, ErrorResponse};
+
+/// Default `ServerConfig::baseline_request_min_interval_ticks` (half a
+/// second at the default tick rate).
+pub const BASELINE_REQUEST_MIN_INTERVAL_TICKS: u64 = 30;
+
+/// Why a baseline was not resent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaselineRequestError {
+    /// The request came from no known session.
+    UnknownSession,
+    /// There is no match state to resend yet.
+    MatchNotStarted,
+    /// The session's previous update was too recent.
+    TooFrequent { retry_after_ticks: u64 },
+}
+
+impl std::fmt::Display for BaselineRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSession => write!(f, "Baseline requested by an unknown session"),
+            Self::MatchNotStarted => write!(f, "The match has not started"),
+            Self::TooFrequent { retry_after_ticks } => {
+                write!(
+                    f,
+                    "Baseline resent recently; retry in {retry_after_ticks} ticks"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BaselineRequestError {}
+
+impl BaselineRequestError {
+    /// The refusal sent to the client.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        ErrorResponse {
+            code: ErrorCode::BaselineUnavailable as i32,
+            message: self.to_string(),
+            retryable: !matches!(self, Self::UnknownSession),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Player display names and cosmetics.
+//!
+//! Ref: DM-0019 (PlayerId), DM-0008 (Session)
+//! - The host sets a `PlayerProfile` per PlayerId (e.g., from its account
+//!   service); profiles are keyed by PlayerId so they survive reconnects
+//! - Players without a profile are announced as "Player <id>"
+//! - `PlayerRoster` is presentation data only; nothing here reaches the
+//!   World, the StateDigest, or the replay
+
+use std::collections::BTreeMap;
+
+use flowstate_sim::{EntityId, PlayerId};
+use flowstate_wire::{PlayerAttribute, PlayerInfo};
+
+/// Longest display name announced; longer names are cut.
+pub const MAX_DISPLAY_NAME_CHARS: usize = 32;
+
+/// Presentation data the host attaches to a player.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerProfile {
+    pub display_name: String,
+    /// Cosmetic key/value pairs (skin, color, ...).
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Name announced for a player without a profile (or with an empty name).
+pub fn default_display_name(player_id: PlayerId) -> String {
+    format!("Player {player_id}")
+}
+
+/// Roster entry for one player.
+pub fn player_info(
+    player_id: PlayerId,
+    entity_id: EntityId,
+    profile: Option<&PlayerProfile>,
+) -> PlayerInfo {
+    let display_name = profile
+        .map(|p| p.display_name.trim())
+        .filter(|name| !name.is_empty())
+        .map_or_else(
+            || default_display_name(player_id),
+            |name| name.chars().take(MAX_DISPLAY_NAME_CHARS).collect(),
+        );
+    PlayerInfo {
+        player_id: u32::from(player_id),
+        entity_id: entity_id.into(),
+        display_name,
+        attributes: profile
+            .into_iter()
+            .flat_map(|p| &p.attributes)
+            .map(|(key, value)| PlayerAttribute {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_info_defaults_and_limits() {
+        let info = player_info(PlayerId(3), EntityId(11), None);
+        assert_eq!(info.display_name, "Player 3");
+        assert!(info.attributes.is_empty());
+
+        let profile = PlayerProfile {
+            display_name: format!("  {}  ", "n".repeat(40)),
+            attributes: BTreeMap::from([
+                ("skin".to_string(), "red".to_string()),
+                ("color".to_string(), "#ff8800".to_string()),
+            ]),
+        };
+        let info = player_info(PlayerId(3), EntityId(11), Some(&profile));
+        assert_eq!(info.display_name, "n".repeat(MAX_DISPLAY_NAME_CHARS));
+        let keys: Vec<_> = info.attributes.iter().map(|a| a.key.as_str()).collect();
+        assert_eq!(keys, vec!["color", "skin"]);
+
+        let blank = PlayerProfile {
+            display_name: " ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            player_info(PlayerId(4), EntityId(12), Some(&blank)).display_name,
+            "Player 4"
+        );
+    }
+}
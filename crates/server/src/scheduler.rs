@@ -0,0 +1,213 @@
+//! Fixed-timestep tick scheduler for server hosts.
+//!
+//! Ref: DM-0011 (Server Edge), ADR-0005
+//! - Tick `n` is due at `epoch + n * 1_000_000 / tick_rate_hz` microseconds:
+//!   deadlines are absolute, so sleep jitter and rounding never accumulate
+//! - A late poll reports every tick that has come due; a `CatchUpPolicy`
+//!   decides how many of those to run and how many to skip
+//! - Time comes from the caller (`Clock::now_micros`), so tests drive the
+//!   schedule manually
+//!
+//! The scheduler only paces the host loop; the Simulation Core never sees
+//! wall-clock time (INV-0004).
+
+/// Default maximum ticks run in one poll under `BoundedCatchUp`.
+pub const DEFAULT_MAX_CATCH_UP_TICKS: u64 = 4;
+
+/// Decides how many overdue ticks to run; the remainder are skipped.
+pub trait CatchUpPolicy: Send {
+    /// `due` ticks (>= 1) have come due; return how many to run now (<= `due`).
+    fn ticks_to_run(&mut self, due: u64) -> u64;
+}
+
+/// Run every overdue tick, however far behind.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CatchUpAll;
+
+impl CatchUpPolicy for CatchUpAll {
+    fn ticks_to_run(&mut self, due: u64) -> u64 {
+        due
+    }
+}
+
+/// Run at most `max_ticks` per poll and skip the rest, so a stall cannot turn
+/// into a long burst of back-to-back steps.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundedCatchUp {
+    pub max_ticks: u64,
+}
+
+impl Default for BoundedCatchUp {
+    fn default() -> Self {
+        Self {
+            max_ticks: DEFAULT_MAX_CATCH_UP_TICKS,
+        }
+    }
+}
+
+impl CatchUpPolicy for BoundedCatchUp {
+    fn ticks_to_run(&mut self, due: u64) -> u64 {
+        due.min(self.max_ticks.max(1))
+    }
+}
+
+/// Run one tick per poll and skip everything else that is overdue.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkipMissed;
+
+impl CatchUpPolicy for SkipMissed {
+    fn ticks_to_run(&mut self, due: u64) -> u64 {
+        due.min(1)
+    }
+}
+
+/// Scheduling counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerStats {
+    /// Ticks handed to the host to run.
+    pub ticks_run: u64,
+    /// Overdue ticks dropped by the catch-up policy.
+    pub ticks_skipped: u64,
+    /// Polls that found more than one tick due.
+    pub overruns: u64,
+    /// Largest observed lateness of a due tick, in microseconds.
+    pub max_lateness_micros: u64,
+}
+
+/// Absolute-deadline scheduler at a fixed tick rate.
+pub struct TickScheduler {
+    tick_rate_hz: u32,
+    epoch_micros: u64,
+    /// Schedule slots consumed (run or skipped) since `epoch_micros`.
+    slots: u64,
+    policy: Box<dyn CatchUpPolicy>,
+    stats: SchedulerStats,
+}
+
+impl TickScheduler {
+    /// Schedule starting at `now_micros`; the first tick is due one interval later.
+    ///
+    /// # Panics
+    /// If `tick_rate_hz` is 0.
+    pub fn new(tick_rate_hz: u32, now_micros: u64) -> Self {
+        assert!(tick_rate_hz > 0, "tick_rate_hz must be positive");
+        Self {
+            tick_rate_hz,
+            epoch_micros: now_micros,
+            slots: 0,
+            policy: Box::new(BoundedCatchUp::default()),
+            stats: SchedulerStats::default(),
+        }
+    }
+
+    /// Replace the catch-up policy (default: `BoundedCatchUp`).
+    pub fn set_policy(&mut self, policy: Box<dyn CatchUpPolicy>) {
+        self.policy = policy;
+    }
+
+    /// Restart the schedule at `now_micros` (e.g., at match start after a long
+    /// lobby wait), without counting the gap as missed ticks.
+    pub fn reset(&mut self, now_micros: u64) {
+        self.epoch_micros = now_micros;
+        self.slots = 0;
+    }
+
+    /// Deadline of schedule slot `slot` (1-based).
+    fn deadline(&self, slot: u64) -> u64 {
+        let offset = u128::from(slot) * 1_000_000 / u128::from(self.tick_rate_hz);
+        self.epoch_micros
+            .saturating_add(u64::try_from(offset).unwrap_or(u64::MAX))
+    }
+
+    /// When the next tick is due.
+    pub fn next_deadline_micros(&self) -> u64 {
+        self.deadline(self.slots + 1)
+    }
+
+    /// Microseconds until the next tick is due (0 if already due).
+    pub fn micros_until_next(&self, now_micros: u64) -> u64 {
+        self.next_deadline_micros().saturating_sub(now_micros)
+    }
+
+    /// Number of ticks the host should run now. Call once per loop iteration
+    /// and run exactly that many steps.
+    pub fn poll(&mut self, now_micros: u64) -> u64 {
+        let next = self.next_deadline_micros();
+        if now_micros < next {
+            return 0;
+        }
+        let elapsed = u128::from(now_micros - self.epoch_micros);
+        let due_slots =
+            u64::try_from(elapsed * u128::from(self.tick_rate_hz) / 1_000_000).unwrap_or(u64::MAX);
+        let due = due_slots.saturating_sub(self.slots).max(1);
+
+        let run = self.policy.ticks_to_run(due).clamp(1, due);
+        self.slots += due;
+        self.stats.ticks_run += run;
+        self.stats.ticks_skipped += due - run;
+        if due > 1 {
+            self.stats.overruns += 1;
+        }
+        self.stats.max_lateness_micros = self.stats.max_lateness_micros.max(now_micros - next);
+        run
+    }
+
+    pub fn stats(&self) -> SchedulerStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_absolute_deadlines_do_not_drift() {
+        let mut scheduler = TickScheduler::new(60, 1_000);
+        assert_eq!(scheduler.next_deadline_micros(), 1_000 + 16_666);
+        assert_eq!(scheduler.poll(1_000 + 16_665), 0);
+
+        // Jittery polls (always slightly late) still yield exactly 60 ticks/s
+        let mut now = 1_000;
+        let mut ticks = 0;
+        while now < 1_000 + 1_000_000 {
+            now += 7_001;
+            ticks += scheduler.poll(now.min(1_000 + 1_000_000));
+        }
+        assert_eq!(ticks, 60);
+        assert_eq!(scheduler.next_deadline_micros(), 1_000 + 1_016_666);
+        assert_eq!(scheduler.stats().ticks_skipped, 0);
+    }
+
+    #[test]
+    fn test_catch_up_policies() {
+        // 10 ticks late
+        let mut bounded = TickScheduler::new(100, 0);
+        assert_eq!(bounded.poll(100_000), DEFAULT_MAX_CATCH_UP_TICKS);
+        let stats = bounded.stats();
+        assert_eq!(stats.ticks_skipped, 10 - DEFAULT_MAX_CATCH_UP_TICKS);
+        assert_eq!(stats.overruns, 1);
+        assert_eq!(stats.max_lateness_micros, 90_000);
+        // Back on schedule afterwards
+        assert_eq!(bounded.poll(105_000), 0);
+        assert_eq!(bounded.poll(110_000), 1);
+
+        let mut all = TickScheduler::new(100, 0);
+        all.set_policy(Box::new(CatchUpAll));
+        assert_eq!(all.poll(100_000), 10);
+
+        let mut skip = TickScheduler::new(100, 0);
+        skip.set_policy(Box::new(SkipMissed));
+        assert_eq!(skip.poll(100_000), 1);
+        assert_eq!(skip.stats().ticks_skipped, 9);
+    }
+
+    #[test]
+    fn test_reset_forgives_gap() {
+        let mut scheduler = TickScheduler::new(60, 0);
+        scheduler.reset(5_000_000);
+        assert_eq!(scheduler.poll(5_000_000), 0);
+        assert_eq!(scheduler.poll(5_016_667), 1);
+        assert_eq!(scheduler.stats().overruns, 0);
+    }
+}
@@ -0,0 +1,206 @@
+//! Scripted input injection for deterministic Server tests.
+//!
+//! Ref: DM-0024 (AppliedInput), INV-0006, FS-0007 manual-step mode
+//! - A script is a timeline of `(tick, session, InputCmdProto)` entries
+//! - `Server::run_script` delivers each entry through `receive_input` when the
+//!   server reaches its tick (before that tick is stepped), steps to the end
+//!   tick, and finalizes the replay
+//! - Entries for the same tick are delivered in script order
+//!
+//! Validation, LastKnownIntent, and replay recording all run exactly as in a
+//! live match; only the transport is replaced. Available in unit tests and
+//! behind the `test-support` feature.
+
+use std::io;
+
+use flowstate_sim::Tick;
+use flowstate_wire::{InputCmdProto, ReplayArtifact};
+
+use crate::session::SessionId;
+use crate::validation::ValidationResult;
+use crate::{EndReason, Server};
+
+/// One scripted delivery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptedInput {
+    /// Server tick at which the input arrives (delivered before stepping it).
+    pub at_tick: Tick,
+    pub session_id: SessionId,
+    pub input: InputCmdProto,
+}
+
+/// Timeline of scripted deliveries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputScript {
+    entries: Vec<ScriptedInput>,
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver `input` from `session_id` when the server reaches `at_tick`.
+    pub fn at(mut self, at_tick: Tick, session_id: SessionId, input: InputCmdProto) -> Self {
+        self.entries.push(ScriptedInput {
+            at_tick,
+            session_id,
+            input,
+        });
+        self
+    }
+
+    /// Deliver a move targeting `target_tick` (with `input_seq = target_tick`)
+    /// when the server reaches `at_tick`.
+    pub fn move_at(
+        self,
+        at_tick: Tick,
+        session_id: SessionId,
+        target_tick: Tick,
+        move_dir: [f64; 2],
+    ) -> Self {
+        self.at(
+            at_tick,
+            session_id,
+            InputCmdProto {
+                tick: target_tick,
+                input_seq: target_tick,
+                move_dir: move_dir.to_vec(),
+            },
+        )
+    }
+
+    pub fn entries(&self) -> &[ScriptedInput] {
+        &self.entries
+    }
+}
+
+/// Result of `Server::run_script`.
+#[derive(Debug)]
+pub struct ScriptRun {
+    /// Validation outcome of every delivery, in delivery order.
+    pub results: Vec<(ScriptedInput, ValidationResult)>,
+    /// The replay (see `Server::finalize`).
+    pub artifact: io::Result<ReplayArtifact>,
+}
+
+impl ScriptRun {
+    /// Outcomes for `session_id`, in delivery order.
+    pub fn results_for(&self, session_id: SessionId) -> Vec<&ValidationResult> {
+        self.results
+            .iter()
+            .filter(|(entry, _)| entry.session_id == session_id)
+            .map(|(_, result)| result)
+            .collect()
+    }
+}
+
+impl Server {
+    /// Play `script` against a started match until the server reaches
+    /// `end_tick`, then finalize with `end_reason`. Entries scheduled before
+    /// the current tick are delivered immediately; entries at or after
+    /// `end_tick` are never delivered.
+    ///
+    /// # Panics
+    /// If the match has not started.
+    pub fn run_script(
+        mut self,
+        script: &InputScript,
+        end_tick: Tick,
+        end_reason: EndReason,
+    ) -> ScriptRun {
+        assert!(self.match_started, "Match not started");
+        let mut timeline: Vec<&ScriptedInput> = script.entries.iter().collect();
+        // Stable: same-tick entries keep script order
+        timeline.sort_by_key(|entry| entry.at_tick);
+        let mut pending = timeline.into_iter().peekable();
+
+        let mut results = Vec::new();
+        while self.current_tick() < end_tick {
+            let now = self.current_tick();
+            while let Some(entry) = pending.next_if(|entry| entry.at_tick <= now) {
+                let result = self.receive_input(entry.session_id, entry.input.clone());
+                results.push((entry.clone(), result));
+            }
+            self.step();
+        }
+        ScriptRun {
+            results,
+            artifact: self.finalize(end_reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ServerConfig;
+    use flowstate_replay::{VerifyOptions, verify_replay};
+
+    #[test]
+    fn test_scripted_validation_lki_and_replay() {
+        let mut server = Server::new(ServerConfig {
+            seed: 1,
+            ..Default::default()
+        });
+        let (s1, _, _) = server.accept_session();
+        let (s2, _, _) = server.accept_session();
+        server.start_match();
+
+        let script = InputScript::new()
+            .move_at(0, s1, 1, [1.0, 0.0])
+            // Arrives at tick 3 for tick 2: below the emitted floor
+            .move_at(3, s1, 2, [0.0, 1.0])
+            .move_at(3, s2, 4, [0.0, -1.0])
+            .at(
+                4,
+                s2,
+                InputCmdProto {
+                    tick: 5,
+                    input_seq: 5,
+                    move_dir: vec![f64::NAN, 0.0],
+                },
+            );
+        let run = server.run_script(&script, 6, EndReason::Complete);
+
+        assert!(run.results_for(s1)[0].is_accepted());
+        assert!(matches!(
+            run.results_for(s1)[1],
+            ValidationResult::DroppedBelowFloor { tick: 2, .. }
+        ));
+        assert_eq!(run.results_for(s2)[1], &ValidationResult::DroppedNanInf);
+
+        let applied = |player_id: u32| -> Vec<(f64, f64, bool)> {
+            run.artifact
+                .as_ref()
+                .unwrap()
+                .inputs
+                .iter()
+                .filter(|i| i.player_id == player_id)
+                .map(|i| (i.move_dir[0], i.move_dir[1], i.is_fallback))
+                .collect()
+        };
+        // Player 0: LKI carries tick 1's input past the dropped one
+        assert_eq!(
+            applied(0),
+            vec![
+                (0.0, 0.0, true),
+                (1.0, 0.0, false),
+                (1.0, 0.0, true),
+                (1.0, 0.0, true),
+                (1.0, 0.0, true),
+                (1.0, 0.0, true),
+            ]
+        );
+        // Player 1: the NaN input is dropped, so tick 5 falls back to tick 4
+        assert_eq!(applied(1)[4], (0.0, -1.0, false));
+        assert_eq!(applied(1)[5], (0.0, -1.0, true));
+
+        let options = VerifyOptions {
+            strict_build_check: false,
+            current_build: None,
+            collect_all_errors: false,
+        };
+        assert!(verify_replay(run.artifact.as_ref().unwrap(), &options).is_ok());
+    }
+}
@@ -0,0 +1,648 @@
+//! Authenticated encryption for the Realtime Channel.
+//!
+//! Ref: ADR-0005 (Control/Realtime Channels)
+//! - Key agreement: ephemeral X25519, exchanged on the Control Channel via
+//!   `KeyExchangeInit` / `KeyExchangeResponse`
+//! - Authentication: the server also holds a static X25519 key (`ServerKey`)
+//!   whose public half clients know ahead of time. The client's ephemeral key
+//!   is combined with it as well, so only the holder of the static secret
+//!   derives the same keys, and a relay that swaps in its own ephemeral key
+//!   cannot read or forge frames
+//! - Per-direction keys: HKDF-SHA256 over both shared secrets, salted with a
+//!   hash of the three public keys, with one `info` label per direction
+//! - Packets are `EncryptedFrame`s (`flowstate_wire::encryption`) whose nonce
+//!   carries a per-direction counter; `FrameCipher` is implemented here over
+//!   ChaCha20-Poly1305
+//! - The Realtime Channel is unreliable, so packets may arrive out of order;
+//!   a 64-packet sliding window rejects replays and stale packets
+//! - `PeerKeys` keeps a peer's current keys and, after a rekey, the previous
+//!   ones, so frames still in flight under the old `key_id` open
+//!
+//! Transport-layer only: the Server and Simulation Core see plaintext messages.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use flowstate_wire::encryption::{self, FrameCipher, FrameError, NONCE_LEN};
+use flowstate_wire::{
+    AeadAlgorithm, EncryptedFrame, ErrorCode, ErrorResponse, KeyExchangeInit, KeyExchangeResponse,
+};
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Length of an X25519 public key.
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Algorithms this server seals frames with, most preferred first.
+pub const SUPPORTED_ALGORITHMS: [AeadAlgorithm; 1] = [AeadAlgorithm::Chacha20Poly1305];
+
+/// Packets tracked by the replay window.
+const REPLAY_WINDOW: u64 = 64;
+
+/// Secure channel failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    /// Peer public key has the wrong length.
+    InvalidPublicKey,
+    /// Key agreement produced a non-contributory (low-order) shared secret.
+    WeakSharedSecret,
+    /// The peer offered no algorithm this side supports.
+    NoCommonAlgorithm,
+    /// `EncryptedFrame` nonce or tag of the wrong length or layout.
+    MalformedFrame,
+    /// `EncryptedFrame` sealed under keys this side does not hold.
+    UnknownKey { key_id: u32 },
+    /// Authentication tag did not verify (forged or corrupted).
+    AuthenticationFailed,
+    /// Counter already seen or older than the replay window.
+    Replayed { counter: u64 },
+    /// Local randomness source failed.
+    Rng,
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPublicKey => write!(f, "Invalid peer public key"),
+            Self::WeakSharedSecret => write!(f, "Non-contributory shared secret"),
+            Self::NoCommonAlgorithm => write!(f, "No common AEAD algorithm"),
+            Self::MalformedFrame => write!(f, "Malformed encrypted frame"),
+            Self::UnknownKey { key_id } => write!(f, "Unknown key id {key_id}"),
+            Self::AuthenticationFailed => write!(f, "Packet authentication failed"),
+            Self::Replayed { counter } => write!(f, "Replayed packet counter {counter}"),
+            Self::Rng => write!(f, "Randomness source failed"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+impl CryptoError {
+    /// The refusal sent for a `KeyExchangeInit` that failed.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        ErrorResponse {
+            code: ErrorCode::KeyExchangeFailed as i32,
+            message: self.to_string(),
+            // A fresh ephemeral key may succeed where a weak one did not
+            retryable: matches!(self, Self::WeakSharedSecret | Self::Rng),
+        }
+    }
+}
+
+/// Which end of the channel this is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Client,
+    Server,
+}
+
+fn x25519_public(bytes: &[u8]) -> Result<PublicKey, CryptoError> {
+    let bytes: [u8; PUBLIC_KEY_LEN] = bytes
+        .try_into()
+        .map_err(|_| CryptoError::InvalidPublicKey)?;
+    Ok(PublicKey::from(bytes))
+}
+
+fn random_secret() -> Result<StaticSecret, CryptoError> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|_| CryptoError::Rng)?;
+    Ok(StaticSecret::from(bytes))
+}
+
+/// The server's long-lived X25519 key, authenticating its side of every
+/// key exchange.
+pub struct ServerKey {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl ServerKey {
+    /// Generate a fresh key from the OS RNG.
+    pub fn generate() -> Result<Self, CryptoError> {
+        Ok(Self::from_secret(random_secret()?))
+    }
+
+    /// Build from stored secret bytes.
+    pub fn from_secret_bytes(bytes: [u8; 32]) -> Self {
+        Self::from_secret(StaticSecret::from(bytes))
+    }
+
+    fn from_secret(secret: StaticSecret) -> Self {
+        let public = PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    /// Public key clients pin to authenticate this server.
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] {
+        self.public.to_bytes()
+    }
+}
+
+/// One side of an in-progress key exchange.
+pub struct KeyExchange {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl KeyExchange {
+    /// Generate a fresh ephemeral keypair from the OS RNG.
+    pub fn generate() -> Result<Self, CryptoError> {
+        let secret = random_secret()?;
+        let public = PublicKey::from(&secret);
+        Ok(Self { secret, public })
+    }
+
+    /// Build from fixed secret bytes (tests and deterministic harnesses).
+    pub fn from_secret_bytes(bytes: [u8; 32]) -> Self {
+        let secret = StaticSecret::from(bytes);
+        let public = PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    /// Public key to send to the peer.
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] {
+        self.public.to_bytes()
+    }
+
+    /// Complete the client side with the server's ephemeral public key and
+    /// its pinned static public key.
+    pub fn finish_client(
+        self,
+        server_public_key: &[u8],
+        server_static_key: &[u8; PUBLIC_KEY_LEN],
+    ) -> Result<SecureChannel, CryptoError> {
+        let server_public = x25519_public(server_public_key)?;
+        let server_static = PublicKey::from(*server_static_key);
+        let ephemeral = self.secret.diffie_hellman(&server_public);
+        let authenticated = self.secret.diffie_hellman(&server_static);
+        if !ephemeral.was_contributory() || !authenticated.was_contributory() {
+            return Err(CryptoError::WeakSharedSecret);
+        }
+        Ok(derive_channel(
+            Role::Client,
+            [ephemeral.as_bytes(), authenticated.as_bytes()],
+            [&self.public, &server_public, &server_static],
+        ))
+    }
+
+    /// Complete the server side with the client's ephemeral public key.
+    pub fn finish_server(
+        self,
+        client_public_key: &[u8],
+        server_key: &ServerKey,
+    ) -> Result<SecureChannel, CryptoError> {
+        let client_public = x25519_public(client_public_key)?;
+        let ephemeral = self.secret.diffie_hellman(&client_public);
+        let authenticated = server_key.secret.diffie_hellman(&client_public);
+        if !ephemeral.was_contributory() || !authenticated.was_contributory() {
+            return Err(CryptoError::WeakSharedSecret);
+        }
+        Ok(derive_channel(
+            Role::Server,
+            [ephemeral.as_bytes(), authenticated.as_bytes()],
+            [&client_public, &self.public, &server_key.public],
+        ))
+    }
+}
+
+/// Per-direction keys from the ephemeral and static shared secrets and the
+/// (client ephemeral, server ephemeral, server static) public keys.
+fn derive_channel(role: Role, secrets: [&[u8; 32]; 2], publics: [&PublicKey; 3]) -> SecureChannel {
+    let mut transcript = Sha256::new();
+    transcript.update(b"flowstate-v1 key exchange");
+    for public in publics {
+        transcript.update(public.as_bytes());
+    }
+    let hkdf = Hkdf::<Sha256>::new(
+        Some(&transcript.finalize()),
+        &[secrets[0].as_slice(), secrets[1]].concat(),
+    );
+    let derive = |label: &[u8]| {
+        let mut key = [0u8; 32];
+        hkdf.expand(label, &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        ChaCha20Poly1305::new(Key::from_slice(&key))
+    };
+    let c2s = derive(b"flowstate-v1 client->server");
+    let s2c = derive(b"flowstate-v1 server->client");
+    let (send, recv) = match role {
+        Role::Client => (c2s, s2c),
+        Role::Server => (s2c, c2s),
+    };
+    SecureChannel {
+        send,
+        recv,
+        next_send_counter: 0,
+        replay: ReplayWindow::default(),
+    }
+}
+
+/// Sliding-window replay protection (RFC 6347 §4.1.2.6 style).
+#[derive(Debug, Clone, Default)]
+struct ReplayWindow {
+    /// Highest counter accepted, if any.
+    highest: Option<u64>,
+    /// Bit i set = counter `highest - i` accepted.
+    bitmap: u64,
+}
+
+impl ReplayWindow {
+    fn check(&self, counter: u64) -> Result<(), CryptoError> {
+        let Some(highest) = self.highest else {
+            return Ok(());
+        };
+        if counter > highest {
+            return Ok(());
+        }
+        let age = highest - counter;
+        if age >= REPLAY_WINDOW || self.bitmap & (1 << age) != 0 {
+            return Err(CryptoError::Replayed { counter });
+        }
+        Ok(())
+    }
+
+    fn accept(&mut self, counter: u64) {
+        match self.highest {
+            Some(highest) if counter <= highest => {
+                self.bitmap |= 1 << (highest - counter);
+            }
+            Some(highest) => {
+                let shift = counter - highest;
+                self.bitmap = if shift >= REPLAY_WINDOW {
+                    0
+                } else {
+                    self.bitmap << shift
+                };
+                self.bitmap |= 1;
+                self.highest = Some(counter);
+            }
+            None => {
+                self.bitmap = 1;
+                self.highest = Some(counter);
+            }
+        }
+    }
+}
+
+/// Established encrypted channel for one peer.
+pub struct SecureChannel {
+    send: ChaCha20Poly1305,
+    recv: ChaCha20Poly1305,
+    next_send_counter: u64,
+    replay: ReplayWindow,
+}
+
+/// `FrameCipher` over one direction's ChaCha20-Poly1305 key.
+struct ChaChaFrameCipher<'a>(&'a ChaCha20Poly1305);
+
+impl FrameCipher for ChaChaFrameCipher<'_> {
+    fn seal(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        self.0
+            .encrypt(Nonce::from_slice(nonce), payload)
+            .expect("ChaCha20-Poly1305 encryption cannot fail for in-memory buffers")
+    }
+
+    fn open(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+        let payload = Payload { msg: sealed, aad };
+        self.0.decrypt(Nonce::from_slice(nonce), payload).ok()
+    }
+}
+
+impl SecureChannel {
+    /// Encrypt one outgoing datagram as an `EncryptedFrame` under `key_id`.
+    pub fn seal_frame(&mut self, key_id: u32, plaintext: &[u8]) -> EncryptedFrame {
+        let counter = self.next_send_counter;
+        self.next_send_counter += 1;
+        encryption::seal_frame(
+            &ChaChaFrameCipher(&self.send),
+            key_id,
+            encryption::counter_nonce(counter),
+            plaintext,
+        )
+        .expect("ChaCha20-Poly1305 output always carries a tag")
+    }
+
+    /// Verify and decrypt one incoming `EncryptedFrame`; the caller has
+    /// matched its `key_id` to this channel.
+    pub fn open_frame(&mut self, frame: &EncryptedFrame) -> Result<Vec<u8>, CryptoError> {
+        let counter = encryption::nonce_counter(&frame.nonce).ok_or(CryptoError::MalformedFrame)?;
+        self.replay.check(counter)?;
+        let plaintext =
+            encryption::open_frame(&ChaChaFrameCipher(&self.recv), frame).map_err(|e| match e {
+                FrameError::AuthenticationFailed => CryptoError::AuthenticationFailed,
+                _ => CryptoError::MalformedFrame,
+            })?;
+        // Only authenticated packets advance the window.
+        self.replay.accept(counter);
+        Ok(plaintext)
+    }
+}
+
+/// Server-side keys agreed with one peer.
+#[derive(Default)]
+pub struct PeerKeys {
+    current: Option<(u32, SecureChannel)>,
+    /// Keys replaced by the latest rekey.
+    previous: Option<(u32, SecureChannel)>,
+    last_key_id: u32,
+}
+
+impl PeerKeys {
+    /// True once a key exchange has completed; from then on the peer's
+    /// Realtime traffic must be sealed.
+    pub fn is_established(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Answer a `KeyExchangeInit` with a fresh ephemeral key.
+    pub fn respond(
+        &mut self,
+        init: &KeyExchangeInit,
+        server_key: &ServerKey,
+    ) -> Result<KeyExchangeResponse, CryptoError> {
+        self.respond_with(KeyExchange::generate()?, init, server_key)
+    }
+
+    /// Answer a `KeyExchangeInit` with `exchange`. The new keys get the next
+    /// `key_id` and seal everything sent from now on; the keys they replace
+    /// still open frames already in flight.
+    pub fn respond_with(
+        &mut self,
+        exchange: KeyExchange,
+        init: &KeyExchangeInit,
+        server_key: &ServerKey,
+    ) -> Result<KeyExchangeResponse, CryptoError> {
+        let algorithm = encryption::choose_algorithm(&init.algorithms, &SUPPORTED_ALGORITHMS)
+            .ok_or(CryptoError::NoCommonAlgorithm)?;
+        let public_key = exchange.public_key();
+        let channel = exchange.finish_server(&init.public_key, server_key)?;
+        // Key id 0 is never issued, so a default frame names no keys
+        let key_id = self.last_key_id.wrapping_add(1).max(1);
+        self.last_key_id = key_id;
+        self.previous = self.current.replace((key_id, channel));
+        Ok(KeyExchangeResponse {
+            public_key: public_key.to_vec(),
+            key_id,
+            algorithm: algorithm as i32,
+        })
+    }
+
+    /// Seal an outgoing datagram under the current keys; `None` before any
+    /// key exchange.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Option<EncryptedFrame> {
+        let (key_id, channel) = self.current.as_mut()?;
+        Some(channel.seal_frame(*key_id, plaintext))
+    }
+
+    /// Open an incoming frame with the keys its `key_id` names.
+    pub fn open(&mut self, frame: &EncryptedFrame) -> Result<Vec<u8>, CryptoError> {
+        [&mut self.current, &mut self.previous]
+            .into_iter()
+            .flatten()
+            .find(|(key_id, _)| *key_id == frame.key_id)
+            .ok_or(CryptoError::UnknownKey {
+                key_id: frame.key_id,
+            })?
+            .1
+            .open_frame(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_key() -> ServerKey {
+        ServerKey::from_secret_bytes([9; 32])
+    }
+
+    fn channel_pair() -> (SecureChannel, SecureChannel) {
+        let client = KeyExchange::from_secret_bytes([1; 32]);
+        let server = KeyExchange::from_secret_bytes([2; 32]);
+        let client_public = client.public_key();
+        let server_public = server.public_key();
+        let server_key = server_key();
+        (
+            client
+                .finish_client(&server_public, &server_key.public_key())
+                .unwrap(),
+            server.finish_server(&client_public, &server_key).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_roundtrip_both_directions() {
+        let (mut client, mut server) = channel_pair();
+        let frame = client.seal_frame(1, b"input");
+        assert_ne!(frame.ciphertext, b"input");
+        assert_eq!(server.open_frame(&frame).unwrap(), b"input");
+        let frame = server.seal_frame(1, b"snapshot");
+        assert_eq!(client.open_frame(&frame).unwrap(), b"snapshot");
+    }
+
+    #[test]
+    fn test_tampered_frame_rejected() {
+        let (mut client, mut server) = channel_pair();
+        let mut frame = client.seal_frame(1, b"input");
+        frame.tag[0] ^= 1;
+        assert_eq!(
+            server.open_frame(&frame),
+            Err(CryptoError::AuthenticationFailed)
+        );
+        let mut frame = client.seal_frame(1, b"input");
+        frame.ciphertext[0] ^= 1;
+        assert_eq!(
+            server.open_frame(&frame),
+            Err(CryptoError::AuthenticationFailed)
+        );
+        frame.nonce.truncate(8);
+        assert_eq!(server.open_frame(&frame), Err(CryptoError::MalformedFrame));
+    }
+
+    #[test]
+    fn test_own_direction_not_accepted() {
+        // A frame reflected back to its sender must not verify.
+        let (mut client, _server) = channel_pair();
+        let frame = client.seal_frame(1, b"input");
+        assert_eq!(
+            client.open_frame(&frame),
+            Err(CryptoError::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_reordering_allowed_replay_rejected() {
+        let (mut client, mut server) = channel_pair();
+        let frames: Vec<_> = (0..5u8).map(|i| client.seal_frame(1, &[i])).collect();
+        assert_eq!(server.open_frame(&frames[3]).unwrap(), vec![3]);
+        assert_eq!(server.open_frame(&frames[1]).unwrap(), vec![1]);
+        assert_eq!(
+            server.open_frame(&frames[3]),
+            Err(CryptoError::Replayed { counter: 3 })
+        );
+        assert_eq!(server.open_frame(&frames[4]).unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn test_stale_frame_outside_window_rejected() {
+        let (mut client, mut server) = channel_pair();
+        let first = client.seal_frame(1, b"old");
+        for _ in 0..REPLAY_WINDOW {
+            let frame = client.seal_frame(1, b"new");
+            server.open_frame(&frame).unwrap();
+        }
+        assert_eq!(
+            server.open_frame(&first),
+            Err(CryptoError::Replayed { counter: 0 })
+        );
+    }
+
+    #[test]
+    fn test_encrypted_frames() {
+        let (mut client, mut server) = channel_pair();
+        let frame = client.seal_frame(4, b"input");
+        assert_eq!(frame.key_id, 4);
+        assert_eq!(server.open_frame(&frame).unwrap(), b"input");
+        assert_eq!(
+            server.open_frame(&frame),
+            Err(CryptoError::Replayed { counter: 0 })
+        );
+
+        // Relabeling the key id breaks authentication
+        let frame = client.seal_frame(4, b"input");
+        let relabeled = EncryptedFrame { key_id: 5, ..frame };
+        assert_eq!(
+            server.open_frame(&relabeled),
+            Err(CryptoError::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_exchange_authenticated_by_server_key() {
+        // A relay answering with its own ephemeral key, but without the
+        // server's static secret, ends up with keys the client does not share
+        let client = KeyExchange::from_secret_bytes([1; 32]);
+        let relay = KeyExchange::from_secret_bytes([2; 32]);
+        let client_public = client.public_key();
+        let relay_public = relay.public_key();
+        let mut client = client
+            .finish_client(&relay_public, &server_key().public_key())
+            .unwrap();
+        let impostor = ServerKey::from_secret_bytes([8; 32]);
+        let mut relay = relay.finish_server(&client_public, &impostor).unwrap();
+
+        let frame = client.seal_frame(1, b"input");
+        assert_eq!(
+            relay.open_frame(&frame),
+            Err(CryptoError::AuthenticationFailed)
+        );
+        let forged = relay.seal_frame(1, b"snapshot");
+        assert_eq!(
+            client.open_frame(&forged),
+            Err(CryptoError::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_invalid_and_weak_public_keys() {
+        let kx = KeyExchange::from_secret_bytes([3; 32]);
+        assert!(matches!(
+            kx.finish_server(&[0; 5], &server_key()),
+            Err(CryptoError::InvalidPublicKey)
+        ));
+        // The all-zero point is low-order and yields a zero shared secret.
+        let kx = KeyExchange::from_secret_bytes([3; 32]);
+        assert!(matches!(
+            kx.finish_server(&[0; 32], &server_key()),
+            Err(CryptoError::WeakSharedSecret)
+        ));
+        let kx = KeyExchange::from_secret_bytes([3; 32]);
+        let server = KeyExchange::from_secret_bytes([4; 32]).public_key();
+        assert!(matches!(
+            kx.finish_client(&server, &[0; 32]),
+            Err(CryptoError::WeakSharedSecret)
+        ));
+    }
+
+    #[test]
+    fn test_peer_keys_respond_and_rekey() {
+        let server_key = server_key();
+        let mut keys = PeerKeys::default();
+        assert!(!keys.is_established());
+        assert!(keys.seal(b"snapshot").is_none());
+
+        let client = KeyExchange::from_secret_bytes([1; 32]);
+        let init = KeyExchangeInit {
+            public_key: client.public_key().to_vec(),
+            algorithms: Vec::new(),
+        };
+        let response = keys.respond(&init, &server_key).unwrap();
+        assert!(keys.is_established());
+        assert_eq!(response.key_id, 1);
+        assert_eq!(response.algorithm(), AeadAlgorithm::Chacha20Poly1305);
+        let mut first = client
+            .finish_client(&response.public_key, &server_key.public_key())
+            .unwrap();
+        let frame = keys.seal(b"snapshot").unwrap();
+        assert_eq!(frame.key_id, 1);
+        assert_eq!(first.open_frame(&frame).unwrap(), b"snapshot");
+        let in_flight = first.seal_frame(1, b"old input");
+
+        // Rekey: new frames use key 2, frames under key 1 still open
+        let client = KeyExchange::from_secret_bytes([5; 32]);
+        let init = KeyExchangeInit {
+            public_key: client.public_key().to_vec(),
+            algorithms: vec![AeadAlgorithm::Chacha20Poly1305 as i32],
+        };
+        let response = keys.respond(&init, &server_key).unwrap();
+        assert_eq!(response.key_id, 2);
+        let mut second = client
+            .finish_client(&response.public_key, &server_key.public_key())
+            .unwrap();
+        assert_eq!(keys.seal(b"snapshot").unwrap().key_id, 2);
+        assert_eq!(keys.open(&in_flight).unwrap(), b"old input");
+        assert_eq!(
+            keys.open(&second.seal_frame(2, b"input")).unwrap(),
+            b"input"
+        );
+        assert_eq!(
+            keys.open(&second.seal_frame(7, b"input")),
+            Err(CryptoError::UnknownKey { key_id: 7 })
+        );
+
+        // A failed exchange leaves the agreed keys in place
+        let unsupported = KeyExchangeInit {
+            public_key: init.public_key.clone(),
+            algorithms: vec![AeadAlgorithm::Aes256Gcm as i32],
+        };
+        assert_eq!(
+            keys.respond(&unsupported, &server_key),
+            Err(CryptoError::NoCommonAlgorithm)
+        );
+        let bad_key = KeyExchangeInit {
+            public_key: vec![0; 3],
+            algorithms: Vec::new(),
+        };
+        let refusal = keys
+            .respond(&bad_key, &server_key)
+            .unwrap_err()
+            .to_error_response();
+        assert_eq!(refusal.code(), ErrorCode::KeyExchangeFailed);
+        assert!(!refusal.retryable);
+        assert_eq!(keys.seal(b"snapshot").unwrap().key_id, 2);
+    }
+
+    #[test]
+    fn test_generate_produces_distinct_keys() {
+        let a = KeyExchange::generate().unwrap();
+        let b = KeyExchange::generate().unwrap();
+        assert_ne!(a.public_key(), b.public_key());
+        let a = ServerKey::generate().unwrap();
+        let b = ServerKey::generate().unwrap();
+        assert_ne!(a.public_key(), b.public_key());
+    }
+}
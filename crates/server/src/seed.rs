@@ -0,0 +1,78 @@
+//! Match seed provisioning.
+//!
+//! Ref: DM-0011 (Server Edge), INV-0004, INV-0006
+//! - Outside test mode, the match seed is drawn from OS entropy by the Server
+//!   Edge; the Simulation Core only ever receives the resulting value
+//! - `seed: 0` (the config default) is never used outside test mode: it marks
+//!   "unset" and is replaced by a fresh non-zero draw
+//! - Test mode keeps the configured seed verbatim, including 0, so harnesses
+//!   stay reproducible
+//!
+//! The seed is recorded in the ReplayArtifact and the MatchSummary, along with
+//! where it came from.
+
+use serde::{Deserialize, Serialize};
+
+/// Where the match seed came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedSource {
+    /// Supplied by the host (config file, `--seed`, or a resumed checkpoint).
+    Configured,
+    /// Drawn from OS entropy at Server creation.
+    Entropy,
+}
+
+impl SeedSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Configured => "configured",
+            Self::Entropy => "entropy",
+        }
+    }
+}
+
+/// Draw a non-zero seed from OS entropy.
+///
+/// # Panics
+///
+/// If the OS entropy source is unavailable. A predictable fallback seed is
+/// worse than refusing to start the match.
+pub fn draw_seed() -> u64 {
+    loop {
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes).expect("OS entropy unavailable for seed provisioning");
+        let seed = u64::from_le_bytes(bytes);
+        if seed != 0 {
+            return seed;
+        }
+    }
+}
+
+/// Resolve the match seed for a config.
+///
+/// Returns the configured seed unless it is 0 outside test mode, in which case
+/// a fresh seed is drawn.
+pub fn provision_seed(configured: u64, test_mode: bool) -> (u64, SeedSource) {
+    if configured == 0 && !test_mode {
+        (draw_seed(), SeedSource::Entropy)
+    } else {
+        (configured, SeedSource::Configured)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_seed_replaced_outside_test_mode() {
+        let (seed, source) = provision_seed(0, false);
+        assert_ne!(seed, 0);
+        assert_eq!(source, SeedSource::Entropy);
+        assert_ne!(provision_seed(0, false).0, seed);
+
+        assert_eq!(provision_seed(0, true), (0, SeedSource::Configured));
+        assert_eq!(provision_seed(42, false), (42, SeedSource::Configured));
+    }
+}
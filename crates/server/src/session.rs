@@ -2,10 +2,40 @@
 //!
 //! Ref: DM-0008 (Session)
 
+pub use flowstate_sim::SessionId;
+
 use flowstate_sim::{EntityId, PlayerId};
+use flowstate_wire::{CompressionParams, TimeSyncReport};
+use serde::{Deserialize, Serialize};
+
+use crate::anomaly::InputAnomalyDetector;
+use crate::anticheat::MovementCheck;
+use crate::auth::PlayerIdentity;
+use crate::chat::ChatLimiter;
+use crate::net_stats::NetworkStats;
+use crate::rejections::InputRejections;
+use crate::replay_chunks::ReplayChunkLimiter;
+use crate::time_sync::{RttEstimator, TimeSyncSample};
 
-/// Session identifier (server-internal).
-pub type SessionId = u64;
+/// What kind of participant a session is; selects its input rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SessionRole {
+    #[default]
+    Player,
+    Spectator,
+    Bot,
+}
+
+/// What to do when an authenticated identity connects while it already has a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateIdentityPolicy {
+    /// Refuse the new connection; the existing session is kept.
+    #[default]
+    RejectNew,
+    /// Close the existing session and rebind its PlayerId and entity to the new one.
+    SupersedeOld,
+}
 
 /// Client session state.
 #[derive(Debug, Clone)]
@@ -13,10 +43,50 @@ pub struct Session {
     pub id: SessionId,
     pub player_id: PlayerId,
     pub controlled_entity_id: EntityId,
+    /// Authenticated identity (`None` for anonymous sessions).
+    pub identity: Option<PlayerIdentity>,
+    /// Participant role (default: Player).
+    pub role: SessionRole,
+    /// Negotiated `flowstate_wire::capabilities` (0 = baseline v0 behavior).
+    pub capabilities: u64,
+    /// Negotiated frame compression (`None` = uncompressed).
+    pub compression: Option<CompressionParams>,
     /// Last valid input tick received from this session (for monotonicity check).
     pub last_valid_tick: Option<u64>,
     /// Last input_seq received from this session.
     pub last_input_seq: Option<u64>,
+    /// Most recent TimeSync RTT/offset measurement (diagnostics).
+    pub time_sync: Option<TimeSyncSample>,
+    /// Smoothed RTT/offset over all TimeSync measurements.
+    rtt_estimator: RttEstimator,
+    /// Latest client-reported clock quality, with the server tick it arrived at.
+    pub client_time_sync: Option<(TimeSyncReport, u64)>,
+    /// Newest snapshot tick the client has acknowledged.
+    pub last_acked_snapshot_tick: Option<u64>,
+    /// The client asked for a keyframe that has not been built yet.
+    pub keyframe_requested: bool,
+    /// Clock time of the last message from this session (pre-match GC).
+    pub last_activity_micros: u64,
+    /// Highest Heartbeat counter received.
+    pub last_heartbeat: Option<u64>,
+    /// Network quality counters (diagnostics).
+    network_stats: NetworkStats,
+    /// Movement plausibility check state.
+    movement_check: MovementCheck,
+    /// Input pattern anomaly detector.
+    anomaly_detector: InputAnomalyDetector,
+    /// Chat rate limiter.
+    pub(crate) chat_limiter: ChatLimiter,
+    /// Replay download rate limiter.
+    pub(crate) replay_chunk_limiter: ReplayChunkLimiter,
+    /// Dropped inputs since the last InputRejectionReport.
+    pub(crate) input_rejections: InputRejections,
+    /// Consecutive below-floor drops since the last accepted input or resend.
+    pub(crate) below_floor_streak: u32,
+    /// Tick of the last FloorUpdate resend (at most one per tick).
+    pub(crate) last_floor_resend_tick: Option<u64>,
+    /// Tick of the last BaselineUpdate sent (see `resync`).
+    pub(crate) last_baseline_tick: Option<u64>,
 }
 
 impl Session {
@@ -26,8 +96,103 @@ impl Session {
             id,
             player_id,
             controlled_entity_id,
+            identity: None,
+            role: SessionRole::Player,
+            capabilities: 0,
+            compression: None,
             last_valid_tick: None,
             last_input_seq: None,
+            time_sync: None,
+            rtt_estimator: RttEstimator::new(),
+            client_time_sync: None,
+            last_acked_snapshot_tick: None,
+            keyframe_requested: false,
+            last_activity_micros: 0,
+            last_heartbeat: None,
+            network_stats: NetworkStats::new(),
+            movement_check: MovementCheck::new(),
+            anomaly_detector: InputAnomalyDetector::new(),
+            chat_limiter: ChatLimiter::default(),
+            replay_chunk_limiter: ReplayChunkLimiter::default(),
+            input_rejections: InputRejections::default(),
+            below_floor_streak: 0,
+            last_floor_resend_tick: None,
+            last_baseline_tick: None,
         }
     }
+
+    /// Move this session's player binding to a new SessionId (reconnect).
+    /// Per-connection sequencing state is reset; per-player diagnostics are kept.
+    pub(crate) fn rebind(&mut self, new_id: SessionId) {
+        self.id = new_id;
+        self.last_valid_tick = None;
+        self.last_input_seq = None;
+        self.time_sync = None;
+        self.rtt_estimator = RttEstimator::new();
+        self.client_time_sync = None;
+        self.last_acked_snapshot_tick = None;
+        self.keyframe_requested = false;
+        self.last_heartbeat = None;
+        self.below_floor_streak = 0;
+        self.last_floor_resend_tick = None;
+        self.last_baseline_tick = None;
+    }
+
+    /// Reset match-scoped state for a rematch on the same connection: the
+    /// World restarts at tick 0, so tick-based sequencing and acks start
+    /// over. Clock sync, heartbeats, and per-player diagnostics are kept.
+    pub(crate) fn begin_rematch(&mut self) {
+        self.last_valid_tick = None;
+        self.last_input_seq = None;
+        self.last_acked_snapshot_tick = None;
+        self.keyframe_requested = false;
+        self.below_floor_streak = 0;
+        self.last_floor_resend_tick = None;
+        self.last_baseline_tick = None;
+        self.movement_check.restart();
+    }
+
+    /// Smoothed round-trip time in microseconds (`None` before two TimeSync pings).
+    pub fn rtt(&self) -> Option<u64> {
+        self.rtt_estimator.rtt_micros()
+    }
+
+    /// Smoothed RTT and clock-offset estimator for this session.
+    pub fn rtt_estimator(&self) -> &RttEstimator {
+        &self.rtt_estimator
+    }
+
+    /// Record a TimeSync measurement.
+    pub(crate) fn record_time_sync(&mut self, sample: TimeSyncSample) {
+        self.rtt_estimator.observe(&sample);
+        self.time_sync = Some(sample);
+    }
+
+    /// Network quality statistics for this session.
+    pub fn network_stats(&self) -> &NetworkStats {
+        &self.network_stats
+    }
+
+    /// Mutable access for the Server Edge to update counters.
+    pub(crate) fn network_stats_mut(&mut self) -> &mut NetworkStats {
+        &mut self.network_stats
+    }
+
+    /// Movement plausibility check state for this session.
+    pub fn movement_check(&self) -> &MovementCheck {
+        &self.movement_check
+    }
+
+    pub(crate) fn movement_check_mut(&mut self) -> &mut MovementCheck {
+        &mut self.movement_check
+    }
+
+    /// Input anomaly detector for this session.
+    pub fn anomaly_detector(&self) -> &InputAnomalyDetector {
+        &self.anomaly_detector
+    }
+
+    pub(crate) fn anomaly_detector_mut(&mut self) -> &mut InputAnomalyDetector {
+        &mut self.anomaly_detector
+    }
 }
@@ -0,0 +1,181 @@
+//! Match result summary artifact.
+//!
+//! Ref: DM-0017 (ReplayArtifact), DM-0011 (Server Edge)
+//! - Headline match data as JSON, so tooling need not decode replay protobufs
+//! - Derived from Server Edge state at finalize; the ReplayArtifact remains the
+//!   authoritative record (INV-0006)
+//!
+//! Digests are rendered as `0x`-prefixed hex strings: JSON numbers lose
+//! precision above 2^53 in most consumers.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use flowstate_sim::{EntityId, PlayerId};
+use serde::{Deserialize, Serialize};
+
+use crate::net_stats::NetworkStats;
+use crate::seed::SeedSource;
+
+/// Summary format version.
+pub const MATCH_SUMMARY_VERSION: u32 = 1;
+
+/// Per-player headline data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerSummary {
+    pub player_id: PlayerId,
+    pub entity_id: EntityId,
+    /// Authenticated identity, if any.
+    pub identity: Option<String>,
+    pub final_position: Option<[f64; 2]>,
+    pub distance_moved: f64,
+    pub inputs_received: u64,
+    pub inputs_accepted: u64,
+    /// Dropped inputs by validation reason.
+    pub inputs_dropped: BTreeMap<String, u64>,
+    pub fallback_ticks: u64,
+    /// Mean target-tick lead of accepted inputs.
+    pub average_input_lead_ticks: Option<f64>,
+    /// Movement plausibility check tripped (see `anticheat`).
+    pub movement_flagged: bool,
+    /// Soft input anomaly flags (see `anomaly`).
+    pub input_anomalies: Vec<String>,
+    /// Network quality of the player's session at finalize (absent with no
+    /// session, or in summaries predating it).
+    #[serde(default)]
+    pub network: Option<NetworkSummary>,
+}
+
+/// Per-session network quality (see `net_stats`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSummary {
+    /// Smoothed input arrival jitter in microseconds.
+    pub jitter_micros: f64,
+    /// Fraction of received inputs dropped as late.
+    pub late_input_rate: f64,
+    pub below_floor_drops: u64,
+    /// Fraction of observed ticks that used LastKnownIntent.
+    pub fallback_rate: f64,
+    /// Fraction of snapshot ticks spanned by acks that went unacknowledged.
+    pub snapshot_loss_rate: f64,
+}
+
+impl From<&NetworkStats> for NetworkSummary {
+    fn from(stats: &NetworkStats) -> Self {
+        Self {
+            jitter_micros: stats.jitter_micros(),
+            late_input_rate: stats.late_input_rate(),
+            below_floor_drops: stats.below_floor_drops(),
+            fallback_rate: stats.fallback_rate(),
+            snapshot_loss_rate: stats.snapshot_loss_rate(),
+        }
+    }
+}
+
+/// Match result summary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchSummary {
+    pub summary_version: u32,
+    /// Host-assigned match id (empty if unset).
+    pub match_id: String,
+    pub seed: u64,
+    /// Where the seed came from (absent in summaries predating it).
+    #[serde(default = "default_seed_source")]
+    pub seed_source: SeedSource,
+    pub tick_rate_hz: u32,
+    pub start_tick: u64,
+    pub end_tick: u64,
+    pub duration_ticks: u64,
+    pub end_reason: String,
+    /// Final StateDigest as `0x`-prefixed hex.
+    pub final_digest: String,
+    /// Where the replay artifact was written, once known.
+    pub replay_path: Option<String>,
+    /// Players in spawn order.
+    pub players: Vec<PlayerSummary>,
+}
+
+impl MatchSummary {
+    /// Record where the replay artifact was written.
+    pub fn set_replay_path(&mut self, path: &Path) {
+        self.replay_path = Some(path.display().to_string());
+    }
+
+    /// Pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("MatchSummary serializes infallibly")
+    }
+
+    /// Parse from JSON.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Write the JSON summary to `path`.
+    pub fn write_json(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+fn default_seed_source() -> SeedSource {
+    SeedSource::Configured
+}
+
+/// Render a digest for the summary.
+pub fn format_digest(digest: u64) -> String {
+    format!("{digest:#018x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_format() {
+        assert_eq!(format_digest(0xab), "0x00000000000000ab");
+        assert_eq!(format_digest(u64::MAX), "0xffffffffffffffff");
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut summary = MatchSummary {
+            summary_version: MATCH_SUMMARY_VERSION,
+            match_id: "m-1".to_string(),
+            seed: 7,
+            seed_source: SeedSource::Entropy,
+            tick_rate_hz: 60,
+            start_tick: 0,
+            end_tick: 60,
+            duration_ticks: 60,
+            end_reason: "complete".to_string(),
+            final_digest: format_digest(42),
+            replay_path: None,
+            players: vec![PlayerSummary {
+                player_id: PlayerId(0),
+                entity_id: EntityId(1),
+                identity: Some("alice".to_string()),
+                final_position: Some([1.0, 2.0]),
+                distance_moved: 2.5,
+                inputs_received: 10,
+                inputs_accepted: 9,
+                inputs_dropped: BTreeMap::from([("late".to_string(), 1)]),
+                fallback_ticks: 3,
+                average_input_lead_ticks: Some(1.0),
+                movement_flagged: false,
+                input_anomalies: vec![],
+                network: Some(NetworkSummary {
+                    jitter_micros: 1250.5,
+                    late_input_rate: 0.1,
+                    below_floor_drops: 2,
+                    fallback_rate: 0.05,
+                    snapshot_loss_rate: 0.25,
+                }),
+            }],
+        };
+        summary.set_replay_path(Path::new("replays/m-1.replay"));
+        let json = summary.to_json();
+        assert!(json.contains("\"final_digest\": \"0x000000000000002a\""));
+        assert_eq!(MatchSummary::from_json(&json).unwrap(), summary);
+    }
+}
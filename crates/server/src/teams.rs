@@ -0,0 +1,144 @@
+//! Team assignment for team game modes.
+//!
+//! Ref: DM-0011 (Server Edge), DM-0019 (PlayerId), INV-0006
+//! - Teams are resolved at match start, once every player and identity is
+//!   known (pre-match GC may still reassign PlayerIds before that)
+//! - The result is passed to the Simulation Core (`World::set_team`), sent in
+//!   each ServerWelcome, and recorded in the ReplayArtifact's entity mapping
+//! - Assignment is a pure function of the mode and the players in spawn order,
+//!   so it is deterministic
+//!
+//! Default `None` leaves every Character teamless (v0 free-for-all).
+
+use std::collections::BTreeMap;
+
+use flowstate_sim::{PlayerId, TeamId};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::PlayerIdentity;
+
+/// How players are split into teams.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "mode")]
+pub enum TeamAssignment {
+    /// No teams.
+    #[default]
+    None,
+    /// Players alternate across `teams` teams in spawn order.
+    RoundRobin { teams: TeamId },
+    /// Party `i` (a list of identities) plays as team `i`. Players in no
+    /// party (including anonymous ones) join the currently smallest team,
+    /// lowest TeamId first.
+    Party { parties: Vec<Vec<PlayerIdentity>> },
+    /// Explicit PlayerId → TeamId; unlisted players get no team.
+    Fixed { teams: BTreeMap<PlayerId, TeamId> },
+}
+
+/// Resolve teams for `players`, given in spawn order with their identities.
+pub fn assign_teams(
+    mode: &TeamAssignment,
+    players: &[(PlayerId, Option<&PlayerIdentity>)],
+) -> BTreeMap<PlayerId, TeamId> {
+    match mode {
+        TeamAssignment::None => BTreeMap::new(),
+        TeamAssignment::RoundRobin { teams } => {
+            let teams = (*teams).max(1);
+            players
+                .iter()
+                .enumerate()
+                .map(|(i, &(player_id, _))| (player_id, (i % usize::from(teams)) as TeamId))
+                .collect()
+        }
+        TeamAssignment::Party { parties } => {
+            let party_team = |identity: &PlayerIdentity| {
+                parties
+                    .iter()
+                    .position(|party| party.contains(identity))
+                    .map(|i| i as TeamId)
+            };
+            let mut assigned = BTreeMap::new();
+            let mut sizes = vec![0usize; parties.len().max(1)];
+            for &(player_id, identity) in players {
+                if let Some(team) = identity.and_then(party_team) {
+                    assigned.insert(player_id, team);
+                    sizes[usize::from(team)] += 1;
+                }
+            }
+            for &(player_id, identity) in players {
+                if identity.and_then(party_team).is_none() {
+                    let (smallest, _) = sizes
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|&(i, &size)| (size, i))
+                        .expect("at least one team");
+                    sizes[smallest] += 1;
+                    assigned.insert(player_id, smallest as TeamId);
+                }
+            }
+            assigned
+        }
+        TeamAssignment::Fixed { teams } => players
+            .iter()
+            .filter_map(|&(player_id, _)| Some((player_id, *teams.get(&player_id)?)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin_and_fixed() {
+        let players = [
+            (PlayerId(0), None),
+            (PlayerId(1), None),
+            (PlayerId(2), None),
+        ];
+        let teams = assign_teams(&TeamAssignment::RoundRobin { teams: 2 }, &players);
+        assert_eq!(
+            teams,
+            BTreeMap::from([(PlayerId(0), 0), (PlayerId(1), 1), (PlayerId(2), 0)])
+        );
+
+        let fixed = TeamAssignment::Fixed {
+            teams: BTreeMap::from([(PlayerId(2), 7)]),
+        };
+        assert_eq!(
+            assign_teams(&fixed, &players),
+            BTreeMap::from([(PlayerId(2), 7)])
+        );
+        assert!(assign_teams(&TeamAssignment::None, &players).is_empty());
+    }
+
+    #[test]
+    fn test_party_assignment() {
+        let (a, b, c) = ("a".to_string(), "b".to_string(), "c".to_string());
+        let mode = TeamAssignment::Party {
+            parties: vec![vec![a.clone(), b.clone()], vec![c.clone()]],
+        };
+        let players = [
+            (PlayerId(0), Some(&a)),
+            (PlayerId(1), None),
+            (PlayerId(2), Some(&c)),
+            (PlayerId(3), Some(&b)),
+        ];
+        // Party members first; the anonymous player fills the smaller team 1
+        assert_eq!(
+            assign_teams(&mode, &players),
+            BTreeMap::from([
+                (PlayerId(0), 0),
+                (PlayerId(1), 1),
+                (PlayerId(2), 1),
+                (PlayerId(3), 0)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_config_json() {
+        let mode: TeamAssignment =
+            serde_json::from_str(r#"{ "mode": "round_robin", "teams": 2 }"#).unwrap();
+        assert_eq!(mode, TeamAssignment::RoundRobin { teams: 2 });
+    }
+}
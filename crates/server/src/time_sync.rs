@@ -0,0 +1,239 @@
+//! TimeSync ping/pong handling for Server Edge.
+//!
+//! Ref: ADR-0005 (Tier 1 debug/telemetry)
+//! - Pong carries server tick, server timestamp, and the echoed ping timestamp
+//! - Server-side RTT uses the echoed pong timestamp minus the client's hold time
+//! - Results are diagnostics only; they never affect simulation state
+//! - `RttEstimator` smooths samples per session (EWMA, RFC 6298 style gains)
+//!   and rejects outliers above `srtt + max(4 * rttvar, RTT_OUTLIER_MIN_MARGIN_MICROS)`;
+//!   after `RTT_OUTLIER_MAX_CONSECUTIVE` rejections in a row the sample is
+//!   accepted anyway, so a genuine path change is followed
+//!
+//! All timestamps are microseconds. Client and server clocks have unrelated epochs.
+
+use flowstate_sim::Tick;
+use flowstate_wire::{TimeSyncPing, TimeSyncPong};
+
+/// One RTT/clock-offset measurement for a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSyncSample {
+    /// Round-trip time in microseconds.
+    pub rtt_micros: u64,
+    /// Estimated `client_clock - server_clock` in microseconds.
+    pub clock_offset_micros: i64,
+    /// Server tick when the sample was taken.
+    pub server_tick: Tick,
+}
+
+/// Minimum margin above the smoothed RTT before a sample counts as an outlier.
+pub const RTT_OUTLIER_MIN_MARGIN_MICROS: u64 = 5_000;
+
+/// Consecutive outliers after which the next one is accepted.
+pub const RTT_OUTLIER_MAX_CONSECUTIVE: u32 = 3;
+
+/// Smoothed RTT and clock-offset estimator for one session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RttEstimator {
+    /// Smoothed RTT (`None` until the first sample).
+    srtt_micros: Option<u64>,
+    /// Smoothed mean deviation of RTT.
+    rttvar_micros: u64,
+    /// Smoothed `client_clock - server_clock`.
+    clock_offset_micros: i64,
+    samples_accepted: u64,
+    samples_rejected: u64,
+    consecutive_outliers: u32,
+}
+
+impl RttEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one measurement. Returns `false` if it was rejected as an outlier.
+    pub fn observe(&mut self, sample: &TimeSyncSample) -> bool {
+        let rtt = sample.rtt_micros;
+        let Some(srtt) = self.srtt_micros else {
+            self.srtt_micros = Some(rtt);
+            self.rttvar_micros = rtt / 2;
+            self.clock_offset_micros = sample.clock_offset_micros;
+            self.samples_accepted = 1;
+            return true;
+        };
+
+        let margin = (4 * self.rttvar_micros).max(RTT_OUTLIER_MIN_MARGIN_MICROS);
+        if rtt > srtt.saturating_add(margin)
+            && self.consecutive_outliers < RTT_OUTLIER_MAX_CONSECUTIVE
+        {
+            self.consecutive_outliers += 1;
+            self.samples_rejected += 1;
+            return false;
+        }
+        self.consecutive_outliers = 0;
+
+        // rttvar <- 3/4 rttvar + 1/4 |srtt - rtt|; srtt <- 7/8 srtt + 1/8 rtt
+        let deviation = srtt.abs_diff(rtt);
+        self.rttvar_micros = self.rttvar_micros - self.rttvar_micros / 4 + deviation / 4;
+        let srtt = i128::from(srtt) + (i128::from(rtt) - i128::from(srtt)) / 8;
+        self.srtt_micros = Some(u64::try_from(srtt).unwrap_or(u64::MAX));
+        let offset = i128::from(self.clock_offset_micros)
+            + (i128::from(sample.clock_offset_micros) - i128::from(self.clock_offset_micros)) / 8;
+        self.clock_offset_micros = i64::try_from(offset).unwrap_or(self.clock_offset_micros);
+        self.samples_accepted += 1;
+        true
+    }
+
+    /// Smoothed RTT in microseconds (`None` until the first sample).
+    pub fn rtt_micros(&self) -> Option<u64> {
+        self.srtt_micros
+    }
+
+    /// Smoothed RTT deviation in microseconds (jitter).
+    pub fn rtt_variance_micros(&self) -> u64 {
+        self.rttvar_micros
+    }
+
+    /// Smoothed `client_clock - server_clock` (`None` until the first sample).
+    pub fn clock_offset_micros(&self) -> Option<i64> {
+        self.srtt_micros.map(|_| self.clock_offset_micros)
+    }
+
+    pub fn samples_accepted(&self) -> u64 {
+        self.samples_accepted
+    }
+
+    pub fn samples_rejected(&self) -> u64 {
+        self.samples_rejected
+    }
+}
+
+/// Build the pong answering `ping`.
+pub fn build_pong(ping: &TimeSyncPing, server_tick: Tick, now_micros: u64) -> TimeSyncPong {
+    TimeSyncPong {
+        server_tick,
+        server_timestamp: now_micros,
+        ping_timestamp_echo: ping.client_timestamp,
+    }
+}
+
+/// Estimate RTT and clock offset from a ping received at `now_micros`.
+///
+/// Requires the ping to echo a previous pong's `server_timestamp`; returns
+/// `None` for the first ping of a session or for inconsistent echoes.
+pub fn estimate(ping: &TimeSyncPing, server_tick: Tick, now_micros: u64) -> Option<TimeSyncSample> {
+    if ping.server_timestamp_echo == 0 || ping.server_timestamp_echo > now_micros {
+        return None;
+    }
+    let rtt_micros =
+        (now_micros - ping.server_timestamp_echo).checked_sub(ping.client_hold_micros)?;
+
+    // The ping left the client ~rtt/2 before it arrived here.
+    let server_time_at_send = i128::from(now_micros) - i128::from(rtt_micros / 2);
+    let offset = i128::from(ping.client_timestamp) - server_time_at_send;
+    let clock_offset_micros = i64::try_from(offset).ok()?;
+
+    Some(TimeSyncSample {
+        rtt_micros,
+        clock_offset_micros,
+        server_tick,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_pong_echoes_ping() {
+        let ping = TimeSyncPing {
+            client_timestamp: 1234,
+            ..Default::default()
+        };
+        let pong = build_pong(&ping, 42, 9999);
+        assert_eq!(pong.server_tick, 42);
+        assert_eq!(pong.server_timestamp, 9999);
+        assert_eq!(pong.ping_timestamp_echo, 1234);
+    }
+
+    #[test]
+    fn test_estimate_requires_echo() {
+        let ping = TimeSyncPing {
+            client_timestamp: 1234,
+            ..Default::default()
+        };
+        assert!(estimate(&ping, 0, 10_000).is_none());
+    }
+
+    #[test]
+    fn test_estimate_rtt_and_offset() {
+        // Server sent pong at 10_000; client held it 5_000us; ping arrives at 55_000.
+        // RTT = 55_000 - 10_000 - 5_000 = 40_000.
+        // Client clock runs 1_000_000 ahead: it sent at server time 35_000.
+        let ping = TimeSyncPing {
+            client_timestamp: 1_035_000,
+            server_timestamp_echo: 10_000,
+            client_hold_micros: 5_000,
+        };
+        let sample = estimate(&ping, 7, 55_000).unwrap();
+        assert_eq!(sample.rtt_micros, 40_000);
+        assert_eq!(sample.clock_offset_micros, 1_000_000);
+        assert_eq!(sample.server_tick, 7);
+    }
+
+    #[test]
+    fn test_estimate_rejects_inconsistent_echo() {
+        // Echo from the future
+        let ping = TimeSyncPing {
+            client_timestamp: 0,
+            server_timestamp_echo: 20_000,
+            client_hold_micros: 0,
+        };
+        assert!(estimate(&ping, 0, 10_000).is_none());
+
+        // Hold time longer than the elapsed time
+        let ping = TimeSyncPing {
+            client_timestamp: 0,
+            server_timestamp_echo: 10_000,
+            client_hold_micros: 50_000,
+        };
+        assert!(estimate(&ping, 0, 20_000).is_none());
+    }
+
+    fn sample(rtt_micros: u64, clock_offset_micros: i64) -> TimeSyncSample {
+        TimeSyncSample {
+            rtt_micros,
+            clock_offset_micros,
+            server_tick: 0,
+        }
+    }
+
+    #[test]
+    fn test_estimator_smooths_and_rejects_outliers() {
+        let mut estimator = RttEstimator::new();
+        assert_eq!(estimator.rtt_micros(), None);
+        assert_eq!(estimator.clock_offset_micros(), None);
+
+        assert!(estimator.observe(&sample(40_000, 1_000)));
+        assert!(estimator.observe(&sample(48_000, 1_800)));
+        assert_eq!(estimator.rtt_micros(), Some(41_000));
+        assert_eq!(estimator.clock_offset_micros(), Some(1_100));
+        // rttvar: 20_000 - 5_000 + 8_000 / 4
+        assert_eq!(estimator.rtt_variance_micros(), 17_000);
+
+        // Settle the variance, then spike
+        for _ in 0..40 {
+            estimator.observe(&sample(40_000, 1_000));
+        }
+        let settled = estimator.rtt_micros().unwrap();
+        assert!(!estimator.observe(&sample(500_000, 90_000)));
+        assert_eq!(estimator.rtt_micros(), Some(settled));
+        assert_eq!(estimator.samples_rejected(), 1);
+
+        // A sustained shift is followed after the consecutive-outlier limit
+        for _ in 1..RTT_OUTLIER_MAX_CONSECUTIVE {
+            assert!(!estimator.observe(&sample(200_000, 1_000)));
+        }
+        assert!(estimator.observe(&sample(200_000, 1_000)));
+        assert!(estimator.rtt_micros().unwrap() > settled);
+    }
+}
@@ -0,0 +1,606 @@
+//! Minimal UDP transport for the server binary.
+//!
+//! Ref: ADR-0005 (Control/Realtime Channels)
+//! - One wire message per datagram: `[channel: u8][envelope bytes]`
+//! - The channel byte selects the envelope (`ControlMessage` or
+//!   `RealtimeMessage`); the envelope's oneof identifies the message kind
+//! - Realtime datagrams may set `CHECKSUM_FLAG` on the channel byte, in which
+//!   case a CRC32C of the envelope precedes it (`flowstate_wire::checksum`);
+//!   a mismatching datagram is dropped before decoding
+//! - Realtime datagrams may set `SEQUENCE_FLAG`, in which case an outer
+//!   sequence number (`flowstate_wire::sequence`) precedes the envelope,
+//!   inside any checksum; the receiver uses it to drop duplicates
+//! - Either channel may set `COMPRESSED_FLAG`, in which case everything
+//!   after the channel byte (checksum, sequence, and envelope) is one
+//!   `flowstate_wire::compression` frame; `compress` applies a session's
+//!   negotiated codec to an already-framed datagram
+//! - Once a peer has agreed keys (`secure_channel::PeerKeys`), each Realtime
+//!   datagram, with its flags, is sealed whole into an `EncryptedFrame` and
+//!   sent as a plain Realtime envelope carrying it (`seal`); `unframe_secure`
+//!   opens it and refuses that peer's plaintext Realtime datagrams. Control
+//!   datagrams are not sealed, except a client's rekey `KeyExchangeInit`
+//! - Every message declares the delivery it needs
+//!   (`flowstate_wire::channel`); debug builds check each queued datagram
+//!   against its `SendClass` with `check_send_class`
+//! - Envelopes decode through `flowstate_wire::limits`, so oversized
+//!   messages, overlong repeated fields, and unparsable bytes are dropped
+//! - Non-blocking socket; the host loop polls between ticks
+//!
+//! v0 carries Control and Realtime messages over the same unreliable socket;
+//! retransmission and ordering of Control messages are not handled here.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use flowstate_wire::compression::{self, Codec, FrameLimits};
+use flowstate_wire::limits::{self, DecodeLimits};
+use flowstate_wire::{
+    CompressionParams, ControlMessage, EncryptedFrame, RealtimeMessage, checksum, control_message,
+    realtime_message, sequence,
+};
+use prost::Message;
+
+use crate::bandwidth::SendClass;
+use crate::secure_channel::PeerKeys;
+
+/// Largest datagram the transport reads.
+pub const MAX_DATAGRAM_LEN: usize = 64 * 1024;
+
+/// Channel-byte bit marking a CRC32C-checked envelope (Realtime only).
+pub const CHECKSUM_FLAG: u8 = 0x80;
+
+/// Channel-byte bit marking a sequenced datagram (Realtime only).
+pub const SEQUENCE_FLAG: u8 = 0x40;
+
+/// Channel-byte bit marking a compressed datagram (either channel).
+pub const COMPRESSED_FLAG: u8 = 0x20;
+
+const REALTIME_FLAGS: u8 = CHECKSUM_FLAG | SEQUENCE_FLAG;
+
+const FLAGS: u8 = REALTIME_FLAGS | COMPRESSED_FLAG;
+
+/// Channel tag (first datagram byte, without flag bits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Channel {
+    Control = 1,
+    Realtime = 2,
+}
+
+impl Channel {
+    pub fn from_u8(tag: u8) -> Option<Self> {
+        Some(match tag {
+            1 => Self::Control,
+            2 => Self::Realtime,
+            _ => return None,
+        })
+    }
+}
+
+/// A decoded datagram.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Datagram {
+    Control(control_message::Payload),
+    Realtime(realtime_message::Payload),
+}
+
+fn frame_envelope(channel: Channel, envelope: &[u8]) -> Vec<u8> {
+    let mut datagram = Vec::with_capacity(1 + envelope.len());
+    datagram.push(channel as u8);
+    datagram.extend_from_slice(envelope);
+    datagram
+}
+
+fn frame_realtime_envelope(envelope: &[u8], sequence: Option<u32>, checked: bool) -> Vec<u8> {
+    let mut datagram =
+        Vec::with_capacity(1 + sequence::SEQUENCE_LEN + checksum::CHECKSUM_LEN + envelope.len());
+    let mut tag = Channel::Realtime as u8;
+    if sequence.is_some() {
+        tag |= SEQUENCE_FLAG;
+    }
+    if checked {
+        tag |= CHECKSUM_FLAG;
+    }
+    datagram.push(tag);
+    let sequenced;
+    let payload = match sequence {
+        Some(number) => {
+            let mut buf = Vec::with_capacity(sequence::SEQUENCE_LEN + envelope.len());
+            sequence::write_sequenced(number, envelope, &mut buf);
+            sequenced = buf;
+            &sequenced[..]
+        }
+        None => envelope,
+    };
+    if checked {
+        checksum::write_checked(payload, &mut datagram);
+    } else {
+        datagram.extend_from_slice(payload);
+    }
+    datagram
+}
+
+/// Frame a Control Channel message for sending.
+pub fn frame_control(payload: control_message::Payload) -> Vec<u8> {
+    frame_envelope(
+        Channel::Control,
+        &ControlMessage::new(payload).encode_to_vec(),
+    )
+}
+
+/// Frame a Realtime Channel message for sending, with a CRC32C if `checked`.
+pub fn frame_realtime(payload: realtime_message::Payload, checked: bool) -> Vec<u8> {
+    frame_realtime_envelope(
+        &RealtimeMessage::new(payload).encode_to_vec(),
+        None,
+        checked,
+    )
+}
+
+/// Frame a Realtime Channel message under an outer `sequence` number, with a
+/// CRC32C if `checked`.
+pub fn frame_realtime_sequenced(
+    payload: realtime_message::Payload,
+    sequence: u32,
+    checked: bool,
+) -> Vec<u8> {
+    frame_realtime_envelope(
+        &RealtimeMessage::new(payload).encode_to_vec(),
+        Some(sequence),
+        checked,
+    )
+}
+
+/// Frame an already-encoded `SnapshotProto` (e.g., a shared snapshot payload),
+/// with a CRC32C if `checked`.
+pub fn frame_snapshot_bytes(snapshot: &[u8], checked: bool) -> Vec<u8> {
+    frame_realtime_envelope(
+        &RealtimeMessage::encode_snapshot_bytes(snapshot),
+        None,
+        checked,
+    )
+}
+
+/// Frame a sealed Realtime datagram for sending.
+pub fn frame_encrypted(frame: EncryptedFrame) -> Vec<u8> {
+    frame_realtime(realtime_message::Payload::EncryptedFrame(frame), false)
+}
+
+/// Seal a framed datagram for a peer with agreed `keys`. Control datagrams,
+/// and any datagram before the key exchange completes, are left as they are.
+pub fn seal(datagram: Vec<u8>, keys: &mut PeerKeys) -> Vec<u8> {
+    let realtime = datagram
+        .first()
+        .is_some_and(|&tag| Channel::from_u8(tag & !FLAGS) == Some(Channel::Realtime));
+    if !realtime {
+        return datagram;
+    }
+    match keys.seal(&datagram) {
+        Some(frame) => frame_encrypted(frame),
+        None => datagram,
+    }
+}
+
+/// Compress a framed datagram with a session's negotiated `params`. Left
+/// as is when smaller than `min_payload_len`, over `max_raw_len`, or when
+/// compression would not shrink it.
+pub fn compress(datagram: Vec<u8>, params: &CompressionParams) -> Vec<u8> {
+    let Some((&tag, rest)) = datagram.split_first() else {
+        return datagram;
+    };
+    let codec = Codec::from_proto(params.codec());
+    if codec == Codec::None || rest.len() < params.min_payload_len as usize {
+        return datagram;
+    }
+    let limits = FrameLimits {
+        max_raw_len: params.max_raw_len as usize,
+    };
+    match compression::encode_frame(codec, rest, &limits) {
+        Ok(frame) if frame.len() < rest.len() => {
+            let mut compressed = Vec::with_capacity(1 + frame.len());
+            compressed.push(tag | COMPRESSED_FLAG);
+            compressed.extend_from_slice(&frame);
+            compressed
+        }
+        _ => datagram,
+    }
+}
+
+/// Decode a datagram; `None` if empty, of unknown channel, failing to
+/// decompress or its checksum, undecodable or over `DecodeLimits::default()`, or carrying an
+/// envelope without a payload.
+/// Unchecked realtime datagrams are still accepted.
+pub fn unframe(datagram: &[u8]) -> Option<Datagram> {
+    unframe_sequenced(datagram).map(|(_, datagram)| datagram)
+}
+
+/// `unframe`, also returning the outer sequence number of a sequenced
+/// realtime datagram.
+pub fn unframe_sequenced(datagram: &[u8]) -> Option<(Option<u32>, Datagram)> {
+    let (&tag, mut envelope) = datagram.split_first()?;
+    let channel = Channel::from_u8(tag & !FLAGS)?;
+    if tag & REALTIME_FLAGS != 0 && channel != Channel::Realtime {
+        return None;
+    }
+    let decompressed;
+    if tag & COMPRESSED_FLAG != 0 {
+        let limits = FrameLimits {
+            max_raw_len: MAX_DATAGRAM_LEN,
+        };
+        let (payload, len) = compression::decode_frame(envelope, &limits).ok()?;
+        if len != envelope.len() {
+            return None;
+        }
+        decompressed = payload;
+        envelope = &decompressed;
+    }
+    if tag & CHECKSUM_FLAG != 0 {
+        envelope = checksum::verify(envelope).ok()?;
+    }
+    let mut sequence = None;
+    if tag & SEQUENCE_FLAG != 0 {
+        let (number, rest) = sequence::read_sequenced(envelope).ok()?;
+        sequence = Some(number);
+        envelope = rest;
+    }
+    let datagram = match channel {
+        Channel::Control => limits::decode_control(envelope, &DecodeLimits::default())
+            .ok()?
+            .payload
+            .map(Datagram::Control),
+        Channel::Realtime => limits::decode_realtime(envelope, &DecodeLimits::default())
+            .ok()?
+            .payload
+            .map(Datagram::Realtime),
+    }?;
+    Some((sequence, datagram))
+}
+
+/// `unframe_sequenced` for a datagram from a peer holding `keys`. Once the
+/// keys are agreed, a Realtime datagram must be an `EncryptedFrame` that
+/// opens under them, and the sequence number and message are those of the
+/// datagram sealed inside; plaintext Realtime datagrams are refused, and
+/// with `require_encryption` they are refused from peers without keys too.
+///
+/// A `KeyExchangeInit` is only accepted sealed (a rekey under the current
+/// keys); the first one rides in `ClientHello`, so a spoofed plaintext one
+/// cannot replace a peer's keys. Frames from peers without keys, and frames
+/// sealing anything else than a plaintext Realtime datagram or a rekey, are
+/// refused.
+pub fn unframe_secure(
+    datagram: &[u8],
+    keys: Option<&mut PeerKeys>,
+    require_encryption: bool,
+) -> Option<(Option<u32>, Datagram)> {
+    use realtime_message::Payload;
+
+    let unframed = unframe_sequenced(datagram)?;
+    let keys = match (&unframed.1, keys) {
+        (Datagram::Control(control_message::Payload::KeyExchangeInit(_)), _) => return None,
+        (Datagram::Control(_), _) => return Some(unframed),
+        (_, Some(keys)) if keys.is_established() => keys,
+        (Datagram::Realtime(Payload::EncryptedFrame(_)), _) => return None,
+        _ if require_encryption => return None,
+        _ => return Some(unframed),
+    };
+    let (_, Datagram::Realtime(Payload::EncryptedFrame(frame))) = unframed else {
+        return None;
+    };
+    let plaintext = keys.open(&frame).ok()?;
+    match unframe_sequenced(&plaintext)? {
+        inner @ (_, Datagram::Control(control_message::Payload::KeyExchangeInit(_))) => Some(inner),
+        (_, Datagram::Control(_) | Datagram::Realtime(Payload::EncryptedFrame(_))) => None,
+        inner => Some(inner),
+    }
+}
+
+/// Debug-build check that `datagram` may be queued as `class`: its message
+/// must unframe and `class` must honor the message's declared delivery
+/// (e.g., a Control message that must arrive may not go out as droppable
+/// chat, and only snapshots may supersede one another).
+pub fn check_send_class(datagram: &[u8], class: SendClass) -> Result<(), String> {
+    let delivery = match unframe(datagram) {
+        Some(Datagram::Control(payload)) => payload.delivery(),
+        Some(Datagram::Realtime(payload)) => payload.delivery(),
+        None => return Err("outbound datagram does not unframe".to_string()),
+    };
+    if class.carries(delivery) {
+        Ok(())
+    } else {
+        Err(format!("{delivery:?} message queued as {class:?}"))
+    }
+}
+
+/// Non-blocking UDP socket.
+pub struct UdpTransport {
+    socket: UdpSocket,
+    buf: Vec<u8>,
+}
+
+impl UdpTransport {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            buf: vec![0; MAX_DATAGRAM_LEN],
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Next pending datagram, or `None` if none is waiting.
+    pub fn recv(&mut self) -> io::Result<Option<(SocketAddr, Vec<u8>)>> {
+        match self.socket.recv_from(&mut self.buf) {
+            Ok((len, from)) => Ok(Some((from, self.buf[..len].to_vec()))),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn send(&self, to: SocketAddr, datagram: &[u8]) -> io::Result<()> {
+        self.socket.send_to(datagram, to).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowstate_wire::{ClientHello, SnapshotAck, SnapshotProto};
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let hello = ClientHello {
+            auth_token: "t".to_string(),
+            ..Default::default()
+        };
+        let datagram = frame_control(control_message::Payload::ClientHello(hello.clone()));
+        assert_eq!(
+            unframe(&datagram),
+            Some(Datagram::Control(control_message::Payload::ClientHello(
+                hello
+            )))
+        );
+
+        let snapshot = SnapshotProto {
+            tick: 4,
+            ..Default::default()
+        };
+        let datagram = frame_snapshot_bytes(&snapshot.encode_to_vec(), false);
+        assert_eq!(
+            unframe(&datagram),
+            Some(Datagram::Realtime(realtime_message::Payload::Snapshot(
+                snapshot
+            )))
+        );
+
+        assert!(unframe(&[]).is_none());
+        assert!(unframe(&[0xff, 1, 2]).is_none());
+        // Known channel, empty envelope
+        assert!(unframe(&[Channel::Realtime as u8]).is_none());
+        // An unknown field after a valid envelope is skipped
+        let mut padded =
+            frame_control(control_message::Payload::ClientHello(ClientHello::default()));
+        padded.extend_from_slice(&[0xf8, 0x07, 0x00]);
+        assert!(unframe(&padded).is_some());
+        // Garbage that does not parse is not
+        padded.push(0xff);
+        assert!(unframe(&padded).is_none());
+    }
+
+    #[test]
+    fn test_compressed_frames_roundtrip() {
+        use flowstate_wire::{CompressionCodec, EntitySnapshotProto};
+
+        let snapshot = SnapshotProto {
+            tick: 7,
+            entities: (0..64)
+                .map(|entity_id| EntitySnapshotProto {
+                    entity_id,
+                    position: vec![1.0, 2.0],
+                    velocity: vec![0.0, 0.0],
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        for codec in [CompressionCodec::Lz4, CompressionCodec::Zstd] {
+            let params = CompressionParams {
+                codec: codec as i32,
+                min_payload_len: 128,
+                max_raw_len: 1024 * 1024,
+            };
+            let plain = frame_realtime_sequenced(
+                realtime_message::Payload::Snapshot(snapshot.clone()),
+                3,
+                true,
+            );
+            let compressed = compress(plain.clone(), &params);
+            assert_eq!(compressed[0], plain[0] | COMPRESSED_FLAG);
+            assert!(compressed.len() < plain.len());
+            assert_eq!(unframe_sequenced(&compressed), unframe_sequenced(&plain));
+
+            // Small datagrams are not worth a frame header
+            let hello = frame_control(control_message::Payload::ClientHello(ClientHello {
+                auth_token: "t".to_string(),
+                ..Default::default()
+            }));
+            assert_eq!(compress(hello.clone(), &params), hello);
+
+            // A corrupt body is dropped like any other bad datagram
+            let mut corrupt = compressed.clone();
+            let last = corrupt.len() - 1;
+            corrupt[last] ^= 0xff;
+            corrupt.push(0);
+            assert!(unframe(&corrupt).is_none());
+        }
+        let none = CompressionParams {
+            codec: CompressionCodec::None as i32,
+            min_payload_len: 0,
+            max_raw_len: 1024 * 1024,
+        };
+        let plain = frame_snapshot_bytes(&snapshot.encode_to_vec(), false);
+        assert_eq!(compress(plain.clone(), &none), plain);
+    }
+
+    #[test]
+    fn test_checked_realtime_frame() {
+        let snapshot = SnapshotProto {
+            tick: 9,
+            ..Default::default()
+        };
+        let datagram = frame_snapshot_bytes(&snapshot.encode_to_vec(), true);
+        assert_eq!(datagram[0], Channel::Realtime as u8 | CHECKSUM_FLAG);
+        assert_eq!(
+            unframe(&datagram),
+            Some(Datagram::Realtime(realtime_message::Payload::Snapshot(
+                snapshot
+            )))
+        );
+
+        // Any single bit flip past the channel byte is caught
+        for i in 1..datagram.len() {
+            let mut corrupted = datagram.clone();
+            corrupted[i] ^= 0x01;
+            assert!(unframe(&corrupted).is_none(), "flip at byte {i}");
+        }
+
+        // Control datagrams never carry the flag
+        let mut control =
+            frame_control(control_message::Payload::ClientHello(ClientHello::default()));
+        control[0] |= CHECKSUM_FLAG;
+        assert!(unframe(&control).is_none());
+    }
+
+    #[test]
+    fn test_sequenced_realtime_frame() {
+        let ack = realtime_message::Payload::SnapshotAck(SnapshotAck { tick: 3 });
+        for checked in [false, true] {
+            let datagram = frame_realtime_sequenced(ack.clone(), 42, checked);
+            assert_ne!(datagram[0] & SEQUENCE_FLAG, 0);
+            assert_eq!(
+                unframe_sequenced(&datagram),
+                Some((Some(42), Datagram::Realtime(ack.clone())))
+            );
+        }
+
+        // The checksum covers the sequence number too
+        let mut corrupted = frame_realtime_sequenced(ack.clone(), 42, true);
+        corrupted[1 + checksum::CHECKSUM_LEN] ^= 0x01;
+        assert!(unframe(&corrupted).is_none());
+
+        let plain = frame_realtime(ack.clone(), true);
+        assert_eq!(
+            unframe_sequenced(&plain),
+            Some((None, Datagram::Realtime(ack)))
+        );
+
+        assert!(unframe(&[Channel::Realtime as u8 | SEQUENCE_FLAG, 1, 2]).is_none());
+        let mut control =
+            frame_control(control_message::Payload::ClientHello(ClientHello::default()));
+        control[0] |= SEQUENCE_FLAG;
+        assert!(unframe(&control).is_none());
+    }
+
+    #[test]
+    fn test_encrypted_realtime_frames() {
+        use crate::secure_channel::{KeyExchange, ServerKey};
+        use flowstate_wire::KeyExchangeInit;
+
+        let server_key = ServerKey::from_secret_bytes([9; 32]);
+        let client = KeyExchange::from_secret_bytes([1; 32]);
+        let init = KeyExchangeInit {
+            public_key: client.public_key().to_vec(),
+            algorithms: Vec::new(),
+        };
+        let mut keys = PeerKeys::default();
+        let ack = realtime_message::Payload::SnapshotAck(SnapshotAck { tick: 3 });
+        let plain = frame_realtime_sequenced(ack.clone(), 8, true);
+
+        // Before the exchange plaintext passes unless encryption is required
+        assert_eq!(seal(plain.clone(), &mut keys), plain);
+        assert!(unframe_secure(&plain, Some(&mut keys), false).is_some());
+        assert!(unframe_secure(&plain, None, false).is_some());
+        assert!(unframe_secure(&plain, Some(&mut keys), true).is_none());
+        assert!(unframe_secure(&plain, None, true).is_none());
+
+        let response = keys.respond(&init, &server_key).unwrap();
+        let mut client = client
+            .finish_client(&response.public_key, &server_key.public_key())
+            .unwrap();
+        let sealed = frame_encrypted(client.seal_frame(response.key_id, &plain));
+        assert!(unframe_secure(&sealed, None, false).is_none());
+        assert_eq!(
+            unframe_secure(&sealed, Some(&mut keys), true),
+            Some((Some(8), Datagram::Realtime(ack.clone())))
+        );
+        // Replays and plaintext are refused once keys are agreed
+        assert!(unframe_secure(&sealed, Some(&mut keys), false).is_none());
+        assert!(unframe_secure(&plain, Some(&mut keys), false).is_none());
+        // Control stays in the clear
+        let hello = frame_control(control_message::Payload::ClientHello(ClientHello::default()));
+        assert_eq!(seal(hello.clone(), &mut keys), hello);
+        assert!(unframe_secure(&hello, Some(&mut keys), true).is_some());
+        // A sealed Control datagram other than a rekey is not accepted
+        let smuggled = frame_encrypted(client.seal_frame(response.key_id, &hello));
+        assert!(unframe_secure(&smuggled, Some(&mut keys), false).is_none());
+
+        // A rekey is only accepted sealed
+        let rekey = frame_control(control_message::Payload::KeyExchangeInit(init.clone()));
+        assert!(unframe_secure(&rekey, Some(&mut keys), false).is_none());
+        assert!(unframe_secure(&rekey, None, false).is_none());
+        let sealed_rekey = frame_encrypted(client.seal_frame(response.key_id, &rekey));
+        assert_eq!(
+            unframe_secure(&sealed_rekey, Some(&mut keys), false),
+            Some((
+                None,
+                Datagram::Control(control_message::Payload::KeyExchangeInit(init))
+            ))
+        );
+
+        // Outbound Realtime datagrams are sealed whole, flags included
+        let snapshot = frame_snapshot_bytes(&SnapshotProto::default().encode_to_vec(), true);
+        let sealed = seal(snapshot.clone(), &mut keys);
+        let Some(Datagram::Realtime(realtime_message::Payload::EncryptedFrame(frame))) =
+            unframe(&sealed)
+        else {
+            panic!("sealed datagram is not an encrypted frame");
+        };
+        assert_eq!(frame.key_id, response.key_id);
+        assert_eq!(client.open_frame(&frame).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_check_send_class() {
+        let match_end = frame_control(control_message::Payload::MatchEnd(Default::default()));
+        assert_eq!(check_send_class(&match_end, SendClass::Event), Ok(()));
+        assert!(check_send_class(&match_end, SendClass::Chat).is_err());
+        assert!(check_send_class(&match_end, SendClass::Snapshot).is_err());
+
+        let chat = frame_control(control_message::Payload::ChatBroadcast(Default::default()));
+        assert_eq!(check_send_class(&chat, SendClass::Chat), Ok(()));
+
+        let snapshot = frame_snapshot_bytes(&SnapshotProto::default().encode_to_vec(), true);
+        assert_eq!(check_send_class(&snapshot, SendClass::Snapshot), Ok(()));
+        assert!(check_send_class(&snapshot, SendClass::Event).is_err());
+        assert!(check_send_class(&[0xff], SendClass::Event).is_err());
+    }
+
+    #[test]
+    fn test_udp_loopback() {
+        let mut server = UdpTransport::bind("127.0.0.1:0").unwrap();
+        let client = UdpTransport::bind("127.0.0.1:0").unwrap();
+        client
+            .send(server.local_addr().unwrap(), &[Channel::Realtime as u8])
+            .unwrap();
+        let received = (0..1000).find_map(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            server.recv().unwrap()
+        });
+        let (from, datagram) = received.expect("datagram delivered on loopback");
+        assert_eq!(from, client.local_addr().unwrap());
+        assert_eq!(datagram, vec![Channel::Realtime as u8]);
+    }
+}
@@ -7,9 +7,12 @@
 //! - Tick non-monotonic: DROP
 //! - Tick window violation: DROP
 //! - Rate limit exceeded: DROP
+//! - Input buffer full (per-player or global cap): DROP
+//! - Redundant copy (InputBundle): DROP before rate limiting
 
 use flowstate_sim::{PlayerId, Tick};
 use flowstate_wire::InputCmdProto;
+use serde::{Deserialize, Serialize};
 
 use crate::input_buffer::InputBuffer;
 
@@ -19,6 +22,10 @@ pub struct ValidationConfig {
     pub max_future_ticks: u64,
     pub input_rate_limit_per_sec: u32,
     pub tick_rate_hz: u32,
+    /// Cap on buffered tick entries per player.
+    pub max_buffered_per_player: usize,
+    /// Cap on buffered entries across all players.
+    pub max_buffered_total: usize,
 }
 
 impl Default for ValidationConfig {
@@ -27,10 +34,31 @@ impl Default for ValidationConfig {
             max_future_ticks: 120,
             input_rate_limit_per_sec: 120,
             tick_rate_hz: 60,
+            max_buffered_per_player: crate::MAX_BUFFERED_INPUTS_PER_PLAYER,
+            max_buffered_total: crate::MAX_BUFFERED_INPUTS_TOTAL,
         }
     }
 }
 
+/// Input rate limit for one session.
+///
+/// `inputs_per_sec` yields the per-target-tick limit
+/// `ceil(inputs_per_sec / tick_rate_hz)`; `burst` is a bucket of extra
+/// inputs usable beyond that limit, refilled by one per simulated tick.
+/// With `burst == 0` this is exactly the FS-0007 v0 limiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub inputs_per_sec: u32,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Per-target-tick limit at `tick_rate_hz`.
+    pub fn per_tick_limit(&self, tick_rate_hz: u32) -> u32 {
+        self.inputs_per_sec.div_ceil(tick_rate_hz)
+    }
+}
+
 /// Result of input validation.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValidationResult {
@@ -54,12 +82,34 @@ pub enum ValidationResult {
     DroppedPreWelcome,
     /// Dropped: Unknown session.
     DroppedUnknownSession,
+    /// Dropped: Redundant copy of an already-buffered (tick, input_seq).
+    DroppedDuplicate,
+    /// Dropped: Input buffer at its per-player or global cap.
+    DroppedBufferFull,
 }
 
 impl ValidationResult {
     pub fn is_accepted(&self) -> bool {
         matches!(self, Self::Accepted | Self::AcceptedWithClamp)
     }
+
+    /// Stable snake_case label for logs and stats.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::AcceptedWithClamp => "accepted_with_clamp",
+            Self::DroppedNanInf => "nan_inf",
+            Self::DroppedBelowFloor { .. } => "below_floor",
+            Self::DroppedLate { .. } => "late",
+            Self::DroppedTooFuture { .. } => "too_future",
+            Self::DroppedRateLimit => "rate_limit",
+            Self::DroppedInputSeqTie => "input_seq_tie",
+            Self::DroppedPreWelcome => "pre_welcome",
+            Self::DroppedUnknownSession => "unknown_session",
+            Self::DroppedDuplicate => "duplicate",
+            Self::DroppedBufferFull => "buffer_full",
+        }
+    }
 }
 
 /// Validate an input command.
@@ -122,6 +172,7 @@ pub fn validate_input(
         }
         BufferResult::RateLimited => ValidationResult::DroppedRateLimit,
         BufferResult::InputSeqTie => ValidationResult::DroppedInputSeqTie,
+        BufferResult::BufferFull => ValidationResult::DroppedBufferFull,
     }
 }
 
@@ -131,6 +182,7 @@ pub enum BufferResult {
     Accepted { clamped: bool },
     RateLimited,
     InputSeqTie,
+    BufferFull,
 }
 
 #[cfg(test)]
@@ -154,7 +206,7 @@ mod tests {
             move_dir: vec![f64::NAN, 0.0],
         };
 
-        let result = validate_input(&input, 0, 0, &mut buffer, 0);
+        let result = validate_input(&input, 0, 0, &mut buffer, PlayerId(0));
         assert_eq!(result, ValidationResult::DroppedNanInf);
     }
 
@@ -167,7 +219,7 @@ mod tests {
             move_dir: vec![0.0, f64::INFINITY],
         };
 
-        let result = validate_input(&input, 0, 0, &mut buffer, 0);
+        let result = validate_input(&input, 0, 0, &mut buffer, PlayerId(0));
         assert_eq!(result, ValidationResult::DroppedNanInf);
     }
 
@@ -177,7 +229,7 @@ mod tests {
         let input = make_valid_input(5, 1);
 
         // Floor is 10, input targets 5
-        let result = validate_input(&input, 0, 10, &mut buffer, 0);
+        let result = validate_input(&input, 0, 10, &mut buffer, PlayerId(0));
         assert!(matches!(result, ValidationResult::DroppedBelowFloor { .. }));
     }
 
@@ -187,7 +239,7 @@ mod tests {
         let input = make_valid_input(5, 1);
 
         // Current tick is 10, input targets 5
-        let result = validate_input(&input, 10, 0, &mut buffer, 0);
+        let result = validate_input(&input, 10, 0, &mut buffer, PlayerId(0));
         assert!(matches!(result, ValidationResult::DroppedLate { .. }));
     }
 
@@ -201,7 +253,7 @@ mod tests {
         let input = make_valid_input(100, 1);
 
         // Current tick is 0, max is 0+10=10, input targets 100
-        let result = validate_input(&input, 0, 0, &mut buffer, 0);
+        let result = validate_input(&input, 0, 0, &mut buffer, PlayerId(0));
         assert!(matches!(result, ValidationResult::DroppedTooFuture { .. }));
     }
 
@@ -210,7 +262,7 @@ mod tests {
         let mut buffer = InputBuffer::new(ValidationConfig::default());
         let input = make_valid_input(5, 1);
 
-        let result = validate_input(&input, 0, 0, &mut buffer, 0);
+        let result = validate_input(&input, 0, 0, &mut buffer, PlayerId(0));
         assert!(result.is_accepted());
     }
 
@@ -225,7 +277,7 @@ mod tests {
             input_seq: 1,
             move_dir: vec![],
         };
-        let _ = validate_input(&input1, 0, 0, &mut buffer, 0);
+        let _ = validate_input(&input1, 0, 0, &mut buffer, PlayerId(0));
 
         // Single element move_dir
         let input2 = InputCmdProto {
@@ -233,7 +285,7 @@ mod tests {
             input_seq: 2,
             move_dir: vec![1.0],
         };
-        let _ = validate_input(&input2, 0, 0, &mut buffer, 0);
+        let _ = validate_input(&input2, 0, 0, &mut buffer, PlayerId(0));
 
         // NaN
         let input3 = InputCmdProto {
@@ -241,7 +293,7 @@ mod tests {
             input_seq: 3,
             move_dir: vec![f64::NAN, f64::NAN],
         };
-        let _ = validate_input(&input3, 0, 0, &mut buffer, 0);
+        let _ = validate_input(&input3, 0, 0, &mut buffer, PlayerId(0));
 
         // Negative infinity
         let input4 = InputCmdProto {
@@ -249,7 +301,7 @@ mod tests {
             input_seq: 4,
             move_dir: vec![f64::NEG_INFINITY, f64::NEG_INFINITY],
         };
-        let _ = validate_input(&input4, 0, 0, &mut buffer, 0);
+        let _ = validate_input(&input4, 0, 0, &mut buffer, PlayerId(0));
 
         // Huge magnitude
         let input5 = InputCmdProto {
@@ -257,7 +309,7 @@ mod tests {
             input_seq: 5,
             move_dir: vec![1e308, 1e308],
         };
-        let _ = validate_input(&input5, 0, 0, &mut buffer, 0);
+        let _ = validate_input(&input5, 0, 0, &mut buffer, PlayerId(0));
 
         // All handled without panic
     }
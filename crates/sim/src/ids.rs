@@ -0,0 +1,126 @@
+//! Identifier newtypes shared by every crate.
+//!
+//! Ref: DM-0019 (PlayerId), DM-0020 (EntityId), DM-0008 (Session)
+//! - Each id wraps the width the Simulation Core uses; wire messages carry
+//!   PlayerIds as `uint32` and EntityIds and SessionIds as `uint64`
+//! - Widening to a wire integer is `From`; narrowing a wire integer back is
+//!   `TryFrom` and fails instead of truncating
+//! - With the `serde` feature, ids (de)serialize as their bare integer
+
+use std::fmt;
+use std::num::TryFromIntError;
+
+/// Per-Match participant identifier used for deterministic ordering.
+/// Ref: DM-0019
+///
+/// NORMATIVE CONSTRAINT: Simulation Core MUST NOT assume PlayerIds are
+/// contiguous, zero-based, or start at specific literal values (e.g., {0,1}).
+/// PlayerId is used only as a stable indexing/ordering key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PlayerId(pub u8);
+
+/// Unique identifier for an Entity within a Match.
+/// Ref: DM-0020
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct EntityId(pub u64);
+
+/// Server Edge session identifier.
+/// Ref: DM-0008
+///
+/// Never seen by the Simulation Core. Recorded in replay input audits and
+/// server logs; clients never receive it and resume with reconnect tokens
+/// instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct SessionId(pub u64);
+
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<PlayerId> for u32 {
+    fn from(id: PlayerId) -> Self {
+        u32::from(id.0)
+    }
+}
+
+impl From<PlayerId> for u64 {
+    fn from(id: PlayerId) -> Self {
+        u64::from(id.0)
+    }
+}
+
+impl TryFrom<u32> for PlayerId {
+    type Error = TryFromIntError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        u8::try_from(value).map(Self)
+    }
+}
+
+impl TryFrom<u64> for PlayerId {
+    type Error = TryFromIntError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        u8::try_from(value).map(Self)
+    }
+}
+
+impl From<EntityId> for u64 {
+    fn from(id: EntityId) -> Self {
+        id.0
+    }
+}
+
+impl From<u64> for EntityId {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<SessionId> for u64 {
+    fn from(id: SessionId) -> Self {
+        id.0
+    }
+}
+
+impl From<u64> for SessionId {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_id_narrowing_is_checked() {
+        assert_eq!(PlayerId::try_from(17u32), Ok(PlayerId(17)));
+        assert!(PlayerId::try_from(256u32).is_err());
+        assert!(PlayerId::try_from(u64::MAX).is_err());
+        assert_eq!(u32::from(PlayerId(255)), 255);
+        assert_eq!(u64::from(EntityId(7)), 7);
+        assert_eq!(u64::from(SessionId(9)), 9);
+        assert_eq!(PlayerId(3).to_string(), "3");
+    }
+}
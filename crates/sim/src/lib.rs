@@ -26,25 +26,24 @@
 
 #![deny(unsafe_code)]
 
+pub mod ids;
+
+pub use ids::{EntityId, PlayerId, SessionId};
+
 // ============================================================================
-// Type Aliases (Ref: DM-0001, DM-0019, DM-0020)
+// Type Aliases (Ref: DM-0001)
 // ============================================================================
 
 /// A single discrete simulation timestep; the atomic unit of game time.
 /// Ref: DM-0001
 pub type Tick = u64;
 
-/// Per-Match participant identifier used for deterministic ordering.
-/// Ref: DM-0019
+/// Team a Character belongs to, for team game modes.
 ///
-/// NORMATIVE CONSTRAINT: Simulation Core MUST NOT assume PlayerIds are
-/// contiguous, zero-based, or start at specific literal values (e.g., {0,1}).
-/// PlayerId is used only as a stable indexing/ordering key.
-pub type PlayerId = u8;
-
-/// Unique identifier for an Entity within a Match.
-/// Ref: DM-0020
-pub type EntityId = u64;
+/// Assigned by the Server Edge before match start. v0 movement does not read
+/// it, so it is not part of the StateDigest; a mode whose rules depend on
+/// teams MUST fold it into the digest under a new algorithm id (ADR-0007).
+pub type TeamId = u8;
 
 // ============================================================================
 // Core Types
@@ -108,6 +107,34 @@ pub struct Snapshot {
 /// with key "move_speed" per INV-0006.
 pub const MOVE_SPEED: f64 = 5.0;
 
+/// Movement-model parameters a World simulates with.
+/// Ref: INV-0006
+///
+/// The default is the compiled v0 model. A World built from a replay uses the
+/// artifact's recorded tuning_parameters instead, so verification follows the
+/// model the match was played with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    /// Movement speed in units per second (tuning key "move_speed").
+    pub move_speed: f64,
+}
+
+impl Tuning {
+    /// True when every parameter is one the movement model supports
+    /// (`move_speed` finite and positive).
+    pub fn is_valid(&self) -> bool {
+        self.move_speed.is_finite() && self.move_speed > 0.0
+    }
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            move_speed: MOVE_SPEED,
+        }
+    }
+}
+
 // ============================================================================
 // StateDigest Implementation (ADR-0007)
 // ============================================================================
@@ -178,6 +205,7 @@ fn canonicalize_f64(value: f64) -> u64 {
 struct Character {
     entity_id: EntityId,
     player_id: PlayerId,
+    team: Option<TeamId>,
     position: [f64; 2],
     velocity: [f64; 2],
 }
@@ -187,6 +215,7 @@ impl Character {
         Self {
             entity_id,
             player_id,
+            team: None,
             position: [0.0, 0.0],
             velocity: [0.0, 0.0],
         }
@@ -218,6 +247,8 @@ pub struct World {
     tick_rate_hz: u32,
     /// Computed delta time per tick (seconds)
     dt_seconds: f64,
+    /// Movement-model parameters
+    tuning: Tuning,
     /// Characters indexed by player_id
     /// Note: We use a Vec and search by player_id to maintain deterministic ordering
     characters: Vec<Character>,
@@ -238,18 +269,67 @@ impl World {
     /// * `seed` - RNG seed (recorded for replay)
     /// * `tick_rate_hz` - Simulation tick rate in Hz
     pub fn new(seed: u64, tick_rate_hz: u32) -> Self {
+        Self::with_tuning(seed, tick_rate_hz, Tuning::default())
+    }
+
+    /// Create a new World at tick 0 that simulates with `tuning` instead of
+    /// the compiled movement constants.
+    /// Ref: DM-0002, INV-0006
+    ///
+    /// Panics if `tuning` is not valid (`Tuning::is_valid`).
+    pub fn with_tuning(seed: u64, tick_rate_hz: u32, tuning: Tuning) -> Self {
         assert!(tick_rate_hz > 0, "tick_rate_hz must be positive");
+        assert!(tuning.is_valid(), "invalid tuning: {tuning:?}");
 
         Self {
             tick: 0,
             tick_rate_hz,
             dt_seconds: 1.0 / f64::from(tick_rate_hz),
+            tuning,
             characters: Vec::new(),
-            next_entity_id: 1, // Start at 1 (0 could be reserved)
+            next_entity_id: EntityId(1), // Start at 1 (0 could be reserved)
             seed,
         }
     }
 
+    /// Rebuild a World from a captured state (crash-recovery checkpoint).
+    /// Ref: DM-0002, DM-0016
+    ///
+    /// `state` supplies tick and per-entity position/velocity; `players` maps
+    /// each PlayerId to its EntityId (spawn order). Entity allocation resumes
+    /// after the highest restored EntityId. Teams are not part of the state;
+    /// callers reapply them with `set_team`.
+    ///
+    /// Returns `None` if an entity in `state` has no player mapping. Callers
+    /// SHOULD confirm `state_digest() == state.digest` before resuming.
+    pub fn restore(
+        seed: u64,
+        tick_rate_hz: u32,
+        tuning: Tuning,
+        state: &Baseline,
+        players: &[(PlayerId, EntityId)],
+    ) -> Option<Self> {
+        let mut world = Self::with_tuning(seed, tick_rate_hz, tuning);
+        world.tick = state.tick;
+        for entity in &state.entities {
+            let &(player_id, _) = players.iter().find(|(_, e)| *e == entity.entity_id)?;
+            world.characters.push(Character {
+                entity_id: entity.entity_id,
+                player_id,
+                team: None,
+                position: entity.position,
+                velocity: entity.velocity,
+            });
+        }
+        // Maintain sorted order by entity_id for deterministic iteration (INV-0007)
+        world.characters.sort_by_key(|c| c.entity_id);
+        world.next_entity_id = world
+            .characters
+            .last()
+            .map_or(world.next_entity_id, |c| EntityId(c.entity_id.0 + 1));
+        Some(world)
+    }
+
     /// Spawn a character for the given player.
     /// Returns the EntityId of the spawned character.
     /// Ref: DM-0003, DM-0020
@@ -257,7 +337,7 @@ impl World {
     /// EntityId assignment is deterministic based on spawn order.
     pub fn spawn_character(&mut self, player_id: PlayerId) -> EntityId {
         let entity_id = self.next_entity_id;
-        self.next_entity_id += 1;
+        self.next_entity_id.0 += 1;
 
         let character = Character::new(entity_id, player_id);
         self.characters.push(character);
@@ -268,6 +348,31 @@ impl World {
         entity_id
     }
 
+    /// Put a player's Character on a team.
+    ///
+    /// Returns false if the player has no Character.
+    pub fn set_team(&mut self, player_id: PlayerId, team: TeamId) -> bool {
+        match self
+            .characters
+            .iter_mut()
+            .find(|c| c.player_id == player_id)
+        {
+            Some(character) => {
+                character.team = Some(team);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Team of a player's Character, if assigned.
+    pub fn team_of(&self, player_id: PlayerId) -> Option<TeamId> {
+        self.characters
+            .iter()
+            .find(|c| c.player_id == player_id)
+            .and_then(|c| c.team)
+    }
+
     /// Get the current simulation tick.
     /// Ref: DM-0001
     pub fn tick(&self) -> Tick {
@@ -279,6 +384,39 @@ impl World {
         self.tick_rate_hz
     }
 
+    /// Get the movement-model parameters this World simulates with.
+    pub fn tuning(&self) -> Tuning {
+        self.tuning
+    }
+
+    /// Get the current position of an entity, if it exists.
+    /// Ref: DM-0020
+    pub fn entity_position(&self, entity_id: EntityId) -> Option<[f64; 2]> {
+        self.characters
+            .iter()
+            .find(|c| c.entity_id == entity_id)
+            .map(|c| c.position)
+    }
+
+    /// Spatial query: EntityIds whose position lies within `radius` of `center`.
+    /// Ref: DM-0002, INV-0007
+    ///
+    /// The boundary is inclusive. Results are sorted by entity_id ascending.
+    /// Read-only; does not affect simulation state or the StateDigest.
+    pub fn entities_within(&self, center: [f64; 2], radius: f64) -> Vec<EntityId> {
+        let radius_sq = radius * radius;
+        // Characters are maintained sorted by entity_id (INV-0007)
+        self.characters
+            .iter()
+            .filter(|c| {
+                let dx = c.position[0] - center[0];
+                let dy = c.position[1] - center[1];
+                dx * dx + dy * dy <= radius_sq
+            })
+            .map(|c| c.entity_id)
+            .collect()
+    }
+
     /// Get the pre-step world state (Baseline) at the current tick.
     /// Ref: DM-0016
     ///
@@ -358,7 +496,7 @@ impl World {
         // Characters are maintained sorted by entity_id
         for character in &self.characters {
             // entity_id (u64, little-endian)
-            hasher.update(&character.entity_id.to_le_bytes());
+            hasher.update(&character.entity_id.0.to_le_bytes());
 
             // position[0] (f64, canonicalized, little-endian)
             hasher.update(&canonicalize_f64(character.position[0]).to_le_bytes());
@@ -395,10 +533,10 @@ impl World {
         let move_dir = clamp_magnitude(input.move_dir, 1.0);
 
         // v0 Movement Model:
-        // velocity = move_dir * MOVE_SPEED
+        // velocity = move_dir * move_speed (MOVE_SPEED unless tuned)
         // position += velocity * dt
-        character.velocity[0] = move_dir[0] * MOVE_SPEED;
-        character.velocity[1] = move_dir[1] * MOVE_SPEED;
+        character.velocity[0] = move_dir[0] * self.tuning.move_speed;
+        character.velocity[1] = move_dir[1] * self.tuning.move_speed;
 
         character.position[0] += character.velocity[0] * self.dt_seconds;
         character.position[1] += character.velocity[1] * self.dt_seconds;
@@ -433,6 +571,92 @@ fn clamp_magnitude(v: [f64; 2], max_magnitude: f64) -> [f64; 2] {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_team_assignment_outside_digest() {
+        let mut world = World::new(0, 60);
+        world.spawn_character(PlayerId(3));
+        let digest = world.state_digest();
+        assert_eq!(world.team_of(PlayerId(3)), None);
+        assert!(world.set_team(PlayerId(3), 1));
+        assert!(!world.set_team(PlayerId(4), 1));
+        assert_eq!(world.team_of(PlayerId(3)), Some(1));
+        assert_eq!(world.state_digest(), digest);
+    }
+
+    #[test]
+    fn test_tuning_sets_move_speed() {
+        let input = [StepInput {
+            player_id: PlayerId(0),
+            move_dir: [1.0, 0.0],
+        }];
+        let tuning = Tuning { move_speed: 8.0 };
+        let mut tuned = World::with_tuning(0, 60, tuning);
+        let mut default = World::new(0, 60);
+        let entity_id = tuned.spawn_character(PlayerId(0));
+        default.spawn_character(PlayerId(0));
+        assert_eq!(tuned.tuning(), tuning);
+        assert_eq!(default.tuning(), Tuning::default());
+        assert_eq!(tuned.advance(0, &input).entities[0].velocity, [8.0, 0.0]);
+        assert_eq!(
+            default.advance(0, &input).entities[0].velocity,
+            [MOVE_SPEED, 0.0]
+        );
+
+        // Restoring keeps the tuning
+        let state = tuned.baseline();
+        let mut restored =
+            World::restore(0, 60, tuning, &state, &[(PlayerId(0), entity_id)]).unwrap();
+        assert_eq!(restored.advance(1, &input), tuned.advance(1, &input));
+
+        for move_speed in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(!Tuning { move_speed }.is_valid());
+        }
+    }
+
+    #[test]
+    fn test_restore_continues_identically() {
+        let mut world = World::new(7, 60);
+        let e0 = world.spawn_character(PlayerId(0));
+        let e1 = world.spawn_character(PlayerId(1));
+        let inputs = [
+            StepInput {
+                player_id: PlayerId(0),
+                move_dir: [1.0, 0.0],
+            },
+            StepInput {
+                player_id: PlayerId(1),
+                move_dir: [0.0, -1.0],
+            },
+        ];
+        for tick in 0..5 {
+            let _ = world.advance(tick, &inputs);
+        }
+
+        let state = world.baseline();
+        let mut restored = World::restore(
+            7,
+            60,
+            Tuning::default(),
+            &state,
+            &[(PlayerId(0), e0), (PlayerId(1), e1)],
+        )
+        .unwrap();
+        assert_eq!(restored.state_digest(), state.digest);
+
+        for tick in 5..10 {
+            let a = world.advance(tick, &inputs);
+            let b = restored.advance(tick, &inputs);
+            assert_eq!(a, b);
+        }
+        assert_eq!(
+            restored.spawn_character(PlayerId(2)),
+            world.spawn_character(PlayerId(2))
+        );
+
+        // Entity without a player mapping
+        assert!(World::restore(7, 60, Tuning::default(), &state, &[(PlayerId(0), e0)]).is_none());
+    }
+
     // ========================================================================
     // Tier 0 Gate: T0.4 — WASD produces deterministic movement
     // ========================================================================
@@ -446,7 +670,7 @@ mod tests {
         const NUM_TICKS: u64 = 10;
 
         let mut world = World::new(SEED, TICK_RATE_HZ);
-        let player_id: PlayerId = 0;
+        let player_id = PlayerId(0);
         world.spawn_character(player_id);
 
         // Move right (x+) for NUM_TICKS ticks
@@ -495,16 +719,16 @@ mod tests {
 
         fn run_simulation() -> (Vec<EntitySnapshot>, u64) {
             let mut world = World::new(SEED, TICK_RATE_HZ);
-            world.spawn_character(0);
-            world.spawn_character(1);
+            world.spawn_character(PlayerId(0));
+            world.spawn_character(PlayerId(1));
 
             let inputs = vec![
                 StepInput {
-                    player_id: 0,
+                    player_id: PlayerId(0),
                     move_dir: [1.0, 0.0],
                 },
                 StepInput {
-                    player_id: 1,
+                    player_id: PlayerId(1),
                     move_dir: [0.0, 1.0],
                 },
             ];
@@ -540,15 +764,15 @@ mod tests {
         let mut world = World::new(SEED, TICK_RATE_HZ);
 
         // Use non-contiguous, non-zero-based PlayerIds as per spec
-        let player_a: PlayerId = 17;
-        let player_b: PlayerId = 99;
+        let player_a = PlayerId(17);
+        let player_b = PlayerId(99);
 
         let entity_a = world.spawn_character(player_a);
         let entity_b = world.spawn_character(player_b);
 
         // Verify entities were created
-        assert!(entity_a > 0);
-        assert!(entity_b > 0);
+        assert!(entity_a > EntityId(0));
+        assert!(entity_b > EntityId(0));
         assert_ne!(entity_a, entity_b);
 
         // Inputs must be sorted by player_id
@@ -600,13 +824,13 @@ mod tests {
         let mut world1 = World::new(0, 60);
         let mut world2 = World::new(0, 60);
 
-        world1.spawn_character(0);
-        world2.spawn_character(0);
+        world1.spawn_character(PlayerId(0));
+        world2.spawn_character(PlayerId(0));
 
         assert_eq!(world1.state_digest(), world2.state_digest());
 
         let input = StepInput {
-            player_id: 0,
+            player_id: PlayerId(0),
             move_dir: [1.0, 0.0],
         };
 
@@ -619,12 +843,12 @@ mod tests {
     #[test]
     fn test_state_digest_changes_with_state() {
         let mut world = World::new(0, 60);
-        world.spawn_character(0);
+        world.spawn_character(PlayerId(0));
 
         let digest_before = world.state_digest();
 
         let input = StepInput {
-            player_id: 0,
+            player_id: PlayerId(0),
             move_dir: [1.0, 0.0],
         };
         world.advance(0, &[input]);
@@ -677,9 +901,9 @@ mod tests {
     fn test_spawn_character_returns_unique_ids() {
         let mut world = World::new(0, 60);
 
-        let id1 = world.spawn_character(0);
-        let id2 = world.spawn_character(1);
-        let id3 = world.spawn_character(2);
+        let id1 = world.spawn_character(PlayerId(0));
+        let id2 = world.spawn_character(PlayerId(1));
+        let id3 = world.spawn_character(PlayerId(2));
 
         assert_ne!(id1, id2);
         assert_ne!(id2, id3);
@@ -696,7 +920,7 @@ mod tests {
     #[test]
     fn test_advance_increments_tick() {
         let mut world = World::new(0, 60);
-        world.spawn_character(0);
+        world.spawn_character(PlayerId(0));
 
         assert_eq!(world.tick(), 0);
 
@@ -713,7 +937,7 @@ mod tests {
     #[should_panic(expected = "advance() tick mismatch")]
     fn test_advance_panics_on_tick_mismatch() {
         let mut world = World::new(0, 60);
-        world.spawn_character(0);
+        world.spawn_character(PlayerId(0));
 
         // Try to advance with wrong tick
         world.advance(5, &[]);
@@ -724,9 +948,9 @@ mod tests {
         let mut world = World::new(0, 60);
 
         // Spawn in reverse order of what entity IDs will be
-        world.spawn_character(99);
-        world.spawn_character(50);
-        world.spawn_character(1);
+        world.spawn_character(PlayerId(99));
+        world.spawn_character(PlayerId(50));
+        world.spawn_character(PlayerId(1));
 
         let baseline = world.baseline();
 
@@ -755,6 +979,30 @@ mod tests {
         assert_eq!(v3, [0.0, 0.0]);
     }
 
+    #[test]
+    fn test_entities_within_radius() {
+        let mut world = World::new(0, 60);
+        let near = world.spawn_character(PlayerId(0));
+        let far = world.spawn_character(PlayerId(1));
+
+        // Move player 1 far along +x (MOVE_SPEED * 60 ticks * 1/60 s = 5.0 units)
+        for tick in 0..60 {
+            world.advance(
+                tick,
+                &[StepInput {
+                    player_id: PlayerId(1),
+                    move_dir: [1.0, 0.0],
+                }],
+            );
+        }
+
+        assert_eq!(world.entity_position(near), Some([0.0, 0.0]));
+        assert_eq!(world.entities_within([0.0, 0.0], 1.0), vec![near]);
+        assert_eq!(world.entities_within([0.0, 0.0], 10.0), vec![near, far]);
+        assert_eq!(world.entities_within([5.0, 0.0], 0.5), vec![far]);
+        assert!(world.entity_position(EntityId(999)).is_none());
+    }
+
     // ========================================================================
     // Tier 0 Gate: T0.5 — Simulation Core Isolation
     // ========================================================================
@@ -763,7 +1011,7 @@ mod tests {
     #[test]
     fn test_t0_05_advance_takes_explicit_tick() {
         let mut world = World::new(0, 60);
-        world.spawn_character(0);
+        world.spawn_character(PlayerId(0));
 
         // This test verifies the API signature matches the spec
         // advance() takes tick as first parameter
@@ -780,7 +1028,7 @@ mod tests {
     fn test_t0_12_empty_inputs_deterministic() {
         fn run_with_gaps() -> u64 {
             let mut world = World::new(0, 60);
-            world.spawn_character(0);
+            world.spawn_character(PlayerId(0));
 
             // Advance with no inputs (simulating LKI scenario)
             for tick in 0..10 {
@@ -0,0 +1,110 @@
+//! Generates the wire types from `protocol/flowstate/wire/*.proto` and
+//! computes `SCHEMA_HASH` from the same schema.
+//!
+//! Ref: T0.19 (Schema Identity)
+//! - The `.proto` files are the canonical schema; non-Rust clients compile
+//!   the same files with their own protobuf toolchain
+//! - Parsed with `protox` (pure Rust), so no `protoc` install is needed
+//! - Every field contributes `Message.field=number,label,type,type_name,oneof`
+//!   and every enum value `Enum.VALUE=number`, hashed in descriptor order
+//!   with 64-bit FNV-1a; comments and formatting do not affect the hash
+//! - The hashed layout is written to `$OUT_DIR/schema_layout.txt`
+//! - With the `serde` feature, every generated type also derives
+//!   `Serialize`/`Deserialize` (missing message fields take their defaults,
+//!   oneof variants use the snake_case field names from the schema)
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const PROTO_ROOT: &str = "../../protocol";
+
+const PROTO_FILES: &[&str] = &[
+    "flowstate/wire/common.proto",
+    "flowstate/wire/control.proto",
+    "flowstate/wire/realtime.proto",
+    "flowstate/wire/envelope.proto",
+    "flowstate/wire/replay.proto",
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed={PROTO_ROOT}/flowstate/wire");
+    let files: Vec<String> = PROTO_FILES
+        .iter()
+        .map(|file| format!("{PROTO_ROOT}/{file}"))
+        .collect();
+    let descriptors = protox::compile(&files, [PROTO_ROOT]).expect("compile wire schema");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR set");
+    let layout = schema_layout(&descriptors);
+    // Kept alongside for diagnosing hash mismatches between builds
+    fs::write(Path::new(&out_dir).join("schema_layout.txt"), &layout).expect("write schema layout");
+    fs::write(
+        Path::new(&out_dir).join("schema_hash.rs"),
+        format!(
+            "/// Hash of the wire message/tag layout (see `build.rs`).\n\
+             pub const SCHEMA_HASH: u64 = {:#018x};\n",
+            fnv1a(layout.as_bytes())
+        ),
+    )
+    .expect("write schema hash");
+
+    prost_build::Config::new()
+        .type_attribute(
+            ".",
+            "#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]",
+        )
+        .message_attribute(".", "#[cfg_attr(feature = \"serde\", serde(default))]")
+        .enum_attribute(
+            ".",
+            "#[cfg_attr(feature = \"serde\", serde(rename_all = \"snake_case\"))]",
+        )
+        .compile_fds(descriptors)
+        .expect("generate wire types");
+}
+
+/// Canonical description of every message field and enum value.
+fn schema_layout(descriptors: &prost_types::FileDescriptorSet) -> String {
+    let mut layout = String::new();
+    for file in &descriptors.file {
+        for message in &file.message_type {
+            for field in &message.field {
+                writeln!(
+                    layout,
+                    "{}.{}.{}={},{},{},{},{:?},{}",
+                    file.package(),
+                    message.name(),
+                    field.name(),
+                    field.number(),
+                    field.label() as i32,
+                    field.r#type() as i32,
+                    field.type_name(),
+                    field.oneof_index,
+                    field.proto3_optional(),
+                )
+                .expect("write to string");
+            }
+        }
+        for enumeration in &file.enum_type {
+            for value in &enumeration.value {
+                writeln!(
+                    layout,
+                    "{}.{}.{}={}",
+                    file.package(),
+                    enumeration.name(),
+                    value.name(),
+                    value.number()
+                )
+                .expect("write to string");
+            }
+        }
+    }
+    layout
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -0,0 +1,63 @@
+//! Client capability flags.
+//!
+//! Ref: ADR-0005 (Control Channel), T0.19 (Schema Identity)
+//! - `ClientHello::capabilities` is a bitfield of the optional encodings
+//!   and features the client understands
+//! - The server answers with the subset it will actually use for the
+//!   session in `ServerWelcome::capabilities`
+//! - 0 (an old client, or one that sends nothing) gets the baseline v0
+//!   behavior, so each new feature is opt-in per session
+//!
+//! Bits are never reused; a retired feature keeps its bit reserved.
+
+/// Accepts `DeltaSnapshotProto` instead of full snapshots.
+pub const DELTA_SNAPSHOTS: u64 = 1 << 0;
+
+/// Decodes LZ4 frames (`compression::Codec::Lz4`).
+pub const COMPRESSION_LZ4: u64 = 1 << 1;
+
+/// Decodes zstd frames (`compression::Codec::Zstd`).
+pub const COMPRESSION_ZSTD: u64 = 1 << 2;
+
+/// Accepts quantized entity positions/velocities.
+pub const QUANTIZED_ENCODING: u64 = 1 << 3;
+
+/// Understands `delay_seconds` in spectator handshakes.
+pub const SPECTATOR_DELAY: u64 = 1 << 4;
+
+/// Verifies CRC32C-checked Realtime datagrams (`checksum`).
+pub const REALTIME_CHECKSUMS: u64 = 1 << 5;
+
+/// Every flag this crate defines.
+pub const ALL: u64 = DELTA_SNAPSHOTS
+    | COMPRESSION_LZ4
+    | COMPRESSION_ZSTD
+    | QUANTIZED_ENCODING
+    | SPECTATOR_DELAY
+    | REALTIME_CHECKSUMS;
+
+/// Capabilities both sides support; unknown client bits are ignored.
+pub fn negotiate(client: u64, server: u64) -> u64 {
+    client & server & ALL
+}
+
+/// Whether `capabilities` includes every bit of `flag`.
+pub fn has(capabilities: u64, flag: u64) -> bool {
+    capabilities & flag == flag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_intersects_known_flags() {
+        let client = DELTA_SNAPSHOTS | REALTIME_CHECKSUMS | (1 << 63);
+        let server = REALTIME_CHECKSUMS | COMPRESSION_ZSTD | (1 << 63);
+        let negotiated = negotiate(client, server);
+        assert_eq!(negotiated, REALTIME_CHECKSUMS);
+        assert!(has(negotiated, REALTIME_CHECKSUMS));
+        assert!(!has(negotiated, DELTA_SNAPSHOTS));
+        assert_eq!(negotiate(0, server), 0);
+    }
+}
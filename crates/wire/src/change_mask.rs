@@ -0,0 +1,188 @@
+//! Per-entity change masks for delta snapshots.
+//!
+//! Ref: DM-0007 (Snapshot)
+//! - `EntitySnapshotProto::change_mask` names the fields an entry carries;
+//!   0 means every field, so full snapshots and baselines are unaffected
+//! - `masked_update` builds the entry a sender puts in
+//!   `DeltaSnapshotProto::changed`, keeping only the dirty fields
+//! - `apply` merges such an entry onto the receiver's copy of the entity
+//! - Kind and owner are not maskable: masked entries leave them out, and an
+//!   entity whose kind or owner changed is sent in full
+//!
+//! Health and effects bits are reserved for component-rich snapshots; no
+//! wire field carries them yet, so `apply` refuses them.
+
+use crate::EntitySnapshotProto;
+
+/// `position` is carried.
+pub const POSITION: u32 = 1 << 0;
+
+/// `velocity` is carried.
+pub const VELOCITY: u32 = 1 << 1;
+
+/// Reserved: health component.
+pub const HEALTH: u32 = 1 << 2;
+
+/// Reserved: status effects component.
+pub const EFFECTS: u32 = 1 << 3;
+
+/// Bits with a wire field today.
+pub const SUPPORTED: u32 = POSITION | VELOCITY;
+
+/// Masked update failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeMaskError {
+    /// The update is for a different entity.
+    EntityMismatch { expected: u64, actual: u64 },
+    /// The mask names fields this build cannot apply.
+    UnsupportedBits(u32),
+}
+
+impl std::fmt::Display for ChangeMaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EntityMismatch { expected, actual } => {
+                write!(f, "Update for entity {actual} applied to entity {expected}")
+            }
+            Self::UnsupportedBits(bits) => write!(f, "Unsupported change mask bits {bits:#x}"),
+        }
+    }
+}
+
+impl std::error::Error for ChangeMaskError {}
+
+/// Fields of `current` that differ from `previous` (bitwise, so a NaN or
+/// signed-zero change counts).
+pub fn diff(previous: &EntitySnapshotProto, current: &EntitySnapshotProto) -> u32 {
+    let differs = |a: &[f64], b: &[f64]| {
+        a.len() != b.len() || a.iter().zip(b).any(|(x, y)| x.to_bits() != y.to_bits())
+    };
+    let mut mask = 0;
+    if differs(&previous.position, &current.position) {
+        mask |= POSITION;
+    }
+    if differs(&previous.velocity, &current.velocity) {
+        mask |= VELOCITY;
+    }
+    mask
+}
+
+/// The `changed` entry for `current` given the receiver's `previous` copy:
+/// a full entry if the receiver lacks the entity or its kind/owner changed,
+/// `None` if nothing changed, otherwise only the dirty fields.
+pub fn masked_update(
+    previous: Option<&EntitySnapshotProto>,
+    current: &EntitySnapshotProto,
+) -> Option<EntitySnapshotProto> {
+    let full = || EntitySnapshotProto {
+        change_mask: 0,
+        ..current.clone()
+    };
+    let Some(previous) = previous else {
+        return Some(full());
+    };
+    if (previous.entity_kind, previous.owner_player_id)
+        != (current.entity_kind, current.owner_player_id)
+    {
+        return Some(full());
+    }
+    let mask = diff(previous, current);
+    if mask == 0 {
+        return None;
+    }
+    let carried = |bit: u32, field: &Vec<f64>| {
+        if mask & bit != 0 {
+            field.clone()
+        } else {
+            Vec::new()
+        }
+    };
+    Some(EntitySnapshotProto {
+        entity_id: current.entity_id,
+        position: carried(POSITION, &current.position),
+        velocity: carried(VELOCITY, &current.velocity),
+        change_mask: mask,
+        ..Default::default()
+    })
+}
+
+/// Merge `update` onto `entity`; the result always carries every field
+/// (`change_mask` 0).
+pub fn apply(
+    entity: &mut EntitySnapshotProto,
+    update: &EntitySnapshotProto,
+) -> Result<(), ChangeMaskError> {
+    if entity.entity_id != update.entity_id {
+        return Err(ChangeMaskError::EntityMismatch {
+            expected: entity.entity_id,
+            actual: update.entity_id,
+        });
+    }
+    let mask = update.change_mask;
+    if mask & !SUPPORTED != 0 {
+        return Err(ChangeMaskError::UnsupportedBits(mask & !SUPPORTED));
+    }
+    if mask == 0 {
+        *entity = update.clone();
+        return Ok(());
+    }
+    if mask & POSITION != 0 {
+        entity.position.clone_from(&update.position);
+    }
+    if mask & VELOCITY != 0 {
+        entity.velocity.clone_from(&update.velocity);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(position: [f64; 2], velocity: [f64; 2]) -> EntitySnapshotProto {
+        EntitySnapshotProto {
+            entity_id: 7,
+            position: position.to_vec(),
+            velocity: velocity.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_masked_update_roundtrip() {
+        let previous = entity([1.0, 2.0], [0.5, 0.0]);
+        let moved = entity([1.5, 2.0], [0.5, 0.0]);
+
+        assert_eq!(masked_update(Some(&previous), &previous), None);
+        assert_eq!(masked_update(None, &moved), Some(moved.clone()));
+
+        let update = masked_update(Some(&previous), &moved).unwrap();
+        assert_eq!(update.change_mask, POSITION);
+        assert!(update.velocity.is_empty());
+
+        let mut client = previous.clone();
+        apply(&mut client, &update).unwrap();
+        assert_eq!(client, moved);
+
+        let mut other = EntitySnapshotProto {
+            entity_id: 8,
+            ..previous.clone()
+        };
+        assert_eq!(
+            apply(&mut other, &update),
+            Err(ChangeMaskError::EntityMismatch {
+                expected: 8,
+                actual: 7
+            })
+        );
+
+        let health = EntitySnapshotProto {
+            change_mask: POSITION | HEALTH,
+            ..update
+        };
+        assert_eq!(
+            apply(&mut client, &health),
+            Err(ChangeMaskError::UnsupportedBits(HEALTH))
+        );
+    }
+}
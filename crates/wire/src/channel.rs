@@ -0,0 +1,154 @@
+//! Intended channel and delivery of each wire message.
+//!
+//! Ref: ADR-0005 (Control/Realtime Channels)
+//! - `ChannelMessage` declares, per message type, the channel it travels on
+//!   and how much loss it tolerates; the envelope oneofs only say which
+//!   messages a channel *can* carry
+//! - `control_message::Payload::delivery` / `realtime_message::Payload::delivery`
+//!   answer the same question for a decoded or about-to-be-sent payload
+//! - `Heartbeat` is valid on both channels, so it has no `ChannelMessage`
+//!   impl; its delivery is `Unreliable` either way
+//!
+//! Transports use this to pick a send queue and to catch a message queued
+//! with weaker guarantees than it needs.
+
+use crate::{control_message, realtime_message};
+
+/// Logical channel (ADR-0005).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Control,
+    Realtime,
+}
+
+/// Delivery a message needs from the sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Must not be dropped by the sender.
+    Reliable,
+    /// Loss is tolerated (redundant, periodic, or advisory).
+    Unreliable,
+    /// Only the newest matters; a newer one supersedes it.
+    Latest,
+}
+
+/// Channel and delivery metadata of a message type.
+pub trait ChannelMessage {
+    const CHANNEL: Channel;
+    const DELIVERY: Delivery;
+}
+
+macro_rules! channel_messages {
+    (
+        $envelope:ident, $channel:ident {
+            $($variant:ident($message:ident) => $delivery:ident,)*
+        }
+        shared {
+            $($shared_variant:ident => $shared_delivery:ident,)*
+        }
+    ) => {
+        $(
+            impl ChannelMessage for crate::$message {
+                const CHANNEL: Channel = Channel::$channel;
+                const DELIVERY: Delivery = Delivery::$delivery;
+            }
+        )*
+
+        impl $envelope::Payload {
+            /// Delivery this payload needs.
+            pub fn delivery(&self) -> Delivery {
+                match self {
+                    $(Self::$variant(_) => <crate::$message as ChannelMessage>::DELIVERY,)*
+                    $(Self::$shared_variant(_) => Delivery::$shared_delivery,)*
+                }
+            }
+        }
+    };
+}
+
+channel_messages! {
+    control_message, Control {
+        ClientHello(ClientHello) => Reliable,
+        ServerWelcome(ServerWelcome) => Reliable,
+        JoinBaseline(JoinBaseline) => Reliable,
+        KeyExchangeInit(KeyExchangeInit) => Reliable,
+        KeyExchangeResponse(KeyExchangeResponse) => Reliable,
+        ChatSend(ChatSend) => Unreliable,
+        ChatBroadcast(ChatBroadcast) => Unreliable,
+        FloorUpdate(FloorUpdate) => Reliable,
+        PlayerLeft(PlayerLeft) => Reliable,
+        TimeSyncPing(TimeSyncPing) => Unreliable,
+        TimeSyncPong(TimeSyncPong) => Unreliable,
+        ErrorResponse(ErrorResponse) => Reliable,
+        MatchEnd(MatchEnd) => Reliable,
+        ReconnectRequest(ReconnectRequest) => Reliable,
+        ReconnectAccept(ReconnectAccept) => Reliable,
+        LobbyState(LobbyState) => Reliable,
+        SetReady(SetReady) => Reliable,
+        MatchStarting(MatchStarting) => Reliable,
+        SpectateRequest(SpectateRequest) => Reliable,
+        SpectateWelcome(SpectateWelcome) => Reliable,
+        InputRejectionReport(InputRejectionReport) => Unreliable,
+        InputTimingReport(InputTimingReport) => Unreliable,
+        PlayerRoster(PlayerRoster) => Reliable,
+        TimeSyncReport(TimeSyncReport) => Unreliable,
+        ReplayChunkRequest(ReplayChunkRequest) => Reliable,
+        ReplayChunk(ReplayChunk) => Unreliable,
+        EntitySpawned(EntitySpawned) => Reliable,
+        EntityRemoved(EntityRemoved) => Reliable,
+        BaselineRequest(BaselineRequest) => Reliable,
+        BaselineUpdate(BaselineUpdate) => Reliable,
+        MatchConfig(MatchConfig) => Reliable,
+    }
+    shared {
+        Heartbeat => Unreliable,
+    }
+}
+
+channel_messages! {
+    realtime_message, Realtime {
+        InputCmd(InputCmdProto) => Unreliable,
+        InputBundle(InputBundle) => Unreliable,
+        Snapshot(SnapshotProto) => Latest,
+        DeltaSnapshot(DeltaSnapshotProto) => Latest,
+        SnapshotAck(SnapshotAck) => Unreliable,
+        RedundantInputCmd(RedundantInputCmd) => Unreliable,
+        KeyframeRequest(KeyframeRequest) => Unreliable,
+        SnapshotV2(SnapshotProtoV2) => Latest,
+        EncryptedFrame(EncryptedFrame) => Unreliable,
+    }
+    shared {
+        Heartbeat => Unreliable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatBroadcast, Heartbeat, MatchEnd, SnapshotProto};
+
+    #[test]
+    fn test_message_metadata() {
+        assert_eq!(<MatchEnd as ChannelMessage>::CHANNEL, Channel::Control);
+        assert_eq!(
+            <SnapshotProto as ChannelMessage>::CHANNEL,
+            Channel::Realtime
+        );
+        assert_eq!(
+            control_message::Payload::MatchEnd(MatchEnd::default()).delivery(),
+            Delivery::Reliable
+        );
+        assert_eq!(
+            control_message::Payload::ChatBroadcast(ChatBroadcast::default()).delivery(),
+            Delivery::Unreliable
+        );
+        assert_eq!(
+            realtime_message::Payload::Snapshot(SnapshotProto::default()).delivery(),
+            Delivery::Latest
+        );
+        assert_eq!(
+            realtime_message::Payload::Heartbeat(Heartbeat::default()).delivery(),
+            Delivery::Unreliable
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! CRC32C payload checksums for realtime frames.
+//!
+//! Ref: ADR-0005 (Realtime Channel)
+//! - A checked payload is `[crc32c: u32 LE][payload]`
+//! - CRC32C (Castagnoli) is computed over the payload bytes only
+//! - `verify` strips the checksum and refuses mismatches, so a corrupted
+//!   datagram is dropped before it reaches prost or input validation
+//!
+//! UDP's own 16-bit checksum is optional on IPv4 and weak; this catches the
+//! corruption it misses. It is not an integrity guarantee against tampering
+//! (see `secure_channel` on the server for that).
+
+use crc::{CRC_32_ISCSI, Crc};
+
+/// Bytes the checksum adds in front of a payload.
+pub const CHECKSUM_LEN: usize = 4;
+
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// Checksum verification failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumError {
+    /// Fewer than `CHECKSUM_LEN` bytes.
+    Truncated,
+    Mismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl std::fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "Checked payload shorter than its checksum"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "Checksum mismatch: expected {expected:#010x}, computed {actual:#010x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChecksumError {}
+
+/// CRC32C of `bytes`.
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    CRC32C.checksum(bytes)
+}
+
+/// Append `[crc32c(payload)][payload]` to `out`.
+pub fn write_checked(payload: &[u8], out: &mut Vec<u8>) {
+    out.reserve(CHECKSUM_LEN + payload.len());
+    out.extend_from_slice(&crc32c(payload).to_le_bytes());
+    out.extend_from_slice(payload);
+}
+
+/// Verify a `[crc32c][payload]` buffer and return the payload.
+pub fn verify(checked: &[u8]) -> Result<&[u8], ChecksumError> {
+    if checked.len() < CHECKSUM_LEN {
+        return Err(ChecksumError::Truncated);
+    }
+    let (crc, payload) = checked.split_at(CHECKSUM_LEN);
+    let expected = u32::from_le_bytes(crc.try_into().expect("4-byte slice"));
+    let actual = crc32c(payload);
+    if expected != actual {
+        return Err(ChecksumError::Mismatch { expected, actual });
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32c_check_value() {
+        // Standard CRC-32C check value
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+    }
+
+    #[test]
+    fn test_verify_detects_corruption() {
+        let mut checked = Vec::new();
+        write_checked(b"snapshot bytes", &mut checked);
+        assert_eq!(verify(&checked), Ok(&b"snapshot bytes"[..]));
+
+        for i in 0..checked.len() {
+            let mut corrupted = checked.clone();
+            corrupted[i] ^= 0x10;
+            assert!(matches!(
+                verify(&corrupted),
+                Err(ChecksumError::Mismatch { .. })
+            ));
+        }
+        assert_eq!(verify(&checked[..3]), Err(ChecksumError::Truncated));
+    }
+}
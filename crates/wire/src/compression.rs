@@ -0,0 +1,325 @@
+//! Compressed payload framing.
+//!
+//! Ref: ADR-0005 (Control/Realtime Channels), DM-0017 (ReplayArtifact)
+//! - A frame is `[codec: u8][raw_len: u32 LE][body_len: u32 LE][body]`
+//! - `body` is the payload encoded with `codec` (none, LZ4 block, or zstd)
+//! - Length prefixes let frames be concatenated on a stream (control channel,
+//!   replay download) as well as sent one per datagram (snapshots)
+//! - Decoding checks both lengths against `FrameLimits` before allocating or
+//!   decompressing, so a small hostile frame cannot expand without bound
+//!
+//! - Codecs are negotiated in the handshake: `ClientHello::compression`
+//!   offers, `negotiate` picks, and `ServerWelcome::compression` reports
+//!   the result
+//!
+//! Framing is independent of prost: the body is opaque bytes, usually an
+//! encoded envelope or ReplayArtifact.
+
+use std::io::Read;
+
+use crate::{CompressionCodec, CompressionOffer, CompressionParams, capabilities};
+
+/// Bytes before the body.
+pub const FRAME_HEADER_LEN: usize = 9;
+
+/// Default cap on a decoded payload (1 MiB: any single wire message).
+pub const DEFAULT_MAX_RAW_LEN: usize = 1024 * 1024;
+
+/// Default size below which negotiated compression is skipped; small
+/// payloads rarely shrink enough to pay for the frame header.
+pub const DEFAULT_MIN_PAYLOAD_LEN: u32 = 128;
+
+/// Payload encoding of a frame body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Codec {
+    None = 0,
+    Lz4 = 1,
+    Zstd = 2,
+}
+
+impl Codec {
+    pub fn from_u8(tag: u8) -> Option<Self> {
+        Some(match tag {
+            0 => Self::None,
+            1 => Self::Lz4,
+            2 => Self::Zstd,
+            _ => return None,
+        })
+    }
+
+    pub fn from_proto(codec: CompressionCodec) -> Self {
+        match codec {
+            CompressionCodec::None => Self::None,
+            CompressionCodec::Lz4 => Self::Lz4,
+            CompressionCodec::Zstd => Self::Zstd,
+        }
+    }
+
+    pub fn to_proto(self) -> CompressionCodec {
+        match self {
+            Self::None => CompressionCodec::None,
+            Self::Lz4 => CompressionCodec::Lz4,
+            Self::Zstd => CompressionCodec::Zstd,
+        }
+    }
+}
+
+/// Codecs a client offered, most preferred first: the explicit offer, or
+/// else its `COMPRESSION_*` capability bits. Unknown codecs are skipped.
+pub fn offered_codecs(offer: Option<&CompressionOffer>, client_capabilities: u64) -> Vec<Codec> {
+    match offer {
+        Some(offer) => offer
+            .codecs
+            .iter()
+            .filter_map(|&codec| CompressionCodec::try_from(codec).ok())
+            .map(Codec::from_proto)
+            .collect(),
+        None => [
+            (capabilities::COMPRESSION_LZ4, Codec::Lz4),
+            (capabilities::COMPRESSION_ZSTD, Codec::Zstd),
+        ]
+        .into_iter()
+        .filter(|&(flag, _)| capabilities::has(client_capabilities, flag))
+        .map(|(_, codec)| codec)
+        .collect(),
+    }
+}
+
+/// Compression for a session: the client's most preferred offered codec
+/// that the server `supported`, capped at the client's `max_raw_len`.
+/// `None` if they share no codec (frames go uncompressed).
+pub fn negotiate(
+    offer: Option<&CompressionOffer>,
+    client_capabilities: u64,
+    supported: &[Codec],
+    min_payload_len: u32,
+) -> Option<CompressionParams> {
+    let codec = offered_codecs(offer, client_capabilities)
+        .into_iter()
+        .find(|codec| *codec != Codec::None && supported.contains(codec))?;
+    let max_raw_len = match offer.map_or(0, |offer| offer.max_raw_len) {
+        0 => DEFAULT_MAX_RAW_LEN as u32,
+        len => len.min(DEFAULT_MAX_RAW_LEN as u32),
+    };
+    Some(CompressionParams {
+        codec: codec.to_proto() as i32,
+        min_payload_len,
+        max_raw_len,
+    })
+}
+
+/// Size caps enforced by `decode_frame` (and by `encode_frame` on the raw side).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLimits {
+    /// Largest decoded payload accepted.
+    pub max_raw_len: usize,
+}
+
+impl Default for FrameLimits {
+    fn default() -> Self {
+        Self {
+            max_raw_len: DEFAULT_MAX_RAW_LEN,
+        }
+    }
+}
+
+impl FrameLimits {
+    /// Largest body accepted. Incompressible data grows slightly under every
+    /// codec, so the body cap leaves headroom over `max_raw_len`.
+    pub fn max_body_len(&self) -> usize {
+        self.max_raw_len
+            .saturating_add(self.max_raw_len / 64)
+            .saturating_add(64)
+    }
+}
+
+/// Frame encode/decode failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// Fewer bytes than the header or declared body length.
+    Truncated,
+    UnknownCodec(u8),
+    /// Declared or actual length exceeds the configured cap.
+    TooLarge {
+        len: usize,
+        max: usize,
+    },
+    /// The body did not decode to exactly `raw_len` bytes.
+    Corrupt,
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "Truncated frame"),
+            Self::UnknownCodec(tag) => write!(f, "Unknown frame codec {tag}"),
+            Self::TooLarge { len, max } => write!(f, "Frame length {len} exceeds cap {max}"),
+            Self::Corrupt => write!(f, "Frame body does not decode to its declared length"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// Frame `payload` with `codec`.
+pub fn encode_frame(
+    codec: Codec,
+    payload: &[u8],
+    limits: &FrameLimits,
+) -> Result<Vec<u8>, FrameError> {
+    check_len(payload.len(), limits.max_raw_len)?;
+    let body = match codec {
+        Codec::None => payload.to_vec(),
+        Codec::Lz4 => lz4_flex::block::compress(payload),
+        Codec::Zstd => {
+            ruzstd::encoding::compress_to_vec(payload, ruzstd::encoding::CompressionLevel::Fastest)
+        }
+    };
+    check_len(body.len(), limits.max_body_len())?;
+
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + body.len());
+    frame.push(codec as u8);
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&body);
+    Ok(frame)
+}
+
+/// Decode the frame at the start of `bytes`.
+/// Returns the payload and the number of bytes the frame occupied.
+pub fn decode_frame(bytes: &[u8], limits: &FrameLimits) -> Result<(Vec<u8>, usize), FrameError> {
+    let header = bytes.get(..FRAME_HEADER_LEN).ok_or(FrameError::Truncated)?;
+    let codec = Codec::from_u8(header[0]).ok_or(FrameError::UnknownCodec(header[0]))?;
+    let raw_len = read_u32(&header[1..5]);
+    let body_len = read_u32(&header[5..9]);
+    check_len(raw_len, limits.max_raw_len)?;
+    check_len(body_len, limits.max_body_len())?;
+    let end = FRAME_HEADER_LEN + body_len;
+    let body = bytes
+        .get(FRAME_HEADER_LEN..end)
+        .ok_or(FrameError::Truncated)?;
+
+    let payload = match codec {
+        Codec::None => body.to_vec(),
+        Codec::Lz4 => {
+            lz4_flex::block::decompress(body, raw_len).map_err(|_| FrameError::Corrupt)?
+        }
+        Codec::Zstd => {
+            let decoder =
+                ruzstd::decoding::StreamingDecoder::new(body).map_err(|_| FrameError::Corrupt)?;
+            let mut payload = Vec::with_capacity(raw_len);
+            // One byte past raw_len is enough to detect an oversized body
+            decoder
+                .take(raw_len as u64 + 1)
+                .read_to_end(&mut payload)
+                .map_err(|_| FrameError::Corrupt)?;
+            payload
+        }
+    };
+    if payload.len() != raw_len {
+        return Err(FrameError::Corrupt);
+    }
+    Ok((payload, end))
+}
+
+fn read_u32(bytes: &[u8]) -> usize {
+    u32::from_le_bytes(bytes.try_into().expect("4-byte slice")) as usize
+}
+
+fn check_len(len: usize, max: usize) -> Result<(), FrameError> {
+    if len > max || u32::try_from(len).is_err() {
+        return Err(FrameError::TooLarge { len, max });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_codecs() {
+        let offer = CompressionOffer {
+            codecs: vec![
+                99,
+                CompressionCodec::Zstd as i32,
+                CompressionCodec::Lz4 as i32,
+            ],
+            max_raw_len: 64 * 1024,
+        };
+        let params = negotiate(Some(&offer), 0, &[Codec::Lz4, Codec::Zstd], 256).unwrap();
+        assert_eq!(params.codec(), CompressionCodec::Zstd);
+        assert_eq!(params.min_payload_len, 256);
+        assert_eq!(params.max_raw_len, 64 * 1024);
+        assert_eq!(negotiate(Some(&offer), 0, &[Codec::None], 256), None);
+
+        // Without an offer, the capability bits stand in
+        let params = negotiate(None, capabilities::COMPRESSION_LZ4, &[Codec::Lz4], 0).unwrap();
+        assert_eq!(params.codec(), CompressionCodec::Lz4);
+        assert_eq!(params.max_raw_len, DEFAULT_MAX_RAW_LEN as u32);
+        assert_eq!(negotiate(None, 0, &[Codec::Lz4], 0), None);
+    }
+
+    #[test]
+    fn test_roundtrip_all_codecs() {
+        let limits = FrameLimits::default();
+        // Repetitive, like a snapshot of entities at rest
+        let payload: Vec<u8> = (0..4096u32).map(|i| (i % 7) as u8).collect();
+        for codec in [Codec::None, Codec::Lz4, Codec::Zstd] {
+            let frame = encode_frame(codec, &payload, &limits).unwrap();
+            if codec != Codec::None {
+                assert!(frame.len() < payload.len() / 4, "{codec:?} compresses");
+            }
+            assert_eq!(
+                decode_frame(&frame, &limits).unwrap(),
+                (payload.clone(), frame.len())
+            );
+        }
+
+        // Concatenated frames decode in sequence
+        let mut stream = encode_frame(Codec::Lz4, b"first", &limits).unwrap();
+        stream.extend(encode_frame(Codec::None, b"second", &limits).unwrap());
+        let (first, used) = decode_frame(&stream, &limits).unwrap();
+        let (second, _) = decode_frame(&stream[used..], &limits).unwrap();
+        assert_eq!(
+            (first.as_slice(), second.as_slice()),
+            (&b"first"[..], &b"second"[..])
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_frames() {
+        let limits = FrameLimits { max_raw_len: 1024 };
+        assert_eq!(
+            encode_frame(Codec::None, &[0; 2048], &limits),
+            Err(FrameError::TooLarge {
+                len: 2048,
+                max: 1024
+            })
+        );
+
+        let frame = encode_frame(Codec::Zstd, &[9; 1000], &limits).unwrap();
+        assert_eq!(
+            decode_frame(&frame[..frame.len() - 1], &limits),
+            Err(FrameError::Truncated)
+        );
+        assert_eq!(
+            decode_frame(&[7; 12], &limits),
+            Err(FrameError::UnknownCodec(7))
+        );
+
+        // A tiny frame claiming a huge payload is refused before decompressing
+        let mut bomb = frame.clone();
+        bomb[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            decode_frame(&bomb, &limits),
+            Err(FrameError::TooLarge { .. })
+        ));
+
+        // Declared length disagreeing with the body
+        let mut lying = frame;
+        lying[1..5].copy_from_slice(&999u32.to_le_bytes());
+        assert_eq!(decode_frame(&lying, &limits), Err(FrameError::Corrupt));
+    }
+}
@@ -0,0 +1,231 @@
+//! Encrypted Realtime frame format.
+//!
+//! Ref: ADR-0005 (Control/Realtime Channels)
+//! - Keys are agreed on the Control Channel (`KeyExchangeInit` /
+//!   `KeyExchangeResponse`); the response names the AEAD algorithm and a
+//!   `key_id` for the derived keys
+//! - Each sealed datagram is an `EncryptedFrame`: `key_id`, a 12-byte
+//!   `nonce`, `ciphertext`, and a 16-byte `tag`, with the `key_id` (u32 LE)
+//!   authenticated as associated data; it travels as the `encrypted_frame`
+//!   member of a `RealtimeMessage`
+//! - Nonces are `counter_nonce(counter)` with a per-direction counter, so a
+//!   receiver can reject replays by counter
+//!
+//! This module is the protocol half only: the crypto comes from a
+//! `FrameCipher` supplied by the server or client crate, so this crate
+//! stays free of cryptographic dependencies.
+
+use crate::{AeadAlgorithm, EncryptedFrame};
+
+/// Nonce length of every supported algorithm.
+pub const NONCE_LEN: usize = 12;
+
+/// Authentication tag length of every supported algorithm.
+pub const TAG_LEN: usize = 16;
+
+/// Frame sealing or opening failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// `nonce` is not `NONCE_LEN` bytes.
+    NonceLength(usize),
+    /// `tag` is not `TAG_LEN` bytes.
+    TagLength(usize),
+    /// The cipher returned a sealed buffer shorter than its tag.
+    SealedTooShort(usize),
+    /// The tag did not verify (forged, corrupted, or wrong key).
+    AuthenticationFailed,
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonceLength(len) => write!(f, "Nonce is {len} bytes, expected {NONCE_LEN}"),
+            Self::TagLength(len) => write!(f, "Tag is {len} bytes, expected {TAG_LEN}"),
+            Self::SealedTooShort(len) => {
+                write!(f, "Sealed output is {len} bytes, shorter than its tag")
+            }
+            Self::AuthenticationFailed => write!(f, "Frame authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// AEAD implementation for one direction's key.
+pub trait FrameCipher {
+    /// Encrypt `plaintext`, authenticating `aad`; returns ciphertext followed
+    /// by the `TAG_LEN`-byte tag.
+    fn seal(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8>;
+
+    /// Decrypt ciphertext-and-tag; `None` if authentication fails.
+    fn open(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Associated data authenticated with every frame.
+pub fn associated_data(key_id: u32) -> [u8; 4] {
+    key_id.to_le_bytes()
+}
+
+/// Nonce for the `counter`th frame in one direction: 4 zero bytes, then
+/// the counter (u64 LE).
+pub fn counter_nonce(counter: u64) -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[4..].copy_from_slice(&counter.to_le_bytes());
+    nonce
+}
+
+/// Counter of a `counter_nonce`; `None` for any other nonce.
+pub fn nonce_counter(nonce: &[u8]) -> Option<u64> {
+    let nonce: &[u8; NONCE_LEN] = nonce.try_into().ok()?;
+    if nonce[..4] != [0; 4] {
+        return None;
+    }
+    Some(u64::from_le_bytes(
+        nonce[4..].try_into().expect("8-byte slice"),
+    ))
+}
+
+/// Seal `plaintext` (a whole datagram) into a frame.
+pub fn seal_frame(
+    cipher: &impl FrameCipher,
+    key_id: u32,
+    nonce: [u8; NONCE_LEN],
+    plaintext: &[u8],
+) -> Result<EncryptedFrame, FrameError> {
+    let mut ciphertext = cipher.seal(&nonce, &associated_data(key_id), plaintext);
+    let Some(tag_start) = ciphertext.len().checked_sub(TAG_LEN) else {
+        return Err(FrameError::SealedTooShort(ciphertext.len()));
+    };
+    let tag = ciphertext.split_off(tag_start);
+    Ok(EncryptedFrame {
+        key_id,
+        nonce: nonce.to_vec(),
+        ciphertext,
+        tag,
+    })
+}
+
+/// Verify and decrypt a frame with the cipher for its `key_id`.
+pub fn open_frame(
+    cipher: &impl FrameCipher,
+    frame: &EncryptedFrame,
+) -> Result<Vec<u8>, FrameError> {
+    let nonce: &[u8; NONCE_LEN] = frame
+        .nonce
+        .as_slice()
+        .try_into()
+        .map_err(|_| FrameError::NonceLength(frame.nonce.len()))?;
+    if frame.tag.len() != TAG_LEN {
+        return Err(FrameError::TagLength(frame.tag.len()));
+    }
+    let mut sealed = Vec::with_capacity(frame.ciphertext.len() + TAG_LEN);
+    sealed.extend_from_slice(&frame.ciphertext);
+    sealed.extend_from_slice(&frame.tag);
+    cipher
+        .open(nonce, &associated_data(frame.key_id), &sealed)
+        .ok_or(FrameError::AuthenticationFailed)
+}
+
+/// The server's most preferred algorithm that the client offered (an empty
+/// offer means ChaCha20-Poly1305 only); `None` if there is no overlap.
+pub fn choose_algorithm(offered: &[i32], supported: &[AeadAlgorithm]) -> Option<AeadAlgorithm> {
+    let offers = |algorithm: AeadAlgorithm| {
+        if offered.is_empty() {
+            algorithm == AeadAlgorithm::Chacha20Poly1305
+        } else {
+            offered.contains(&(algorithm as i32))
+        }
+    };
+    supported
+        .iter()
+        .copied()
+        .find(|&algorithm| algorithm != AeadAlgorithm::Unspecified && offers(algorithm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+
+    /// Not a real AEAD: XOR keystream and a CRC32C "tag", enough to check
+    /// the framing.
+    struct ToyCipher(u8);
+
+    impl ToyCipher {
+        fn tag(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_LEN] {
+            let crc = checksum::crc32c(&[&[self.0], nonce, aad, ciphertext].concat());
+            let mut tag = [0u8; TAG_LEN];
+            for chunk in tag.chunks_mut(4) {
+                chunk.copy_from_slice(&crc.to_le_bytes());
+            }
+            tag
+        }
+    }
+
+    impl FrameCipher for ToyCipher {
+        fn seal(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+            let mut sealed: Vec<u8> = plaintext.iter().map(|b| b ^ self.0).collect();
+            let tag = self.tag(nonce, aad, &sealed);
+            sealed.extend_from_slice(&tag);
+            sealed
+        }
+
+        fn open(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+            let (ciphertext, tag) = sealed.split_at(sealed.len().checked_sub(TAG_LEN)?);
+            (self.tag(nonce, aad, ciphertext) == tag)
+                .then(|| ciphertext.iter().map(|b| b ^ self.0).collect())
+        }
+    }
+
+    #[test]
+    fn test_frame_roundtrip_and_tamper() {
+        let cipher = ToyCipher(0x5a);
+        let frame = seal_frame(&cipher, 7, counter_nonce(3), b"datagram").unwrap();
+        assert_eq!(frame.key_id, 7);
+        assert_eq!(frame.tag.len(), TAG_LEN);
+        assert_eq!(nonce_counter(&frame.nonce), Some(3));
+        assert_eq!(open_frame(&cipher, &frame).unwrap(), b"datagram");
+
+        // key_id is authenticated
+        let relabeled = EncryptedFrame {
+            key_id: 8,
+            ..frame.clone()
+        };
+        assert_eq!(
+            open_frame(&cipher, &relabeled),
+            Err(FrameError::AuthenticationFailed)
+        );
+        let mut flipped = frame.clone();
+        flipped.ciphertext[0] ^= 1;
+        assert_eq!(
+            open_frame(&cipher, &flipped),
+            Err(FrameError::AuthenticationFailed)
+        );
+        let short_tag = EncryptedFrame {
+            tag: vec![0; 4],
+            ..frame
+        };
+        assert_eq!(
+            open_frame(&cipher, &short_tag),
+            Err(FrameError::TagLength(4))
+        );
+        assert_eq!(nonce_counter(&[1; NONCE_LEN]), None);
+    }
+
+    #[test]
+    fn test_choose_algorithm() {
+        use AeadAlgorithm::{Aes256Gcm, Chacha20Poly1305};
+        let server = [Chacha20Poly1305, Aes256Gcm];
+        assert_eq!(choose_algorithm(&[], &server), Some(Chacha20Poly1305));
+        assert_eq!(
+            choose_algorithm(&[Aes256Gcm as i32, Chacha20Poly1305 as i32], &server),
+            Some(Chacha20Poly1305)
+        );
+        assert_eq!(
+            choose_algorithm(&[Aes256Gcm as i32], &server),
+            Some(Aes256Gcm)
+        );
+        assert_eq!(choose_algorithm(&[99], &server), None);
+        assert_eq!(choose_algorithm(&[], &[Aes256Gcm]), None);
+    }
+}
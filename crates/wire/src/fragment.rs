@@ -0,0 +1,319 @@
+//! MTU-aware fragmentation and reassembly.
+//!
+//! Ref: ADR-0005 (Realtime Channel)
+//! - A fragment is `[message_id: u16 LE][index: u8][count: u8][chunk]`
+//! - `Fragmenter` splits a payload into `count` chunks so that every fragment
+//!   fits in `mtu` bytes; a payload that already fits is still sent as a
+//!   single 1-of-1 fragment so receivers need only one code path
+//! - `Reassembler` buffers fragments per `message_id` and yields the payload
+//!   once all `count` chunks have arrived, in any order
+//! - Incomplete messages are discarded after `timeout_micros`, and at most
+//!   `max_pending` are buffered (the oldest is evicted first), so a lossy or
+//!   hostile peer cannot grow the buffer without bound
+//!
+//! Fragments are unreliable like any Realtime datagram: losing one loses the
+//! whole message, which for snapshots is superseded by the next tick anyway.
+
+use std::collections::BTreeMap;
+
+/// Bytes before each fragment's chunk.
+pub const FRAGMENT_HEADER_LEN: usize = 4;
+
+/// Most fragments one message may be split into.
+pub const MAX_FRAGMENTS: usize = u8::MAX as usize;
+
+/// Default datagram budget: fits a 1280-byte IPv6 minimum MTU after the
+/// IP/UDP headers and transport framing.
+pub const DEFAULT_MTU: usize = 1200;
+
+/// Default time an incomplete message is kept (250 ms).
+pub const DEFAULT_REASSEMBLY_TIMEOUT_MICROS: u64 = 250_000;
+
+/// Default number of incomplete messages buffered at once.
+pub const DEFAULT_MAX_PENDING: usize = 16;
+
+/// Fragmentation or reassembly failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentError {
+    /// `mtu` leaves no room for a chunk after the header.
+    MtuTooSmall { mtu: usize },
+    /// The payload needs more than `MAX_FRAGMENTS` fragments at this MTU.
+    TooManyFragments { needed: usize },
+    /// Fewer bytes than the header.
+    Truncated,
+    /// `count` is zero or `index` is not below it.
+    InvalidIndex { index: u8, count: u8 },
+    /// A fragment disagrees with earlier fragments of the same message.
+    Inconsistent { message_id: u16 },
+}
+
+impl std::fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MtuTooSmall { mtu } => write!(f, "MTU {mtu} leaves no room for a fragment"),
+            Self::TooManyFragments { needed } => {
+                write!(f, "Payload needs {needed} fragments (max {MAX_FRAGMENTS})")
+            }
+            Self::Truncated => write!(f, "Truncated fragment"),
+            Self::InvalidIndex { index, count } => {
+                write!(f, "Fragment index {index} invalid for count {count}")
+            }
+            Self::Inconsistent { message_id } => {
+                write!(f, "Fragments of message {message_id} disagree")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FragmentError {}
+
+/// Splits outbound payloads into MTU-sized fragments.
+#[derive(Debug, Clone)]
+pub struct Fragmenter {
+    mtu: usize,
+    next_message_id: u16,
+}
+
+impl Default for Fragmenter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MTU)
+    }
+}
+
+impl Fragmenter {
+    /// `mtu` is the largest fragment produced, header included.
+    pub fn new(mtu: usize) -> Self {
+        Self {
+            mtu,
+            next_message_id: 0,
+        }
+    }
+
+    pub fn mtu(&self) -> usize {
+        self.mtu
+    }
+
+    /// Split `payload` into fragments under a fresh message id.
+    pub fn split(&mut self, payload: &[u8]) -> Result<Vec<Vec<u8>>, FragmentError> {
+        let chunk_len = self
+            .mtu
+            .checked_sub(FRAGMENT_HEADER_LEN)
+            .filter(|&len| len > 0)
+            .ok_or(FragmentError::MtuTooSmall { mtu: self.mtu })?;
+        let count = payload.len().div_ceil(chunk_len).max(1);
+        if count > MAX_FRAGMENTS {
+            return Err(FragmentError::TooManyFragments { needed: count });
+        }
+
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+        let mut chunks: Vec<&[u8]> = payload.chunks(chunk_len).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        Ok(chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut fragment = Vec::with_capacity(FRAGMENT_HEADER_LEN + chunk.len());
+                fragment.extend_from_slice(&message_id.to_le_bytes());
+                fragment.push(index as u8);
+                fragment.push(count as u8);
+                fragment.extend_from_slice(chunk);
+                fragment
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug)]
+struct PendingMessage {
+    first_seen_micros: u64,
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// Reassembles inbound fragments into payloads.
+#[derive(Debug)]
+pub struct Reassembler {
+    timeout_micros: u64,
+    max_pending: usize,
+    pending: BTreeMap<u16, PendingMessage>,
+    expired: u64,
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_REASSEMBLY_TIMEOUT_MICROS, DEFAULT_MAX_PENDING)
+    }
+}
+
+impl Reassembler {
+    /// `max_pending` is clamped to at least 1.
+    pub fn new(timeout_micros: u64, max_pending: usize) -> Self {
+        Self {
+            timeout_micros,
+            max_pending: max_pending.max(1),
+            pending: BTreeMap::new(),
+            expired: 0,
+        }
+    }
+
+    /// Accept one fragment received at `now_micros`. Returns the payload when
+    /// this fragment completes its message. Duplicate fragments are ignored.
+    pub fn insert(
+        &mut self,
+        fragment: &[u8],
+        now_micros: u64,
+    ) -> Result<Option<Vec<u8>>, FragmentError> {
+        let header = fragment
+            .get(..FRAGMENT_HEADER_LEN)
+            .ok_or(FragmentError::Truncated)?;
+        let message_id = u16::from_le_bytes([header[0], header[1]]);
+        let (index, count) = (header[2], header[3]);
+        if count == 0 || index >= count {
+            return Err(FragmentError::InvalidIndex { index, count });
+        }
+        let chunk = &fragment[FRAGMENT_HEADER_LEN..];
+        if count == 1 {
+            return Ok(Some(chunk.to_vec()));
+        }
+
+        self.expire(now_micros);
+        if !self.pending.contains_key(&message_id) && self.pending.len() >= self.max_pending {
+            self.evict_oldest();
+        }
+        let message = self
+            .pending
+            .entry(message_id)
+            .or_insert_with(|| PendingMessage {
+                first_seen_micros: now_micros,
+                chunks: vec![None; count as usize],
+                received: 0,
+            });
+        if message.chunks.len() != count as usize {
+            self.pending.remove(&message_id);
+            return Err(FragmentError::Inconsistent { message_id });
+        }
+        let slot = &mut message.chunks[index as usize];
+        if slot.is_none() {
+            *slot = Some(chunk.to_vec());
+            message.received += 1;
+        }
+        if message.received < message.chunks.len() {
+            return Ok(None);
+        }
+
+        let message = self.pending.remove(&message_id).expect("present");
+        Ok(Some(
+            message.chunks.into_iter().flatten().flatten().collect(),
+        ))
+    }
+
+    /// Drop incomplete messages older than the timeout. Returns how many were
+    /// dropped.
+    pub fn expire(&mut self, now_micros: u64) -> usize {
+        let before = self.pending.len();
+        let timeout = self.timeout_micros;
+        self.pending
+            .retain(|_, message| now_micros.saturating_sub(message.first_seen_micros) < timeout);
+        let dropped = before - self.pending.len();
+        self.expired += dropped as u64;
+        dropped
+    }
+
+    /// Incomplete messages currently buffered.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Incomplete messages discarded so far (timed out or evicted).
+    pub fn expired(&self) -> u64 {
+        self.expired
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .pending
+            .iter()
+            .min_by_key(|(_, message)| message.first_seen_micros)
+            .map(|(&id, _)| id);
+        if let Some(id) = oldest {
+            self.pending.remove(&id);
+            self.expired += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_reassemble_out_of_order() {
+        let payload: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+        let mut fragmenter = Fragmenter::new(1200);
+        let fragments = fragmenter.split(&payload).unwrap();
+        assert_eq!(fragments.len(), 5);
+        assert!(fragments.iter().all(|f| f.len() <= 1200));
+
+        let mut reassembler = Reassembler::default();
+        // Reversed, with a duplicate of the last fragment sent
+        let mut arrivals: Vec<&Vec<u8>> = fragments.iter().rev().collect();
+        arrivals.insert(1, arrivals[0]);
+        let mut completed = None;
+        for fragment in arrivals {
+            if let Some(done) = reassembler.insert(fragment, 0).unwrap() {
+                completed = Some(done);
+            }
+        }
+        assert_eq!(completed, Some(payload));
+        assert_eq!(reassembler.pending_len(), 0);
+
+        // Small payloads are a single fragment and need no buffering
+        let single = fragmenter.split(b"tiny").unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(
+            reassembler.insert(&single[0], 0).unwrap(),
+            Some(b"tiny".to_vec())
+        );
+
+        assert_eq!(
+            Fragmenter::new(4).split(b"x"),
+            Err(FragmentError::MtuTooSmall { mtu: 4 })
+        );
+        assert_eq!(
+            Fragmenter::new(5).split(&[0; 256]),
+            Err(FragmentError::TooManyFragments { needed: 256 })
+        );
+    }
+
+    #[test]
+    fn test_incomplete_messages_expire_and_are_bounded() {
+        let mut fragmenter = Fragmenter::new(8);
+        let mut reassembler = Reassembler::new(1_000, 2);
+
+        let lost = fragmenter.split(&[1; 12]).unwrap();
+        assert_eq!(reassembler.insert(&lost[0], 0).unwrap(), None);
+        assert_eq!(reassembler.expire(999), 0);
+        assert_eq!(reassembler.expire(1_000), 1);
+        // The late remainder starts a new, never-completing message
+        assert_eq!(reassembler.insert(&lost[1], 1_000).unwrap(), None);
+
+        for at in [1_001, 1_002] {
+            let partial = fragmenter.split(&[2; 12]).unwrap();
+            reassembler.insert(&partial[0], at).unwrap();
+        }
+        assert_eq!(reassembler.pending_len(), 2);
+        assert_eq!(reassembler.expired(), 2);
+
+        assert_eq!(
+            reassembler.insert(&[0, 0, 3], 0),
+            Err(FragmentError::Truncated)
+        );
+        assert_eq!(
+            reassembler.insert(&[0, 0, 2, 2], 0),
+            Err(FragmentError::InvalidIndex { index: 2, count: 2 })
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Canonical encoding golden vectors.
+//!
+//! Ref: T0.18 (byte-identical broadcast), DM-0017 (ReplayArtifact)
+//! - Each vector pairs a fixed message with its exact prost encoding
+//! - The expected bytes live in `protocol/flowstate/wire/golden_vectors.txt`
+//!   (`name hex` per line), so non-Rust clients can check their encoders
+//!   against the same fixtures
+//! - A vector's bytes never change once published; a schema change adds new
+//!   fields or vectors instead, so an encoding shift in existing fields is
+//!   caught before it breaks broadcast payloads or recorded replays
+
+use prost::Message;
+
+use crate::{
+    AppliedInputProto, ClientHello, ControlMessage, DeltaSnapshotProto, EntitySnapshotProto,
+    ErrorCode, ErrorResponse, InputCmdProto, JoinBaseline, LeaveReason, MatchEndReason,
+    PlayerEntityMapping, PlayerLeft, RealtimeMessage, ReplayArtifact, ServerWelcome,
+    SetTeamCommand, SimCommandProto, SnapshotProto, SpawnCharacterCommand, TuningParameter,
+    control_message, realtime_message, sim_command_proto,
+};
+
+/// The embedded fixture file.
+pub const FIXTURES: &str = include_str!("../../../protocol/flowstate/wire/golden_vectors.txt");
+
+/// A message whose encoding is pinned by a fixture.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoldenMessage {
+    Control(ControlMessage),
+    Realtime(RealtimeMessage),
+    Replay(Box<ReplayArtifact>),
+}
+
+impl GoldenMessage {
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        match self {
+            Self::Control(msg) => msg.encode_to_vec(),
+            Self::Realtime(msg) => msg.encode_to_vec(),
+            Self::Replay(msg) => msg.encode_to_vec(),
+        }
+    }
+
+    /// Decode `bytes` as the same message type as `self`.
+    pub fn decode_like(&self, bytes: &[u8]) -> Result<Self, prost::DecodeError> {
+        Ok(match self {
+            Self::Control(_) => Self::Control(ControlMessage::decode(bytes)?),
+            Self::Realtime(_) => Self::Realtime(RealtimeMessage::decode(bytes)?),
+            Self::Replay(_) => Self::Replay(Box::new(ReplayArtifact::decode(bytes)?)),
+        })
+    }
+}
+
+/// One golden vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenVector {
+    pub name: &'static str,
+    pub message: GoldenMessage,
+    /// Expected encoding, from `FIXTURES`.
+    pub expected: Vec<u8>,
+}
+
+/// Every golden vector, in fixture order.
+///
+/// # Panics
+/// If the embedded fixtures are malformed or do not match `messages()`.
+pub fn vectors() -> Vec<GoldenVector> {
+    let mut expected = parse_fixtures(FIXTURES);
+    let vectors: Vec<GoldenVector> = messages()
+        .into_iter()
+        .map(|(name, message)| {
+            let position = expected
+                .iter()
+                .position(|(fixture, _)| *fixture == name)
+                .unwrap_or_else(|| panic!("no fixture for golden vector {name}"));
+            GoldenVector {
+                name,
+                message,
+                expected: expected.remove(position).1,
+            }
+        })
+        .collect();
+    assert!(
+        expected.is_empty(),
+        "fixtures without a golden message: {:?}",
+        expected.iter().map(|(name, _)| name).collect::<Vec<_>>()
+    );
+    vectors
+}
+
+fn parse_fixtures(text: &'static str) -> Vec<(&'static str, Vec<u8>)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, hex) = line
+                .split_once(' ')
+                .unwrap_or_else(|| panic!("malformed fixture line {line:?}"));
+            (name, decode_hex(hex.trim()))
+        })
+        .collect()
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    assert!(hex.len().is_multiple_of(2), "odd-length hex {hex:?}");
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("hex digit"))
+        .collect()
+}
+
+fn entity(entity_id: u64, position: [f64; 2], velocity: [f64; 2]) -> EntitySnapshotProto {
+    EntitySnapshotProto {
+        entity_id,
+        position: position.to_vec(),
+        velocity: velocity.to_vec(),
+        ..Default::default()
+    }
+}
+
+fn spawn_command(player_id: u32) -> SimCommandProto {
+    SimCommandProto {
+        tick: 0,
+        command: Some(sim_command_proto::Command::SpawnCharacter(
+            SpawnCharacterCommand { player_id },
+        )),
+    }
+}
+
+/// The pinned messages, by vector name.
+fn messages() -> Vec<(&'static str, GoldenMessage)> {
+    use control_message::Payload as Control;
+    use realtime_message::Payload as Realtime;
+    use sim_command_proto::Command as SimCommand;
+
+    let baseline = JoinBaseline {
+        tick: 0,
+        entities: vec![entity(1, [0.0, 0.0], [0.0, 0.0])],
+        digest: 0x0123_4567_89ab_cdef,
+    };
+    // Published as version 1, before `end_reason` was typed
+    let replay_v1 = ReplayArtifact {
+        replay_format_version: 1,
+        initial_baseline: Some(baseline.clone()),
+        seed: 42,
+        rng_algorithm: "ChaCha8Rng".to_string(),
+        tick_rate_hz: 60,
+        state_digest_algo_id: "statedigest-v0-fnv1a64-le-f64canon-eidasc-posvel".to_string(),
+        entity_spawn_order: vec![0, 1],
+        player_entity_mapping: vec![PlayerEntityMapping {
+            player_id: 0,
+            entity_id: 1,
+            team_id: None,
+        }],
+        tuning_parameters: vec![TuningParameter {
+            key: "move_speed".to_string(),
+            value: 5.0,
+        }],
+        inputs: vec![AppliedInputProto {
+            tick: 0,
+            player_id: 0,
+            move_dir: vec![0.0, 1.0],
+            is_fallback: true,
+        }],
+        build_fingerprint: None,
+        final_digest: 0xabcd,
+        checkpoint_tick: 1,
+        legacy_end_reason: "complete".to_string(),
+        end_reason: MatchEndReason::Unspecified as i32,
+        end_reason_detail: String::new(),
+        test_mode: true,
+        test_player_ids: vec![0, 1],
+        match_id: "m-1".to_string(),
+        checkpoint_digests: vec![],
+        metadata: None,
+        input_chain_hash: None,
+        input_audit: None,
+        snapshot_anchors: vec![],
+        sim_commands: vec![],
+    };
+    vec![
+        (
+            "client_hello",
+            GoldenMessage::Control(ControlMessage::new(Control::ClientHello(ClientHello {
+                auth_token: "invite-123".to_string(),
+                protocol_version: 1,
+                schema_hash: 0x1122_3344_5566_7788,
+                capabilities: 0,
+                compression: None,
+                key_exchange: None,
+            }))),
+        ),
+        (
+            "server_welcome",
+            GoldenMessage::Control(ControlMessage::new(Control::ServerWelcome(ServerWelcome {
+                target_tick_floor: 2,
+                tick_rate_hz: 60,
+                player_id: 1,
+                controlled_entity_id: 2,
+                team_id: Some(1),
+                match_id: "m-1".to_string(),
+                schema_hash: 0x1122_3344_5566_7788,
+                capabilities: 0,
+                compression: None,
+            }))),
+        ),
+        (
+            "join_baseline",
+            GoldenMessage::Control(ControlMessage::new(Control::JoinBaseline(baseline.clone()))),
+        ),
+        (
+            "player_left",
+            GoldenMessage::Control(ControlMessage::new(Control::PlayerLeft(PlayerLeft {
+                player_id: 1,
+                reason: LeaveReason::TimedOut as i32,
+                tick: 300,
+            }))),
+        ),
+        (
+            "error_response",
+            GoldenMessage::Control(ControlMessage::new(Control::ErrorResponse(ErrorResponse {
+                code: ErrorCode::ServerBusy as i32,
+                message: "Server busy".to_string(),
+                retryable: true,
+            }))),
+        ),
+        (
+            "input_cmd",
+            GoldenMessage::Realtime(RealtimeMessage::new(Realtime::InputCmd(InputCmdProto {
+                tick: 42,
+                input_seq: 7,
+                move_dir: vec![1.0, -0.5],
+            }))),
+        ),
+        (
+            "snapshot",
+            GoldenMessage::Realtime(RealtimeMessage::new(Realtime::Snapshot(SnapshotProto {
+                tick: 10,
+                entities: vec![
+                    entity(1, [0.5, 0.0], [1.0, 0.0]),
+                    entity(2, [0.0, -0.25], [0.0, -1.0]),
+                ],
+                digest: 0xdead_beef,
+                target_tick_floor: 12,
+                truncated: false,
+                server_time_micros: 0,
+            }))),
+        ),
+        (
+            "delta_snapshot",
+            GoldenMessage::Realtime(RealtimeMessage::new(Realtime::DeltaSnapshot(
+                DeltaSnapshotProto {
+                    tick: 11,
+                    base_tick: 10,
+                    keyframe: false,
+                    changed: vec![entity(1, [0.75, 0.0], [1.0, 0.0])],
+                    removed_entity_ids: vec![2],
+                    digest: 0xfeed,
+                    target_tick_floor: 13,
+                    server_time_micros: 0,
+                },
+            ))),
+        ),
+        (
+            "replay_artifact",
+            GoldenMessage::Replay(Box::new(replay_v1.clone())),
+        ),
+        (
+            "replay_artifact_v2",
+            GoldenMessage::Replay(Box::new(ReplayArtifact {
+                replay_format_version: 2,
+                legacy_end_reason: String::new(),
+                end_reason: MatchEndReason::Complete as i32,
+                ..replay_v1.clone()
+            })),
+        ),
+        (
+            "replay_artifact_v2_sim_commands",
+            GoldenMessage::Replay(Box::new(ReplayArtifact {
+                replay_format_version: 2,
+                legacy_end_reason: String::new(),
+                end_reason: MatchEndReason::Complete as i32,
+                sim_commands: vec![
+                    spawn_command(0),
+                    spawn_command(1),
+                    SimCommandProto {
+                        tick: 0,
+                        command: Some(SimCommand::SetTeam(SetTeamCommand {
+                            player_id: 0,
+                            team_id: 1,
+                        })),
+                    },
+                ],
+                ..replay_v1
+            })),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_encodings_are_stable() {
+        for vector in vectors() {
+            assert_eq!(
+                vector.message.encode_to_vec(),
+                vector.expected,
+                "encoding of {} shifted",
+                vector.name
+            );
+            assert_eq!(
+                vector.message.decode_like(&vector.expected).unwrap(),
+                vector.message,
+                "decoding of {}",
+                vector.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_shared_snapshot_bytes_match_golden() {
+        let snapshot = vectors()
+            .into_iter()
+            .find(|vector| vector.name == "snapshot")
+            .unwrap();
+        let GoldenMessage::Realtime(RealtimeMessage {
+            payload: Some(realtime_message::Payload::Snapshot(proto)),
+        }) = &snapshot.message
+        else {
+            panic!("snapshot vector is a realtime snapshot");
+        };
+        assert_eq!(
+            RealtimeMessage::encode_snapshot_bytes(&proto.encode_to_vec()),
+            snapshot.expected
+        );
+    }
+}
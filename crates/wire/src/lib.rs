@@ -4,11 +4,20 @@
 //! between Game Client and Server Edge. Both client and server binaries MUST
 //! depend on this crate (T0.19: Schema Identity CI Gate).
 //!
+//! The types are generated at build time from `protocol/flowstate/wire/*.proto`,
+//! which non-Rust clients (C#, TypeScript) compile with their own toolchains.
+//! The `serde` feature adds `Serialize`/`Deserialize` to every message, for
+//! JSON debugging output, log pipelines, and test fixtures.
+//!
 //! # Message Categories
 //!
 //! - **Control Channel** (reliable + ordered): Handshake, lifecycle messages
 //! - **Realtime Channel** (unreliable + sequenced): Inputs, snapshots
 //!
+//! Each channel has an envelope (`ControlMessage`, `RealtimeMessage`) whose
+//! oneof payload identifies the message kind, so any mix of kinds can share
+//! one socket or datagram stream.
+//!
 //! # References
 //!
 //! - ADR-0005: v0 Networking Architecture
@@ -21,7 +30,19 @@
 
 #![deny(unsafe_code)]
 
-use prost::Message;
+pub mod capabilities;
+pub mod change_mask;
+pub mod channel;
+pub mod checksum;
+pub mod compression;
+pub mod encryption;
+pub mod fragment;
+pub mod golden;
+pub mod limits;
+pub mod sequence;
+pub mod snapshot_size;
+
+pub use flowstate_sim::{EntityId, PlayerId};
 
 // ============================================================================
 // Type Aliases (matching simulation crate)
@@ -30,300 +51,112 @@ use prost::Message;
 /// Tick type alias for wire protocol.
 pub type Tick = u64;
 
-/// PlayerId type alias for wire protocol.
-pub type PlayerId = u8;
-
-/// EntityId type alias for wire protocol.
-pub type EntityId = u64;
-
 /// InputSeq type alias for wire protocol.
 /// Ref: DM-0026
 pub type InputSeq = u64;
 
-// ============================================================================
-// Control Channel Messages
-// ============================================================================
-
-/// Client initiates handshake.
-/// Ref: ADR-0005 (Control Channel)
-///
-/// v0: No fields required (handshake initiation only).
-/// Future versions MAY add fields (e.g., protocol version, client capabilities).
-#[derive(Clone, PartialEq, Message)]
-pub struct ClientHello {
-    // Empty for v0
-}
+/// Wire protocol version sent in `ClientHello::protocol_version`.
+/// Bumped on incompatible schema changes.
+pub const PROTOCOL_VERSION: u32 = 1;
 
-/// Server welcome response with session info and tick guidance.
-/// Ref: ADR-0005, ADR-0006 (Control Channel)
-#[derive(Clone, PartialEq, Message)]
-pub struct ServerWelcome {
-    /// Initial TargetTickFloor for client input targeting.
-    /// Ref: DM-0025
-    #[prost(uint64, tag = "1")]
-    pub target_tick_floor: Tick,
-
-    /// Server tick rate in Hz.
-    #[prost(uint32, tag = "2")]
-    pub tick_rate_hz: u32,
-
-    /// Assigned PlayerId for this session.
-    /// Ref: DM-0019
-    #[prost(uint32, tag = "3")]
-    pub player_id: u32, // Wire as u32 for protobuf compatibility
-
-    /// EntityId of the Character this client controls.
-    /// Ref: DM-0020
-    #[prost(uint64, tag = "4")]
-    pub controlled_entity_id: EntityId,
-}
-
-/// Initial baseline state sent to client after welcome.
-/// Ref: DM-0016 (Control Channel)
-#[derive(Clone, PartialEq, Message)]
-pub struct JoinBaseline {
-    /// Baseline tick.
-    #[prost(uint64, tag = "1")]
-    pub tick: Tick,
-
-    /// Entity snapshots, ordered by entity_id ascending per INV-0007.
-    #[prost(message, repeated, tag = "2")]
-    pub entities: Vec<EntitySnapshotProto>,
-
-    /// StateDigest at this tick (ADR-0007).
-    #[prost(uint64, tag = "3")]
-    pub digest: u64,
-}
+// Hash of the message/tag layout, computed by build.rs (T0.19). Exchanged in
+// ClientHello/ServerWelcome so mismatched binaries are caught at runtime.
+include!(concat!(env!("OUT_DIR"), "/schema_hash.rs"));
 
 // ============================================================================
-// Realtime Channel Messages
+// Generated Messages
 // ============================================================================
 
-/// Client input command targeting a specific tick.
-/// Ref: DM-0006, ADR-0006 (Realtime Channel)
-///
-/// Note: `player_id` is NOT included - bound by Server Edge from session.
-#[derive(Clone, PartialEq, Message)]
-pub struct InputCmdProto {
-    /// Target tick for this input.
-    /// MUST be >= TargetTickFloor.
-    #[prost(uint64, tag = "1")]
-    pub tick: Tick,
-
-    /// Per-session sequence number for deterministic selection.
-    /// Ref: DM-0026
-    #[prost(uint64, tag = "2")]
-    pub input_seq: InputSeq,
-
-    /// Movement direction [x, y], magnitude <= 1.0.
-    #[prost(double, repeated, tag = "3")]
-    pub move_dir: Vec<f64>,
-}
-
-/// Server snapshot broadcast.
-/// Ref: DM-0007, ADR-0006 (Realtime Channel)
-#[derive(Clone, PartialEq, Message)]
-pub struct SnapshotProto {
-    /// Post-step tick.
-    #[prost(uint64, tag = "1")]
-    pub tick: Tick,
-
-    /// Entity snapshots, ordered by entity_id ascending per INV-0007.
-    #[prost(message, repeated, tag = "2")]
-    pub entities: Vec<EntitySnapshotProto>,
-
-    /// StateDigest at this tick (ADR-0007).
-    #[prost(uint64, tag = "3")]
-    pub digest: u64,
-
-    /// TargetTickFloor for client input targeting.
-    /// Ref: DM-0025, ADR-0006
-    #[prost(uint64, tag = "4")]
-    pub target_tick_floor: Tick,
-}
-
-/// Entity snapshot embedded in JoinBaseline/SnapshotProto.
-#[derive(Clone, PartialEq, Message)]
-pub struct EntitySnapshotProto {
-    /// EntityId.
-    /// Ref: DM-0020
-    #[prost(uint64, tag = "1")]
-    pub entity_id: EntityId,
-
-    /// Position [x, y].
-    #[prost(double, repeated, tag = "2")]
-    pub position: Vec<f64>,
-
-    /// Velocity [vx, vy].
-    #[prost(double, repeated, tag = "3")]
-    pub velocity: Vec<f64>,
-}
+// Every message, enum, and envelope is generated by build.rs from
+// `protocol/flowstate/wire/*.proto`, the canonical schema shared with
+// non-Rust clients. Edit the .proto files, not generated code.
+include!(concat!(env!("OUT_DIR"), "/flowstate.wire.rs"));
 
 // ============================================================================
-// Time Sync Messages (Tier 1 - Stub for future)
+// Message Helpers
 // ============================================================================
 
-/// Time synchronization ping from client.
-/// Ref: Tier 1 (debug/telemetry only)
-#[derive(Clone, PartialEq, Message)]
-pub struct TimeSyncPing {
-    /// Client-side timestamp (opaque to server).
-    #[prost(uint64, tag = "1")]
-    pub client_timestamp: u64,
-}
-
-/// Time synchronization pong from server.
-/// Ref: Tier 1 (debug/telemetry only)
-#[derive(Clone, PartialEq, Message)]
-pub struct TimeSyncPong {
-    /// Server's current tick at time of response.
-    #[prost(uint64, tag = "1")]
-    pub server_tick: Tick,
-
-    /// Server-side timestamp.
-    #[prost(uint64, tag = "2")]
-    pub server_timestamp: u64,
-
-    /// Echo of client's ping timestamp.
-    #[prost(uint64, tag = "3")]
-    pub ping_timestamp_echo: u64,
-}
-
-// ============================================================================
-// Replay Artifact Types
-// ============================================================================
-
-/// Applied input recorded for replay.
-/// Ref: DM-0024
-#[derive(Clone, PartialEq, Message)]
-pub struct AppliedInputProto {
-    /// Tick at which this input was applied.
-    #[prost(uint64, tag = "1")]
-    pub tick: Tick,
-
-    /// Player this input is for.
-    #[prost(uint32, tag = "2")]
-    pub player_id: u32,
-
-    /// Normalized movement direction.
-    #[prost(double, repeated, tag = "3")]
-    pub move_dir: Vec<f64>,
-
-    /// True if generated via LastKnownIntent fallback.
-    /// Ref: DM-0023
-    #[prost(bool, tag = "4")]
-    pub is_fallback: bool,
-}
-
-/// Player to Entity mapping for replay initialization.
-#[derive(Clone, PartialEq, Message)]
-pub struct PlayerEntityMapping {
-    #[prost(uint32, tag = "1")]
-    pub player_id: u32,
+impl RedundantInputCmd {
+    /// Encode `latest` plus `previous` inputs (any order). Inputs that do not
+    /// precede `latest` in both tick and InputSeq, or lie too far behind to
+    /// fit a delta, are left out.
+    pub fn new(latest: InputCmdProto, previous: &[InputCmdProto]) -> Self {
+        let mut history: Vec<InputHistoryEntry> = previous
+            .iter()
+            .filter_map(|input| {
+                let tick_delta = u32::try_from(latest.tick.checked_sub(input.tick)?).ok()?;
+                let seq_delta =
+                    u32::try_from(latest.input_seq.checked_sub(input.input_seq)?).ok()?;
+                (tick_delta > 0 && seq_delta > 0).then(|| InputHistoryEntry {
+                    tick_delta,
+                    seq_delta,
+                    move_dir: input.move_dir.clone(),
+                })
+            })
+            .collect();
+        history.sort_by_key(|entry| entry.tick_delta);
+        Self {
+            latest: Some(latest),
+            history,
+        }
+    }
 
-    #[prost(uint64, tag = "2")]
-    pub entity_id: EntityId,
+    /// Absolute inputs, oldest first with `latest` last. `None` if `latest`
+    /// is missing or a delta is zero or reaches below tick/InputSeq 0.
+    pub fn to_bundle(&self) -> Option<InputBundle> {
+        let latest = self.latest.as_ref()?;
+        let mut inputs = self
+            .history
+            .iter()
+            .map(|entry| {
+                if entry.tick_delta == 0 || entry.seq_delta == 0 {
+                    return None;
+                }
+                Some(InputCmdProto {
+                    tick: latest.tick.checked_sub(u64::from(entry.tick_delta))?,
+                    input_seq: latest.input_seq.checked_sub(u64::from(entry.seq_delta))?,
+                    move_dir: entry.move_dir.clone(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        inputs.sort_by_key(|input| input.tick);
+        inputs.push(latest.clone());
+        Some(InputBundle { inputs })
+    }
 }
 
-/// Tuning parameter key-value pair.
-#[derive(Clone, PartialEq, Message)]
-pub struct TuningParameter {
-    #[prost(string, tag = "1")]
-    pub key: String,
+/// Oneof tag of `realtime_message::Payload::Snapshot`.
+const REALTIME_SNAPSHOT_TAG: u32 = 3;
 
-    #[prost(double, tag = "2")]
-    pub value: f64,
+impl ControlMessage {
+    pub fn new(payload: control_message::Payload) -> Self {
+        Self {
+            payload: Some(payload),
+        }
+    }
 }
 
-/// Build fingerprint for replay scope verification.
-#[derive(Clone, PartialEq, Message)]
-pub struct BuildFingerprint {
-    /// SHA-256 of server executable bytes.
-    #[prost(string, tag = "1")]
-    pub binary_sha256: String,
-
-    /// Target triple (e.g., "x86_64-pc-windows-msvc").
-    #[prost(string, tag = "2")]
-    pub target_triple: String,
-
-    /// Build profile ("release" or "dev").
-    #[prost(string, tag = "3")]
-    pub profile: String,
-
-    /// Git commit hash (metadata/traceability).
-    #[prost(string, tag = "4")]
-    pub git_commit: String,
-}
+impl RealtimeMessage {
+    pub fn new(payload: realtime_message::Payload) -> Self {
+        Self {
+            payload: Some(payload),
+        }
+    }
 
-/// Complete replay artifact.
-/// Ref: DM-0017, INV-0006
-#[derive(Clone, PartialEq, Message)]
-pub struct ReplayArtifact {
-    /// Schema version (v0 starts at 1).
-    #[prost(uint32, tag = "1")]
-    pub replay_format_version: u32,
-
-    /// Initial baseline at match start.
-    /// Ref: DM-0016
-    #[prost(message, optional, tag = "2")]
-    pub initial_baseline: Option<JoinBaseline>,
-
-    /// RNG seed.
-    #[prost(uint64, tag = "3")]
-    pub seed: u64,
-
-    /// RNG algorithm identifier (e.g., "ChaCha8Rng").
-    #[prost(string, tag = "4")]
-    pub rng_algorithm: String,
-
-    /// Simulation tick rate.
-    #[prost(uint32, tag = "5")]
-    pub tick_rate_hz: u32,
-
-    /// StateDigest algorithm identifier (ADR-0007).
-    #[prost(string, tag = "6")]
-    pub state_digest_algo_id: String,
-
-    /// Entity spawn order (PlayerIds in spawn sequence).
-    #[prost(uint32, repeated, tag = "7")]
-    pub entity_spawn_order: Vec<u32>,
-
-    /// Player to Entity mapping.
-    #[prost(message, repeated, tag = "8")]
-    pub player_entity_mapping: Vec<PlayerEntityMapping>,
-
-    /// Tuning parameters (sorted by key).
-    #[prost(message, repeated, tag = "9")]
-    pub tuning_parameters: Vec<TuningParameter>,
-
-    /// Applied input stream.
-    /// Ref: DM-0024
-    #[prost(message, repeated, tag = "10")]
-    pub inputs: Vec<AppliedInputProto>,
-
-    /// Build fingerprint for verification scope.
-    #[prost(message, optional, tag = "11")]
-    pub build_fingerprint: Option<BuildFingerprint>,
-
-    /// StateDigest at checkpoint_tick.
-    #[prost(uint64, tag = "12")]
-    pub final_digest: u64,
-
-    /// Post-step tick for verification anchor.
-    #[prost(uint64, tag = "13")]
-    pub checkpoint_tick: Tick,
-
-    /// Match termination reason.
-    #[prost(string, tag = "14")]
-    pub end_reason: String,
-
-    /// Test mode flag.
-    #[prost(bool, tag = "15")]
-    pub test_mode: bool,
-
-    /// Test player IDs (when test_mode=true).
-    #[prost(uint32, repeated, tag = "16")]
-    pub test_player_ids: Vec<u32>,
+    /// Encode a `RealtimeMessage` around an already-encoded `SnapshotProto`
+    /// (e.g., one payload shared by many sessions) without decoding it.
+    /// Byte-identical to encoding the envelope with the decoded snapshot.
+    pub fn encode_snapshot_bytes(snapshot: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(snapshot.len() + 6);
+        prost::encoding::encode_key(
+            REALTIME_SNAPSHOT_TAG,
+            prost::encoding::WireType::LengthDelimited,
+            &mut buf,
+        );
+        prost::encoding::encode_varint(snapshot.len() as u64, &mut buf);
+        buf.extend_from_slice(snapshot);
+        buf
+    }
 }
 
 // ============================================================================
@@ -333,9 +166,10 @@ pub struct ReplayArtifact {
 impl From<flowstate_sim::EntitySnapshot> for EntitySnapshotProto {
     fn from(e: flowstate_sim::EntitySnapshot) -> Self {
         Self {
-            entity_id: e.entity_id,
+            entity_id: e.entity_id.into(),
             position: e.position.to_vec(),
             velocity: e.velocity.to_vec(),
+            ..Default::default()
         }
     }
 }
@@ -351,7 +185,7 @@ impl TryFrom<EntitySnapshotProto> for flowstate_sim::EntitySnapshot {
             return Err("velocity must have exactly 2 elements");
         }
         Ok(Self {
-            entity_id: e.entity_id,
+            entity_id: EntityId(e.entity_id),
             position: [e.position[0], e.position[1]],
             velocity: [e.velocity[0], e.velocity[1]],
         })
@@ -388,10 +222,137 @@ impl From<flowstate_sim::Snapshot> for SnapshotProto {
             entities: s.entities.into_iter().map(Into::into).collect(),
             digest: s.digest,
             target_tick_floor: 0, // Must be set by caller
+            truncated: false,
+            server_time_micros: 0,
         }
     }
 }
 
+impl TryFrom<SnapshotProto> for flowstate_sim::Snapshot {
+    type Error = &'static str;
+
+    /// Wire-only fields (`target_tick_floor`, `truncated`, entity kind and
+    /// owner) are dropped; masked delta entries fail the vector checks.
+    fn try_from(s: SnapshotProto) -> Result<Self, Self::Error> {
+        let entities: Result<Vec<_>, _> = s.entities.into_iter().map(TryInto::try_into).collect();
+        Ok(Self {
+            tick: s.tick,
+            entities: entities?,
+            digest: s.digest,
+        })
+    }
+}
+
+impl TryFrom<EntitySnapshotProto> for EntitySnapshotProtoV2 {
+    type Error = &'static str;
+
+    /// Empty v1 fields (left out by a change mask) become absent components.
+    fn try_from(e: EntitySnapshotProto) -> Result<Self, Self::Error> {
+        use entity_component::Component;
+
+        let vec2 = |v: &[f64], error| match *v {
+            [] => Ok(None),
+            [x, y] => Ok(Some(Vec2 { x, y })),
+            _ => Err(error),
+        };
+        let position = vec2(&e.position, "position must have 0 or 2 elements")?;
+        let velocity = vec2(&e.velocity, "velocity must have 0 or 2 elements")?;
+        let components = position
+            .map(Component::Position)
+            .into_iter()
+            .chain(velocity.map(Component::Velocity))
+            .map(|component| EntityComponent {
+                component: Some(component),
+            })
+            .collect();
+        Ok(Self {
+            entity_id: e.entity_id,
+            entity_kind: e.entity_kind,
+            owner_player_id: e.owner_player_id,
+            components,
+        })
+    }
+}
+
+impl TryFrom<EntitySnapshotProtoV2> for EntitySnapshotProto {
+    type Error = &'static str;
+
+    /// Missing components yield a masked entry (see `change_mask`); unknown
+    /// components are dropped, since v1 has no field for them.
+    fn try_from(e: EntitySnapshotProtoV2) -> Result<Self, Self::Error> {
+        use entity_component::Component;
+
+        let mut position = None;
+        let mut velocity = None;
+        for component in e.components {
+            let (slot, value) = match component.component {
+                Some(Component::Position(v)) => (&mut position, v),
+                Some(Component::Velocity(v)) => (&mut velocity, v),
+                None => continue,
+            };
+            if slot.replace(vec![value.x, value.y]).is_some() {
+                return Err("duplicate entity component");
+            }
+        }
+        let mut change_mask = 0;
+        if position.is_some() {
+            change_mask |= change_mask::POSITION;
+        }
+        if velocity.is_some() {
+            change_mask |= change_mask::VELOCITY;
+        }
+        if change_mask == change_mask::SUPPORTED {
+            change_mask = 0;
+        }
+        Ok(Self {
+            entity_id: e.entity_id,
+            position: position.unwrap_or_default(),
+            velocity: velocity.unwrap_or_default(),
+            change_mask,
+            entity_kind: e.entity_kind,
+            owner_player_id: e.owner_player_id,
+        })
+    }
+}
+
+impl TryFrom<SnapshotProto> for SnapshotProtoV2 {
+    type Error = &'static str;
+
+    fn try_from(s: SnapshotProto) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tick: s.tick,
+            entities: s
+                .entities
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            digest: s.digest,
+            target_tick_floor: s.target_tick_floor,
+            truncated: s.truncated,
+            server_time_micros: s.server_time_micros,
+        })
+    }
+}
+
+impl TryFrom<SnapshotProtoV2> for SnapshotProto {
+    type Error = &'static str;
+
+    fn try_from(s: SnapshotProtoV2) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tick: s.tick,
+            entities: s
+                .entities
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            digest: s.digest,
+            target_tick_floor: s.target_tick_floor,
+            truncated: s.truncated,
+            server_time_micros: s.server_time_micros,
+        })
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -399,15 +360,373 @@ impl From<flowstate_sim::Snapshot> for SnapshotProto {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use prost::Message;
 
     #[test]
     fn test_client_hello_roundtrip() {
-        let msg = ClientHello {};
+        let msg = ClientHello::default();
+        let encoded = msg.encode_to_vec();
+        let decoded = ClientHello::decode(encoded.as_slice()).unwrap();
+        assert_eq!(msg, decoded);
+
+        let msg = ClientHello {
+            auth_token: "invite-123".to_string(),
+            ..Default::default()
+        };
         let encoded = msg.encode_to_vec();
         let decoded = ClientHello::decode(encoded.as_slice()).unwrap();
         assert_eq!(msg, decoded);
     }
 
+    #[test]
+    fn test_envelopes_distinguish_kinds() {
+        let input = RealtimeMessage::new(realtime_message::Payload::InputCmd(InputCmdProto {
+            tick: 3,
+            input_seq: 3,
+            move_dir: vec![1.0, 0.0],
+        }));
+        let bundle = RealtimeMessage::new(realtime_message::Payload::InputBundle(InputBundle {
+            inputs: vec![InputCmdProto {
+                tick: 3,
+                input_seq: 3,
+                move_dir: vec![1.0, 0.0],
+            }],
+        }));
+        assert_ne!(input.encode_to_vec(), bundle.encode_to_vec());
+        assert_eq!(
+            RealtimeMessage::decode(input.encode_to_vec().as_slice()).unwrap(),
+            input
+        );
+        assert_eq!(
+            RealtimeMessage::decode(bundle.encode_to_vec().as_slice()).unwrap(),
+            bundle
+        );
+
+        let ping = ControlMessage::new(control_message::Payload::TimeSyncPing(TimeSyncPing {
+            client_timestamp: 3,
+            ..Default::default()
+        }));
+        assert_eq!(
+            ControlMessage::decode(ping.encode_to_vec().as_slice()).unwrap(),
+            ping
+        );
+
+        let left = ControlMessage::new(control_message::Payload::PlayerLeft(PlayerLeft {
+            player_id: 1,
+            reason: LeaveReason::Kicked as i32,
+            tick: 9,
+        }));
+        let decoded = ControlMessage::decode(left.encode_to_vec().as_slice()).unwrap();
+        assert!(matches!(
+            decoded.payload,
+            Some(control_message::Payload::PlayerLeft(ref p)) if p.tick == 9
+        ));
+
+        let snapshot = SnapshotProto {
+            tick: 7,
+            digest: 42,
+            target_tick_floor: 8,
+            ..Default::default()
+        };
+        let envelope = RealtimeMessage::new(realtime_message::Payload::Snapshot(snapshot.clone()));
+        assert_eq!(
+            RealtimeMessage::encode_snapshot_bytes(&snapshot.encode_to_vec()),
+            envelope.encode_to_vec()
+        );
+    }
+
+    #[test]
+    fn test_delta_snapshot_roundtrip() {
+        let delta = DeltaSnapshotProto {
+            tick: 12,
+            base_tick: 9,
+            keyframe: false,
+            changed: vec![EntitySnapshotProto {
+                entity_id: 2,
+                position: vec![1.0, 2.0],
+                velocity: vec![0.0, 5.0],
+                ..Default::default()
+            }],
+            removed_entity_ids: vec![4, 7],
+            digest: 0xfeed,
+            target_tick_floor: 13,
+            server_time_micros: 1_500_000,
+        };
+        let envelope = RealtimeMessage::new(realtime_message::Payload::DeltaSnapshot(delta));
+        let decoded = RealtimeMessage::decode(envelope.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, envelope);
+
+        // An empty delta (nothing changed) still identifies its ticks
+        let empty = DeltaSnapshotProto {
+            tick: 13,
+            base_tick: 12,
+            ..Default::default()
+        };
+        let decoded = DeltaSnapshotProto::decode(empty.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, empty);
+    }
+
+    #[test]
+    fn test_error_response_roundtrip() {
+        let error = ErrorResponse {
+            code: ErrorCode::UnsupportedProtocolVersion as i32,
+            message: "Unsupported protocol version 9".to_string(),
+            retryable: false,
+        };
+        let decoded = ErrorResponse::decode(error.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, error);
+        assert_eq!(decoded.code(), ErrorCode::UnsupportedProtocolVersion);
+
+        // Unknown codes from newer servers decode as Unspecified
+        let future = ErrorResponse {
+            code: 99,
+            ..Default::default()
+        };
+        let decoded = ErrorResponse::decode(future.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded.code(), ErrorCode::Unspecified);
+    }
+
+    #[test]
+    fn test_redundant_input_roundtrip() {
+        let cmd = |tick: Tick, input_seq: InputSeq, x: f64| InputCmdProto {
+            tick,
+            input_seq,
+            move_dir: vec![x, 0.0],
+        };
+        let latest = cmd(1_000_000, 500_000, 1.0);
+        let previous = [
+            cmd(999_998, 499_998, 0.5),
+            cmd(999_999, 499_999, 0.75),
+            cmd(1_000_000, 499_999, 0.0),
+        ];
+        let redundant = RedundantInputCmd::new(latest.clone(), &previous);
+        // Same-tick input is not history
+        assert_eq!(redundant.history.len(), 2);
+        assert_eq!(redundant.history[0].tick_delta, 1);
+
+        let decoded = RedundantInputCmd::decode(redundant.encode_to_vec().as_slice()).unwrap();
+        let bundle = decoded.to_bundle().unwrap();
+        assert_eq!(
+            bundle.inputs,
+            vec![previous[0].clone(), previous[1].clone(), latest]
+        );
+
+        // Deltas are cheaper than repeating large absolute ticks
+        let explicit = InputBundle {
+            inputs: bundle.inputs.clone(),
+        };
+        assert!(redundant.encoded_len() < explicit.encoded_len());
+
+        let underflow = RedundantInputCmd {
+            latest: Some(cmd(1, 1, 0.0)),
+            history: vec![InputHistoryEntry {
+                tick_delta: 2,
+                seq_delta: 1,
+                move_dir: vec![0.0, 0.0],
+            }],
+        };
+        assert!(underflow.to_bundle().is_none());
+        assert!(RedundantInputCmd::default().to_bundle().is_none());
+    }
+
+    #[test]
+    fn test_key_exchange_roundtrip() {
+        let init = KeyExchangeInit {
+            public_key: vec![7; 32],
+            algorithms: vec![AeadAlgorithm::Chacha20Poly1305 as i32],
+        };
+        let decoded = KeyExchangeInit::decode(init.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(init, decoded);
+
+        let response = KeyExchangeResponse {
+            public_key: vec![9; 32],
+            key_id: 1,
+            algorithm: AeadAlgorithm::Chacha20Poly1305 as i32,
+        };
+        let decoded = KeyExchangeResponse::decode(response.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(response, decoded);
+    }
+
+    #[test]
+    fn test_chat_roundtrip() {
+        let send = ChatSend {
+            text: "gg".to_string(),
+        };
+        assert_eq!(
+            send,
+            ChatSend::decode(send.encode_to_vec().as_slice()).unwrap()
+        );
+
+        let msg = ChatBroadcast {
+            player_id: 1,
+            text: "gg".to_string(),
+            server_tick: 300,
+        };
+        assert_eq!(
+            msg,
+            ChatBroadcast::decode(msg.encode_to_vec().as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_player_left_roundtrip() {
+        let left = PlayerLeft {
+            player_id: 1,
+            reason: LeaveReason::Kicked as i32,
+            tick: 120,
+        };
+        let decoded = PlayerLeft::decode(left.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, left);
+        assert_eq!(decoded.reason(), LeaveReason::Kicked);
+    }
+
+    #[test]
+    fn test_floor_update_roundtrip() {
+        let update = FloorUpdate {
+            target_tick_floor: 42,
+            server_tick: 41,
+        };
+        assert_eq!(
+            update,
+            FloorUpdate::decode(update.encode_to_vec().as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reconnect_roundtrip() {
+        let request = ControlMessage::new(control_message::Payload::ReconnectRequest(
+            ReconnectRequest {
+                session_token: "player-token".to_string(),
+                last_seen_tick: 120,
+                protocol_version: PROTOCOL_VERSION,
+            },
+        ));
+        let decoded = ControlMessage::decode(request.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, request);
+
+        let accept = ReconnectAccept {
+            welcome: Some(ServerWelcome {
+                target_tick_floor: 133,
+                tick_rate_hz: 60,
+                player_id: 1,
+                controlled_entity_id: 2,
+                team_id: None,
+                match_id: "m-1".to_string(),
+                schema_hash: SCHEMA_HASH,
+                capabilities: 0,
+                compression: None,
+            }),
+            baseline_tick: 131,
+            baseline_digest: 0xfeed,
+        };
+        let decoded = ReconnectAccept::decode(accept.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, accept);
+    }
+
+    #[test]
+    fn test_spectate_roundtrip() {
+        let request =
+            ControlMessage::new(control_message::Payload::SpectateRequest(SpectateRequest {
+                auth_token: "caster".to_string(),
+                protocol_version: PROTOCOL_VERSION,
+                delay_seconds: Some(30),
+            }));
+        let decoded = ControlMessage::decode(request.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, request);
+
+        let live = SpectateWelcome {
+            tick_rate_hz: 60,
+            match_id: "m-1".to_string(),
+            delay_seconds: None,
+        };
+        let decoded = SpectateWelcome::decode(live.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, live);
+    }
+
+    #[test]
+    fn test_lobby_roundtrip() {
+        let lobby = ControlMessage::new(control_message::Payload::LobbyState(LobbyState {
+            players: vec![
+                LobbyPlayer {
+                    player_id: 0,
+                    ready: true,
+                },
+                LobbyPlayer {
+                    player_id: 1,
+                    ready: false,
+                },
+            ],
+            required_players: 2,
+        }));
+        let decoded = ControlMessage::decode(lobby.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, lobby);
+
+        for payload in [
+            control_message::Payload::SetReady(SetReady { ready: true }),
+            control_message::Payload::MatchStarting(MatchStarting {
+                countdown_ticks: 180,
+                server_tick: 7,
+            }),
+        ] {
+            let msg = ControlMessage::new(payload);
+            let decoded = ControlMessage::decode(msg.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_v2_converts_from_v1() {
+        let v1 = SnapshotProto {
+            tick: 10,
+            entities: vec![EntitySnapshotProto {
+                entity_id: 1,
+                position: vec![0.5, -1.0],
+                velocity: vec![2.0, 0.0],
+                entity_kind: EntityKind::Character as i32,
+                owner_player_id: Some(3),
+                ..Default::default()
+            }],
+            digest: 0xbeef,
+            target_tick_floor: 12,
+            truncated: true,
+            server_time_micros: 2_000_000,
+        };
+        let v2 = SnapshotProtoV2::try_from(v1.clone()).unwrap();
+        let entity = &v2.entities[0];
+        assert_eq!(entity.owner_player_id, Some(3));
+        assert_eq!(
+            entity.components[0].component,
+            Some(entity_component::Component::Position(Vec2 {
+                x: 0.5,
+                y: -1.0
+            }))
+        );
+        let decoded = SnapshotProtoV2::decode(v2.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(SnapshotProto::try_from(decoded).unwrap(), v1);
+
+        // A velocity-only entity maps to a masked v1 entry
+        let partial = EntitySnapshotProtoV2 {
+            entity_id: 2,
+            components: vec![EntityComponent {
+                component: Some(entity_component::Component::Velocity(Vec2 {
+                    x: 1.0,
+                    y: 1.0,
+                })),
+            }],
+            ..Default::default()
+        };
+        let masked = EntitySnapshotProto::try_from(partial.clone()).unwrap();
+        assert_eq!(masked.change_mask, change_mask::VELOCITY);
+        assert!(masked.position.is_empty());
+        assert_eq!(EntitySnapshotProtoV2::try_from(masked).unwrap(), partial);
+
+        let duplicated = EntitySnapshotProtoV2 {
+            components: vec![partial.components[0]; 2],
+            ..partial
+        };
+        assert!(EntitySnapshotProto::try_from(duplicated).is_err());
+    }
+
     #[test]
     fn test_server_welcome_roundtrip() {
         let msg = ServerWelcome {
@@ -415,6 +734,15 @@ mod tests {
             tick_rate_hz: 60,
             player_id: 1,
             controlled_entity_id: 42,
+            team_id: Some(1),
+            match_id: "m-1".to_string(),
+            schema_hash: SCHEMA_HASH,
+            capabilities: capabilities::REALTIME_CHECKSUMS,
+            compression: Some(CompressionParams {
+                codec: CompressionCodec::Lz4 as i32,
+                min_payload_len: compression::DEFAULT_MIN_PAYLOAD_LEN,
+                max_raw_len: 64 * 1024,
+            }),
         };
         let encoded = msg.encode_to_vec();
         let decoded = ServerWelcome::decode(encoded.as_slice()).unwrap();
@@ -433,6 +761,27 @@ mod tests {
         assert_eq!(msg, decoded);
     }
 
+    #[test]
+    fn test_input_bundle_roundtrip() {
+        let msg = InputBundle {
+            inputs: vec![
+                InputCmdProto {
+                    tick: 99,
+                    input_seq: 49,
+                    move_dir: vec![1.0, 0.0],
+                },
+                InputCmdProto {
+                    tick: 100,
+                    input_seq: 50,
+                    move_dir: vec![0.707, 0.707],
+                },
+            ],
+        };
+        let encoded = msg.encode_to_vec();
+        let decoded = InputBundle::decode(encoded.as_slice()).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
     #[test]
     fn test_snapshot_roundtrip() {
         let msg = SnapshotProto {
@@ -441,19 +790,61 @@ mod tests {
                 entity_id: 1,
                 position: vec![10.5, 20.5],
                 velocity: vec![1.0, 0.0],
+                ..Default::default()
             }],
             digest: 0xdeadbeef,
             target_tick_floor: 101,
+            truncated: true,
+            server_time_micros: 1_666_666,
         };
         let encoded = msg.encode_to_vec();
         let decoded = SnapshotProto::decode(encoded.as_slice()).unwrap();
         assert_eq!(msg, decoded);
+
+        let snapshot = flowstate_sim::Snapshot::try_from(decoded).unwrap();
+        assert_eq!(snapshot.tick, 100);
+        assert_eq!(snapshot.digest, 0xdeadbeef);
+        assert_eq!(snapshot.entities[0].position, [10.5, 20.5]);
+        assert_eq!(SnapshotProto::from(snapshot).entities, msg.entities);
+
+        let masked = SnapshotProto {
+            entities: vec![EntitySnapshotProto {
+                entity_id: 1,
+                position: vec![10.5, 20.5],
+                change_mask: change_mask::POSITION,
+                ..Default::default()
+            }],
+            ..msg
+        };
+        assert_eq!(
+            flowstate_sim::Snapshot::try_from(masked),
+            Err("velocity must have exactly 2 elements")
+        );
+    }
+
+    #[test]
+    fn test_time_sync_roundtrip() {
+        let ping = TimeSyncPing {
+            client_timestamp: 1_000,
+            server_timestamp_echo: 500,
+            client_hold_micros: 20,
+        };
+        let decoded = TimeSyncPing::decode(ping.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(ping, decoded);
+
+        let pong = TimeSyncPong {
+            server_tick: 10,
+            server_timestamp: 2_000,
+            ping_timestamp_echo: 1_000,
+        };
+        let decoded = TimeSyncPong::decode(pong.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(pong, decoded);
     }
 
     #[test]
     fn test_replay_artifact_roundtrip() {
         let msg = ReplayArtifact {
-            replay_format_version: 1,
+            replay_format_version: 2,
             initial_baseline: Some(JoinBaseline {
                 tick: 0,
                 entities: vec![],
@@ -468,10 +859,12 @@ mod tests {
                 PlayerEntityMapping {
                     player_id: 0,
                     entity_id: 1,
+                    team_id: Some(0),
                 },
                 PlayerEntityMapping {
                     player_id: 1,
                     entity_id: 2,
+                    team_id: None,
                 },
             ],
             tuning_parameters: vec![TuningParameter {
@@ -487,15 +880,84 @@ mod tests {
             }),
             final_digest: 0xfeedface,
             checkpoint_tick: 3600,
-            end_reason: "complete".to_string(),
+            legacy_end_reason: String::new(),
+            end_reason: MatchEndReason::Other as i32,
+            end_reason_detail: "server_shutdown".to_string(),
             test_mode: false,
             test_player_ids: vec![],
+            match_id: "m-1".to_string(),
+            checkpoint_digests: vec![TickDigest {
+                tick: 60,
+                digest: 0xabc,
+            }],
+            metadata: Some(ReplayMetadata {
+                start_unix_ms: 1_700_000_000_000,
+                end_unix_ms: 1_700_000_060_000,
+                map_id: "arena".to_string(),
+                players: vec![PlayerDisplayName {
+                    player_id: 0,
+                    display_name: "Ada".to_string(),
+                }],
+            }),
+            input_chain_hash: Some(0x5eed),
+            input_audit: Some(InputAudit {
+                rejected: vec![RejectedInput {
+                    receive_seq: 7,
+                    server_tick: 30,
+                    session_id: 1,
+                    player_id: Some(0),
+                    input: Some(InputCmdProto {
+                        tick: 12,
+                        input_seq: 4,
+                        move_dir: vec![1.0, 0.0],
+                    }),
+                    reason: "below_floor".to_string(),
+                }],
+                omitted: 2,
+            }),
+            snapshot_anchors: vec![JoinBaseline {
+                tick: 600,
+                entities: vec![EntitySnapshotProto {
+                    entity_id: 1,
+                    position: vec![1.5, -2.0],
+                    velocity: vec![5.0, 0.0],
+                    ..Default::default()
+                }],
+                digest: 0x5a5a,
+            }],
+            sim_commands: vec![SimCommandProto {
+                tick: 0,
+                command: Some(sim_command_proto::Command::SpawnCharacter(
+                    SpawnCharacterCommand { player_id: 0 },
+                )),
+            }],
         };
         let encoded = msg.encode_to_vec();
         let decoded = ReplayArtifact::decode(encoded.as_slice()).unwrap();
         assert_eq!(msg, decoded);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_roundtrip() {
+        let msg = ControlMessage::new(control_message::Payload::ClientHello(ClientHello {
+            auth_token: "t".to_string(),
+            ..Default::default()
+        }));
+        let json = serde_json::to_string(&msg).unwrap();
+        assert_eq!(
+            json,
+            r#"{"payload":{"client_hello":{"auth_token":"t","protocol_version":0,"schema_hash":0,"capabilities":0,"compression":null,"key_exchange":null}}}"#
+        );
+        assert_eq!(serde_json::from_str::<ControlMessage>(&json).unwrap(), msg);
+
+        // Missing fields take proto3 defaults
+        let artifact: ReplayArtifact =
+            serde_json::from_str(r#"{"seed":7,"match_id":"m-1"}"#).unwrap();
+        assert_eq!(artifact.seed, 7);
+        assert_eq!(artifact.initial_baseline, None);
+    }
+
     /// T0.19: Verify this crate exists and can be depended upon.
     #[test]
     fn test_t0_19_wire_crate_exists() {
@@ -503,4 +965,16 @@ mod tests {
         // CI will verify both server and client depend on this crate.
         // The test body is empty - the existence of this test is the assertion.
     }
+
+    /// T0.19: the schema hash is derived and travels in the handshake.
+    #[test]
+    fn test_t0_19_schema_hash_in_handshake() {
+        assert_ne!(SCHEMA_HASH, 0);
+        let hello = ClientHello {
+            schema_hash: SCHEMA_HASH,
+            ..Default::default()
+        };
+        let decoded = ClientHello::decode(hello.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded.schema_hash, SCHEMA_HASH);
+    }
 }
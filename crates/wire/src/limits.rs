@@ -0,0 +1,262 @@
+//! Bounded decoding of untrusted envelopes.
+//!
+//! Ref: ADR-0005 (Server Edge validation)
+//! - The encoded size is checked before prost sees the bytes, which bounds
+//!   what a single datagram can make the decoder allocate
+//! - Repeated fields (entities, inputs, move_dir) are then checked against
+//!   per-field caps, before anything downstream iterates them
+//! - Unknown fields are skipped as usual, so a newer peer's additions do
+//!   not get its datagrams dropped; garbage that does not parse is refused
+//!
+//! Server-produced payloads (replays, checkpoints) are trusted and decode
+//! with plain `prost::Message::decode`.
+
+use prost::Message;
+
+use crate::{
+    ControlMessage, EntitySnapshotProto, InputCmdProto, RealtimeMessage, control_message,
+    realtime_message,
+};
+
+/// Default largest accepted envelope (the largest UDP datagram read).
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+/// Default most entities in one snapshot or baseline.
+pub const DEFAULT_MAX_ENTITIES: usize = 1024;
+
+/// Default most components in a `move_dir` (v0 directions are 2D).
+pub const DEFAULT_MAX_MOVE_DIR_LEN: usize = 2;
+
+/// Default most inputs in one bundle or redundant history.
+pub const DEFAULT_MAX_INPUTS: usize = 32;
+
+/// Caps applied by `decode_control`/`decode_realtime`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    pub max_message_len: usize,
+    /// Applies to `entities`, `changed`, and `removed_entity_ids`.
+    pub max_entities: usize,
+    pub max_move_dir_len: usize,
+    /// Applies to `InputBundle::inputs` and `RedundantInputCmd::history`.
+    pub max_inputs: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            max_entities: DEFAULT_MAX_ENTITIES,
+            max_move_dir_len: DEFAULT_MAX_MOVE_DIR_LEN,
+            max_inputs: DEFAULT_MAX_INPUTS,
+        }
+    }
+}
+
+/// Bounded decode failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeLimitError {
+    /// The encoded envelope exceeds `max_message_len`.
+    TooLarge { len: usize, max: usize },
+    /// Not a valid encoding of the envelope.
+    Malformed(prost::DecodeError),
+    /// A repeated field exceeds its cap.
+    TooManyElements {
+        field: &'static str,
+        len: usize,
+        max: usize,
+    },
+}
+
+impl std::fmt::Display for DecodeLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge { len, max } => {
+                write!(f, "Message of {len} bytes exceeds {max}")
+            }
+            Self::Malformed(e) => write!(f, "Malformed message: {e}"),
+            Self::TooManyElements { field, len, max } => {
+                write!(f, "Field {field} has {len} elements (max {max})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeLimitError {}
+
+impl From<prost::DecodeError> for DecodeLimitError {
+    fn from(e: prost::DecodeError) -> Self {
+        Self::Malformed(e)
+    }
+}
+
+/// Decode an untrusted Control Channel envelope.
+pub fn decode_control(
+    bytes: &[u8],
+    limits: &DecodeLimits,
+) -> Result<ControlMessage, DecodeLimitError> {
+    let message: ControlMessage = decode_bounded(bytes, limits)?;
+    if let Some(control_message::Payload::JoinBaseline(baseline)) = &message.payload {
+        check_entities("entities", &baseline.entities, limits)?;
+    }
+    Ok(message)
+}
+
+/// Decode an untrusted Realtime Channel envelope.
+pub fn decode_realtime(
+    bytes: &[u8],
+    limits: &DecodeLimits,
+) -> Result<RealtimeMessage, DecodeLimitError> {
+    use realtime_message::Payload;
+
+    let message: RealtimeMessage = decode_bounded(bytes, limits)?;
+    match &message.payload {
+        Some(Payload::InputCmd(cmd)) => check_move_dir(&cmd.move_dir, limits)?,
+        Some(Payload::InputBundle(bundle)) => check_inputs(&bundle.inputs, limits)?,
+        Some(Payload::RedundantInputCmd(cmd)) => {
+            check_len("history", cmd.history.len(), limits.max_inputs)?;
+            if let Some(latest) = &cmd.latest {
+                check_move_dir(&latest.move_dir, limits)?;
+            }
+            for entry in &cmd.history {
+                check_move_dir(&entry.move_dir, limits)?;
+            }
+        }
+        Some(Payload::Snapshot(snapshot)) => {
+            check_entities("entities", &snapshot.entities, limits)?;
+        }
+        Some(Payload::SnapshotV2(snapshot)) => {
+            check_len("entities", snapshot.entities.len(), limits.max_entities)?;
+        }
+        Some(Payload::DeltaSnapshot(delta)) => {
+            check_entities("changed", &delta.changed, limits)?;
+            check_len(
+                "removed_entity_ids",
+                delta.removed_entity_ids.len(),
+                limits.max_entities,
+            )?;
+        }
+        _ => {}
+    }
+    Ok(message)
+}
+
+fn decode_bounded<M: Message + Default>(
+    bytes: &[u8],
+    limits: &DecodeLimits,
+) -> Result<M, DecodeLimitError> {
+    if bytes.len() > limits.max_message_len {
+        return Err(DecodeLimitError::TooLarge {
+            len: bytes.len(),
+            max: limits.max_message_len,
+        });
+    }
+    Ok(M::decode(bytes)?)
+}
+
+fn check_len(field: &'static str, len: usize, max: usize) -> Result<(), DecodeLimitError> {
+    if len > max {
+        return Err(DecodeLimitError::TooManyElements { field, len, max });
+    }
+    Ok(())
+}
+
+fn check_move_dir(move_dir: &[f64], limits: &DecodeLimits) -> Result<(), DecodeLimitError> {
+    check_len("move_dir", move_dir.len(), limits.max_move_dir_len)
+}
+
+fn check_inputs(inputs: &[InputCmdProto], limits: &DecodeLimits) -> Result<(), DecodeLimitError> {
+    check_len("inputs", inputs.len(), limits.max_inputs)?;
+    inputs
+        .iter()
+        .try_for_each(|cmd| check_move_dir(&cmd.move_dir, limits))
+}
+
+fn check_entities(
+    field: &'static str,
+    entities: &[EntitySnapshotProto],
+    limits: &DecodeLimits,
+) -> Result<(), DecodeLimitError> {
+    check_len(field, entities.len(), limits.max_entities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientHello, InputBundle};
+
+    fn bundle(count: usize) -> Vec<u8> {
+        RealtimeMessage::new(realtime_message::Payload::InputBundle(InputBundle {
+            inputs: (0..count as u64)
+                .map(|i| InputCmdProto {
+                    tick: i,
+                    input_seq: i,
+                    move_dir: vec![0.0, 1.0],
+                })
+                .collect(),
+        }))
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn test_limits_reject_hostile_payloads() {
+        let limits = DecodeLimits::default();
+        assert!(decode_realtime(&bundle(DEFAULT_MAX_INPUTS), &limits).is_ok());
+        assert_eq!(
+            decode_realtime(&bundle(DEFAULT_MAX_INPUTS + 1), &limits),
+            Err(DecodeLimitError::TooManyElements {
+                field: "inputs",
+                len: DEFAULT_MAX_INPUTS + 1,
+                max: DEFAULT_MAX_INPUTS,
+            })
+        );
+
+        let long_dir = RealtimeMessage::new(realtime_message::Payload::InputCmd(InputCmdProto {
+            tick: 1,
+            input_seq: 1,
+            move_dir: vec![0.0; 3],
+        }))
+        .encode_to_vec();
+        assert!(matches!(
+            decode_realtime(&long_dir, &limits),
+            Err(DecodeLimitError::TooManyElements {
+                field: "move_dir",
+                ..
+            })
+        ));
+
+        let small = DecodeLimits {
+            max_message_len: 8,
+            ..limits
+        };
+        assert!(matches!(
+            decode_realtime(&bundle(1), &small),
+            Err(DecodeLimitError::TooLarge { max: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_limits_skip_unknown_fields() {
+        let limits = DecodeLimits::default();
+        let mut hello = ControlMessage::new(control_message::Payload::ClientHello(ClientHello {
+            auth_token: "t".to_string(),
+            ..Default::default()
+        }))
+        .encode_to_vec();
+        assert!(decode_control(&hello, &limits).is_ok());
+
+        // A field this build does not know, as a newer peer might send
+        hello.extend_from_slice(&[0xf8, 0x07, 0x01]);
+        let decoded = decode_control(&hello, &limits).unwrap();
+        assert!(matches!(
+            decoded.payload,
+            Some(control_message::Payload::ClientHello(ClientHello { ref auth_token, .. }))
+                if auth_token == "t"
+        ));
+
+        hello.push(0xff);
+        assert!(matches!(
+            decode_control(&hello, &limits),
+            Err(DecodeLimitError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+//! Outer sequence numbers for realtime frames.
+//!
+//! Ref: ADR-0005 (Realtime Channel)
+//! - A sequenced payload is `[sequence: u32 LE][payload]`; the sender numbers
+//!   its realtime datagrams with a `Sequencer`, wrapping at `u32::MAX`
+//! - `SequenceWindow` remembers the last `WINDOW_LEN` sequence numbers seen
+//!   and classifies each arrival, so a receiver can drop duplicated and
+//!   too-old datagrams before decoding them
+//! - Comparisons are wrap-aware: a sequence number is newer if it is less
+//!   than 2^31 ahead of the latest one
+//!
+//! Sequencing sits below the envelope: it says nothing about message
+//! content, only about datagram order on one channel from one peer.
+
+/// Bytes the sequence number adds in front of a payload.
+pub const SEQUENCE_LEN: usize = 4;
+
+/// Sequence numbers a `SequenceWindow` tracks behind the latest one.
+pub const WINDOW_LEN: u32 = 64;
+
+/// Sequenced payload failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceError {
+    /// Fewer than `SEQUENCE_LEN` bytes.
+    Truncated,
+}
+
+impl std::fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "Sequenced payload shorter than its sequence number"),
+        }
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+/// Append `[sequence][payload]` to `out`.
+pub fn write_sequenced(sequence: u32, payload: &[u8], out: &mut Vec<u8>) {
+    out.reserve(SEQUENCE_LEN + payload.len());
+    out.extend_from_slice(&sequence.to_le_bytes());
+    out.extend_from_slice(payload);
+}
+
+/// Split a `[sequence][payload]` buffer.
+pub fn read_sequenced(sequenced: &[u8]) -> Result<(u32, &[u8]), SequenceError> {
+    if sequenced.len() < SEQUENCE_LEN {
+        return Err(SequenceError::Truncated);
+    }
+    let (sequence, payload) = sequenced.split_at(SEQUENCE_LEN);
+    let sequence = u32::from_le_bytes(sequence.try_into().expect("4-byte slice"));
+    Ok((sequence, payload))
+}
+
+/// Numbers outbound datagrams on one channel.
+#[derive(Debug, Clone, Default)]
+pub struct Sequencer {
+    next: u32,
+}
+
+impl Sequencer {
+    /// Sequence number for the next datagram.
+    pub fn next_sequence(&mut self) -> u32 {
+        let sequence = self.next;
+        self.next = self.next.wrapping_add(1);
+        sequence
+    }
+}
+
+/// How a sequence number relates to those already seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrival {
+    /// Newer than anything seen so far.
+    Newest,
+    /// Older than the latest, but not seen before and still in the window.
+    Reordered,
+    /// Already seen.
+    Duplicate,
+    /// Too far behind the latest to tell; treat as a duplicate.
+    Stale,
+}
+
+impl Arrival {
+    /// Whether the datagram should be processed.
+    pub fn is_fresh(self) -> bool {
+        matches!(self, Self::Newest | Self::Reordered)
+    }
+}
+
+/// Duplicate and reorder detection for one peer's sequence numbers.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceWindow {
+    latest: Option<u32>,
+    /// Bit `i` set: `latest - i` was seen.
+    seen: u64,
+}
+
+impl SequenceWindow {
+    /// Latest sequence number seen.
+    pub fn latest(&self) -> Option<u32> {
+        self.latest
+    }
+
+    /// Classify `sequence` and record it.
+    pub fn accept(&mut self, sequence: u32) -> Arrival {
+        let Some(latest) = self.latest else {
+            self.latest = Some(sequence);
+            self.seen = 1;
+            return Arrival::Newest;
+        };
+        let ahead = sequence.wrapping_sub(latest);
+        if ahead == 0 {
+            return Arrival::Duplicate;
+        }
+        if ahead < 1 << 31 {
+            self.seen = if ahead < WINDOW_LEN {
+                (self.seen << ahead) | 1
+            } else {
+                1
+            };
+            self.latest = Some(sequence);
+            return Arrival::Newest;
+        }
+        let behind = latest.wrapping_sub(sequence);
+        if behind >= WINDOW_LEN {
+            return Arrival::Stale;
+        }
+        let bit = 1 << behind;
+        if self.seen & bit != 0 {
+            return Arrival::Duplicate;
+        }
+        self.seen |= bit;
+        Arrival::Reordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequenced_roundtrip() {
+        let mut sequenced = Vec::new();
+        write_sequenced(0x0102_0304, b"input", &mut sequenced);
+        assert_eq!(sequenced[..SEQUENCE_LEN], [4, 3, 2, 1]);
+        assert_eq!(read_sequenced(&sequenced), Ok((0x0102_0304, &b"input"[..])));
+        assert_eq!(
+            read_sequenced(&sequenced[..3]),
+            Err(SequenceError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_window_classifies_arrivals() {
+        let mut window = SequenceWindow::default();
+        assert_eq!(window.accept(10), Arrival::Newest);
+        assert_eq!(window.accept(12), Arrival::Newest);
+        assert_eq!(window.accept(11), Arrival::Reordered);
+        assert_eq!(window.accept(11), Arrival::Duplicate);
+        assert_eq!(window.accept(12), Arrival::Duplicate);
+        assert_eq!(window.latest(), Some(12));
+
+        // A jump past the window forgets everything behind it
+        assert_eq!(window.accept(12 + WINDOW_LEN), Arrival::Newest);
+        assert_eq!(window.accept(12), Arrival::Stale);
+        assert_eq!(window.accept(13), Arrival::Reordered);
+        assert!(!Arrival::Stale.is_fresh());
+    }
+
+    #[test]
+    fn test_window_wraps() {
+        let mut sequencer = Sequencer { next: u32::MAX - 1 };
+        let mut window = SequenceWindow::default();
+        let sent: Vec<u32> = (0..4).map(|_| sequencer.next_sequence()).collect();
+        assert_eq!(sent, vec![u32::MAX - 1, u32::MAX, 0, 1]);
+
+        assert_eq!(window.accept(sent[0]), Arrival::Newest);
+        assert_eq!(window.accept(sent[2]), Arrival::Newest);
+        assert_eq!(window.accept(sent[1]), Arrival::Reordered);
+        assert_eq!(window.accept(sent[3]), Arrival::Newest);
+        assert_eq!(window.accept(sent[0]), Arrival::Duplicate);
+        assert_eq!(window.latest(), Some(1));
+    }
+}
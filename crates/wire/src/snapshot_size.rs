@@ -0,0 +1,204 @@
+//! Encoded snapshot sizes for bandwidth planning.
+//!
+//! Ref: DM-0007 (Snapshot), ADR-0006 (Realtime Channel)
+//! - `snapshot_len(entities, encoding)` is the size of a `RealtimeMessage`
+//!   carrying a snapshot of `entities` entities, before transport framing
+//!   (channel byte, sequence, checksum)
+//! - Every value is worst case for its field (maximal ids, ticks, digest,
+//!   and owner; every optional field set), so results are ceilings rather
+//!   than averages
+//! - `SnapshotEncoding::Quantized` is a size model for the
+//!   `capabilities::QUANTIZED_ENCODING` layout; no message carries it yet
+//!
+//! Budgets compare these against `fragment::DEFAULT_MTU` and the per-client
+//! send rate; the tests pin the current numbers.
+
+use prost::Message;
+use prost::encoding::{encoded_len_varint, key_len};
+
+use crate::{
+    DeltaSnapshotProto, EntityKind, EntitySnapshotProto, RealtimeMessage, SnapshotProto,
+    change_mask, realtime_message,
+};
+
+/// How entity state is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotEncoding {
+    /// `SnapshotProto` with f64 position and velocity.
+    Full,
+    /// `SnapshotProto` layout with position and velocity as packed sint32
+    /// fixed-point values of at most `bits` bits (sign included, 1..=32).
+    Quantized { bits: u32 },
+    /// `DeltaSnapshotProto` in which every entity moved: each carries only
+    /// its position (`change_mask::POSITION`), as at constant velocity.
+    Delta,
+}
+
+/// Encoded size of a snapshot of `entities` entities.
+pub fn snapshot_len(entities: usize, encoding: SnapshotEncoding) -> usize {
+    match encoding {
+        SnapshotEncoding::Full => {
+            envelope_len(realtime_message::Payload::Snapshot(SnapshotProto {
+                entities: (0..entities).map(worst_case_entity).collect(),
+                ..worst_case_snapshot()
+            }))
+        }
+        SnapshotEncoding::Quantized { bits } => {
+            let entity_len = quantized_entity_len(bits.clamp(1, 32));
+            let snapshot_len =
+                worst_case_snapshot().encoded_len() + entities * field_len(2, entity_len);
+            field_len(3, snapshot_len)
+        }
+        SnapshotEncoding::Delta => {
+            let changed = (0..entities)
+                .map(|i| EntitySnapshotProto {
+                    entity_id: worst_case_entity(i).entity_id,
+                    position: vec![f64::MAX, f64::MAX],
+                    change_mask: change_mask::POSITION,
+                    ..Default::default()
+                })
+                .collect();
+            let snapshot = worst_case_snapshot();
+            envelope_len(realtime_message::Payload::DeltaSnapshot(
+                DeltaSnapshotProto {
+                    tick: snapshot.tick,
+                    base_tick: snapshot.tick - 1,
+                    keyframe: false,
+                    changed,
+                    removed_entity_ids: Vec::new(),
+                    digest: snapshot.digest,
+                    target_tick_floor: snapshot.target_tick_floor,
+                    server_time_micros: snapshot.server_time_micros,
+                },
+            ))
+        }
+    }
+}
+
+/// Most entities whose snapshot fits in `max_len` bytes.
+pub fn max_entities_within(max_len: usize, encoding: SnapshotEncoding) -> usize {
+    if snapshot_len(0, encoding) > max_len {
+        return 0;
+    }
+    // Sizes grow monotonically with the entity count
+    let (mut fits, mut too_many) = (0, 1);
+    while snapshot_len(too_many, encoding) <= max_len {
+        fits = too_many;
+        too_many *= 2;
+    }
+    while too_many - fits > 1 {
+        let mid = fits + (too_many - fits) / 2;
+        if snapshot_len(mid, encoding) <= max_len {
+            fits = mid;
+        } else {
+            too_many = mid;
+        }
+    }
+    fits
+}
+
+fn worst_case_snapshot() -> SnapshotProto {
+    SnapshotProto {
+        tick: u64::MAX,
+        entities: Vec::new(),
+        digest: u64::MAX,
+        target_tick_floor: u64::MAX,
+        truncated: true,
+        server_time_micros: u64::MAX,
+    }
+}
+
+fn worst_case_entity(index: usize) -> EntitySnapshotProto {
+    EntitySnapshotProto {
+        entity_id: u64::MAX - index as u64,
+        position: vec![f64::MAX, f64::MAX],
+        velocity: vec![f64::MAX, f64::MAX],
+        change_mask: 0,
+        entity_kind: EntityKind::Obstacle as i32,
+        owner_player_id: Some(u32::MAX),
+    }
+}
+
+/// A worst-case entity with its two vectors as packed sint32 values.
+fn quantized_entity_len(bits: u32) -> usize {
+    let without_vectors = EntitySnapshotProto {
+        position: Vec::new(),
+        velocity: Vec::new(),
+        ..worst_case_entity(0)
+    }
+    .encoded_len();
+    // Zigzag encoding of -2^(bits - 1), the largest magnitude
+    let value_len = encoded_len_varint((1u64 << bits) - 1);
+    without_vectors + field_len(2, 2 * value_len) + field_len(3, 2 * value_len)
+}
+
+/// Length-delimited field `tag` with a `len`-byte body.
+fn field_len(tag: u32, len: usize) -> usize {
+    key_len(tag) + encoded_len_varint(len as u64) + len
+}
+
+fn envelope_len(payload: realtime_message::Payload) -> usize {
+    RealtimeMessage {
+        payload: Some(payload),
+    }
+    .encoded_len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fragment::DEFAULT_MTU;
+    use crate::limits::{DEFAULT_MAX_ENTITIES, DEFAULT_MAX_MESSAGE_LEN};
+
+    const QUANTIZED_16: SnapshotEncoding = SnapshotEncoding::Quantized { bits: 16 };
+
+    #[test]
+    fn test_size_ceilings() {
+        // Per-entity cost of each encoding
+        let per_entity = |encoding| snapshot_len(101, encoding) - snapshot_len(1, encoding);
+        assert!(per_entity(SnapshotEncoding::Full) <= 100 * 58);
+        assert!(per_entity(QUANTIZED_16) <= 100 * 38);
+        assert!(per_entity(SnapshotEncoding::Delta) <= 100 * 34);
+
+        assert!(snapshot_len(0, SnapshotEncoding::Full) <= 48);
+        assert!(snapshot_len(16, SnapshotEncoding::Full) <= DEFAULT_MTU);
+        assert!(snapshot_len(24, QUANTIZED_16) <= DEFAULT_MTU);
+        assert!(snapshot_len(32, SnapshotEncoding::Delta) <= DEFAULT_MTU);
+        assert!(
+            snapshot_len(DEFAULT_MAX_ENTITIES, SnapshotEncoding::Full) <= DEFAULT_MAX_MESSAGE_LEN
+        );
+    }
+
+    #[test]
+    fn test_full_len_matches_encoding() {
+        let snapshot = SnapshotProto {
+            entities: (0..3).map(worst_case_entity).collect(),
+            ..worst_case_snapshot()
+        };
+        let encoded = RealtimeMessage {
+            payload: Some(realtime_message::Payload::Snapshot(snapshot)),
+        }
+        .encode_to_vec();
+        assert_eq!(snapshot_len(3, SnapshotEncoding::Full), encoded.len());
+    }
+
+    #[test]
+    fn test_quantized_bits_and_max_entities() {
+        let quantized = |bits| snapshot_len(10, SnapshotEncoding::Quantized { bits });
+        assert!(quantized(8) < quantized(16));
+        assert!(quantized(16) < quantized(32));
+        assert_eq!(quantized(0), quantized(1));
+        assert!(quantized(32) < snapshot_len(10, SnapshotEncoding::Full));
+
+        for encoding in [
+            SnapshotEncoding::Full,
+            QUANTIZED_16,
+            SnapshotEncoding::Delta,
+        ] {
+            let max = max_entities_within(DEFAULT_MTU, encoding);
+            assert!(snapshot_len(max, encoding) <= DEFAULT_MTU);
+            assert!(snapshot_len(max + 1, encoding) > DEFAULT_MTU);
+        }
+        assert_eq!(max_entities_within(1, SnapshotEncoding::Full), 0);
+    }
+}